@@ -62,12 +62,12 @@ fn main() {
     println!("\n❄️  Phase 4: CONSOLIDATE (Ice-Caking)");
     {
         let session = agi.session_mut().unwrap();
-        session.ice_cake(&moment_id, "Project-scoped versioning is the canonical pattern");
+        session.ice_cake(&moment_id, "Project-scoped versioning is the canonical pattern").unwrap();
     }
     println!("   ✓ Decision frozen: Project-scoped versioning");
 
     // Capture to resonance - collect moments first
-    let moments: Vec<_> = agi.session().unwrap().moments.iter().cloned().collect();
+    let moments = agi.session().unwrap().moments.to_vec();
     for moment in &moments {
         agi.capture_moment(moment);
     }
@@ -127,7 +127,7 @@ fn main() {
         println!("   ✓ Pattern recognition accelerated learning!");
         println!("   📊 Lower effort due to resonance: effort={:.2}", effort);
 
-        let moments: Vec<_> = agi.session().unwrap().moments.iter().cloned().collect();
+        let moments = agi.session().unwrap().moments.to_vec();
         for moment in &moments {
             agi.capture_moment(moment);
         }
@@ -1,9 +1,11 @@
 //! Cognitive primitives - embedded for standalone operation
 
 use std::fmt;
+use serde::{Serialize, Deserialize};
+use crate::core::Clock;
 
 /// Thinking style
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct ThinkingStyle {
     pub analytical: f32,
     pub creative: f32,
@@ -27,10 +29,27 @@ impl ThinkingStyle {
     pub fn reflective() -> Self {
         Self { analytical: 0.6, creative: 0.5, focused: 0.5, exploratory: 0.6 }
     }
+    
+    /// All dimensions zero, as for moments recorded without a style
+    pub fn is_unset(&self) -> bool {
+        *self == Self::default()
+    }
+    
+    /// Name of the strongest dimension; the earlier one on ties
+    pub fn dominant(&self) -> &'static str {
+        [
+            ("analytical", self.analytical),
+            ("creative", self.creative),
+            ("focused", self.focused),
+            ("exploratory", self.exploratory),
+        ].into_iter()
+            .fold(("analytical", f32::MIN), |best, dim| if dim.1 > best.1 { dim } else { best })
+            .0
+    }
 }
 
 /// Collapse gate state
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GateState {
     Flow,
     Hold,
@@ -99,6 +118,16 @@ pub struct CollapseDecision {
 
 /// Evaluate collapse gate
 pub fn evaluate_gate(candidate_scores: &[f32], clarification_available: bool) -> CollapseDecision {
+    evaluate_gate_keyed(candidate_scores, clarification_available, rand_u64)
+}
+
+/// `evaluate_gate` with any SPPM key taken from `clock`, so the decision
+/// is reproducible under a `MockClock`
+pub fn evaluate_gate_with_clock(candidate_scores: &[f32], clarification_available: bool, clock: &dyn Clock) -> CollapseDecision {
+    evaluate_gate_keyed(candidate_scores, clarification_available, || clock.now_millis())
+}
+
+fn evaluate_gate_keyed(candidate_scores: &[f32], clarification_available: bool, key: impl Fn() -> u64) -> CollapseDecision {
     if candidate_scores.is_empty() {
         return CollapseDecision {
             state: GateState::Block,
@@ -146,7 +175,7 @@ pub fn evaluate_gate(candidate_scores: &[f32], clarification_available: bool) ->
             state: GateState::Hold,
             sd,
             can_collapse: false,
-            action: CollapseAction::Hold { sppm_key: format!("sppm_{:x}", rand_u64()) },
+            action: CollapseAction::Hold { sppm_key: format!("sppm_{:x}", key()) },
             reason: format!("Medium dispersion (SD={:.3})", sd),
             winner_index: Some(winner_idx),
             winner_score: Some(winner_score),
@@ -169,7 +198,7 @@ pub fn evaluate_gate(candidate_scores: &[f32], clarification_available: bool) ->
                     state: GateState::Block,
                     sd,
                     can_collapse: false,
-                    action: CollapseAction::Hold { sppm_key: format!("sppm_{:x}", rand_u64()) },
+                    action: CollapseAction::Hold { sppm_key: format!("sppm_{:x}", key()) },
                     reason: format!("High dispersion, holding (SD={:.3})", sd),
                     winner_index: Some(winner_idx),
                     winner_score: Some(winner_score),
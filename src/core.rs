@@ -2,12 +2,32 @@
 
 use std::hash::{Hash, Hasher};
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use thiserror::Error;
 
 /// Fingerprint dimensions
 pub const FINGERPRINT_BITS: usize = 10_000;
 pub const FINGERPRINT_U64: usize = 157;  // ceil(10000/64)
 
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Length of `Fingerprint::to_compact_string` output
+pub const FINGERPRINT_COMPACT_LEN: usize = (FINGERPRINT_U64 * 8 * 4).div_ceil(3);
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ParseFingerprintError {
+    #[error("compact fingerprint must be {FINGERPRINT_COMPACT_LEN} characters, got {0}")]
+    InvalidLength(usize),
+    #[error("invalid base64 character {0:?} in compact fingerprint")]
+    InvalidCharacter(char),
+}
+
 /// 10,000-bit VSA fingerprint for resonance operations
+///
+/// Serializes as its compact string form.
 #[repr(align(64))]
 #[derive(Clone)]
 pub struct Fingerprint {
@@ -21,12 +41,22 @@ impl Fingerprint {
     
     /// Create from content string (deterministic)
     pub fn from_content(content: &str) -> Self {
+        Self::from_seed(Self::content_seed(content))
+    }
+    
+    /// The seed `from_content` expands for `content`
+    pub fn content_seed(content: &str) -> u64 {
         use std::collections::hash_map::DefaultHasher;
         
         let mut hasher = DefaultHasher::new();
         content.hash(&mut hasher);
-        let mut state = hasher.finish();
-        
+        hasher.finish()
+    }
+    
+    /// Expand a 64-bit seed into a full fingerprint with an LFSR; the same
+    /// seed always gives the same fingerprint
+    pub fn from_seed(seed: u64) -> Self {
+        let mut state = seed;
         let mut data = [0u64; FINGERPRINT_U64];
         for word in &mut data {
             let mut val = 0u64;
@@ -98,8 +128,8 @@ impl Fingerprint {
     /// XOR bind
     pub fn bind(&self, other: &Fingerprint) -> Fingerprint {
         let mut result = [0u64; FINGERPRINT_U64];
-        for i in 0..FINGERPRINT_U64 {
-            result[i] = self.data[i] ^ other.data[i];
+        for (r, (a, b)) in result.iter_mut().zip(self.data.iter().zip(other.data.iter())) {
+            *r = a ^ b;
         }
         Fingerprint { data: result }
     }
@@ -123,6 +153,93 @@ impl Fingerprint {
         }
         result
     }
+    
+    /// Majority-vote bundle: the result stays similar to every input. Ties
+    /// take the first input's bit; an empty slice bundles to zero.
+    pub fn bundle(items: &[Fingerprint]) -> Fingerprint {
+        let mut result = Self::zero();
+        let Some(first) = items.first() else { return result };
+        for i in 0..FINGERPRINT_BITS {
+            let ones = items.iter().filter(|fp| fp.get_bit(i)).count();
+            let bit = match (ones * 2).cmp(&items.len()) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Equal => first.get_bit(i),
+            };
+            result.set_bit(i, bit);
+        }
+        result
+    }
+    
+    /// Bundle of per-word fingerprints, so texts sharing words land close
+    /// together. Words are lowercased and split on non-alphanumerics; words
+    /// under three characters are skipped. Unlike `from_content`, small edits
+    /// only move the result a little.
+    pub fn from_words(text: &str) -> Self {
+        let mut words: Vec<String> = text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.chars().count() >= 3)
+            .map(str::to_lowercase)
+            .collect();
+        words.sort();
+        words.dedup();
+        let fps: Vec<Fingerprint> = words.iter().map(|w| Self::from_content(w)).collect();
+        Self::bundle(&fps)
+    }
+    
+    /// Unpadded standard base64 of the words in little-endian byte order
+    pub fn to_compact_string(&self) -> String {
+        let bytes: Vec<u8> = self.data.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let mut out = String::with_capacity(FINGERPRINT_COMPACT_LEN);
+        for chunk in bytes.chunks(3) {
+            let n = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i)) as usize & 63] as char);
+            }
+        }
+        out
+    }
+    
+    pub fn from_compact_string(s: &str) -> Result<Self, ParseFingerprintError> {
+        let len = s.chars().count();
+        if len != FINGERPRINT_COMPACT_LEN {
+            return Err(ParseFingerprintError::InvalidLength(len));
+        }
+        let mut bytes = Vec::with_capacity(FINGERPRINT_U64 * 8);
+        let sextets = s.chars()
+            .map(|c| match c {
+                'A'..='Z' => Ok(c as u32 - 'A' as u32),
+                'a'..='z' => Ok(c as u32 - 'a' as u32 + 26),
+                '0'..='9' => Ok(c as u32 - '0' as u32 + 52),
+                '+' => Ok(62),
+                '/' => Ok(63),
+                _ => Err(ParseFingerprintError::InvalidCharacter(c)),
+            })
+            .collect::<Result<Vec<u32>, _>>()?;
+        for chunk in sextets.chunks(4) {
+            let n = chunk.iter().enumerate().fold(0u32, |acc, (i, &v)| acc | v << (18 - 6 * i));
+            for i in 0..chunk.len() - 1 {
+                bytes.push((n >> (16 - 8 * i)) as u8);
+            }
+        }
+        let mut data = [0u64; FINGERPRINT_U64];
+        for (word, chunk) in data.iter_mut().zip(bytes.chunks_exact(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().expect("chunks_exact yields 8 bytes"));
+        }
+        Ok(Self { data })
+    }
+}
+
+impl Serialize for Fingerprint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_compact_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Fingerprint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_compact_string(&s).map_err(serde::de::Error::custom)
+    }
 }
 
 impl PartialEq for Fingerprint {
@@ -151,15 +268,87 @@ impl Default for Fingerprint {
     }
 }
 
+/// Source of epoch milliseconds, injectable for deterministic tests
+pub trait Clock: Send + Sync {
+    fn now_millis(&self) -> u64;
+}
+
+/// `Arc<dyn Clock>` for structs that derive `Debug` and `Default`; the
+/// default is the system clock
+#[derive(Clone)]
+pub(crate) struct SharedClock(pub(crate) Arc<dyn Clock>);
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self(Arc::new(SystemClock))
+    }
+}
+
+impl fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedClock")
+    }
+}
+
+/// The real wall clock
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Manually advanced clock for deterministic tests. Clones share one time.
+#[derive(Clone, Debug, Default)]
+pub struct MockClock {
+    now: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    pub fn new(start_ms: u64) -> Self {
+        Self { now: Arc::new(AtomicU64::new(start_ms)) }
+    }
+    
+    pub fn set(&self, ms: u64) {
+        self.now.store(ms, Ordering::SeqCst);
+    }
+    
+    pub fn advance(&self, ms: u64) {
+        self.now.fetch_add(ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_millis(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_from_words_tracks_shared_words() {
+        let base = Fingerprint::from_words("borrowck rejects the cache lookup");
+        let related = Fingerprint::from_words("split the cache lookup borrow");
+        let unrelated = Fingerprint::from_words("flaky network tests on ci");
+        assert!(base.similarity(&related) > base.similarity(&unrelated) + 0.1);
+        assert_eq!(base, Fingerprint::from_words("Borrowck rejects the CACHE lookup!"));
+        assert_eq!(Fingerprint::from_words("a b"), Fingerprint::zero());
+    }
+    
     #[test]
     fn test_deterministic() {
         let fp1 = Fingerprint::from_content("hello");
         let fp2 = Fingerprint::from_content("hello");
         assert_eq!(fp1, fp2);
+        assert_eq!(Fingerprint::from_seed(Fingerprint::content_seed("hello")), fp1);
     }
     
     #[test]
@@ -181,4 +370,21 @@ mod tests {
         let recovered = bound.unbind(&a);
         assert_eq!(recovered, b);
     }
+    
+    #[test]
+    fn test_compact_string_round_trip() {
+        for fp in [Fingerprint::from_content("hello"), Fingerprint::zero(), Fingerprint::from_raw([u64::MAX; FINGERPRINT_U64])] {
+            let s = fp.to_compact_string();
+            assert_eq!(s.len(), FINGERPRINT_COMPACT_LEN);
+            assert_eq!(Fingerprint::from_compact_string(&s).unwrap(), fp);
+        }
+        assert_eq!(Fingerprint::zero().to_compact_string(), "A".repeat(FINGERPRINT_COMPACT_LEN));
+    }
+    
+    #[test]
+    fn test_compact_string_rejects_garbage() {
+        assert_eq!(Fingerprint::from_compact_string("abc"), Err(ParseFingerprintError::InvalidLength(3)));
+        let bad = format!("{}!", "A".repeat(FINGERPRINT_COMPACT_LEN - 1));
+        assert_eq!(Fingerprint::from_compact_string(&bad), Err(ParseFingerprintError::InvalidCharacter('!')));
+    }
 }
@@ -0,0 +1,122 @@
+//! Agent attribution — which agent wrote what on a blackboard shared by
+//! several
+
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use crate::learning::blackboard::Blackboard;
+
+/// Name writes are attributed to when no agent was set
+pub const UNATTRIBUTED_AGENT: &str = "unattributed";
+
+/// An agent writing to blackboards: its name and, to tell running copies of
+/// it apart, an optional instance suffix. Displays as "name" or
+/// "name#instance".
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct AgentId {
+    pub name: String,
+    #[serde(default)]
+    pub instance: Option<String>,
+}
+
+impl AgentId {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), instance: None }
+    }
+    
+    pub fn with_instance(mut self, instance: &str) -> Self {
+        self.instance = Some(instance.to_string());
+        self
+    }
+    
+    pub fn unattributed() -> Self {
+        Self::new(UNATTRIBUTED_AGENT)
+    }
+    
+    /// Decisions, questions, next steps and log entries of the unattributed
+    /// agent are written without their `agent`
+    pub fn is_unattributed(&self) -> bool {
+        self.name == UNATTRIBUTED_AGENT && self.instance.is_none()
+    }
+}
+
+impl Default for AgentId {
+    fn default() -> Self { Self::unattributed() }
+}
+
+impl fmt::Display for AgentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.instance {
+            Some(instance) => write!(f, "{}#{}", self.name, instance),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// What one agent wrote, from `Blackboard::contributions_by_agent`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentContributions {
+    /// Including decisions in `decision_conflicts`
+    pub decisions: usize,
+    pub questions: usize,
+    pub next_steps: usize,
+    pub log_entries: usize,
+}
+
+impl fmt::Display for AgentContributions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} decisions, {} questions, {} next steps, {} log entries",
+            self.decisions, self.questions, self.next_steps, self.log_entries)
+    }
+}
+
+impl Blackboard {
+    /// What each agent that wrote to the blackboard contributed. Entries
+    /// folded into `compacted_log` are no longer counted.
+    pub fn contributions_by_agent(&self) -> BTreeMap<AgentId, AgentContributions> {
+        let mut by_agent: BTreeMap<AgentId, AgentContributions> = BTreeMap::new();
+        for decision in self.decisions.iter().chain(self.decision_conflicts.iter().flat_map(|c| &c.decisions)) {
+            by_agent.entry(decision.agent.clone()).or_default().decisions += 1;
+        }
+        for question in &self.questions {
+            by_agent.entry(question.agent.clone()).or_default().questions += 1;
+        }
+        for step in &self.next_steps {
+            by_agent.entry(step.agent.clone()).or_default().next_steps += 1;
+        }
+        for entry in &self.log {
+            by_agent.entry(entry.agent.clone()).or_default().log_entries += 1;
+        }
+        by_agent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cognitive::GateState;
+    use crate::learning::BbMergePolicy;
+    
+    #[test]
+    fn test_display() {
+        assert_eq!(AgentId::default().to_string(), "unattributed");
+        assert_eq!(AgentId::new("planner").with_instance("2").to_string(), "planner#2");
+    }
+    
+    #[test]
+    fn test_contributions_survive_merge() {
+        let planner = AgentId::new("planner");
+        let mut ours = Blackboard::new("s-1", "parser", "Parser rejects UTF-8 input").with_agent(planner.clone());
+        ours.record_decision("lexer", "Track byte offsets", "cheap", GateState::Flow);
+        ours.add_question("Do tabs count as one column?");
+        let mut theirs = Blackboard::new("s-2", "parser", "Parser rejects UTF-8 input");
+        theirs.record_decision("lexer", "Track char offsets", "columns", GateState::Flow);
+        theirs.add_next_step("Benchmark the lexer", 1);
+        
+        let merged = ours.merge(&theirs, &BbMergePolicy::new()).unwrap();
+        assert_eq!(merged.contributions_by_agent(), BTreeMap::from([
+            (planner, AgentContributions { decisions: 1, questions: 1, next_steps: 0, log_entries: 3 }),
+            (AgentId::unattributed(), AgentContributions { decisions: 1, questions: 0, next_steps: 1, log_entries: 2 }),
+        ]), "the conflicting decisions keep their agents; the merge itself is logged by ours");
+    }
+}
@@ -0,0 +1,158 @@
+//! ArchivedMoment — compact, long-term form of a moment
+//!
+//! Keeps what resonance and attribution need and drops the rest: edit
+//! history, artifacts, links, raw content and thinking style.
+
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use crate::core::Fingerprint;
+use crate::learning::moment::{Moment, MomentType, Qualia, summarize_content};
+
+/// Content summaries in archives are capped at this many characters by default
+pub const DEFAULT_ARCHIVE_SUMMARY_CHARS: usize = 200;
+
+/// A content fingerprint, stored as the seed it was expanded from when
+/// possible; 8 bytes instead of 1,250
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArchivedFingerprint {
+    Seed(u64),
+    Full(Box<Fingerprint>),
+}
+
+impl ArchivedFingerprint {
+    /// `Seed` if `fingerprint` is `Fingerprint::from_content(content)`
+    pub fn of(fingerprint: &Fingerprint, content: &str) -> Self {
+        let seed = Fingerprint::content_seed(content);
+        if Fingerprint::from_seed(seed) == *fingerprint {
+            ArchivedFingerprint::Seed(seed)
+        } else {
+            ArchivedFingerprint::Full(Box::new(fingerprint.clone()))
+        }
+    }
+    
+    pub fn expand(&self) -> Fingerprint {
+        match self {
+            ArchivedFingerprint::Seed(seed) => Fingerprint::from_seed(*seed),
+            ArchivedFingerprint::Full(fingerprint) => (**fingerprint).clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedMoment {
+    pub id: String,
+    pub session_id: String,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub subtask_id: Option<String>,
+    pub seq: u64,
+    pub created_at: u64,
+    pub moment_type: MomentType,
+    /// Content, summarized when longer than the archive cap
+    pub summary: String,
+    pub fingerprint: ArchivedFingerprint,
+    pub qualia: Qualia,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl ArchivedMoment {
+    pub fn from_moment(moment: &Moment, max_summary_chars: usize) -> Self {
+        Self {
+            id: moment.id.clone(),
+            session_id: moment.session_id.clone(),
+            parent_id: moment.parent_id.clone(),
+            subtask_id: moment.subtask_id.clone(),
+            seq: moment.seq,
+            created_at: moment.created_at,
+            moment_type: moment.moment_type.clone(),
+            summary: summarize_content(&moment.content, max_summary_chars).unwrap_or_else(|| moment.content.clone()),
+            fingerprint: ArchivedFingerprint::of(&moment.fingerprint, &moment.content),
+            qualia: moment.qualia.clone(),
+            tags: moment.tags.clone(),
+            metadata: moment.metadata.clone(),
+        }
+    }
+    
+    /// `Moment::is_breakthrough`, except that custom moments never count:
+    /// the archive does not keep their breakthrough eligibility
+    pub fn is_breakthrough(&self) -> bool {
+        match &self.moment_type {
+            MomentType::Breakthrough => true,
+            MomentType::Custom(_) => false,
+            _ => self.qualia.is_breakthrough(),
+        }
+    }
+    
+    /// The moment's content fingerprint
+    pub fn content_fingerprint(&self) -> Fingerprint {
+        self.fingerprint.expand()
+    }
+    
+    /// Equal to the original moment's `resonance_vector`
+    pub fn resonance_vector(&self) -> Fingerprint {
+        self.qualia.weight_fingerprint(&self.content_fingerprint())
+    }
+    
+    /// Equal to the original moment's `feeling_fingerprint`
+    pub fn feeling_fingerprint(&self) -> Fingerprint {
+        self.content_fingerprint().bind(&self.qualia.to_fingerprint())
+    }
+    
+    /// Similarity of `query` to the resonance vector, as `find_resonant` scores it
+    pub fn similarity(&self, query: &Fingerprint) -> f32 {
+        query.similarity(&self.resonance_vector())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::MomentBuilder;
+    
+    fn full_moment() -> Moment {
+        let trace: String = (0..40).map(|i| format!("frame {i}: at parser::lex (src/lexer.rs:{i})\n")).collect();
+        let mut moment = MomentBuilder::new("s", &trace)
+            .struggle()
+            .qualia(0.3, 0.8, 0.2)
+            .tag("lexer")
+            .meta("commit", "3f2a9c1").unwrap()
+            .file_lines("src/lexer.rs", 1, 40)
+            .snippet("rust", &"let x = 1;\n".repeat(30))
+            .build();
+        moment.edit_content(&format!("{trace}(edited)"));
+        moment
+    }
+    
+    #[test]
+    fn test_archive_is_small() {
+        let moment = full_moment();
+        let archived = moment.to_archive();
+        
+        assert!(serde_json::to_string(&moment).unwrap().len() > 4_000);
+        let json = serde_json::to_string(&archived).unwrap();
+        assert!(json.len() < 1_024, "archived moment is {} bytes", json.len());
+        assert!(archived.summary.chars().count() <= DEFAULT_ARCHIVE_SUMMARY_CHARS);
+        assert!(matches!(archived.fingerprint, ArchivedFingerprint::Seed(_)));
+        assert_eq!(serde_json::from_str::<ArchivedMoment>(&json).unwrap(), archived);
+    }
+    
+    #[test]
+    fn test_archive_keeps_fingerprints() {
+        let moment = full_moment();
+        let archived: ArchivedMoment = serde_json::from_str(&serde_json::to_string(&moment.to_archive()).unwrap()).unwrap();
+        assert_eq!(archived.content_fingerprint(), moment.fingerprint);
+        assert_eq!(archived.resonance_vector(), moment.resonance_vector);
+        assert_eq!(archived.feeling_fingerprint(), moment.feeling_fingerprint());
+        assert_eq!(archived.similarity(&moment.resonance_vector), 1.0);
+        
+        let mut reindexed = MomentBuilder::new("s", "byte offsets in the lexer").build();
+        reindexed.fingerprint = Fingerprint::from_words(&reindexed.content);
+        let archived = reindexed.to_archive();
+        assert!(matches!(archived.fingerprint, ArchivedFingerprint::Full(_)));
+        assert_eq!(archived.content_fingerprint(), reindexed.fingerprint);
+    }
+}
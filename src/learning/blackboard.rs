@@ -1,11 +1,22 @@
 //! Blackboard — Persistent session state for agent handoffs
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
 use serde::{Serialize, Deserialize};
-use crate::cognitive::GateState;
-use crate::learning::session::{SessionState, IceCakedDecision};
+use thiserror::Error;
+use crate::cognitive::{CollapseAction, CollapseDecision, GateState};
+use crate::core::{Clock, Fingerprint, SharedClock};
+use crate::nars::TruthValue;
+use crate::learning::agent::AgentId;
+use crate::learning::handover::{HandoverTemplate, Section};
+use crate::learning::moment::{Artifact, LinkKind, Moment, MomentType, SourceLocation};
+use crate::learning::redact::Redactor;
+use crate::learning::reuse::ReuseReport;
+use crate::learning::risk::{default_risk_frustration, Risk, DEFAULT_RISK_FRUSTRATION};
+use crate::learning::session::{LearningSession, SessionPhase, SessionState, IceCakedDecision, KeyMoment, PhaseOverride, SessionMetrics, SessionOutcome, StyleShare};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct IceCakedLayer {
     pub layer_id: u32,
     pub decision_id: String,
@@ -13,27 +24,79 @@ pub struct IceCakedLayer {
     pub rationale: String,
     pub gate_state: String,
     pub ice_caked_at_cycle: u64,
+    #[serde(default)]
+    pub sources: Vec<SourceLocation>,
+    /// Reversal moment of the previous freeze, for re-frozen decisions
+    #[serde(default)]
+    pub after_reversal: Option<String>,
+    #[serde(default = "TruthValue::certain_true")]
+    pub truth: TruthValue,
+    /// Decision id of the layer that replaced this one
+    #[serde(default)]
+    pub superseded_by: Option<String>,
+    /// Phase the frozen moment was recorded in
+    #[serde(default)]
+    pub phase: Option<SessionPhase>,
+}
+
+/// "FLOW", "HOLD" or "BLOCK"
+fn gate_label(gate: GateState) -> &'static str {
+    match gate {
+        GateState::Flow => "FLOW",
+        GateState::Hold => "HOLD",
+        GateState::Block => "BLOCK",
+    }
+}
+
+/// The gate of a `gate_label`
+pub(crate) fn gate_from_label(label: &str) -> Option<GateState> {
+    match label {
+        "FLOW" => Some(GateState::Flow),
+        "HOLD" => Some(GateState::Hold),
+        "BLOCK" => Some(GateState::Block),
+        _ => None,
+    }
 }
 
 impl From<&IceCakedDecision> for IceCakedLayer {
     fn from(d: &IceCakedDecision) -> Self {
-        let gate_state = match d.gate_state {
-            GateState::Flow => "FLOW",
-            GateState::Hold => "HOLD",
-            GateState::Block => "BLOCK",
-        };
         Self {
             layer_id: 0,
             decision_id: d.moment_id.clone(),
             content: d.content.clone(),
             rationale: d.rationale.clone(),
-            gate_state: gate_state.to_string(),
+            gate_state: gate_label(d.gate_state).to_string(),
             ice_caked_at_cycle: d.ice_caked_at_cycle,
+            sources: d.sources.clone(),
+            after_reversal: d.after_reversal.clone(),
+            truth: d.truth.clone(),
+            superseded_by: d.superseded_by.clone(),
+            phase: d.phase.clone(),
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// `Fingerprint::from_words` similarity a layer's content needs with the
+/// query of `Blackboard::find_ice_caked`
+pub const DEFAULT_ICE_CAKED_SEARCH_SIMILARITY: f32 = 0.6;
+
+/// Layers shown in the handover summary's load-bearing section unless its
+/// `SectionSpec::max_items` says otherwise
+pub const DEFAULT_LOAD_BEARING_LAYERS: usize = 5;
+
+/// Order of `Blackboard::ice_caked_ordered_by`
+#[derive(Clone, Copy, Debug)]
+pub enum OrderBy<'a> {
+    /// Latest ice-caked first
+    Recency,
+    /// Highest truth confidence first, then latest
+    Confidence,
+    /// Content most similar to the fingerprint first, compared as
+    /// `Fingerprint::from_words`
+    Similarity(&'a Fingerprint),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Decision {
     pub id: String,
     pub task: String,
@@ -42,6 +105,183 @@ pub struct Decision {
     pub gate_state: String,
     pub ice_caked: bool,
     pub cycle: u64,
+    /// Id of the decision this one revised
+    #[serde(default)]
+    pub supersedes: Option<String>,
+    /// Id of the decision that revised this one
+    #[serde(default)]
+    pub superseded_by: Option<String>,
+    /// Moments the decision came from; see `Blackboard::decisions_from_moment`
+    #[serde(default)]
+    pub provenance: Vec<Provenance>,
+    /// Agent that recorded the decision
+    #[serde(default, skip_serializing_if = "AgentId::is_unattributed")]
+    pub agent: AgentId,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProvenanceKind {
+    /// The decision is the moment, ice-caked
+    IceCake,
+    /// The breakthrough that led to the decision
+    Breakthrough,
+}
+
+/// A moment a decision came from; displays as a compact reference for
+/// exports, e.g. "↳ session s-1 / moment 4f2a9c1e…"
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub session_id: String,
+    pub moment_id: String,
+    pub kind: ProvenanceKind,
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let short: String = self.moment_id.chars().take(8).collect();
+        let ellipsis = if short.len() < self.moment_id.len() { "…" } else { "" };
+        write!(f, "↳ session {} / moment {}{}", self.session_id, short, ellipsis)
+    }
+}
+
+/// `Fingerprint::from_words` similarity an ice-caked decision needs with an
+/// expected decision to check it off
+pub const DEFAULT_EXPECTED_DECISION_SIMILARITY: f32 = 0.6;
+
+/// A decision a `SessionTemplate` expects the task to make
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExpectedDecision {
+    pub description: String,
+    /// Layer of the live ice-caked decision that best matches; None while pending
+    #[serde(default)]
+    pub matched_layer: Option<u32>,
+}
+
+/// Handle returned by `Blackboard::add_question`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct QuestionId(pub String);
+
+impl QuestionId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for QuestionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A question tracked on the blackboard until it is resolved
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Question {
+    pub id: QuestionId,
+    pub text: String,
+    /// Hypothesis moment the question was raised from
+    #[serde(default)]
+    pub moment_id: Option<String>,
+    /// Who is expected to answer it; see `Blackboard::assign_question`
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub resolution: Option<QuestionResolution>,
+    /// Agent that asked it
+    #[serde(default, skip_serializing_if = "AgentId::is_unattributed")]
+    pub agent: AgentId,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuestionResolution {
+    pub text: String,
+    /// Moment that answered the question, e.g. a verification
+    pub moment_id: Option<String>,
+    pub cycle: u64,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QuestionError {
+    #[error("no question {0} on the blackboard")]
+    UnknownQuestion(QuestionId),
+    #[error("question {0} is already resolved")]
+    AlreadyResolved(QuestionId),
+}
+
+/// Priority of a next step when nothing makes it more or less urgent; 0 is
+/// the most urgent
+pub const DEFAULT_NEXT_STEP_PRIORITY: u8 = 2;
+
+/// Handle returned by `Blackboard::add_next_step`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NextStepId(pub String);
+
+impl NextStepId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for NextStepId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NextStep {
+    pub id: NextStepId,
+    pub text: String,
+    /// 0 is the most urgent
+    pub priority: u8,
+    /// Suggested by `Blackboard::suggest_next_steps` rather than added
+    #[serde(default)]
+    pub auto_generated: bool,
+    /// Struggle or hypothesis a suggested step came from
+    #[serde(default)]
+    pub moment_id: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+    /// Agent that added or suggested it
+    #[serde(default, skip_serializing_if = "AgentId::is_unattributed")]
+    pub agent: AgentId,
+}
+
+impl NextStep {
+    fn new(text: &str, priority: u8, agent: AgentId) -> Self {
+        Self {
+            id: NextStepId(uuid::Uuid::new_v4().to_string()),
+            text: text.to_string(),
+            priority,
+            auto_generated: false,
+            moment_id: None,
+            completed: false,
+            agent,
+        }
+    }
+}
+
+/// Next steps were plain strings before they had priorities
+fn next_steps_compat<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<NextStep>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Compat {
+        Step(NextStep),
+        Text(String),
+    }
+    Ok(Vec::<Compat>::deserialize(deserializer)?.into_iter()
+        .map(|step| match step {
+            Compat::Step(step) => step,
+            Compat::Text(text) => NextStep::new(&text, DEFAULT_NEXT_STEP_PRIORITY, AgentId::default()),
+        })
+        .collect())
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum NextStepError {
+    #[error("no next step {0} on the blackboard")]
+    UnknownNextStep(NextStepId),
+    #[error("next step {0} is already completed")]
+    AlreadyCompleted(NextStepId),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -50,6 +290,9 @@ pub struct TaskState {
     pub description: String,
     pub phase: String,
     pub progress: f32,
+    /// Gate of the latest struggle, e.g. "BLOCK"
+    #[serde(default)]
+    pub gate_state: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -60,31 +303,286 @@ pub struct ConsciousnessState {
     pub emergence: f32,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AttachedArtifact {
+    /// Moment the artifact was attached to; None for `Blackboard::add_artifact`
+    pub moment_id: Option<String>,
+    pub artifact: Artifact,
+}
+
+/// What two artifacts must share to count as one on a blackboard: the path
+/// of a file, whatever its lines; a URL, case-sensitive but without
+/// trailing slashes; or the whole snippet
+fn artifact_key(artifact: &Artifact) -> (&'static str, String) {
+    let key = match artifact {
+        Artifact::FilePath { path, .. } => path.clone(),
+        Artifact::Url(url) => url.trim_end_matches('/').to_string(),
+        Artifact::Snippet { language, text } => format!("{}\n{}", language, text),
+    };
+    (artifact.kind(), key)
+}
+
+/// Entries `Blackboard::log` keeps before compacting the oldest
+pub const DEFAULT_LOG_CAPACITY: usize = 200;
+
+/// Latest log entries the handover summary and Markdown export show
+pub const DEFAULT_LOG_EXPORT_ENTRIES: usize = 10;
+
+fn default_log_capacity() -> usize {
+    DEFAULT_LOG_CAPACITY
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LogCategory {
+    /// `update_from_session`
+    StateUpdate,
+    Decision,
+    Question,
+    NextStep,
+    Risk,
+    /// The blackboard handed over between sessions
+    Sync,
+    /// `Blackboard::note`
+    Note,
+}
+
+impl fmt::Display for LogCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::StateUpdate => "state update",
+            Self::Decision => "decision",
+            Self::Question => "question",
+            Self::NextStep => "next step",
+            Self::Risk => "risk",
+            Self::Sync => "sync",
+            Self::Note => "note",
+        })
+    }
+}
+
+/// One write to the blackboard, in `Blackboard::log`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Clock time of the write
+    pub at: u64,
+    pub category: LogCategory,
+    pub text: String,
+    /// Agent that made the write
+    #[serde(default, skip_serializing_if = "AgentId::is_unattributed")]
+    pub agent: AgentId,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "t={}ms {}: {}", self.at, self.category, self.text)
+    }
+}
+
+/// Log entries compacted away once the log reached its capacity
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogSummary {
+    pub entries: usize,
+    /// Clock times of the oldest and newest compacted entry
+    pub from: u64,
+    pub until: u64,
+    pub counts: BTreeMap<LogCategory, usize>,
+}
+
+impl fmt::Display for LogSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts: Vec<String> = self.counts.iter().map(|(category, n)| format!("{} {}", n, category)).collect();
+        write!(f, "{} entries compacted, t={}ms to t={}ms: {}", self.entries, self.from, self.until, counts.join(", "))
+    }
+}
+
+/// `Fingerprint::from_words` similarity of two decisions' tasks at which
+/// `Blackboard::merge` treats them as the same topic
+pub const DEFAULT_DECISION_TOPIC_SIMILARITY: f32 = 0.9;
+
+/// How `Blackboard::merge` combines two blackboards
+#[derive(Clone, Debug, PartialEq)]
+pub struct BbMergePolicy {
+    /// Let blackboards of different tasks merge
+    pub allow_cross_task: bool,
+    pub topic_similarity: f32,
+}
+
+impl BbMergePolicy {
+    pub fn new() -> Self {
+        Self { allow_cross_task: false, topic_similarity: DEFAULT_DECISION_TOPIC_SIMILARITY }
+    }
+}
+
+impl Default for BbMergePolicy {
+    fn default() -> Self { Self::new() }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum BbMergeError {
+    #[error("blackboards belong to different tasks ({a} and {b})")]
+    TaskMismatch { a: String, b: String },
+}
+
+/// Current decisions on the same topic with different choices, found by
+/// `Blackboard::merge`; none of them is kept in `decisions`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecisionConflict {
+    /// Task of the first decision
+    pub topic: String,
+    pub decisions: Vec<Decision>,
+}
+
+/// Version written by `Blackboard::to_yaml`; bump on incompatible changes
+pub const BLACKBOARD_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReviseDecisionError {
+    #[error("no decision {0} on the blackboard")]
+    UnknownDecision(String),
+    #[error("decision {id} is already superseded by {by}; revise that one instead")]
+    AlreadySuperseded { id: String, by: String },
+}
+
+#[derive(Debug, Error)]
+pub enum BlackboardParseError {
+    #[error("not a valid blackboard YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("blackboard has schema version {found}, newer than the supported {BLACKBOARD_SCHEMA_VERSION}; upgrade to load it")]
+    UnsupportedVersion { found: u32 },
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Blackboard {
+    /// 0 when loaded from YAML written before the field existed
+    #[serde(default)]
+    pub schema_version: u32,
     pub session_id: String,
+    /// Stamped on the decisions, questions, next steps and log entries
+    /// written through this blackboard; see `with_agent`
+    #[serde(default)]
+    pub agent: AgentId,
     pub current_task: TaskState,
+    /// In the order added; see `pending_next_steps`
+    #[serde(deserialize_with = "next_steps_compat")]
+    pub next_steps: Vec<NextStep>,
     pub consciousness: ConsciousnessState,
     pub decisions: Vec<Decision>,
+    /// From `merge`; resolve by recording the chosen decision again
+    #[serde(default)]
+    pub decision_conflicts: Vec<DecisionConflict>,
     pub ice_cake_layers: Vec<IceCakedLayer>,
     pub files_modified: Vec<String>,
     pub blockers: Vec<String>,
-    pub next_steps: Vec<String>,
+    /// Most severe first; see `add_risk`
+    #[serde(default)]
+    pub risks: Vec<Risk>,
+    /// Frustration of a failure at which `promote_failure_risks` raises a
+    /// risk
+    #[serde(default = "default_risk_frustration")]
+    pub risk_frustration_threshold: f32,
     pub resonance_captures: u64,
     pub concepts_extracted: u64,
+    /// Moment count per type name, custom types included
+    #[serde(default)]
+    pub moment_types: BTreeMap<String, usize>,
+    /// Moment count per tag
+    #[serde(default)]
+    pub tags: BTreeMap<String, usize>,
+    /// Metadata keys whose values are copied from the session and shown in the summary
+    #[serde(default)]
+    pub summary_meta_keys: Vec<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, Vec<String>>,
+    /// Active time, excluding pauses
+    #[serde(default)]
+    pub session_duration_ms: u64,
+    #[serde(default)]
+    pub wall_clock_ms: u64,
+    #[serde(default)]
+    pub longest_gap_ms: u64,
+    #[serde(default)]
+    pub artifacts: Vec<AttachedArtifact>,
+    /// Sparkline per qualia dimension, from the session trajectory
+    #[serde(default)]
+    pub learning_curve: Vec<(String, String)>,
+    /// Most important moments by `LearningSession::top_moments`
+    #[serde(default)]
+    pub key_moments: Vec<KeyMoment>,
+    /// Hypotheses the session has not verified yet
+    #[serde(default)]
+    pub open_questions: Vec<String>,
+    /// Questions from `add_question`, Clarify collapses and hypotheses,
+    /// resolved ones included
+    #[serde(default)]
+    pub questions: Vec<Question>,
+    /// Phase changes forced past the transition graph, with reasons
+    #[serde(default)]
+    pub phase_overrides: Vec<PhaseOverride>,
+    /// Per-phase time, effort and moment counts
+    #[serde(default)]
+    pub metrics: SessionMetrics,
+    /// Moments and time per dominant thinking style
+    #[serde(default)]
+    pub style_distribution: Vec<StyleShare>,
+    /// Name of the `SessionTemplate` the session started from
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Questions from the template, listed with `open_questions`
+    #[serde(default)]
+    pub expected_questions: Vec<String>,
+    #[serde(default)]
+    pub expected_decisions: Vec<ExpectedDecision>,
+    /// How the session ended; None while it is open
+    #[serde(default)]
+    pub outcome: Option<SessionOutcome>,
+    /// Latest struggle no breakthrough resolved
+    #[serde(default)]
+    pub last_open_struggle: Option<String>,
+    /// Concept reuse of the closed session, set by `MetaAGI::end_session`
+    #[serde(default)]
+    pub reuse: Option<ReuseReport>,
+    /// Session this one resumed from a handed-over blackboard
+    #[serde(default)]
+    pub predecessor_session_id: Option<String>,
+    /// Writes to the blackboard, oldest first, at most `log_capacity`
+    #[serde(default)]
+    pub log: Vec<LogEntry>,
+    #[serde(default = "default_log_capacity")]
+    pub log_capacity: usize,
+    /// Entries dropped from the front of `log` to stay within capacity
+    #[serde(default)]
+    pub compacted_log: Option<LogSummary>,
+    /// Size `to_yaml` and `to_json` keep their output under by summarizing
+    /// it; see `budget`
+    #[serde(default)]
+    pub size_budget_bytes: Option<usize>,
+    /// What an export summarized to fit `size_budget_bytes`; only set in
+    /// such an export, never on a live blackboard
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub summarized: Vec<String>,
+    /// Set on the copy `export_redacted` writes: free text went through a
+    /// `Redactor` and session and moment ids are pseudonyms
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,
     pub cycle: u64,
+    #[serde(skip)]
+    clock: SharedClock,
 }
 
 impl Blackboard {
     pub fn new(session_id: &str, task_id: &str, task_description: &str) -> Self {
         Self {
+            schema_version: BLACKBOARD_SCHEMA_VERSION,
             session_id: session_id.to_string(),
+            agent: AgentId::default(),
             current_task: TaskState {
                 id: task_id.to_string(),
                 description: task_description.to_string(),
                 phase: "Initialize".to_string(),
                 progress: 0.0,
+                gate_state: None,
             },
+            next_steps: Vec::new(),
             consciousness: ConsciousnessState {
                 thinking_style: "analytical".to_string(),
                 coherence: 0.0,
@@ -92,22 +590,300 @@ impl Blackboard {
                 emergence: 0.0,
             },
             decisions: Vec::new(),
+            decision_conflicts: Vec::new(),
             ice_cake_layers: Vec::new(),
             files_modified: Vec::new(),
             blockers: Vec::new(),
-            next_steps: Vec::new(),
+            risks: Vec::new(),
+            risk_frustration_threshold: DEFAULT_RISK_FRUSTRATION,
             resonance_captures: 0,
             concepts_extracted: 0,
+            moment_types: BTreeMap::new(),
+            tags: BTreeMap::new(),
+            summary_meta_keys: Vec::new(),
+            metadata: BTreeMap::new(),
+            session_duration_ms: 0,
+            wall_clock_ms: 0,
+            longest_gap_ms: 0,
+            artifacts: Vec::new(),
+            learning_curve: Vec::new(),
+            key_moments: Vec::new(),
+            open_questions: Vec::new(),
+            questions: Vec::new(),
+            phase_overrides: Vec::new(),
+            metrics: SessionMetrics::default(),
+            style_distribution: Vec::new(),
+            template: None,
+            expected_questions: Vec::new(),
+            expected_decisions: Vec::new(),
+            outcome: None,
+            last_open_struggle: None,
+            reuse: None,
+            predecessor_session_id: None,
+            log: Vec::new(),
+            log_capacity: DEFAULT_LOG_CAPACITY,
+            compacted_log: None,
+            size_budget_bytes: None,
+            summarized: Vec::new(),
+            redacted: false,
             cycle: 0,
+            clock: SharedClock::default(),
+        }
+    }
+    
+    /// Writes from now on are attributed to `agent` rather than
+    /// "unattributed"
+    pub fn with_agent(mut self, agent: AgentId) -> Self {
+        self.agent = agent;
+        self
+    }
+    
+    /// Log entries are stamped by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = SharedClock(clock);
+        self
+    }
+    
+    /// Append to `log`, folding the oldest entries into `compacted_log`
+    /// once it holds more than `log_capacity`
+    pub fn append_log(&mut self, category: LogCategory, text: &str) {
+        self.log.push(LogEntry { at: self.clock.0.now_millis(), category, text: text.to_string(), agent: self.agent.clone() });
+        self.compact_log();
+    }
+    
+    fn compact_log(&mut self) {
+        self.fold_log(self.log_capacity.max(1));
+    }
+    
+    /// Fold all but the newest `keep` entries of `log` into `compacted_log`
+    pub(crate) fn fold_log(&mut self, keep: usize) {
+        let excess = self.log.len().saturating_sub(keep);
+        for entry in self.log.drain(..excess) {
+            let summary = self.compacted_log.get_or_insert_with(|| LogSummary { from: entry.at, ..LogSummary::default() });
+            summary.entries += 1;
+            summary.until = entry.at;
+            *summary.counts.entry(entry.category).or_default() += 1;
+        }
+    }
+    
+    /// One blackboard from two kept by agents working the same task, with
+    /// the session, task and state of `self`. Decisions are unioned, except
+    /// that current decisions of the two on the same topic (their tasks'
+    /// `Fingerprint::from_words` similarity reaches
+    /// `policy.topic_similarity`) but with different choices become a
+    /// `DecisionConflict`. Ice-caked decisions, whose task is the
+    /// blackboard's rather than a topic, are never in conflict. Counters,
+    /// moment and tag counts and active time are summed; logs are
+    /// interleaved by time; questions, files, blockers, next steps, risks
+    /// and artifacts are unioned without duplicates, a next step on both
+    /// keeping the more urgent priority and a risk on both the higher
+    /// severity.
+    pub fn merge(&self, other: &Blackboard, policy: &BbMergePolicy) -> Result<Blackboard, BbMergeError> {
+        if self.current_task.id != other.current_task.id && !policy.allow_cross_task {
+            return Err(BbMergeError::TaskMismatch { a: self.current_task.id.clone(), b: other.current_task.id.clone() });
+        }
+        let mut merged = self.clone();
+        let same_topic = |a: &Decision, b: &Decision| {
+            Fingerprint::from_words(&a.task).similarity(&Fingerprint::from_words(&b.task)) >= policy.topic_similarity
+        };
+        for theirs in other.decisions.iter().filter(|d| !self.decisions.iter().any(|ours| ours.id == d.id)) {
+            if theirs.superseded_by.is_some() || theirs.ice_caked {
+                merged.decisions.push(theirs.clone());
+                continue;
+            }
+            let clashing = |ours: &Decision| ours.superseded_by.is_none() && !ours.ice_caked && same_topic(ours, theirs)
+                && self.decisions.iter().any(|known| known.id == ours.id);
+            if merged.decisions.iter().chain(merged.decision_conflicts.iter().flat_map(|c| &c.decisions))
+                .any(|ours| clashing(ours) && ours.choice == theirs.choice) {
+                continue;
+            }
+            if let Some(conflict) = merged.decision_conflicts.iter_mut().find(|c| c.decisions.iter().any(clashing)) {
+                conflict.decisions.push(theirs.clone());
+                continue;
+            }
+            let (mut decisions, kept): (Vec<Decision>, Vec<Decision>) = merged.decisions.drain(..).partition(clashing);
+            merged.decisions = kept;
+            if decisions.is_empty() {
+                merged.decisions.push(theirs.clone());
+            } else {
+                decisions.push(theirs.clone());
+                merged.decision_conflicts.push(DecisionConflict { topic: decisions[0].task.clone(), decisions });
+            }
+        }
+        for layer in other.ice_cake_layers.iter().filter(|l| !self.ice_cake_layers.iter().any(|ours| ours.decision_id == l.decision_id)) {
+            let layer_id = merged.ice_cake_layers.len() as u32 + 1;
+            merged.ice_cake_layers.push(IceCakedLayer { layer_id, ..layer.clone() });
+        }
+        
+        merged.resonance_captures += other.resonance_captures;
+        merged.concepts_extracted += other.concepts_extracted;
+        for (kind, count) in &other.moment_types {
+            *merged.moment_types.entry(kind.clone()).or_default() += count;
+        }
+        for (tag, count) in &other.tags {
+            *merged.tags.entry(tag.clone()).or_default() += count;
+        }
+        merged.session_duration_ms += other.session_duration_ms;
+        merged.wall_clock_ms = self.wall_clock_ms.max(other.wall_clock_ms);
+        merged.longest_gap_ms = self.longest_gap_ms.max(other.longest_gap_ms);
+        merged.current_task.progress = self.current_task.progress.max(other.current_task.progress);
+        merged.cycle = self.cycle.max(other.cycle);
+        
+        let same_question = |a: &str, b: &str| a.trim().eq_ignore_ascii_case(b.trim());
+        for question in &other.questions {
+            match merged.questions.iter_mut().find(|q| q.id == question.id || same_question(&q.text, &question.text)) {
+                Some(known) => {
+                    if known.resolution.is_none() {
+                        known.resolution = question.resolution.clone();
+                    }
+                }
+                None => merged.questions.push(question.clone()),
+            }
+        }
+        let union = |ours: &mut Vec<String>, theirs: &[String]| {
+            for item in theirs {
+                if !ours.iter().any(|known| same_question(known, item)) {
+                    ours.push(item.clone());
+                }
+            }
+        };
+        union(&mut merged.open_questions, &other.open_questions);
+        union(&mut merged.expected_questions, &other.expected_questions);
+        union(&mut merged.files_modified, &other.files_modified);
+        union(&mut merged.blockers, &other.blockers);
+        for step in &other.next_steps {
+            match merged.next_steps.iter_mut().find(|s| s.id == step.id || same_question(&s.text, &step.text)) {
+                Some(known) => {
+                    known.priority = known.priority.min(step.priority);
+                    known.completed |= step.completed;
+                }
+                None => merged.next_steps.push(step.clone()),
+            }
+        }
+        for risk in &other.risks {
+            match merged.risks.iter_mut().find(|r| r.id == risk.id || same_question(&r.text, &risk.text)) {
+                Some(known) => {
+                    known.severity = known.severity.max(risk.severity);
+                    if known.mitigation.is_none() {
+                        known.mitigation = risk.mitigation.clone();
+                    }
+                    if known.closed.is_none() {
+                        known.closed = risk.closed.clone();
+                    }
+                }
+                None => merged.insert_risk(risk.clone()),
+            }
+        }
+        merged.risks.sort_by_key(|risk| std::cmp::Reverse(risk.severity));
+        for attached in &other.artifacts {
+            merged.attach_artifact(attached.moment_id.as_deref(), &attached.artifact);
+        }
+        
+        merged.log.extend(other.log.iter().cloned());
+        merged.log.sort_by_key(|entry| entry.at);
+        merged.compacted_log = match (&self.compacted_log, &other.compacted_log) {
+            (Some(ours), Some(theirs)) => {
+                let mut summary = LogSummary {
+                    entries: ours.entries + theirs.entries,
+                    from: ours.from.min(theirs.from),
+                    until: ours.until.max(theirs.until),
+                    counts: ours.counts.clone(),
+                };
+                for (category, n) in &theirs.counts {
+                    *summary.counts.entry(*category).or_default() += n;
+                }
+                Some(summary)
+            }
+            (ours, theirs) => ours.clone().or_else(|| theirs.clone()),
+        };
+        merged.compact_log();
+        merged.append_log(LogCategory::Sync, &format!("merged the blackboard of session {}", other.session_id));
+        Ok(merged)
+    }
+    
+    /// Point the handover at a file, PR or doc no moment carries. False when
+    /// the blackboard already lists it (see `artifact_key`).
+    pub fn add_artifact(&mut self, artifact: Artifact) -> bool {
+        self.attach_artifact(None, &artifact)
+    }
+    
+    fn attach_artifact(&mut self, moment_id: Option<&str>, artifact: &Artifact) -> bool {
+        let key = artifact_key(artifact);
+        if self.artifacts.iter().any(|attached| artifact_key(&attached.artifact) == key) {
+            return false;
         }
+        self.artifacts.push(AttachedArtifact { moment_id: moment_id.map(str::to_string), artifact: artifact.clone() });
+        true
+    }
+    
+    /// Artifacts per `Artifact::kind`, each in the order added
+    pub fn artifacts_by_kind(&self) -> BTreeMap<&'static str, Vec<&Artifact>> {
+        let mut kinds: BTreeMap<&'static str, Vec<&Artifact>> = BTreeMap::new();
+        for attached in &self.artifacts {
+            kinds.entry(attached.artifact.kind()).or_default().push(&attached.artifact);
+        }
+        kinds
+    }
+    
+    /// A free-form `Note` entry in the log
+    pub fn note(&mut self, text: &str) {
+        self.append_log(LogCategory::Note, text);
+    }
+    
+    /// The last `n` log entries, preceded by a line for everything older
+    pub(crate) fn log_lines(&self, n: usize) -> Vec<String> {
+        let shown = &self.log[self.log.len().saturating_sub(n)..];
+        let older = self.log.len() - shown.len();
+        let mut lines = Vec::new();
+        match (&self.compacted_log, older) {
+            (Some(summary), 0) => lines.push(summary.to_string()),
+            (Some(summary), older) => lines.push(format!("{}; {} more entries not shown", summary, older)),
+            (None, 0) => {}
+            (None, older) => lines.push(format!("{} earlier entries not shown", older)),
+        }
+        lines.extend(shown.iter().map(LogEntry::to_string));
+        lines
     }
     
     pub fn update_from_session(&mut self, state: &SessionState) {
         self.current_task.phase = format!("{:?}", state.phase);
         self.current_task.progress = state.progress;
+        self.current_task.gate_state = state.gate_state.map(|gate| gate_label(gate).to_string());
+        self.ice_cake_layers = state.ice_caked.iter().enumerate()
+            .map(|(i, decision)| IceCakedLayer { layer_id: i as u32 + 1, ..IceCakedLayer::from(decision) })
+            .collect();
+        for decision in &state.ice_caked {
+            self.record_ice_caked(&state.session_id, decision);
+        }
+        self.match_expected_decisions();
+        if !state.thinking_style.is_unset() {
+            self.consciousness.thinking_style = state.thinking_style.dominant().to_string();
+        }
         self.consciousness.coherence = state.coherence;
         self.resonance_captures = state.moment_count as u64;
+        self.moment_types = state.moment_types.clone();
+        self.tags = state.tags.clone();
+        self.metadata = self.summary_meta_keys.iter()
+            .filter_map(|key| state.metadata.get(key).map(|values| (key.clone(), values.clone())))
+            .collect();
+        self.session_duration_ms = state.duration_ms;
+        self.wall_clock_ms = state.wall_clock_ms;
+        self.longest_gap_ms = state.longest_gap_ms;
+        for (moment_id, artifact) in &state.artifacts {
+            self.attach_artifact(Some(moment_id), artifact);
+        }
+        self.key_moments = state.key_moments.clone();
+        self.open_questions = state.open_hypotheses.clone();
+        self.phase_overrides = state.phase_overrides.clone();
+        self.metrics = state.metrics.clone();
+        self.style_distribution = state.style_distribution.clone();
+        self.outcome = state.outcome.clone();
+        self.last_open_struggle = state.last_open_struggle.clone();
+        self.predecessor_session_id = state.predecessor_session_id.clone();
+        self.learning_curve = if state.trajectory.is_empty() { Vec::new() } else { state.trajectory.sparklines() };
         self.cycle = state.cycle;
+        self.append_log(LogCategory::StateUpdate, &format!("phase {} at {:.0}%, moment count {}",
+            self.current_task.phase, state.progress * 100.0, state.moment_count));
     }
     
     pub fn record_decision(&mut self, task: &str, choice: &str, rationale: &str, gate: GateState) {
@@ -119,14 +895,290 @@ impl Blackboard {
             gate_state: format!("{:?}", gate),
             ice_caked: false,
             cycle: self.cycle,
+            supersedes: None,
+            superseded_by: None,
+            provenance: Vec::new(),
+            agent: self.agent.clone(),
         };
+        self.append_log(LogCategory::Decision, &format!("recorded: {}", decision.choice));
         self.decisions.push(decision);
     }
     
+    /// Replace a current decision with `new_choice`, giving `reason` as its
+    /// rationale. The old decision stays on the blackboard, marked
+    /// superseded; the new one keeps its task and gate. Returns the new id.
+    pub fn revise_decision(&mut self, decision_id: &str, new_choice: &str, reason: &str) -> Result<String, ReviseDecisionError> {
+        let old = self.decisions.iter_mut()
+            .find(|d| d.id == decision_id)
+            .ok_or_else(|| ReviseDecisionError::UnknownDecision(decision_id.to_string()))?;
+        if let Some(by) = &old.superseded_by {
+            return Err(ReviseDecisionError::AlreadySuperseded { id: decision_id.to_string(), by: by.clone() });
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        old.superseded_by = Some(id.clone());
+        let old_choice = old.choice.clone();
+        let revised = Decision {
+            id: id.clone(),
+            task: old.task.clone(),
+            choice: new_choice.to_string(),
+            rationale: reason.to_string(),
+            gate_state: old.gate_state.clone(),
+            ice_caked: false,
+            cycle: self.cycle,
+            supersedes: Some(decision_id.to_string()),
+            superseded_by: None,
+            provenance: Vec::new(),
+            agent: self.agent.clone(),
+        };
+        self.append_log(LogCategory::Decision, &format!("revised: {} (was: {})", new_choice, old_choice));
+        self.decisions.push(revised);
+        Ok(id)
+    }
+    
+    /// Point a decision at a moment it came from, e.g. a breakthrough;
+    /// provenance already listed is not added again
+    pub fn add_provenance(&mut self, decision_id: &str, provenance: Provenance) -> Result<(), ReviseDecisionError> {
+        let decision = self.decisions.iter_mut()
+            .find(|d| d.id == decision_id)
+            .ok_or_else(|| ReviseDecisionError::UnknownDecision(decision_id.to_string()))?;
+        if !decision.provenance.contains(&provenance) {
+            decision.provenance.push(provenance);
+        }
+        Ok(())
+    }
+    
+    /// Decisions with `moment_id` in their provenance, in the order recorded
+    pub fn decisions_from_moment(&self, moment_id: &str) -> Vec<&Decision> {
+        self.decisions.iter()
+            .filter(|d| d.provenance.iter().any(|p| p.moment_id == moment_id))
+            .collect()
+    }
+    
+    /// Record an ice-caked decision of `session_id` as a decision, once per
+    /// moment. A decision frozen again on resume is added as provenance of
+    /// the decision recorded for its `prior_moment_id` instead.
+    fn record_ice_caked(&mut self, session_id: &str, ice_caked: &IceCakedDecision) {
+        let frozen_under = |moment_id: &str| self.decisions.iter()
+            .find(|d| d.provenance.iter().any(|p| p.kind == ProvenanceKind::IceCake && p.moment_id == moment_id))
+            .map(|d| d.id.clone());
+        if frozen_under(&ice_caked.moment_id).is_some() {
+            return;
+        }
+        let provenance = Provenance {
+            session_id: session_id.to_string(),
+            moment_id: ice_caked.moment_id.clone(),
+            kind: ProvenanceKind::IceCake,
+        };
+        if let Some(id) = ice_caked.prior_moment_id.as_deref().and_then(frozen_under) {
+            self.add_provenance(&id, provenance).expect("decision was just found");
+            return;
+        }
+        self.decisions.push(Decision {
+            id: uuid::Uuid::new_v4().to_string(),
+            task: self.current_task.id.clone(),
+            choice: ice_caked.content.clone(),
+            rationale: ice_caked.rationale.clone(),
+            gate_state: format!("{:?}", ice_caked.gate_state),
+            ice_caked: true,
+            cycle: ice_caked.ice_caked_at_cycle,
+            supersedes: None,
+            superseded_by: None,
+            provenance: vec![provenance],
+            agent: self.agent.clone(),
+        });
+    }
+    
+    pub fn add_question(&mut self, text: &str) -> QuestionId {
+        let id = QuestionId(uuid::Uuid::new_v4().to_string());
+        self.append_log(LogCategory::Question, &format!("raised: {}", text));
+        self.questions.push(Question {
+            id: id.clone(),
+            text: text.to_string(),
+            moment_id: None,
+            owner: None,
+            resolution: None,
+            agent: self.agent.clone(),
+        });
+        id
+    }
+    
+    /// Make `owner` responsible for answering a question, replacing any owner
+    pub fn assign_question(&mut self, id: &QuestionId, owner: &str) -> Result<(), QuestionError> {
+        let question = self.questions.iter_mut()
+            .find(|q| q.id == *id)
+            .ok_or_else(|| QuestionError::UnknownQuestion(id.clone()))?;
+        question.owner = Some(owner.to_string());
+        let text = format!("assigned to {}: {}", owner, question.text);
+        self.append_log(LogCategory::Question, &text);
+        Ok(())
+    }
+    
+    /// Answer an open question, optionally pointing at the moment that did
+    pub fn resolve_question(&mut self, id: &QuestionId, resolution: &str, moment_id: Option<&str>) -> Result<(), QuestionError> {
+        let cycle = self.cycle;
+        let question = self.questions.iter_mut()
+            .find(|q| q.id == *id)
+            .ok_or_else(|| QuestionError::UnknownQuestion(id.clone()))?;
+        if question.resolution.is_some() {
+            return Err(QuestionError::AlreadyResolved(id.clone()));
+        }
+        question.resolution = Some(QuestionResolution {
+            text: resolution.to_string(),
+            moment_id: moment_id.map(str::to_string),
+            cycle,
+        });
+        let text = format!("resolved: {} → {}", question.text, resolution);
+        self.append_log(LogCategory::Question, &text);
+        Ok(())
+    }
+    
+    /// Open tracked questions, then template questions, then open
+    /// hypotheses no tracked question covers
+    pub fn all_open_questions(&self) -> Vec<&str> {
+        let tracked: Vec<&str> = self.questions.iter().map(|q| q.text.as_str()).collect();
+        self.unresolved_questions().map(|q| q.text.as_str())
+            .chain(self.expected_questions.iter().map(String::as_str))
+            .chain(self.open_questions.iter().map(String::as_str).filter(|q| !tracked.contains(q)))
+            .collect()
+    }
+    
+    /// Questions without a resolution, in the order raised
+    pub fn unresolved_questions(&self) -> impl Iterator<Item = &Question> {
+        self.questions.iter().filter(|q| q.resolution.is_none())
+    }
+    
+    pub fn resolved_questions(&self) -> impl Iterator<Item = &Question> {
+        self.questions.iter().filter(|q| q.resolution.is_some())
+    }
+    
+    /// Raise the question of a `CollapseAction::Clarify`; None for other actions
+    pub fn record_collapse(&mut self, decision: &CollapseDecision) -> Option<QuestionId> {
+        match &decision.action {
+            CollapseAction::Clarify { question } => Some(self.add_question(question)),
+            _ => None,
+        }
+    }
+    
+    /// Raise a question for a Hypothesis moment, once per moment; None for
+    /// other moments and hypotheses already tracked
+    pub fn record_hypothesis(&mut self, moment: &Moment) -> Option<QuestionId> {
+        if moment.moment_type != MomentType::Hypothesis || self.questions.iter().any(|q| q.moment_id.as_deref() == Some(moment.id.as_str())) {
+            return None;
+        }
+        let id = self.add_question(&moment.content);
+        self.questions.last_mut().expect("just added").moment_id = Some(moment.id.clone());
+        Some(id)
+    }
+    
+    /// `record_hypothesis` for each of `session`'s hypotheses, resolving the
+    /// question of a verified one with the verification
+    pub fn track_hypotheses(&mut self, session: &LearningSession) {
+        let moments = session.moments_ordered();
+        for hypothesis in moments.iter().filter(|m| m.moment_type == MomentType::Hypothesis) {
+            self.record_hypothesis(hypothesis);
+            let verification = moments.iter().find(|m| m.links_of(LinkKind::Verifies).any(|target| target == hypothesis.id));
+            let question = self.questions.iter()
+                .find(|q| q.moment_id.as_deref() == Some(hypothesis.id.as_str()) && q.resolution.is_none())
+                .map(|q| q.id.clone());
+            if let (Some(verification), Some(question)) = (verification, question) {
+                self.resolve_question(&question, &verification.content, Some(&verification.id)).expect("question is open");
+            }
+        }
+    }
+    
+    /// Decisions not superseded, in the order recorded
+    pub fn current_decisions(&self) -> impl Iterator<Item = &Decision> {
+        self.decisions.iter().filter(|d| d.superseded_by.is_none())
+    }
+    
+    /// The revision chain through `decision_id`, oldest first; empty for an
+    /// unknown id
+    pub fn decision_history(&self, decision_id: &str) -> Vec<&Decision> {
+        let find = |id: &str| self.decisions.iter().find(|d| d.id == id);
+        let Some(mut first) = find(decision_id) else { return Vec::new() };
+        while let Some(older) = first.supersedes.as_deref().and_then(find) {
+            first = older;
+        }
+        let mut chain = vec![first];
+        while let Some(newer) = chain[chain.len() - 1].superseded_by.as_deref().and_then(find) {
+            chain.push(newer);
+        }
+        chain
+    }
+    
+    /// Add a layer for an ice-caked decision of this blackboard's session,
+    /// and record it as a decision
     pub fn add_ice_cake(&mut self, decision: &IceCakedDecision) {
         let mut layer = IceCakedLayer::from(decision);
         layer.layer_id = self.ice_cake_layers.len() as u32 + 1;
+        self.append_log(LogCategory::Decision, &format!("ice-caked layer {}: {}", layer.layer_id, layer.content));
         self.ice_cake_layers.push(layer);
+        let session_id = self.session_id.clone();
+        self.record_ice_caked(&session_id, decision);
+        self.match_expected_decisions();
+    }
+    
+    /// Live (not superseded) layers in `order`
+    pub fn ice_caked_ordered_by(&self, order: OrderBy) -> Vec<&IceCakedLayer> {
+        let mut layers: Vec<&IceCakedLayer> = self.ice_cake_layers.iter().filter(|l| l.superseded_by.is_none()).collect();
+        let latest = |l: &IceCakedLayer| (l.ice_caked_at_cycle, l.layer_id);
+        match order {
+            OrderBy::Recency => layers.sort_by_key(|l| std::cmp::Reverse(latest(l))),
+            OrderBy::Confidence => layers.sort_by(|a, b| {
+                b.truth.confidence.total_cmp(&a.truth.confidence).then_with(|| latest(b).cmp(&latest(a)))
+            }),
+            OrderBy::Similarity(query) => {
+                let similarity = |l: &IceCakedLayer| query.similarity(&Fingerprint::from_words(&l.content));
+                layers.sort_by(|a, b| similarity(b).total_cmp(&similarity(a)));
+            }
+        }
+        layers
+    }
+    
+    /// Live layers whose content reaches
+    /// `DEFAULT_ICE_CAKED_SEARCH_SIMILARITY` with `query`, most similar first
+    pub fn find_ice_caked(&self, query: &str) -> Vec<(&IceCakedLayer, f32)> {
+        let words = Fingerprint::from_words(query);
+        self.ice_caked_ordered_by(OrderBy::Similarity(&words)).into_iter()
+            .map(|layer| (layer, words.similarity(&Fingerprint::from_words(&layer.content))))
+            .filter(|&(_, similarity)| similarity >= DEFAULT_ICE_CAKED_SEARCH_SIMILARITY)
+            .collect()
+    }
+    
+    /// Layer count per phase their moment was recorded in, as the phase's
+    /// name; layers without a phase are left out
+    pub fn ice_caked_by_phase(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for phase in self.ice_cake_layers.iter().filter_map(|l| l.phase.as_ref()) {
+            *counts.entry(format!("{:?}", phase)).or_default() += 1;
+        }
+        counts
+    }
+    
+    /// Pair each expected decision with the live (not superseded) layer whose
+    /// content and rationale are most similar, if any reaches
+    /// `DEFAULT_EXPECTED_DECISION_SIMILARITY`
+    fn match_expected_decisions(&mut self) {
+        let layers: Vec<(u32, Fingerprint)> = self.ice_cake_layers.iter()
+            .filter(|layer| layer.superseded_by.is_none())
+            .map(|layer| (layer.layer_id, Fingerprint::from_words(&format!("{} {}", layer.content, layer.rationale))))
+            .collect();
+        for expected in &mut self.expected_decisions {
+            let words = Fingerprint::from_words(&expected.description);
+            expected.matched_layer = layers.iter()
+                .map(|(layer_id, fp)| (*layer_id, words.similarity(fp)))
+                .filter(|&(_, similarity)| similarity >= DEFAULT_EXPECTED_DECISION_SIMILARITY)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(layer_id, _)| layer_id);
+        }
+    }
+    
+    /// Expected decisions no ice-caked decision matches yet
+    pub fn unmatched_expected_decisions(&self) -> Vec<&str> {
+        self.expected_decisions.iter()
+            .filter(|expected| expected.matched_layer.is_none())
+            .map(|expected| expected.description.as_str())
+            .collect()
     }
     
     pub fn record_file_modified(&mut self, path: &str) {
@@ -135,44 +1187,1037 @@ impl Blackboard {
         }
     }
     
-    pub fn add_next_step(&mut self, step: &str) {
-        self.next_steps.push(step.to_string());
+    /// Add a step to do next; `priority` 0 is the most urgent
+    pub fn add_next_step(&mut self, text: &str, priority: u8) -> NextStepId {
+        let step = NextStep::new(text, priority, self.agent.clone());
+        self.append_log(LogCategory::NextStep, &format!("added: {} (P{})", text, priority));
+        let id = step.id.clone();
+        self.next_steps.push(step);
+        id
+    }
+    
+    pub fn complete_next_step(&mut self, id: &NextStepId) -> Result<(), NextStepError> {
+        let step = self.next_steps.iter_mut()
+            .find(|step| step.id == *id)
+            .ok_or_else(|| NextStepError::UnknownNextStep(id.clone()))?;
+        if step.completed {
+            return Err(NextStepError::AlreadyCompleted(id.clone()));
+        }
+        step.completed = true;
+        let text = format!("done: {}", step.text);
+        self.append_log(LogCategory::NextStep, &text);
+        Ok(())
+    }
+    
+    /// Steps not completed, most urgent first, then in the order added
+    pub fn pending_next_steps(&self) -> Vec<&NextStep> {
+        let mut pending: Vec<&NextStep> = self.next_steps.iter().filter(|step| !step.completed).collect();
+        pending.sort_by_key(|step| step.priority);
+        pending
+    }
+    
+    /// Suggest a step per unresolved struggle of `session` (priority 1) and
+    /// per open hypothesis (priority 2), once per moment. A pending
+    /// suggestion whose moment is no longer open is completed.
+    pub fn suggest_next_steps(&mut self, session: &LearningSession) {
+        let struggles = session.unresolved_struggles().into_iter().map(|m| (m, "Resolve", 1));
+        let hypotheses = session.open_hypotheses().into_iter().map(|m| (m, "Verify", 2));
+        let open: Vec<(&Moment, &str, u8)> = struggles.chain(hypotheses).collect();
+        let done: Vec<NextStepId> = self.next_steps.iter()
+            .filter(|step| step.auto_generated && !step.completed)
+            .filter(|step| !open.iter().any(|(m, _, _)| step.moment_id.as_deref() == Some(m.id.as_str())))
+            .map(|step| step.id.clone())
+            .collect();
+        for id in &done {
+            self.complete_next_step(id).expect("step is pending");
+        }
+        for (moment, verb, priority) in open {
+            if self.next_steps.iter().any(|step| step.moment_id.as_deref() == Some(moment.id.as_str())) {
+                continue;
+            }
+            let mut step = NextStep::new(&format!("{}: {}", verb, moment.content), priority, self.agent.clone());
+            step.auto_generated = true;
+            step.moment_id = Some(moment.id.clone());
+            self.append_log(LogCategory::NextStep, &format!("suggested: {} (P{})", step.text, priority));
+            self.next_steps.push(step);
+        }
+    }
+    
+    /// Remove the pending suggested steps; returns how many
+    pub fn clear_auto_next_steps(&mut self) -> usize {
+        let before = self.next_steps.len();
+        self.next_steps.retain(|step| !step.auto_generated || step.completed);
+        let cleared = before - self.next_steps.len();
+        if cleared > 0 {
+            self.append_log(LogCategory::NextStep, &format!("cleared {} suggested", cleared));
+        }
+        cleared
+    }
+    
+    /// Copy with every free-text field run through `redactor`
+    pub fn redacted(&self, redactor: &Redactor) -> Blackboard {
+        let r = |s: &String| redactor.redact(s);
+        let mut bb = self.clone();
+        bb.current_task.description = r(&self.current_task.description);
+        for d in bb.decisions.iter_mut().chain(bb.decision_conflicts.iter_mut().flat_map(|c| &mut c.decisions)) {
+            d.task = r(&d.task);
+            d.choice = r(&d.choice);
+            d.rationale = r(&d.rationale);
+        }
+        for conflict in &mut bb.decision_conflicts {
+            conflict.topic = r(&conflict.topic);
+        }
+        for layer in &mut bb.ice_cake_layers {
+            layer.content = r(&layer.content);
+            layer.rationale = r(&layer.rationale);
+            for source in &mut layer.sources {
+                source.path = r(&source.path);
+            }
+        }
+        bb.tags = BTreeMap::new();
+        for (tag, count) in &self.tags {
+            *bb.tags.entry(r(tag)).or_default() += count;
+        }
+        bb.files_modified = self.files_modified.iter().map(r).collect();
+        bb.blockers = self.blockers.iter().map(r).collect();
+        for risk in &mut bb.risks {
+            risk.text = r(&risk.text);
+            risk.mitigation = risk.mitigation.as_ref().map(r);
+            if let Some(closed) = &mut risk.closed {
+                closed.note = r(&closed.note);
+            }
+        }
+        for step in &mut bb.next_steps {
+            step.text = r(&step.text);
+        }
+        bb.open_questions = self.open_questions.iter().map(r).collect();
+        bb.expected_questions = self.expected_questions.iter().map(r).collect();
+        for question in &mut bb.questions {
+            question.text = r(&question.text);
+            question.owner = question.owner.as_ref().map(r);
+            if let Some(resolution) = &mut question.resolution {
+                resolution.text = r(&resolution.text);
+            }
+        }
+        bb.last_open_struggle = self.last_open_struggle.as_ref().map(r);
+        for concept in bb.reuse.iter_mut().flat_map(|reuse| &mut reuse.concepts) {
+            concept.name = r(&concept.name);
+        }
+        if let Some(SessionOutcome::Blocked { reason }) = &mut bb.outcome {
+            *reason = r(reason);
+        }
+        for expected in &mut bb.expected_decisions {
+            expected.description = r(&expected.description);
+        }
+        for o in &mut bb.phase_overrides {
+            o.reason = r(&o.reason);
+        }
+        for values in bb.metadata.values_mut() {
+            *values = values.iter().map(r).collect();
+        }
+        for attached in &mut bb.artifacts {
+            attached.artifact = redactor.redact_artifact(&attached.artifact);
+        }
+        for key in &mut bb.key_moments {
+            key.content = r(&key.content);
+        }
+        for entry in &mut bb.log {
+            entry.text = r(&entry.text);
+        }
+        bb
+    }
+    
+    pub fn to_yaml_redacted(&self, redactor: &Redactor) -> String {
+        self.redacted(redactor).to_yaml()
     }
     
+    /// Summarized to fit `size_budget_bytes` when set
     pub fn to_yaml(&self) -> String {
-        serde_yaml::to_string(self).unwrap_or_default()
+        self.fit_budget(serde_yaml::to_string).unwrap_or_default()
     }
     
-    pub fn to_json(&self) -> String {
-        serde_json::to_string_pretty(self).unwrap_or_default()
+    /// Load a blackboard written by `to_yaml`, e.g. to resume a handed-over
+    /// session. YAML from before `schema_version` existed loads as the
+    /// current version.
+    pub fn from_yaml(yaml: &str) -> Result<Blackboard, BlackboardParseError> {
+        let mut bb: Blackboard = serde_yaml::from_str(yaml)?;
+        if bb.schema_version > BLACKBOARD_SCHEMA_VERSION {
+            return Err(BlackboardParseError::UnsupportedVersion { found: bb.schema_version });
+        }
+        bb.schema_version = BLACKBOARD_SCHEMA_VERSION;
+        Ok(bb)
+    }
+    
+    /// Machine-readable handover, e.g. for a dashboard. The schema is the
+    /// one `to_yaml` writes and is versioned by `schema_version`
+    /// (`BLACKBOARD_SCHEMA_VERSION`): field names are the struct fields and
+    /// stay stable within a version, new fields are only ever added, enums
+    /// are tagged by variant name (`"Completed"`, `{"Blocked": {"reason":
+    /// …}}`), and times are integer milliseconds in fields ending `_ms`.
+    /// Decisions carry their `id`; the task description is under
+    /// `current_task.description`. Summarized to fit `size_budget_bytes`
+    /// when set, with `summarized` saying how.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        self.fit_budget(serde_json::to_string)
     }
     
+    /// `to_json`, indented
+    pub fn to_json_pretty(&self) -> Result<String, serde_json::Error> {
+        self.fit_budget(serde_json::to_string_pretty)
+    }
+    
+    /// `handover_with` the default `HandoverTemplate`: current decisions
+    /// only, with a count of superseded ones
     pub fn handover_summary(&self) -> String {
-        let mut s = String::new();
-        s.push_str(&format!("# Session Handover: {}\n\n", self.session_id));
-        s.push_str("## Current Task\n");
-        s.push_str(&format!("- **ID**: {}\n", self.current_task.id));
-        s.push_str(&format!("- **Phase**: {}\n", self.current_task.phase));
-        s.push_str(&format!("- **Progress**: {:.0}%\n\n", self.current_task.progress * 100.0));
-        
-        if !self.ice_cake_layers.is_empty() {
-            s.push_str("## Ice-Caked (Frozen Commitments) ❄️\n");
-            for layer in &self.ice_cake_layers {
-                s.push_str(&format!("{}. {}\n", layer.layer_id, layer.content));
-                s.push_str(&format!("   Rationale: {}\n", layer.rationale));
-            }
-            s.push_str("\n");
+        self.handover_with(&HandoverTemplate::standard())
+    }
+    
+    /// `handover_summary`, with each decision's revision chain
+    pub fn handover_summary_with_history(&self) -> String {
+        let mut template = HandoverTemplate::standard();
+        for spec in template.sections.iter_mut().filter(|spec| spec.section == Section::Decisions) {
+            spec.section = Section::DecisionHistory;
         }
+        self.handover_with(&template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cognitive::ThinkingStyle;
+    use crate::learning::{AbandonReason, LearningSession, LinkKind, MarkdownOptions, MockClock, MomentBuilder, Qualia, SessionPhase};
+    use crate::nars::TruthValue;
+    
+    #[test]
+    fn test_summary_and_yaml_keep_custom_types() {
+        let mut session = LearningSession::new("task");
+        session.custom_moment("Experiment", "tried arena allocation", Qualia::default());
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
         
-        if !self.next_steps.is_empty() {
-            s.push_str("## Next Steps\n");
-            for (i, step) in self.next_steps.iter().enumerate() {
-                s.push_str(&format!("{}. {}\n", i + 1, step));
-            }
+        assert!(bb.handover_summary().contains("- Experiment: 1"));
+        let yaml = bb.to_yaml();
+        assert!(yaml.contains("Experiment: 1"));
+        let back: Blackboard = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back.moment_types, bb.moment_types);
+    }
+    
+    #[test]
+    fn test_summary_surfaces_selected_meta_keys() {
+        let mut session = LearningSession::new("task");
+        session.encounter("bisecting");
+        session.moments[0].set_meta("commit", "3f2a9c1").unwrap();
+        session.moments[0].set_meta("scratch", "ignore me").unwrap();
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        assert!(!bb.handover_summary().contains("3f2a9c1"));
+        
+        bb.summary_meta_keys = vec!["commit".to_string()];
+        bb.update_from_session(&session.state());
+        let summary = bb.handover_summary();
+        assert!(summary.contains("- commit: 3f2a9c1"));
+        assert!(!summary.contains("ignore me"));
+    }
+    
+    #[test]
+    fn test_yaml_round_trip_awkward_text() {
+        let mut bb = Blackboard::new("s", "täsk: «ünïcode» 🚀", "desc with \"quotes\" and 'apostrophes'");
+        bb.record_decision("db", "key: value\n- not a list\n  indented: yes", "# not a comment, {not: a map}", GateState::Hold);
+        bb.blockers.push("---\n...".to_string());
+        bb.add_next_step("@at & *star !tag %percent", 0);
+        bb.open_questions.push("  leading and trailing spaces  ".to_string());
+        
+        let yaml = bb.to_yaml();
+        let back = Blackboard::from_yaml(&yaml).unwrap();
+        assert_eq!(back.to_yaml(), yaml);
+        assert_eq!(back.decisions[0].choice, "key: value\n- not a list\n  indented: yes");
+        assert_eq!(back.current_task.id, "täsk: «ünïcode» 🚀");
+        assert_eq!(back.schema_version, BLACKBOARD_SCHEMA_VERSION);
+    }
+    
+    #[test]
+    fn test_from_yaml_versions() {
+        let legacy = concat!(
+            "session_id: s\n",
+            "current_task:\n  id: task\n  description: desc\n  phase: Struggle\n  progress: 0.5\n",
+            "consciousness:\n  thinking_style: focused\n  coherence: 0.8\n  dominant_layer: L5\n  emergence: 0.1\n",
+            "decisions: []\nice_cake_layers: []\nfiles_modified: []\nblockers: []\nnext_steps:\n- write tests\n",
+            "resonance_captures: 3\nconcepts_extracted: 1\ncycle: 7\n",
+        );
+        let bb = Blackboard::from_yaml(legacy).unwrap();
+        assert_eq!(bb.schema_version, BLACKBOARD_SCHEMA_VERSION);
+        assert_eq!((bb.current_task.phase.as_str(), bb.cycle), ("Struggle", 7));
+        assert_eq!((bb.next_steps[0].text.as_str(), bb.next_steps[0].priority), ("write tests", DEFAULT_NEXT_STEP_PRIORITY));
+        
+        let newer = format!("schema_version: {}\n{}", BLACKBOARD_SCHEMA_VERSION + 1, legacy);
+        assert!(matches!(Blackboard::from_yaml(&newer), Err(BlackboardParseError::UnsupportedVersion { found }) if found == BLACKBOARD_SCHEMA_VERSION + 1));
+        assert!(matches!(Blackboard::from_yaml("session_id: [unclosed"), Err(BlackboardParseError::Yaml(_))));
+    }
+    
+    #[test]
+    fn test_yaml_redacted() {
+        let redactor = Redactor::new().with_pattern("dsn", "postgres://*").unwrap();
+        let mut bb = Blackboard::new("s", "task", "migrate postgres://admin:pw@db/prod");
+        bb.add_next_step("rotate postgres://admin:pw@db/prod credentials", DEFAULT_NEXT_STEP_PRIORITY);
+        bb.record_decision("db", "use postgres://ro@db/prod", "read only", GateState::Flow);
+        
+        let yaml = bb.to_yaml_redacted(&redactor);
+        assert!(!yaml.contains("admin:pw"));
+        assert!(!yaml.contains("ro@db"));
+        assert!(yaml.contains("rotate [REDACTED:dsn] credentials"));
+        assert!(bb.to_yaml().contains("admin:pw"));
+    }
+    
+    #[test]
+    fn test_key_moments_section() {
+        let mut session = LearningSession::new("task");
+        session.encounter("routine");
+        let id = session.breakthrough("the big one", 0.9).id.clone();
+        session.ice_cake(&id, "keep").unwrap();
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        let summary = bb.handover_summary();
+        let section = summary.split("## Key Moments\n").nth(1).unwrap();
+        assert!(section.starts_with("- the big one ("));
+    }
+    
+    #[test]
+    fn test_learning_curve_section() {
+        let mut session = LearningSession::new("task");
+        session.struggle("stuck", 1.0, 1.0);
+        session.breakthrough("free", 1.0);
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        let summary = bb.handover_summary();
+        assert!(summary.contains("## Learning Curve\n"));
+        assert!(summary.contains("- frustration  █▁\n"));
+    }
+    
+    #[test]
+    fn test_tags_section() {
+        let mut session = LearningSession::new("task");
+        let a = session.encounter("login loops").id.clone();
+        let b = session.encounter("token refresh").id.clone();
+        session.tag(&a, "perf");
+        session.tag(&a, "Auth");
+        session.tag(&b, "#auth");
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        
+        assert!(bb.handover_summary().contains("## Tags\n| Tag | Moments |\n|---|---|\n| #auth | 2 |\n| #perf | 1 |\n"));
+        let back: Blackboard = serde_yaml::from_str(&bb.to_yaml()).unwrap();
+        assert_eq!(back.tags, bb.tags);
+    }
+    
+    #[test]
+    fn test_yaml_export_uses_content_summary() {
+        let mut session = LearningSession::new("task");
+        session.config.max_content_chars = 100;
+        let trace = format!("error[E0502]: cannot borrow\n{}  = note: borrow later used here", "  | secret middle frame\n".repeat(50));
+        let id = session.struggle(&trace, 0.9, 0.9).id.clone();
+        session.ice_cake(&id, "keep").unwrap();
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        
+        let yaml = bb.to_yaml();
+        assert!(yaml.contains("50 lines omitted"));
+        assert!(!yaml.contains("secret middle frame"));
+    }
+    
+    #[test]
+    fn test_phase_overrides_section() {
+        let mut session = LearningSession::new("task");
+        session.encounter("parser rejects UTF-8");
+        session.force_phase(SessionPhase::Consolidate, "fix known from ticket 42").unwrap();
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        
+        assert_eq!(bb.phase_overrides.len(), 1);
+        assert!(bb.handover_summary().contains("## Phase Overrides\n- Encounter → Consolidate: fix known from ticket 42\n"));
+    }
+    
+    #[test]
+    fn test_open_questions_section() {
+        let mut session = LearningSession::new("task");
+        let checked = session.hypothesize("cache is stale", TruthValue::new(0.6, 0.3)).id.clone();
+        session.hypothesize("lock is contended", TruthValue::new(0.5, 0.2));
+        session.verify(&checked, true, "flushing fixed it");
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        
+        assert_eq!(bb.open_questions, vec!["lock is contended".to_string()]);
+        assert!(bb.handover_summary().contains("## Open Questions\n- lock is contended\n"));
+    }
+    
+    #[test]
+    fn test_artifacts_section() {
+        let mut session = LearningSession::new("task");
+        session.encounter("found it");
+        session.moments[0].artifacts = MomentBuilder::new("s", "")
+            .file_lines("version.rb", 42, 42)
+            .url("https://example.com/issues/3")
+            .build()
+            .artifacts;
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        
+        let summary = bb.handover_summary();
+        assert!(summary.contains("## Artifacts\n- version.rb:42\n- https://example.com/issues/3\n"));
+        let yaml = bb.to_yaml();
+        assert!(yaml.contains("artifacts:"));
+        assert!(yaml.contains("!FilePath"));
+        let back: Blackboard = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back.artifacts, bb.artifacts);
+    }
+    
+    #[test]
+    fn test_artifacts_aggregate_across_syncs() {
+        let mut session = LearningSession::new("task");
+        session.encounter("found it");
+        session.moments[0].artifacts = MomentBuilder::new("s", "")
+            .file_lines("src/version.rb", 42, 42)
+            .url("https://example.com/pull/7/")
+            .build()
+            .artifacts;
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        assert!(bb.add_artifact(Artifact::url("https://example.com/docs/Design")));
+        
+        session.struggle("still failing", 0.5, 0.5);
+        let last = session.moments.len() - 1;
+        session.moments[last].artifacts = MomentBuilder::new("s", "")
+            .file_lines("src/version.rb", 50, 60)
+            .url("https://example.com/pull/7")
+            .url("https://example.com/docs/design")
+            .snippet("ruby", "x = 1")
+            .build()
+            .artifacts;
+        bb.update_from_session(&session.state());
+        bb.update_from_session(&session.state());
+        
+        let listed: Vec<String> = bb.artifacts.iter().map(|attached| attached.artifact.to_string()).collect();
+        assert_eq!(listed, [
+            "src/version.rb:42",
+            "https://example.com/pull/7/",
+            "https://example.com/docs/Design",
+            "https://example.com/docs/design",
+            "ruby snippet (1 lines)",
+        ]);
+        assert_eq!(bb.artifacts[0].moment_id.as_deref(), Some(session.moments[0].id.as_str()));
+        assert_eq!(bb.artifacts[2].moment_id, None);
+        assert!(!bb.add_artifact(Artifact::url("https://example.com/pull/7//")));
+        assert!(!bb.add_artifact(Artifact::file("src/version.rb")));
+        assert!(!bb.add_artifact(Artifact::snippet("ruby", "x = 1")));
+        
+        let kinds = bb.artifacts_by_kind();
+        assert_eq!(kinds.keys().copied().collect::<Vec<_>>(), ["file", "snippet", "url"]);
+        assert_eq!(kinds["url"].len(), 3);
+        let markdown = bb.to_markdown(&MarkdownOptions::default());
+        assert!(markdown.contains(concat!(
+            "## Artifacts\n",
+            "\n",
+            "- file: `src/version.rb:42`\n",
+            "- snippet: ruby snippet (1 lines)\n",
+            "- url: <https://example.com/pull/7/>\n",
+            "- url: <https://example.com/docs/Design>\n",
+            "- url: <https://example.com/docs/design>\n",
+        )), "{markdown}");
+        let json: serde_json::Value = serde_json::from_str(&bb.to_json().unwrap()).unwrap();
+        assert_eq!(json["artifacts"][2], serde_json::json!({ "moment_id": null, "artifact": { "Url": "https://example.com/docs/Design" } }));
+        assert_eq!(Blackboard::from_yaml(&bb.to_yaml()).unwrap().artifacts, bb.artifacts);
+    }
+    
+    #[test]
+    fn test_summary_reports_duration_and_gap() {
+        let clock = crate::learning::MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", std::sync::Arc::new(clock.clone()));
+        session.encounter("start");
+        clock.advance(2_500);
+        session.struggle("hmm", 0.5, 0.5);
+        clock.advance(500);
+        session.breakthrough("got it", 0.9);
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        
+        let summary = bb.handover_summary();
+        assert!(summary.contains("- Session Duration: 3.0s active / 3.0s wall clock"));
+        assert!(summary.contains("- Longest Gap: 2.5s"));
+    }
+    
+    #[test]
+    fn test_summary_separates_active_and_wall_clock_time() {
+        let clock = crate::learning::MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", std::sync::Arc::new(clock.clone()));
+        session.encounter("start");
+        clock.advance(1_000);
+        session.pause();
+        clock.advance(60_000);
+        session.resume();
+        clock.advance(1_000);
+        session.breakthrough("got it", 0.9);
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        
+        let summary = bb.handover_summary();
+        assert!(summary.contains("- Session Duration: 2.0s active / 62.0s wall clock"));
+        assert!(summary.contains("- Longest Gap: 2.0s"));
+    }
+    
+    #[test]
+    fn test_phase_metrics_table() {
+        let clock = crate::learning::MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", std::sync::Arc::new(clock.clone()));
+        session.encounter("start");
+        clock.advance(2_500);
+        session.struggle("hmm", 0.6, 0.4);
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        assert!(bb.handover_summary().contains(concat!(
+            "## Phase Metrics\n",
+            "| Phase | Moments | Time | Effort (mean/max) | Frustration (mean/max) |\n",
+            "|---|---|---|---|---|\n",
+            "| Encounter | 1 | 2.5s | 0.20/0.20 | 0.00/0.00 |\n",
+            "| Struggle | 1 | 0.0s | 0.60/0.60 | 0.40/0.40 |\n",
+            "- Time to first breakthrough: none yet\n",
+            "\n",
+        )));
+        
+        clock.advance(500);
+        session.breakthrough("got it", 0.9);
+        bb.update_from_session(&session.state());
+        let summary = bb.handover_summary();
+        assert!(summary.contains("- Time to first breakthrough: 3.0s\n- Struggles per breakthrough: 1.00\n"));
+        assert!(!Blackboard::new("s", "task", "desc").handover_summary().contains("## Phase Metrics"));
+    }
+    
+    #[test]
+    fn test_thinking_styles_section() {
+        let mut bb = Blackboard::new("s", "task", "desc");
+        bb.style_distribution = vec![
+            StyleShare { axis: "focused".to_string(), moment_count: 3, duration_ms: 6_000 },
+            StyleShare { axis: "creative".to_string(), moment_count: 1, duration_ms: 2_000 },
+        ];
+        assert!(bb.handover_summary().contains(concat!(
+            "## Thinking Styles\n| Style | Moments | Time |\n|---|---|---|\n",
+            "| focused | 3 (75%) | 6.0s (75%) |\n",
+            "| creative | 1 (25%) | 2.0s (25%) |\n\n",
+        )));
+        
+        let mut session = LearningSession::new("task");
+        session.set_style(ThinkingStyle::creative());
+        session.encounter("sketch options");
+        session.struggle("too many options", 0.6, 0.1);
+        session.struggle("still too many options", 0.6, 0.9);
+        bb.update_from_session(&session.state());
+        assert_eq!(bb.consciousness.thinking_style, "creative");
+        assert_eq!(bb.current_task.gate_state.as_deref(), Some("BLOCK"));
+        assert!(bb.handover_summary().contains("%\n- **Gate**: BLOCK\n\n## "));
+        assert_eq!(bb.style_distribution.len(), 1);
+    }
+    
+    #[test]
+    fn test_outcome_leads_the_summary() {
+        let mut session = LearningSession::new("orm");
+        session.struggle("patching the ORM to scope keys", 0.9, 0.8);
+        let flaky = session.struggle("upgrade test suite flakes", 0.4, 0.3).id.clone();
+        session.breakthrough_resolving(&flaky, "pin the test seed", 0.8);
+        let mut bb = Blackboard::new(&session.id, "orm", "scope keys per project");
+        bb.update_from_session(&session.state());
+        assert!(!bb.handover_summary().contains("## Outcome"));
+        
+        session.close(SessionOutcome::Abandoned { reason: AbandonReason::WrongApproach }).unwrap();
+        bb.update_from_session(&session.state());
+        let summary = bb.handover_summary();
+        assert!(summary.starts_with(&format!(concat!(
+            "# Session Handover: {}\n\n",
+            "## Outcome\n",
+            "- **Abandoned**: wrong approach\n",
+            "- **Last open struggle**: patching the ORM to scope keys\n\n",
+            "## Current Task\n",
+        ), session.id)));
+    }
+    
+    #[test]
+    fn test_expected_decisions_match_by_similarity() {
+        let mut session = LearningSession::new("task");
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.expected_decisions = ["primary key and foreign key scoping", "pagination strategy for the index endpoint"]
+            .map(|description| ExpectedDecision { description: description.to_string(), matched_layer: None })
+            .to_vec();
+        let unrelated = session.breakthrough("cache the compiled templates", 0.9).id.clone();
+        session.ice_cake(&unrelated, "render time").unwrap();
+        let scoping = session.breakthrough("scope the foreign key to the project primary key", 0.9).id.clone();
+        session.ice_cake(&scoping, "key scoping").unwrap();
+        bb.update_from_session(&session.state());
+        
+        assert_eq!(bb.expected_decisions[0].matched_layer, Some(2));
+        assert_eq!(bb.unmatched_expected_decisions(), vec!["pagination strategy for the index endpoint"]);
+        let summary = bb.handover_summary();
+        assert!(summary.contains(concat!(
+            "## Expected Decisions (1 of 2 pending)\n",
+            "- [x] primary key and foreign key scoping (layer 2)\n",
+            "- [ ] pagination strategy for the index endpoint\n",
+        )));
+        
+        bb.expected_questions = vec!["Who may delete records?".to_string()];
+        assert!(bb.handover_summary().contains("## Open Questions\n- Who may delete records?\n"));
+        let back: Blackboard = serde_yaml::from_str(&bb.to_yaml()).unwrap();
+        assert_eq!(back.expected_decisions, bb.expected_decisions);
+    }
+    
+    #[test]
+    fn test_layers_follow_thaw_and_refreeze() {
+        let mut session = LearningSession::new("task");
+        let cache = session.breakthrough("cache the parsed config", 0.9).id.clone();
+        let fix = session.breakthrough("track byte offsets", 0.9).id.clone();
+        session.ice_cake(&cache, "startup is slow").unwrap();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        assert_eq!(bb.ice_cake_layers.len(), 2);
+        
+        session.thaw(&cache, "config changes at runtime").unwrap();
+        bb.update_from_session(&session.state());
+        assert_eq!(bb.ice_cake_layers.len(), 1);
+        assert_eq!((bb.ice_cake_layers[0].layer_id, bb.ice_cake_layers[0].decision_id.as_str()), (1, fix.as_str()));
+        assert_eq!(bb.moment_types.get("DecisionReversal"), Some(&1));
+        
+        session.ice_cake(&cache, "cache with invalidation").unwrap();
+        bb.update_from_session(&session.state());
+        let ids: Vec<u32> = bb.ice_cake_layers.iter().map(|l| l.layer_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(bb.ice_cake_layers[1].after_reversal, session.reversals_of(&cache).last().map(|m| m.id.clone()));
+        assert!(bb.handover_summary().contains("2. cache the parsed config\n   Rationale: cache with invalidation\n"));
+    }
+    
+    #[test]
+    fn test_summary_shows_truth_and_superseded_layers() {
+        let mut session = LearningSession::new("task");
+        let cache = session.breakthrough("cache the parsed config", 0.9).id.clone();
+        let lazy = session.breakthrough("load the config lazily", 0.9).id.clone();
+        session.ice_cake_with_confidence(&cache, "parse the config file once at startup", TruthValue::new(0.9, 0.6)).unwrap();
+        session.ice_cake_with_confidence(&lazy, "parse the config file once, lazily", TruthValue::new(0.8, 0.5)).unwrap();
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        
+        let summary = bb.handover_summary();
+        assert!(summary.contains("   Truth: ⟨90%, 60%⟩ (confidence 0.60)\n   Superseded by layer 2\n"));
+        assert!(summary.contains(&format!("   Truth: {} (confidence {:.2})\n\n", bb.ice_cake_layers[1].truth, bb.ice_cake_layers[1].truth.confidence)));
+        let back: Blackboard = serde_yaml::from_str(&bb.to_yaml()).unwrap();
+        assert_eq!(back.ice_cake_layers, bb.ice_cake_layers);
+    }
+    
+    #[test]
+    fn test_ice_cake_sources_rendered() {
+        let mut session = LearningSession::new("task");
+        let struggle = MomentBuilder::new(&session.id, "offsets mixed up").struggle().source_at("src/lexer.rs", 42);
+        session.add_moment_deduped(struggle.build(), 1.1).unwrap();
+        let struggle_id = session.moments[0].id.clone();
+        let fix = session.encounter_here("track byte offsets").id.clone();
+        session.link(&fix, LinkKind::Resolves, &struggle_id);
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.add_ice_cake(&session.ice_caked[0]);
+        
+        let sources = &bb.ice_cake_layers[0].sources;
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].path, file!());
+        assert_eq!(sources[1], SourceLocation::new("src/lexer.rs", Some(42), None));
+        let summary = bb.handover_summary();
+        assert!(summary.contains(&format!("   Sources:\n   - [{}:", file!())));
+        assert!(summary.contains("   - [src/lexer.rs:42](src/lexer.rs#L42)\n"));
+        
+        let yaml = bb.to_yaml();
+        assert!(yaml.contains("sources:"));
+        assert!(yaml.contains("path: src/lexer.rs"));
+        let back: Blackboard = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(&back.ice_cake_layers[0].sources, sources);
+    }
+    
+    fn dashboard_handover() -> Blackboard {
+        let mut bb = Blackboard::new("s-1", "utf8-parser", "Parser rejects naïve UTF-8 input").with_clock(Arc::new(MockClock::new(0)));
+        bb.record_decision("lexer", "Track byte offsets only", "columns come later", GateState::Flow);
+        bb.decisions[0].id = "d-1".to_string();
+        bb.resonance_captures = 4;
+        bb.concepts_extracted = 1;
+        bb.session_duration_ms = 90_000;
+        bb.outcome = Some(SessionOutcome::Blocked { reason: "needs a DBA".to_string() });
+        bb
+    }
+    
+    #[test]
+    fn test_revise_decision_chain() {
+        let mut bb = Blackboard::new("s-1", "utf8-parser", "desc");
+        bb.record_decision("lexer", "Track char offsets", "simplest", GateState::Flow);
+        bb.record_decision("cli", "Read stdin", "pipes", GateState::Hold);
+        let first = bb.decisions[0].id.clone();
+        let second = bb.revise_decision(&first, "Track byte offsets", "chars are slow to index").unwrap();
+        let third = bb.revise_decision(&second, "Track byte offsets, map columns lazily", "columns are needed for errors").unwrap();
+        
+        assert_eq!(bb.revise_decision(&first, "again", "r"), Err(ReviseDecisionError::AlreadySuperseded { id: first.clone(), by: second.clone() }));
+        assert_eq!(bb.revise_decision("nope", "x", "r"), Err(ReviseDecisionError::UnknownDecision("nope".to_string())));
+        let chain: Vec<&str> = bb.decision_history(&second).iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(chain, [first.as_str(), second.as_str(), third.as_str()]);
+        let current: Vec<&str> = bb.current_decisions().map(|d| d.choice.as_str()).collect();
+        assert_eq!(current, ["Read stdin", "Track byte offsets, map columns lazily"]);
+        assert_eq!(bb.decisions[3].task, "lexer");
+        
+        let summary = bb.handover_summary();
+        assert!(summary.contains(concat!(
+            "## Decisions (2 superseded)\n",
+            "- Read stdin (cli, Hold)\n",
+            "  Rationale: pipes\n",
+            "- Track byte offsets, map columns lazily (lexer, Flow)\n",
+            "  Rationale: columns are needed for errors\n",
+            "\n",
+        )), "{summary}");
+        assert!(!summary.contains("- Track char offsets ("));
+        assert!(summary.contains("decision: revised: Track byte offsets, map columns lazily (was: Track byte offsets)\n"));
+        assert!(bb.handover_summary_with_history().contains(concat!(
+            "- Track byte offsets, map columns lazily (lexer, Flow)\n",
+            "  Rationale: columns are needed for errors\n",
+            "  History:\n",
+            "  1. Track char offsets (simplest)\n",
+            "  2. Track byte offsets (chars are slow to index)\n",
+            "  3. Track byte offsets, map columns lazily (columns are needed for errors)\n",
+        )));
+        
+        let back = Blackboard::from_yaml(&bb.to_yaml()).unwrap();
+        let exported: Vec<(&str, Option<&str>)> = back.decisions.iter().map(|d| (d.choice.as_str(), d.supersedes.as_deref())).collect();
+        assert_eq!(exported, [
+            ("Track char offsets", None),
+            ("Read stdin", None),
+            ("Track byte offsets", Some(first.as_str())),
+            ("Track byte offsets, map columns lazily", Some(second.as_str())),
+        ]);
+        let markdown = bb.to_markdown(&MarkdownOptions::default());
+        let statuses: Vec<&str> = markdown.lines().filter(|line| line.contains("| lexer, cycle")).map(|line| line.split(" | ").nth(2).unwrap()).collect();
+        assert_eq!(statuses, ["superseded", "superseded", "recorded"]);
+    }
+    
+    #[test]
+    fn test_question_lifecycle() {
+        let mut bb = Blackboard::new("s-1", "utf8-parser", "desc");
+        bb.expected_questions.push("Which encodings must we accept?".to_string());
+        bb.record_decision("lexer", "Track byte offsets", "cheap", GateState::Flow);
+        let tabs = bb.add_question("Do tabs count as one column?");
+        let bom = bb.add_question("Strip a leading BOM?");
+        bb.cycle = 3;
+        bb.resolve_question(&bom, "yes, editors add it", Some("m-7")).unwrap();
+        
+        assert_eq!(bb.resolve_question(&bom, "again", None), Err(QuestionError::AlreadyResolved(bom.clone())));
+        let unknown = QuestionId("q-0".to_string());
+        assert_eq!(bb.resolve_question(&unknown, "x", None), Err(QuestionError::UnknownQuestion(unknown.clone())));
+        let open: Vec<&QuestionId> = bb.unresolved_questions().map(|q| &q.id).collect();
+        assert_eq!(open, [&tabs]);
+        let resolution = bb.resolved_questions().next().unwrap().resolution.clone().unwrap();
+        assert_eq!(resolution, QuestionResolution { text: "yes, editors add it".to_string(), moment_id: Some("m-7".to_string()), cycle: 3 });
+        
+        let summary = bb.handover_summary();
+        assert!(summary.contains(concat!(
+            "## Open Questions\n",
+            "- Do tabs count as one column?\n",
+            "- Which encodings must we accept?\n",
+            "\n",
+            "## Resolved Questions\n",
+            "- Strip a leading BOM? → yes, editors add it\n",
+            "\n",
+            "## Decisions\n",
+        )), "{summary}");
+        let markdown = bb.to_markdown(&MarkdownOptions { truncate: None, emoji: false, compact: None });
+        assert!(markdown.contains(concat!(
+            "## Open Questions\n",
+            "\n",
+            "- Do tabs count as one column?\n",
+            "- Which encodings must we accept?\n",
+            "- ~~Strip a leading BOM?~~ yes, editors add it (moment `m-7`)\n",
+        )), "{markdown}");
+        assert_eq!(Blackboard::from_yaml(&bb.to_yaml()).unwrap().questions, bb.questions);
+    }
+    
+    fn layered() -> Blackboard {
+        let mut session = LearningSession::new("parser");
+        let report = session.encounter("parser rejects UTF-8 input").id.clone();
+        session.ice_cake_with_confidence(&report, "the bug report reproduces", TruthValue::new(1.0, 0.8)).unwrap();
+        let mixup = session.struggle("byte and char offsets mixed up", 0.6, 0.5).id.clone();
+        session.ice_cake_with_confidence(&mixup, "columns need chars", TruthValue::new(1.0, 0.7)).unwrap();
+        let fix = session.breakthrough("track byte offsets only in the lexer", 0.9).id.clone();
+        session.ice_cake_with_confidence(&fix, "lexer invariant", TruthValue::new(1.0, 0.95)).unwrap();
+        let mut bb = Blackboard::new(&session.id, "parser", "desc");
+        bb.update_from_session(&session.state());
+        bb
+    }
+    
+    #[test]
+    fn test_ice_caked_orderings_and_search() {
+        let bb = layered();
+        let ids = |layers: Vec<&IceCakedLayer>| -> Vec<u32> { layers.iter().map(|l| l.layer_id).collect() };
+        assert_eq!(ids(bb.ice_caked_ordered_by(OrderBy::Recency)), [3, 2, 1]);
+        assert_eq!(ids(bb.ice_caked_ordered_by(OrderBy::Confidence)), [3, 1, 2]);
+        let query = Fingerprint::from_words("char columns are mixed up");
+        assert_eq!(bb.ice_caked_ordered_by(OrderBy::Similarity(&query))[0].layer_id, 2);
+        
+        let found: Vec<u32> = bb.find_ice_caked("track byte offsets").iter().map(|(l, _)| l.layer_id).collect();
+        assert_eq!(found, [3, 2]);
+        assert!(bb.find_ice_caked("cache invalidation").is_empty());
+        assert_eq!(bb.ice_caked_by_phase(), BTreeMap::from([
+            ("Breakthrough".to_string(), 1),
+            ("Encounter".to_string(), 1),
+            ("Struggle".to_string(), 1),
+        ]));
+        
+        let top = HandoverTemplate::new("top").section_with(Section::LoadBearing, Some(2), None);
+        assert!(bb.handover_with(&top).ends_with(concat!(
+            "## Load-Bearing Decisions (top 2 of 3)\n",
+            "1. track byte offsets only in the lexer (confidence 0.95, layer 3)\n",
+            "2. parser rejects UTF-8 input (confidence 0.80, layer 1)\n",
+            "- Ice-caked by phase: Breakthrough 1, Encounter 1, Struggle 1\n",
+        )));
+        assert!(bb.handover_summary().contains("## Load-Bearing Decisions (top 3 of 3)\n1. track byte offsets only in the lexer"));
+    }
+    
+    #[test]
+    fn test_provenance_lookup_and_export() {
+        let mut bb = Blackboard::new("s-1", "parser", "desc");
+        bb.record_decision("lexer", "Track byte offsets", "cheap", GateState::Flow);
+        bb.record_decision("errors", "Report char columns", "editors count chars", GateState::Flow);
+        let offsets = bb.decisions[0].id.clone();
+        let breakthrough = Provenance { session_id: "s-0".to_string(), moment_id: "m-7".to_string(), kind: ProvenanceKind::Breakthrough };
+        bb.add_provenance(&offsets, breakthrough.clone()).unwrap();
+        bb.add_provenance(&offsets, breakthrough.clone()).unwrap();
+        assert_eq!(bb.decisions[0].provenance, [breakthrough]);
+        assert_eq!(bb.add_provenance("missing", bb.decisions[0].provenance[0].clone()), Err(ReviseDecisionError::UnknownDecision("missing".to_string())));
+        
+        let found: Vec<&str> = bb.decisions_from_moment("m-7").iter().map(|d| d.choice.as_str()).collect();
+        assert_eq!(found, ["Track byte offsets"]);
+        assert!(bb.handover_summary().contains("  Rationale: cheap\n  ↳ session s-0 / moment m-7\n- Report char columns"));
+        let markdown = bb.to_markdown(&MarkdownOptions::default());
+        assert!(markdown.contains("| lexer, cycle 0, ↳ session s-0 / moment m-7 |"), "{markdown}");
+    }
+    
+    #[test]
+    fn test_next_steps_by_priority_then_insertion() {
+        let mut bb = Blackboard::new("s", "task", "desc");
+        let fixture = bb.add_next_step("Add a CRLF fixture", DEFAULT_NEXT_STEP_PRIORITY);
+        bb.add_next_step("Map offsets to columns", 0);
+        bb.add_next_step("Update the changelog", DEFAULT_NEXT_STEP_PRIORITY);
+        let pending: Vec<&str> = bb.pending_next_steps().iter().map(|step| step.text.as_str()).collect();
+        assert_eq!(pending, ["Map offsets to columns", "Add a CRLF fixture", "Update the changelog"]);
+        assert!(bb.handover_summary().contains(concat!(
+            "## Next Steps\n",
+            "1. [P0] Map offsets to columns\n",
+            "2. [P2] Add a CRLF fixture\n",
+            "3. [P2] Update the changelog\n",
+        )));
+        
+        bb.complete_next_step(&fixture).unwrap();
+        assert_eq!(bb.complete_next_step(&fixture), Err(NextStepError::AlreadyCompleted(fixture.clone())));
+        let unknown = NextStepId("missing".to_string());
+        assert_eq!(bb.complete_next_step(&unknown), Err(NextStepError::UnknownNextStep(unknown)));
+        assert_eq!(bb.pending_next_steps().len(), 2);
+        assert!(bb.handover_summary().contains("## Next Steps\n1. [P0] Map offsets to columns\n2. [P2] Update the changelog\n\n"));
+        assert_eq!(Blackboard::from_yaml(&bb.to_yaml()).unwrap().next_steps, bb.next_steps);
+    }
+    
+    #[test]
+    fn test_questions_from_hypotheses_and_clarify() {
+        let mut session = LearningSession::new("task");
+        let stale = session.hypothesize("cache is stale", TruthValue::new(0.6, 0.3)).id.clone();
+        session.hypothesize("lock is contended", TruthValue::new(0.5, 0.2));
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        bb.track_hypotheses(&session);
+        bb.track_hypotheses(&session);
+        assert_eq!(bb.unresolved_questions().count(), 2);
+        assert_eq!(bb.all_open_questions(), ["cache is stale", "lock is contended"]);
+        
+        let flushed = session.verify(&stale, true, "flushing fixed it").unwrap().id.clone();
+        bb.update_from_session(&session.state());
+        bb.track_hypotheses(&session);
+        let resolved: Vec<&Question> = bb.resolved_questions().collect();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].moment_id.as_deref(), Some(stale.as_str()));
+        assert_eq!(resolved[0].resolution.as_ref().unwrap().moment_id.as_deref(), Some(flushed.as_str()));
+        
+        let clarify = crate::cognitive::evaluate_gate(&[1.0, 0.0], true);
+        let id = bb.record_collapse(&clarify).unwrap();
+        assert_eq!(bb.questions.last().unwrap().id, id);
+        assert_eq!(bb.all_open_questions(), ["lock is contended", "Multiple interpretations possible"]);
+        assert!(bb.record_collapse(&crate::cognitive::evaluate_gate(&[0.5, 0.5], true)).is_none());
+    }
+    
+    #[test]
+    fn test_log_compacts_oldest_entries() {
+        let clock = MockClock::new(1_000);
+        let mut bb = Blackboard::new("s-1", "task", "desc").with_clock(Arc::new(clock.clone()));
+        bb.log_capacity = 3;
+        bb.record_decision("lexer", "Track byte offsets", "cheap", GateState::Flow);
+        clock.advance(10);
+        bb.add_question("Tabs?");
+        for i in 0..3 {
+            clock.advance(10);
+            bb.note(&format!("note {}", i));
         }
         
-        s.push_str(&format!("\n## Stats\n- Resonance Captures: {}\n- Concepts Extracted: {}\n", 
-            self.resonance_captures, self.concepts_extracted));
-        s
+        let texts: Vec<&str> = bb.log.iter().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts, ["note 0", "note 1", "note 2"]);
+        let summary = bb.compacted_log.clone().unwrap();
+        assert_eq!((summary.entries, summary.from, summary.until), (2, 1_000, 1_010));
+        assert_eq!(summary.to_string(), "2 entries compacted, t=1000ms to t=1010ms: 1 decision, 1 question");
+        
+        bb.note("note 3");
+        assert_eq!(bb.log.len(), 3);
+        assert_eq!(bb.compacted_log.as_ref().unwrap().counts[&LogCategory::Note], 1);
+        assert_eq!(bb.log_lines(2), [
+            "3 entries compacted, t=1000ms to t=1020ms: 1 decision, 1 question, 1 note; 1 more entries not shown",
+            "t=1040ms note: note 2",
+            "t=1040ms note: note 3",
+        ]);
+        let back = Blackboard::from_yaml(&bb.to_yaml()).unwrap();
+        assert_eq!((back.log, back.compacted_log), (bb.log.clone(), bb.compacted_log.clone()));
+    }
+    
+    fn agent_board(session_id: &str, clock: &MockClock) -> Blackboard {
+        Blackboard::new(session_id, "utf8-parser", "desc").with_clock(Arc::new(clock.clone()))
+    }
+    
+    #[test]
+    fn test_merge_surfaces_decision_conflicts() {
+        let clock = MockClock::new(0);
+        let mut a = agent_board("s-a", &clock);
+        let mut b = agent_board("s-b", &clock);
+        a.record_decision("lexer offsets", "Track byte offsets", "cheap slicing", GateState::Flow);
+        a.record_decision("cli", "Read stdin", "pipes", GateState::Flow);
+        b.record_decision("lexer offsets", "Track char offsets", "columns for free", GateState::Hold);
+        b.record_decision("cli", "Read stdin", "pipes too", GateState::Flow);
+        b.record_decision("errors", "Use miette", "pretty reports", GateState::Flow);
+        
+        let merged = a.merge(&b, &BbMergePolicy::new()).unwrap();
+        let current: Vec<&str> = merged.current_decisions().map(|d| d.choice.as_str()).collect();
+        assert_eq!(current, ["Read stdin", "Use miette"]);
+        assert_eq!(merged.decision_conflicts.len(), 1);
+        let conflict = &merged.decision_conflicts[0];
+        assert_eq!(conflict.topic, "lexer offsets");
+        let choices: Vec<&str> = conflict.decisions.iter().map(|d| d.choice.as_str()).collect();
+        assert_eq!(choices, ["Track byte offsets", "Track char offsets"]);
+        assert!(merged.handover_summary().contains(concat!(
+            "## Decision Conflicts ⚠️\n",
+            "- lexer offsets:\n",
+            "  - Track byte offsets (cheap slicing)\n",
+            "  - Track char offsets (columns for free)\n",
+        )));
+        
+        let strict = BbMergePolicy { topic_similarity: 1.01, ..BbMergePolicy::new() };
+        assert!(a.merge(&b, &strict).unwrap().decision_conflicts.is_empty());
+        assert_eq!(a.merge(&a, &BbMergePolicy::new()).unwrap().decisions.len(), 2, "shared decisions are kept once");
+    }
+    
+    #[test]
+    fn test_merge_keeps_ice_caked_decisions_out_of_conflicts() {
+        let mut boards = Vec::new();
+        for (fix, rationale) in [("track byte offsets", "slicing stays cheap"), ("strip a leading BOM", "editors add one")] {
+            let mut session = LearningSession::new("utf8-parser");
+            let id = session.breakthrough(fix, 0.9).id.clone();
+            session.ice_cake(&id, rationale).unwrap();
+            let mut bb = Blackboard::new(&session.id, "utf8-parser", "desc");
+            bb.update_from_session(&session.state());
+            boards.push(bb);
+        }
+        boards[1].record_decision("utf8-parser", "Reject overlong encodings", "they are invalid UTF-8", GateState::Flow);
+        
+        let merged = boards[0].merge(&boards[1], &BbMergePolicy::new()).unwrap();
+        assert!(merged.decision_conflicts.is_empty(), "{:?}", merged.decision_conflicts);
+        let choices: Vec<&str> = merged.current_decisions().map(|d| d.choice.as_str()).collect();
+        assert_eq!(choices, ["track byte offsets", "strip a leading BOM", "Reject overlong encodings"]);
+        assert_eq!(merged.ice_cake_layers.len(), 2);
+    }
+    
+    #[test]
+    fn test_merge_sums_counters_and_interleaves_logs() {
+        let clock = MockClock::new(1_000);
+        let mut a = agent_board("s-a", &clock);
+        let mut b = agent_board("s-b", &clock);
+        a.note("a starts");
+        clock.advance(10);
+        b.note("b starts");
+        clock.advance(10);
+        a.note("a continues");
+        a.resonance_captures = 4;
+        b.resonance_captures = 6;
+        a.concepts_extracted = 1;
+        b.concepts_extracted = 2;
+        a.moment_types.insert("Struggle".to_string(), 2);
+        b.moment_types.insert("Struggle".to_string(), 1);
+        b.moment_types.insert("Breakthrough".to_string(), 1);
+        a.session_duration_ms = 60_000;
+        b.session_duration_ms = 30_000;
+        
+        let merged = a.merge(&b, &BbMergePolicy::new()).unwrap();
+        assert_eq!((merged.resonance_captures, merged.concepts_extracted, merged.session_duration_ms), (10, 3, 90_000));
+        assert_eq!(merged.moment_types, BTreeMap::from([("Breakthrough".to_string(), 1), ("Struggle".to_string(), 3)]));
+        let log: Vec<&str> = merged.log.iter().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(log, ["a starts", "b starts", "a continues", "merged the blackboard of session s-b"]);
+        assert_eq!(merged.session_id, "s-a");
+        
+        let other_task = Blackboard::new("s-c", "ci", "desc");
+        assert_eq!(a.merge(&other_task, &BbMergePolicy::new()).err(), Some(BbMergeError::TaskMismatch {
+            a: "utf8-parser".to_string(),
+            b: "ci".to_string(),
+        }));
+        let cross = BbMergePolicy { allow_cross_task: true, ..BbMergePolicy::new() };
+        assert!(a.merge(&other_task, &cross).is_ok());
+    }
+    
+    #[test]
+    fn test_merge_dedups_questions() {
+        let clock = MockClock::new(0);
+        let mut a = agent_board("s-a", &clock);
+        let mut b = agent_board("s-b", &clock);
+        a.add_question("Do tabs count as one column?");
+        let bom = b.add_question("Strip a leading BOM?");
+        b.add_question("do tabs count as one column? ");
+        let tabs = b.questions[1].id.clone();
+        b.resolve_question(&tabs, "no, they expand", None).unwrap();
+        a.add_question("Strip a leading BOM?");
+        a.open_questions.push("cache is stale".to_string());
+        b.open_questions.push("Cache is stale".to_string());
+        b.expected_questions.push("Which encodings?".to_string());
+        
+        let merged = a.merge(&b, &BbMergePolicy::new()).unwrap();
+        let texts: Vec<&str> = merged.questions.iter().map(|q| q.text.as_str()).collect();
+        assert_eq!(texts, ["Do tabs count as one column?", "Strip a leading BOM?"]);
+        assert_eq!(merged.questions[0].resolution.as_ref().unwrap().text, "no, they expand", "a resolution from either side is kept");
+        assert!(merged.questions.iter().all(|q| q.id != bom));
+        assert_eq!(merged.all_open_questions(), ["Strip a leading BOM?", "Which encodings?", "cache is stale"]);
+    }
+    
+    #[test]
+    fn test_json_schema_golden() {
+        let bb = dashboard_handover();
+        let json = bb.to_json_pretty().unwrap();
+        assert_eq!(json, include_str!("testdata/blackboard_v1.json").trim_end());
+        let compact: serde_json::Value = serde_json::from_str(&bb.to_json().unwrap()).unwrap();
+        assert_eq!(compact, serde_json::from_str::<serde_json::Value>(&json).unwrap());
+    }
+    
+    #[test]
+    fn test_json_new_decision_only_appends() {
+        let mut bb = dashboard_handover();
+        let before: serde_json::Value = serde_json::from_str(&bb.to_json().unwrap()).unwrap();
+        bb.record_decision("lexer", "Keep &str slices", "no copies", GateState::Hold);
+        let after: serde_json::Value = serde_json::from_str(&bb.to_json().unwrap()).unwrap();
+        
+        let (before, after) = (before.as_object().unwrap(), after.as_object().unwrap());
+        assert_eq!(before.keys().collect::<Vec<_>>(), after.keys().collect::<Vec<_>>());
+        let changed: Vec<&String> = after.keys().filter(|key| before[*key] != after[*key]).collect();
+        assert_eq!(changed, ["decisions", "log"]);
+        assert_eq!(after["log"][1], serde_json::json!({ "at": 0, "category": "Decision", "text": "recorded: Keep &str slices" }));
+        let decisions = after["decisions"].as_array().unwrap();
+        assert_eq!(decisions.len(), 2);
+        assert_eq!(decisions[0], before["decisions"][0]);
+        assert_eq!(decisions[1]["choice"], "Keep &str slices");
+        assert_eq!(decisions[1]["gate_state"], "Hold");
+        assert!(decisions[1]["id"].as_str().is_some_and(|id| !id.is_empty()));
     }
 }
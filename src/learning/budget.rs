@@ -0,0 +1,148 @@
+//! Size budget — exports of a large blackboard summarized until they fit
+//! `Blackboard::size_budget_bytes`
+
+use std::borrow::Cow;
+use crate::learning::blackboard::Blackboard;
+
+/// `text` up to the end of its first sentence, or None if that is all of it
+fn first_sentence(text: &str) -> Option<String> {
+    let trimmed = text.trim();
+    let end = trimmed.char_indices()
+        .find(|&(i, c)| c == '\n' || (matches!(c, '.' | '!' | '?') && trimmed[i + 1..].starts_with(char::is_whitespace)))
+        .map(|(i, c)| if c == '\n' { i } else { i + 1 })?;
+    Some(format!("{}…", trimmed[..end].trim_end()))
+}
+
+impl Blackboard {
+    /// `render` this blackboard, or, when its output is over
+    /// `size_budget_bytes`, a copy summarized step by step until it fits:
+    /// superseded decisions are dropped (their chain's current decision
+    /// stays), then the activity log is folded into per-category counts,
+    /// then decision and layer texts are cut to their first sentence. Each
+    /// step taken is noted in the copy's `summarized`, as is still being
+    /// over budget after the last. The blackboard itself is left as is.
+    pub(crate) fn fit_budget<E>(&self, render: impl Fn(&Blackboard) -> Result<String, E>) -> Result<String, E> {
+        let rendered = render(self)?;
+        let Some(budget) = self.size_budget_bytes else { return Ok(rendered) };
+        if rendered.len() <= budget {
+            return Ok(rendered);
+        }
+        
+        let mut bb = Cow::Borrowed(self);
+        let steps: [fn(&mut Blackboard) -> Option<String>; 3] = [
+            |bb| {
+                let before = bb.decisions.len();
+                bb.decisions.retain(|d| d.superseded_by.is_none());
+                let dropped = before - bb.decisions.len();
+                (dropped > 0).then(|| format!("dropped {} superseded decisions, keeping the current one of each chain", dropped))
+            },
+            |bb| {
+                let entries = bb.log.len();
+                bb.fold_log(0);
+                (entries > 0).then(|| format!("folded {} activity log entries into counts per category", entries))
+            },
+            |bb| {
+                let mut shortened = 0;
+                let texts = bb.decisions.iter_mut().flat_map(|d| [&mut d.choice, &mut d.rationale])
+                    .chain(bb.ice_cake_layers.iter_mut().flat_map(|layer| [&mut layer.content, &mut layer.rationale]));
+                for text in texts {
+                    if let Some(sentence) = first_sentence(text) {
+                        *text = sentence;
+                        shortened += 1;
+                    }
+                }
+                (shortened > 0).then(|| format!("cut {} decision texts to their first sentence", shortened))
+            },
+        ];
+        for step in steps {
+            let summarized = bb.to_mut();
+            if let Some(note) = step(summarized) {
+                summarized.summarized.push(note);
+                let rendered = render(&bb)?;
+                if rendered.len() <= budget {
+                    return Ok(rendered);
+                }
+            }
+        }
+        let summarized = bb.to_mut();
+        summarized.summarized.push(format!("still over the budget of {} bytes", budget));
+        render(&bb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cognitive::GateState;
+    
+    /// 60 topics each decided, then revised twice, with long rationales
+    fn large() -> Blackboard {
+        let mut bb = Blackboard::new("s-1", "parser", "Parser rejects UTF-8 input");
+        for topic in 0..60 {
+            let rationale = format!("Topic {} needs a call. Benchmarks on the corpus were run twice and agree within noise. The fallback is documented.", topic);
+            bb.record_decision(&format!("topic-{}", topic), &format!("Option A for topic {}", topic), &rationale, GateState::Flow);
+            let mut id = bb.decisions.last().unwrap().id.clone();
+            for revision in ["B", "C"] {
+                id = bb.revise_decision(&id, &format!("Option {} for topic {}", revision, topic), &rationale).unwrap();
+            }
+        }
+        bb
+    }
+    
+    #[test]
+    fn test_first_sentence() {
+        assert_eq!(first_sentence("Use slices. They are cheap.").as_deref(), Some("Use slices.…"));
+        assert_eq!(first_sentence("v1.2 is out\nupgrade").as_deref(), Some("v1.2 is out…"));
+        assert_eq!(first_sentence("Use slices."), None);
+    }
+    
+    #[test]
+    fn test_no_budget_or_within_budget() {
+        let bb = large();
+        let plain = bb.to_yaml();
+        let mut roomy = bb.clone();
+        roomy.size_budget_bytes = Some(plain.len() + 100);
+        let yaml = roomy.to_yaml();
+        assert!(!yaml.contains("summarized:"));
+        assert_eq!(Blackboard::from_yaml(&yaml).unwrap().decisions, bb.decisions);
+    }
+    
+    #[test]
+    fn test_steps_until_under_budget() {
+        let mut bb = large();
+        let full = bb.to_yaml().len();
+        
+        bb.size_budget_bytes = Some(full / 2);
+        let yaml = bb.to_yaml();
+        assert!(yaml.len() <= full / 2, "{} bytes", yaml.len());
+        let exported = Blackboard::from_yaml(&yaml).unwrap();
+        assert_eq!(exported.summarized, ["dropped 120 superseded decisions, keeping the current one of each chain"]);
+        assert_eq!(exported.decisions.len(), 60);
+        assert_eq!(exported.log.len(), 180);
+        
+        bb.size_budget_bytes = Some(full / 4);
+        let json = bb.to_json().unwrap();
+        assert!(json.len() <= full / 4, "{} bytes", json.len());
+        let exported: Blackboard = serde_json::from_str(&json).unwrap();
+        assert_eq!(exported.summarized, [
+            "dropped 120 superseded decisions, keeping the current one of each chain",
+            "folded 180 activity log entries into counts per category",
+            "cut 60 decision texts to their first sentence",
+        ]);
+        assert_eq!(exported.decisions[0].rationale, "Topic 0 needs a call.…");
+        assert_eq!(exported.compacted_log.unwrap().entries, 180);
+        
+        assert_eq!(bb.decisions.len(), 180, "the blackboard itself is not summarized");
+        assert_eq!(bb.log.len(), 180);
+        assert!(bb.summarized.is_empty());
+    }
+    
+    #[test]
+    fn test_budget_out_of_reach() {
+        let mut bb = large();
+        bb.size_budget_bytes = Some(100);
+        let exported = Blackboard::from_yaml(&bb.to_yaml()).unwrap();
+        assert_eq!(exported.summarized.len(), 4);
+        assert_eq!(exported.summarized[3], "still over the budget of 100 bytes");
+    }
+}
@@ -0,0 +1,121 @@
+//! QualiaCalibration — per-user baseline for subjective qualia
+
+use serde::{Serialize, Deserialize};
+use crate::learning::moment::{Qualia, QUALIA_DIMENSIONS};
+
+/// Running mean and variance of each qualia dimension (on the `normalized`
+/// scale), used to map one person's "effort 0.7" onto a shared scale
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QualiaCalibration {
+    count: u64,
+    mean: [f64; QUALIA_DIMENSIONS],
+    /// Sum of squared deviations from the mean (Welford)
+    m2: [f64; QUALIA_DIMENSIONS],
+}
+
+/// Below this variance a dimension is treated as uncalibrated
+const MIN_VARIANCE: f64 = 1e-9;
+
+impl QualiaCalibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn observe(&mut self, qualia: &Qualia) {
+        self.count += 1;
+        let n = self.count as f64;
+        for (dim, value) in qualia.normalized().iter().enumerate() {
+            let x = *value as f64;
+            let delta = x - self.mean[dim];
+            self.mean[dim] += delta / n;
+            self.m2[dim] += delta * (x - self.mean[dim]);
+        }
+    }
+    
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+    
+    pub fn mean(&self) -> [f64; QUALIA_DIMENSIONS] {
+        self.mean
+    }
+    
+    /// Sample variance per dimension; zero until two moments were observed
+    pub fn variance(&self) -> [f64; QUALIA_DIMENSIONS] {
+        if self.count < 2 {
+            return [0.0; QUALIA_DIMENSIONS];
+        }
+        self.m2.map(|m2| m2 / (self.count - 1) as f64)
+    }
+    
+    /// Z-score each dimension against the baseline and squash it back into
+    /// [0, 1] with the logistic function, so the personal mean lands on 0.5.
+    /// Dimensions without variance, and every dimension of a calibration
+    /// with fewer than two observations, pass through unchanged.
+    pub fn normalize(&self, qualia: &Qualia) -> Qualia {
+        if self.count < 2 {
+            return qualia.clone();
+        }
+        let variance = self.variance();
+        let mut values = qualia.normalized();
+        for (dim, value) in values.iter_mut().enumerate() {
+            if variance[dim] < MIN_VARIANCE {
+                continue;
+            }
+            let z = (*value as f64 - self.mean[dim]) / variance[dim].sqrt();
+            *value = (1.0 / (1.0 + (-z).exp())) as f32;
+        }
+        Qualia::from_normalized(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_empty_calibration_is_identity() {
+        let calibration = QualiaCalibration::new();
+        let qualia = Qualia::from_metrics(0.9, 0.7, 0.2).with_affect(-0.4, 0.8, 0.3);
+        assert_eq!(calibration.normalize(&qualia), qualia);
+    }
+    
+    #[test]
+    fn test_skewed_qualia_are_centered() {
+        let mut calibration = QualiaCalibration::new();
+        // Someone who rates every effort between 0.7 and 0.9
+        for effort in [0.7, 0.75, 0.8, 0.85, 0.9] {
+            calibration.observe(&Qualia::from_metrics(0.5, effort, 0.5));
+        }
+        assert!((calibration.mean()[1] - 0.8).abs() < 1e-6);
+        
+        let typical = calibration.normalize(&Qualia::from_metrics(0.5, 0.8, 0.5));
+        assert!((typical.effort - 0.5).abs() < 1e-6);
+        let low = calibration.normalize(&Qualia::from_metrics(0.5, 0.7, 0.5));
+        assert!(low.effort < 0.3);
+        // No variance in novelty: passed through
+        assert_eq!(typical.novelty, 0.5);
+    }
+    
+    #[test]
+    fn test_persistence_keeps_statistics() {
+        let mut calibration = QualiaCalibration::new();
+        calibration.observe(&Qualia::from_metrics(0.2, 0.9, 0.1).with_affect(-0.5, 0.7, 0.4));
+        calibration.observe(&Qualia::from_metrics(0.6, 0.4, 0.8).with_affect(0.5, 0.3, 0.9));
+        
+        // Float parsing may be off by an ulp, so compare with a tolerance
+        let close = |a: [f64; QUALIA_DIMENSIONS], b: [f64; QUALIA_DIMENSIONS]| {
+            a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-12)
+        };
+        let json = serde_json::to_string(&calibration).unwrap();
+        let yaml = serde_yaml::to_string(&calibration).unwrap();
+        for back in [
+            serde_json::from_str::<QualiaCalibration>(&json).unwrap(),
+            serde_yaml::from_str::<QualiaCalibration>(&yaml).unwrap(),
+        ] {
+            assert_eq!(back.count(), 2);
+            assert!(close(back.mean(), calibration.mean()));
+            assert!(close(back.variance(), calibration.variance()));
+        }
+    }
+}
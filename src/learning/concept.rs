@@ -2,8 +2,11 @@
 
 use std::collections::HashMap;
 use crate::core::Fingerprint;
-use crate::nars::TruthValue;
-use crate::learning::moment::Moment;
+use crate::nars::{TruthValue, TruthParams, DecayPolicy, EvidenceCounter, Term, Copula, Statement};
+use crate::learning::cypher::escape_cypher;
+use crate::learning::moment::{Artifact, Moment, MomentType};
+use crate::learning::redact::Redactor;
+use crate::learning::session::AbandonReason;
 
 #[derive(Clone, Debug)]
 pub struct ExtractedConcept {
@@ -17,6 +20,16 @@ pub struct ExtractedConcept {
     pub truth: TruthValue,
     pub relations: Vec<ConceptRelation>,
     pub tags: Vec<String>,
+    pub last_confirmed_cycle: u64,
+    /// Raw evidence from later confirmations and contradictions
+    pub evidence: EvidenceCounter,
+    /// Artifacts of every moment the concept was extracted from
+    pub artifacts: Vec<Artifact>,
+    /// The struggle the source breakthrough resolved, when extracted from a pair
+    pub problem_moment_id: Option<String>,
+    /// Extracted from the struggle an abandoned session never resolved: an
+    /// approach to avoid rather than one to reuse
+    pub anti_pattern: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -46,13 +59,34 @@ impl RelationType {
             Self::Requires => "REQUIRES",
         }
     }
+    
+    /// Copula used when the relation is emitted as a NARS statement.
+    /// `Contradicts` has no positive copula and is not emitted.
+    pub fn copula(&self) -> Option<Copula> {
+        match self {
+            Self::SimilarTo => Some(Copula::Similarity),
+            Self::Refines | Self::Abstracts | Self::Grounds | Self::PartOf => Some(Copula::Inheritance),
+            Self::Enables | Self::Causes | Self::Supports | Self::Requires => Some(Copula::Implication),
+            Self::Contradicts => None,
+        }
+    }
 }
 
+/// Cycles after which a concept's confidence is halved before revision
+pub const DEFAULT_PROJECTION_HORIZON: f32 = 1_000.0;
+
 pub struct ConceptExtractor {
     concepts: HashMap<String, ExtractedConcept>,
     cam_index: HashMap<u64, String>,
     pub total_extractions: u64,
     pub duplicate_hits: u64,
+    pub projection_horizon: f32,
+    /// Applied lazily by `truth_at`; stored truths are never decayed in place
+    pub decay: Option<DecayPolicy>,
+    /// Relations whose truth is below this confidence are not emitted
+    pub min_relation_confidence: f32,
+    /// Evidential horizon used when re-extraction revises a concept
+    pub truth_params: TruthParams,
 }
 
 impl ConceptExtractor {
@@ -62,10 +96,31 @@ impl ConceptExtractor {
             cam_index: HashMap::new(),
             total_extractions: 0,
             duplicate_hits: 0,
+            projection_horizon: DEFAULT_PROJECTION_HORIZON,
+            decay: None,
+            min_relation_confidence: 0.0,
+            truth_params: TruthParams::default(),
         }
     }
     
     pub fn extract(&mut self, moment: &Moment) -> Option<ExtractedConcept> {
+        self.extract_at(moment, 0)
+    }
+    
+    /// Extract at a global cycle. Re-extracting a known concept projects its
+    /// truth onto `cycle` and revises it with the new evidence.
+    pub fn extract_at(&mut self, moment: &Moment, cycle: u64) -> Option<ExtractedConcept> {
+        self.extract_with(moment, None, cycle)
+    }
+    
+    /// Extract from a breakthrough together with the struggle it resolved,
+    /// e.g. a pair from `LearningSession::infer_resolutions`. The description
+    /// carries both the problem and the solution.
+    pub fn extract_resolution(&mut self, struggle: &Moment, breakthrough: &Moment, cycle: u64) -> Option<ExtractedConcept> {
+        self.extract_with(breakthrough, Some(struggle), cycle)
+    }
+    
+    fn extract_with(&mut self, moment: &Moment, problem: Option<&Moment>, cycle: u64) -> Option<ExtractedConcept> {
         if !moment.is_breakthrough() { return None; }
         
         self.total_extractions += 1;
@@ -73,20 +128,79 @@ impl ConceptExtractor {
         
         if let Some(existing_id) = self.cam_index.get(&cam) {
             self.duplicate_hits += 1;
-            return self.concepts.get(existing_id).cloned();
+            let horizon = self.projection_horizon;
+            let params = self.truth_params;
+            let concept = self.concepts.get_mut(existing_id)?;
+            let projected = concept.truth.project(concept.last_confirmed_cycle, cycle, horizon);
+            let reliability = Self::evidence_reliability(&moment.moment_type);
+            concept.truth = projected.revision_weighted_with(&Self::moment_truth(moment), 1.0, reliability, &params);
+            concept.evidence.record_success(reliability);
+            concept.last_confirmed_cycle = concept.last_confirmed_cycle.max(cycle);
+            for artifact in &moment.artifacts {
+                if !concept.artifacts.contains(artifact) {
+                    concept.artifacts.push(artifact.clone());
+                }
+            }
+            return Some(concept.clone());
         }
         
         let concept = ExtractedConcept {
             id: uuid::Uuid::new_v4().to_string(),
             name: self.extract_name(&moment.content),
-            description: moment.content.clone(),
+            description: match problem {
+                Some(problem) => format!("Problem: {} | Solution: {}", problem.content, moment.content),
+                None => moment.content.clone(),
+            },
             cam_fingerprint: cam,
             full_fingerprint: moment.fingerprint.clone(),
             abstraction_level: self.estimate_abstraction(&moment.content),
             source_moment_id: moment.id.clone(),
-            truth: TruthValue::new(moment.qualia.satisfaction, 0.5 + moment.qualia.satisfaction * 0.4),
+            truth: Self::moment_truth(moment),
             relations: Vec::new(),
             tags: moment.tags.clone(),
+            last_confirmed_cycle: cycle,
+            evidence: EvidenceCounter::new(),
+            artifacts: moment.artifacts.clone(),
+            problem_moment_id: problem.map(|p| p.id.clone()),
+            anti_pattern: false,
+        };
+        
+        self.cam_index.insert(cam, concept.id.clone());
+        self.concepts.insert(concept.id.clone(), concept.clone());
+        Some(concept)
+    }
+    
+    /// Extract an anti-pattern from the dominant unresolved struggle of an
+    /// abandoned session, e.g. `LearningSession::dominant_unresolved_struggle`.
+    /// The truth is how strongly the approach failed: frequency rises as the
+    /// struggle's satisfaction falls. A known struggle is confirmed instead.
+    pub fn extract_anti_pattern(&mut self, struggle: &Moment, reason: AbandonReason, cycle: u64) -> Option<ExtractedConcept> {
+        if struggle.moment_type != MomentType::Struggle { return None; }
+        
+        self.total_extractions += 1;
+        let cam = self.content_addressable_fingerprint(&struggle.content);
+        if let Some(existing_id) = self.cam_index.get(&cam).cloned() {
+            self.duplicate_hits += 1;
+            self.confirm(&existing_id, Self::evidence_reliability(&struggle.moment_type), cycle)?;
+            return self.concepts.get(&existing_id).cloned();
+        }
+        
+        let concept = ExtractedConcept {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: self.extract_name(&struggle.content),
+            description: format!("Anti-pattern (abandoned: {}): {}", reason, struggle.content),
+            cam_fingerprint: cam,
+            full_fingerprint: struggle.fingerprint.clone(),
+            abstraction_level: self.estimate_abstraction(&struggle.content),
+            source_moment_id: struggle.id.clone(),
+            truth: TruthValue::new(1.0 - struggle.qualia.satisfaction, 0.5),
+            relations: Vec::new(),
+            tags: struggle.tags.clone(),
+            last_confirmed_cycle: cycle,
+            evidence: EvidenceCounter::new(),
+            artifacts: struggle.artifacts.clone(),
+            problem_moment_id: Some(struggle.id.clone()),
+            anti_pattern: true,
         };
         
         self.cam_index.insert(cam, concept.id.clone());
@@ -94,6 +208,49 @@ impl ConceptExtractor {
         Some(concept)
     }
     
+    /// A later session confirmed the concept. Returns the updated truth.
+    pub fn confirm(&mut self, id: &str, weight: f32, cycle: u64) -> Option<TruthValue> {
+        self.record_evidence(id, weight, 0.0, cycle)
+    }
+    
+    /// A later session contradicted the concept. Returns the updated truth.
+    pub fn contradict(&mut self, id: &str, weight: f32, cycle: u64) -> Option<TruthValue> {
+        self.record_evidence(id, 0.0, weight, cycle)
+    }
+    
+    /// Revising with from_evidence(p, n) adds exactly that much evidence
+    /// weight, so the truth stays in step with the counter.
+    fn record_evidence(&mut self, id: &str, positive: f32, negative: f32, cycle: u64) -> Option<TruthValue> {
+        let horizon = self.projection_horizon;
+        let concept = self.concepts.get_mut(id)?;
+        concept.evidence.record_success(positive);
+        concept.evidence.record_failure(negative);
+        let projected = concept.truth.project(concept.last_confirmed_cycle, cycle, horizon);
+        concept.truth = projected.revision(&TruthValue::from_evidence(positive.max(0.0), negative.max(0.0)));
+        concept.last_confirmed_cycle = concept.last_confirmed_cycle.max(cycle);
+        Some(concept.truth.clone())
+    }
+    
+    /// How much a moment of this type counts as evidence for a concept.
+    /// Failures carry no supporting weight; they are counter-evidence.
+    pub fn evidence_reliability(moment_type: &MomentType) -> f32 {
+        match moment_type {
+            MomentType::Breakthrough => 1.0,
+            MomentType::Application
+            | MomentType::MetaReflection
+            | MomentType::Verification { confirmed: true } => 0.8,
+            MomentType::Struggle | MomentType::Encounter | MomentType::Custom(_) => 0.5,
+            MomentType::Hypothesis => 0.3,
+            MomentType::Failure
+            | MomentType::Verification { confirmed: false }
+            | MomentType::DecisionReversal => 0.0,
+        }
+    }
+    
+    fn moment_truth(moment: &Moment) -> TruthValue {
+        TruthValue::new(moment.qualia.satisfaction, 0.5 + moment.qualia.satisfaction * 0.4)
+    }
+    
     fn content_addressable_fingerprint(&self, content: &str) -> u64 {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
@@ -119,16 +276,72 @@ impl ConceptExtractor {
         (abs - con + 5).clamp(0, 10) as u8
     }
     
+    /// Relate two known concepts. Returns false if either id is unknown.
+    pub fn relate(&mut self, source_id: &str, target_id: &str, relation_type: RelationType, strength: f32) -> bool {
+        if !self.concepts.contains_key(target_id) { return false; }
+        let Some(source) = self.concepts.get_mut(source_id) else { return false; };
+        source.relations.push(ConceptRelation {
+            target_id: target_id.to_string(),
+            relation_type,
+            strength: strength.clamp(0.0, 1.0),
+        });
+        true
+    }
+    
+    /// Relations as NARS statements over fingerprint-grounded terms. The truth
+    /// takes the relation strength as frequency and the source concept's
+    /// confidence; relations below `min_relation_confidence` are skipped.
+    pub fn relation_statements(&self) -> Vec<(Statement, TruthValue)> {
+        let mut out = Vec::new();
+        for source in self.concepts.values() {
+            for rel in &source.relations {
+                let (Some(copula), Some(target)) = (rel.relation_type.copula(), self.concepts.get(&rel.target_id)) else {
+                    continue;
+                };
+                let statement = Statement::new(
+                    Term::grounded(&source.name, source.full_fingerprint.clone()),
+                    copula,
+                    Term::grounded(&target.name, target.full_fingerprint.clone()),
+                );
+                let truth = TruthValue::new(rel.strength, source.truth.confidence);
+                if truth.is_informative(self.min_relation_confidence) {
+                    out.push((statement, truth));
+                }
+            }
+        }
+        out
+    }
+    
     pub fn get(&self, id: &str) -> Option<&ExtractedConcept> { self.concepts.get(id) }
     
+    /// A concept's truth as seen at `current_cycle`, after the decay policy
+    pub fn truth_at(&self, id: &str, current_cycle: u64) -> Option<TruthValue> {
+        let concept = self.concepts.get(id)?;
+        Some(match &self.decay {
+            Some(policy) => policy.apply(&concept.truth, concept.last_confirmed_cycle, current_cycle),
+            None => concept.truth.clone(),
+        })
+    }
+    
     pub fn all(&self) -> impl Iterator<Item = &ExtractedConcept> { self.concepts.values() }
     
     pub fn to_cypher(&self) -> String {
+        self.cypher_with(|name| name.to_string())
+    }
+    
+    /// Cypher export with concept names run through `redactor`
+    pub fn to_cypher_redacted(&self, redactor: &Redactor) -> String {
+        self.cypher_with(|name| redactor.redact(name))
+    }
+    
+    /// One statement per concept, MERGEd on its id and ending in `;` like the
+    /// blackboard graph, so the two can run as one script
+    fn cypher_with(&self, text: impl Fn(&str) -> String) -> String {
         let mut cypher = String::new();
         for c in self.concepts.values() {
             cypher.push_str(&format!(
-                "CREATE (c:Concept {{id: '{}', name: '{}', cam: {}, abstraction: {}}})\n",
-                c.id, c.name.replace('\'', "\\'"), c.cam_fingerprint, c.abstraction_level
+                "MERGE (c:Concept {{id: '{}'}}) SET c.name = '{}', c.cam = {}, c.abstraction = {};\n",
+                escape_cypher(&c.id), escape_cypher(&text(&c.name)), c.cam_fingerprint, c.abstraction_level
             ));
         }
         cypher
@@ -138,3 +351,142 @@ impl ConceptExtractor {
 impl Default for ConceptExtractor {
     fn default() -> Self { Self::new() }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::moment::MomentBuilder;
+    
+    fn breakthrough(content: &str) -> Moment {
+        MomentBuilder::new("session", content).breakthrough().qualia(0.8, 0.5, 0.9).build()
+    }
+    
+    #[test]
+    fn test_provenance_collects_artifacts() {
+        let mut extractor = ConceptExtractor::new();
+        let first = MomentBuilder::new("s", "Version constant drives the gem release")
+            .breakthrough()
+            .file_lines("version.rb", 42, 42)
+            .build();
+        let again = MomentBuilder::new("s", "Version constant drives the gem release")
+            .breakthrough()
+            .file_lines("version.rb", 42, 42)
+            .url("https://example.com/release")
+            .build();
+        extractor.extract(&first).unwrap();
+        let concept = extractor.extract(&again).unwrap();
+        assert_eq!(concept.artifacts, vec![
+            Artifact::file_lines("version.rb", 42, 42),
+            Artifact::url("https://example.com/release"),
+        ]);
+    }
+    
+    #[test]
+    fn test_extract_resolution_keeps_problem_and_solution() {
+        let struggle = MomentBuilder::new("s", "borrowck rejects the cache lookup").struggle().build();
+        let fix = breakthrough("Clone the cache key. The lookup no longer borrows");
+        let mut extractor = ConceptExtractor::new();
+        
+        let concept = extractor.extract_resolution(&struggle, &fix, 1).unwrap();
+        assert_eq!(concept.name, "Clone the cache key");
+        assert_eq!(concept.description, "Problem: borrowck rejects the cache lookup | Solution: Clone the cache key. The lookup no longer borrows");
+        assert_eq!(concept.problem_moment_id.as_deref(), Some(struggle.id.as_str()));
+        assert!(extractor.extract_resolution(&fix, &struggle, 1).is_none());
+    }
+    
+    #[test]
+    fn test_extract_anti_pattern() {
+        let struggle = MomentBuilder::new("s", "Patching the ORM to scope keys. Every upgrade breaks it")
+            .struggle()
+            .qualia(0.3, 0.9, 0.2)
+            .build();
+        let mut extractor = ConceptExtractor::new();
+        
+        let concept = extractor.extract_anti_pattern(&struggle, AbandonReason::WrongApproach, 3).unwrap();
+        assert!(concept.anti_pattern);
+        assert_eq!(concept.name, "Patching the ORM to scope keys");
+        assert_eq!(concept.description, "Anti-pattern (abandoned: wrong approach): Patching the ORM to scope keys. Every upgrade breaks it");
+        assert!((concept.truth.frequency - 0.8).abs() < 1e-6);
+        
+        let again = extractor.extract_anti_pattern(&struggle, AbandonReason::WrongApproach, 4).unwrap();
+        assert_eq!(again.id, concept.id);
+        assert!(again.truth.confidence > concept.truth.confidence);
+        assert_eq!(extractor.duplicate_hits, 1);
+        assert!(extractor.extract_anti_pattern(&breakthrough("Scope keys in a view"), AbandonReason::Blocked, 4).is_none());
+        assert!(!extractor.extract(&breakthrough("Scope keys in a view")).unwrap().anti_pattern);
+    }
+    
+    #[test]
+    fn test_cypher_redacted() {
+        let mut extractor = ConceptExtractor::new();
+        extractor.extract(&breakthrough("Cache auth needs password=hunter2 set. Then restart")).unwrap();
+        let redactor = Redactor::new().with_pattern("password", "password=*").unwrap();
+        let cypher = extractor.to_cypher_redacted(&redactor);
+        assert!(extractor.to_cypher().contains("hunter2"));
+        assert!(!cypher.contains("hunter2"));
+        assert!(cypher.contains("c.name = 'Cache auth needs [REDACTED:password] set'"));
+    }
+    
+    #[test]
+    fn test_relation_statements() {
+        let mut extractor = ConceptExtractor::new();
+        let versions = extractor.extract(&breakthrough("Versions are project-scoped")).unwrap();
+        let sprints = extractor.extract(&breakthrough("Sprints are project-scoped")).unwrap();
+        
+        assert!(extractor.relate(&sprints.id, &versions.id, RelationType::SimilarTo, 0.8));
+        assert!(extractor.relate(&sprints.id, &versions.id, RelationType::Contradicts, 0.1));
+        assert!(!extractor.relate(&sprints.id, "missing", RelationType::Refines, 0.5));
+        
+        let statements = extractor.relation_statements();
+        assert_eq!(statements.len(), 1);
+        let (statement, truth) = &statements[0];
+        assert_eq!(statement.to_string(), "<Sprints are project-scoped <-> Versions are project-scoped>");
+        assert!((truth.frequency - 0.8).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_relation_confidence_floor() {
+        let mut extractor = ConceptExtractor::new();
+        let a = extractor.extract(&breakthrough("Versions are project-scoped")).unwrap();
+        let b = extractor.extract(&breakthrough("Sprints are project-scoped")).unwrap();
+        extractor.relate(&a.id, &b.id, RelationType::SimilarTo, 0.9);
+        
+        extractor.min_relation_confidence = a.truth.confidence;
+        assert_eq!(extractor.relation_statements().len(), 1);
+        extractor.min_relation_confidence = a.truth.confidence + 0.01;
+        assert!(extractor.relation_statements().is_empty());
+    }
+    
+    #[test]
+    fn test_confirm_and_contradict() {
+        let mut extractor = ConceptExtractor::new();
+        let concept = extractor.extract(&breakthrough("Versions are project-scoped")).unwrap();
+        
+        let confirmed = extractor.confirm(&concept.id, 3.0, 0).unwrap();
+        assert!(confirmed.confidence > concept.truth.confidence);
+        
+        let contradicted = extractor.contradict(&concept.id, 3.0, 0).unwrap();
+        assert!(contradicted.frequency < confirmed.frequency);
+        
+        let stored = extractor.get(&concept.id).unwrap();
+        assert_eq!(stored.evidence.positive, 3.0);
+        assert_eq!(stored.evidence.negative, 3.0);
+        assert!(extractor.confirm("missing", 1.0, 0).is_none());
+    }
+    
+    #[test]
+    fn test_truth_decays_until_reconfirmed() {
+        let mut extractor = ConceptExtractor::new();
+        extractor.decay = Some(DecayPolicy::new(100.0));
+        let moment = breakthrough("Versions are project-scoped");
+        let concept = extractor.extract_at(&moment, 10).unwrap();
+        
+        let fresh = extractor.truth_at(&concept.id, 10).unwrap();
+        let faded = extractor.truth_at(&concept.id, 110).unwrap();
+        assert!((faded.confidence - fresh.confidence / 2.0).abs() < 1e-6);
+        
+        extractor.extract_at(&moment, 110);
+        let reconfirmed = extractor.truth_at(&concept.id, 110).unwrap();
+        assert!(reconfirmed.confidence > faded.confidence);
+    }
+}
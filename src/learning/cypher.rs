@@ -0,0 +1,241 @@
+//! Cypher export — a blackboard's decision graph for Neo4j, and the string
+//! escaping it shares with `ConceptExtractor::to_cypher`
+
+use std::fmt::Write;
+use crate::learning::blackboard::Blackboard;
+
+/// Escape text for a single-quoted Cypher string literal: backslashes,
+/// quotes, line breaks, tabs and other control characters
+pub fn escape_cypher(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => { let _ = write!(out, "\\u{:04X}", c as u32); }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Node labels of the decision graph
+const SESSION: &str = "Session";
+const MOMENT: &str = "Moment";
+const DECISION: &str = "Decision";
+const QUESTION: &str = "Question";
+const LAYER: &str = "IceCakedLayer";
+
+/// Statements of a graph being built, nodes before the edges between them
+#[derive(Default)]
+struct Graph<'a> {
+    nodes: Vec<String>,
+    edges: Vec<String>,
+    /// Sessions and moments seen so far, merged once each
+    known: Vec<(&'static str, &'a str)>,
+}
+
+impl<'a> Graph<'a> {
+    /// MERGE on `id`, then SET `props` (name and Cypher literal) if any
+    fn node(&mut self, label: &str, id: &str, props: &[(&str, String)]) {
+        let mut statement = format!("MERGE (n:{} {{id: '{}'}})", label, escape_cypher(id));
+        for (i, (name, value)) in props.iter().enumerate() {
+            let _ = write!(statement, "{} n.{} = {}", if i == 0 { " SET" } else { "," }, name, value);
+        }
+        statement.push(';');
+        self.nodes.push(statement);
+    }
+    
+    /// A Session or Moment node, unless already merged
+    fn once(&mut self, label: &'static str, id: &'a str) {
+        if !self.known.contains(&(label, id)) {
+            self.known.push((label, id));
+            self.node(label, id, &[]);
+        }
+    }
+    
+    /// MERGE an edge between merged nodes, unless the same edge already is
+    fn edge(&mut self, from: (&str, &str), relation: &str, to: (&str, &str)) {
+        let statement = format!("MATCH (a:{} {{id: '{}'}}), (b:{} {{id: '{}'}}) MERGE (a)-[:{}]->(b);",
+            from.0, escape_cypher(from.1), to.0, escape_cypher(to.1), relation);
+        if !self.edges.contains(&statement) {
+            self.edges.push(statement);
+        }
+    }
+    
+    fn finish(self) -> String {
+        self.nodes.into_iter().chain(self.edges).map(|statement| statement + "\n").collect()
+    }
+}
+
+fn text(value: &str) -> String {
+    format!("'{}'", escape_cypher(value))
+}
+
+impl Blackboard {
+    /// The decision graph as Cypher, one statement per line, each ending
+    /// in `;`. Nodes come first: the Session, Decision (conflicting ones
+    /// included), Question and IceCakedLayer nodes, and a Moment node for
+    /// each moment they point at, all MERGEd on their ids (a layer's is its
+    /// `decision_id`), so running the export again updates the graph
+    /// instead of duplicating it. Then the edges: SUPERSEDES between
+    /// revised decisions and replaced layers, RESOLVES from the moment
+    /// that answered a question, DERIVED_FROM to the moments a decision,
+    /// layer or question came from, and BELONGS_TO from everything to the
+    /// blackboard's session and from provenance moments to theirs.
+    pub fn to_cypher(&self) -> String {
+        let mut graph = Graph::default();
+        let session = (SESSION, self.session_id.as_str());
+        graph.known.push(session);
+        graph.node(SESSION, &self.session_id, &[("task", text(&self.current_task.id))]);
+        
+        for d in self.decisions.iter().chain(self.decision_conflicts.iter().flat_map(|c| &c.decisions)) {
+            graph.node(DECISION, &d.id, &[
+                ("task", text(&d.task)),
+                ("choice", text(&d.choice)),
+                ("rationale", text(&d.rationale)),
+                ("gate_state", text(&d.gate_state)),
+                ("ice_caked", d.ice_caked.to_string()),
+                ("cycle", d.cycle.to_string()),
+                ("agent", text(&d.agent.to_string())),
+            ]);
+            graph.edge((DECISION, &d.id), "BELONGS_TO", session);
+            if let Some(old) = &d.supersedes {
+                graph.edge((DECISION, &d.id), "SUPERSEDES", (DECISION, old));
+            }
+            for source in &d.provenance {
+                graph.once(SESSION, &source.session_id);
+                graph.once(MOMENT, &source.moment_id);
+                graph.edge((DECISION, &d.id), "DERIVED_FROM", (MOMENT, &source.moment_id));
+                graph.edge((MOMENT, &source.moment_id), "BELONGS_TO", (SESSION, &source.session_id));
+            }
+        }
+        
+        for q in &self.questions {
+            let id = q.id.as_str();
+            let mut props = vec![("text", text(&q.text)), ("resolved", q.resolution.is_some().to_string())];
+            if let Some(resolution) = &q.resolution {
+                props.push(("resolution", text(&resolution.text)));
+            }
+            graph.node(QUESTION, id, &props);
+            graph.edge((QUESTION, id), "BELONGS_TO", session);
+            if let Some(moment_id) = &q.moment_id {
+                graph.once(MOMENT, moment_id);
+                graph.edge((QUESTION, id), "DERIVED_FROM", (MOMENT, moment_id));
+            }
+            if let Some(moment_id) = q.resolution.as_ref().and_then(|r| r.moment_id.as_deref()) {
+                graph.once(MOMENT, moment_id);
+                graph.edge((MOMENT, moment_id), "RESOLVES", (QUESTION, id));
+            }
+        }
+        
+        for layer in &self.ice_cake_layers {
+            let id = layer.decision_id.as_str();
+            graph.node(LAYER, id, &[
+                ("layer_id", layer.layer_id.to_string()),
+                ("content", text(&layer.content)),
+                ("rationale", text(&layer.rationale)),
+                ("confidence", format!("{:.2}", layer.truth.confidence)),
+                ("cycle", layer.ice_caked_at_cycle.to_string()),
+            ]);
+            graph.edge((LAYER, id), "BELONGS_TO", session);
+            graph.once(MOMENT, id);
+            graph.edge((LAYER, id), "DERIVED_FROM", (MOMENT, id));
+            if let Some(by) = &layer.superseded_by {
+                graph.edge((LAYER, by), "SUPERSEDES", (LAYER, id));
+            }
+        }
+        graph.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cognitive::GateState;
+    use crate::learning::{LearningSession, Provenance, ProvenanceKind};
+    
+    /// String literals of each statement, unescaped; panics on a statement
+    /// spread over lines, not ending in `;` or with an unterminated string
+    fn parse(cypher: &str) -> Vec<Vec<String>> {
+        cypher.lines().map(|line| {
+            assert!(line.ends_with(';'), "{}", line);
+            let mut literals = Vec::new();
+            let mut chars = line.chars();
+            while let Some(c) = chars.next() {
+                if c != '\'' {
+                    continue;
+                }
+                let mut literal = String::new();
+                loop {
+                    match chars.next().expect("unterminated string") {
+                        '\'' => break,
+                        '\\' => literal.push(match chars.next().expect("dangling escape") {
+                            'n' => '\n',
+                            'r' => '\r',
+                            't' => '\t',
+                            escaped => escaped,
+                        }),
+                        c => literal.push(c),
+                    }
+                }
+                literals.push(literal);
+            }
+            literals
+        }).collect()
+    }
+    
+    fn count(cypher: &str, pattern: &str) -> usize {
+        cypher.lines().filter(|line| line.contains(pattern)).count()
+    }
+    
+    #[test]
+    fn test_one_statement_per_node_and_edge() {
+        let mut session = LearningSession::new("lexer");
+        let fix = session.breakthrough("track byte offsets", 0.9).id.clone();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        let mut bb = Blackboard::new(&session.id, "lexer", "Parser rejects UTF-8 input");
+        bb.update_from_session(&session.state());
+        bb.record_decision("columns", "Count chars", "simple", GateState::Flow);
+        let chars = bb.decisions.last().unwrap().id.clone();
+        let graphemes = bb.revise_decision(&chars, "Count graphemes", "emoji").unwrap();
+        bb.add_provenance(&graphemes, Provenance { session_id: "s-0".to_string(), moment_id: "m-7".to_string(), kind: ProvenanceKind::Breakthrough }).unwrap();
+        let tabs = bb.add_question("Do tabs count as one column?");
+        bb.resolve_question(&tabs, "yes", Some("m-9")).unwrap();
+        bb.add_question("Should CRLF reset the column?");
+        
+        let cypher = bb.to_cypher();
+        let nodes = [(SESSION, 2), (MOMENT, 3), (DECISION, 3), (QUESTION, 2), (LAYER, 1)];
+        for (label, n) in nodes {
+            assert_eq!(count(&cypher, &format!("MERGE (n:{} ", label)), n, "{} nodes", label);
+        }
+        let edges = [("SUPERSEDES", 1), ("RESOLVES", 1), ("DERIVED_FROM", 3), ("BELONGS_TO", 8)];
+        for (relation, n) in edges {
+            assert_eq!(count(&cypher, &format!("MERGE (a)-[:{}]->(b)", relation)), n, "{} edges", relation);
+        }
+        let entities: usize = nodes.iter().chain(&edges).map(|(_, n)| n).sum();
+        assert_eq!(cypher.lines().count(), entities);
+        assert_eq!(parse(&cypher).len(), entities);
+        assert!(cypher.contains(&format!("MATCH (a:Decision {{id: '{}'}}), (b:Decision {{id: '{}'}}) MERGE (a)-[:SUPERSEDES]->(b);\n", graphemes, chars)));
+        assert!(cypher.contains(&format!("MATCH (a:Moment {{id: 'm-9'}}), (b:Question {{id: '{}'}}) MERGE (a)-[:RESOLVES]->(b);\n", tabs)));
+        assert_eq!(bb.to_cypher(), cypher, "stable across runs");
+    }
+    
+    #[test]
+    fn test_quotes_and_newlines_stay_in_their_literal() {
+        let choice = "Use 'slices' over \"owned\" strings\nsecond line \\ with a backslash\tand a tab";
+        let mut bb = Blackboard::new("s-1", "parser", "Parser rejects UTF-8 input");
+        bb.record_decision("lexer", choice, "it's cheaper", GateState::Flow);
+        let cypher = bb.to_cypher();
+        assert_eq!(cypher.lines().count(), 3, "session, decision and its BELONGS_TO");
+        let decision = &parse(&cypher)[1];
+        assert_eq!(decision[0], bb.decisions[0].id);
+        assert_eq!(decision[2], choice);
+        assert_eq!(decision[3], "it's cheaper");
+        assert_eq!(escape_cypher("a\u{7}b"), "a\\u0007b");
+    }
+}
@@ -0,0 +1,474 @@
+//! MomentDiff — what changed between two related moments; BlackboardDiff —
+//! what changed on a blackboard since a snapshot
+
+use std::fmt::Write;
+use crate::learning::blackboard::{Blackboard, Decision, Question, QuestionId};
+use crate::learning::markdown::escape_markdown;
+use crate::learning::moment::{Moment, MomentLink, MomentType, Qualia};
+
+/// Qualia deltas smaller than this are treated as unchanged
+const QUALIA_EPSILON: f32 = 1e-6;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetadataChange {
+    pub key: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Changes from one moment (`before`) to another (`after`)
+#[derive(Clone, Debug, PartialEq)]
+pub struct MomentDiff {
+    /// Whitespace tokens present only in `after`, in order
+    pub added_tokens: Vec<String>,
+    /// Whitespace tokens present only in `before`, in order
+    pub removed_tokens: Vec<String>,
+    /// `after − before` for every qualia dimension that changed
+    pub qualia_deltas: Vec<(&'static str, f32)>,
+    pub fingerprint_similarity: f32,
+    pub type_change: Option<(MomentType, MomentType)>,
+    pub links_added: Vec<MomentLink>,
+    pub links_removed: Vec<MomentLink>,
+    pub metadata_changes: Vec<MetadataChange>,
+}
+
+impl Moment {
+    /// What changed going from `self` to `other`
+    pub fn diff(&self, other: &Moment) -> MomentDiff {
+        let before: Vec<&str> = self.content.split_whitespace().collect();
+        let after: Vec<&str> = other.content.split_whitespace().collect();
+        let (removed_tokens, added_tokens) = token_diff(&before, &after);
+        
+        let qualia_deltas = dimensions(&self.qualia).iter()
+            .zip(dimensions(&other.qualia).iter())
+            .map(|((name, a), (_, b))| (*name, b - a))
+            .filter(|(_, delta)| delta.abs() > QUALIA_EPSILON)
+            .collect();
+        
+        let type_change = (self.moment_type != other.moment_type)
+            .then(|| (self.moment_type.clone(), other.moment_type.clone()));
+        
+        let links_added = other.links.iter().filter(|l| !self.links.contains(l)).cloned().collect();
+        let links_removed = self.links.iter().filter(|l| !other.links.contains(l)).cloned().collect();
+        
+        let mut keys: Vec<&String> = self.metadata.keys().chain(other.metadata.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        let metadata_changes = keys.into_iter()
+            .filter(|k| self.metadata.get(*k) != other.metadata.get(*k))
+            .map(|k| MetadataChange {
+                key: k.clone(),
+                before: self.metadata.get(k).cloned(),
+                after: other.metadata.get(k).cloned(),
+            })
+            .collect();
+        
+        MomentDiff {
+            added_tokens,
+            removed_tokens,
+            qualia_deltas,
+            fingerprint_similarity: self.fingerprint.similarity(&other.fingerprint),
+            type_change,
+            links_added,
+            links_removed,
+            metadata_changes,
+        }
+    }
+}
+
+impl MomentDiff {
+    /// True when nothing but the identity of the moments differs
+    pub fn is_empty(&self) -> bool {
+        self.added_tokens.is_empty()
+            && self.removed_tokens.is_empty()
+            && self.qualia_deltas.is_empty()
+            && self.type_change.is_none()
+            && self.links_added.is_empty()
+            && self.links_removed.is_empty()
+            && self.metadata_changes.is_empty()
+    }
+    
+    pub fn to_report(&self) -> String {
+        let mut s = String::new();
+        if self.is_empty() {
+            s.push_str("No differences\n");
+            return s;
+        }
+        if let Some((from, to)) = &self.type_change {
+            let _ = writeln!(s, "Type: {} -> {}", from.name(), to.name());
+        }
+        if self.added_tokens.is_empty() && self.removed_tokens.is_empty() {
+            s.push_str("Content: identical\n");
+        } else {
+            s.push_str("Content:\n");
+            if !self.removed_tokens.is_empty() {
+                let _ = writeln!(s, "  - {}", self.removed_tokens.join(" "));
+            }
+            if !self.added_tokens.is_empty() {
+                let _ = writeln!(s, "  + {}", self.added_tokens.join(" "));
+            }
+        }
+        let _ = writeln!(s, "Fingerprint similarity: {:.3}", self.fingerprint_similarity);
+        if !self.qualia_deltas.is_empty() {
+            s.push_str("Qualia:\n");
+            for (name, delta) in &self.qualia_deltas {
+                let _ = writeln!(s, "  {}: {:+.2}", name, delta);
+            }
+        }
+        if !self.links_added.is_empty() || !self.links_removed.is_empty() {
+            s.push_str("Links:\n");
+            for link in &self.links_removed {
+                let _ = writeln!(s, "  - {:?} {}", link.kind, link.target_id);
+            }
+            for link in &self.links_added {
+                let _ = writeln!(s, "  + {:?} {}", link.kind, link.target_id);
+            }
+        }
+        if !self.metadata_changes.is_empty() {
+            s.push_str("Metadata:\n");
+            for change in &self.metadata_changes {
+                let _ = writeln!(s, "  {}: {} -> {}",
+                    change.key,
+                    change.before.as_deref().unwrap_or("(none)"),
+                    change.after.as_deref().unwrap_or("(none)"));
+            }
+        }
+        s
+    }
+}
+
+/// What `Blackboard::diff` compares against, from `Blackboard::snapshot`:
+/// decision and question ids, counters and state texts, not the content
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlackboardSnapshot {
+    /// Id and whether it was superseded, per decision
+    decisions: Vec<(String, bool)>,
+    resolved_questions: Vec<QuestionId>,
+    counters: Vec<(&'static str, u64)>,
+    state: Vec<(&'static str, String)>,
+}
+
+/// A state text that changed, e.g. the phase
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateChange {
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+/// Changes on a blackboard since a `BlackboardSnapshot`
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlackboardDiff {
+    pub new_decisions: Vec<Decision>,
+    /// Decisions current at the snapshot and superseded since
+    pub superseded_decisions: Vec<Decision>,
+    pub resolved_questions: Vec<Question>,
+    /// Now minus then, for every counter that changed
+    pub counter_deltas: Vec<(&'static str, i64)>,
+    pub state_changes: Vec<StateChange>,
+}
+
+fn counters(bb: &Blackboard) -> Vec<(&'static str, u64)> {
+    vec![
+        ("moments", bb.moment_types.values().sum::<usize>() as u64),
+        ("resonance captures", bb.resonance_captures),
+        ("concepts extracted", bb.concepts_extracted),
+        ("ice-caked layers", bb.ice_cake_layers.iter().filter(|layer| layer.superseded_by.is_none()).count() as u64),
+        ("open questions", bb.all_open_questions().len() as u64),
+    ]
+}
+
+fn state_texts(bb: &Blackboard) -> Vec<(&'static str, String)> {
+    let or_none = |text: Option<String>| text.unwrap_or_else(|| "(none)".to_string());
+    vec![
+        ("phase", bb.current_task.phase.clone()),
+        ("progress", format!("{:.0}%", bb.current_task.progress * 100.0)),
+        ("gate", or_none(bb.current_task.gate_state.clone())),
+        ("thinking style", bb.consciousness.thinking_style.clone()),
+        ("last open struggle", or_none(bb.last_open_struggle.clone())),
+        ("outcome", or_none(bb.outcome.as_ref().map(|outcome| outcome.to_string()))),
+    ]
+}
+
+impl Blackboard {
+    pub fn snapshot(&self) -> BlackboardSnapshot {
+        BlackboardSnapshot {
+            decisions: self.decisions.iter().map(|d| (d.id.clone(), d.superseded_by.is_some())).collect(),
+            resolved_questions: self.resolved_questions().map(|q| q.id.clone()).collect(),
+            counters: counters(self),
+            state: state_texts(self),
+        }
+    }
+    
+    /// What changed since `since` was taken, e.g. for a status update
+    pub fn diff(&self, since: &BlackboardSnapshot) -> BlackboardDiff {
+        let then = |id: &str| since.decisions.iter().find(|(known, _)| known == id).map(|&(_, superseded)| superseded);
+        BlackboardDiff {
+            new_decisions: self.decisions.iter().filter(|d| then(&d.id).is_none()).cloned().collect(),
+            superseded_decisions: self.decisions.iter()
+                .filter(|d| d.superseded_by.is_some() && then(&d.id) == Some(false))
+                .cloned()
+                .collect(),
+            resolved_questions: self.resolved_questions()
+                .filter(|q| !since.resolved_questions.contains(&q.id))
+                .cloned()
+                .collect(),
+            counter_deltas: counters(self).into_iter().zip(&since.counters)
+                .map(|((name, now), (_, then))| (name, now as i64 - *then as i64))
+                .filter(|&(_, delta)| delta != 0)
+                .collect(),
+            state_changes: state_texts(self).into_iter().zip(&since.state)
+                .filter(|((_, now), (_, then))| now != then)
+                .map(|((field, after), (_, before))| StateChange { field, before: before.clone(), after })
+                .collect(),
+        }
+    }
+}
+
+impl BlackboardDiff {
+    pub fn is_empty(&self) -> bool {
+        self.new_decisions.is_empty()
+            && self.superseded_decisions.is_empty()
+            && self.resolved_questions.is_empty()
+            && self.counter_deltas.is_empty()
+            && self.state_changes.is_empty()
+    }
+    
+    /// Status-update Markdown; content is escaped
+    pub fn to_markdown(&self) -> String {
+        let mut s = String::from("## Blackboard Changes\n\n");
+        if self.is_empty() {
+            s.push_str("No changes since the snapshot.\n");
+            return s;
+        }
+        if !self.state_changes.is_empty() {
+            s.push_str("### State\n\n");
+            for change in &self.state_changes {
+                let _ = writeln!(s, "- {}: {} → {}", change.field, escape_markdown(&change.before), escape_markdown(&change.after));
+            }
+            s.push('\n');
+        }
+        if !self.new_decisions.is_empty() || !self.superseded_decisions.is_empty() {
+            s.push_str("### Decisions\n\n");
+            for decision in &self.new_decisions {
+                let _ = writeln!(s, "- New: {} ({})", escape_markdown(&decision.choice), escape_markdown(&decision.task));
+            }
+            for decision in &self.superseded_decisions {
+                let _ = writeln!(s, "- Superseded: {}", escape_markdown(&decision.choice));
+            }
+            s.push('\n');
+        }
+        if !self.resolved_questions.is_empty() {
+            s.push_str("### Resolved Questions\n\n");
+            for question in &self.resolved_questions {
+                let resolution = question.resolution.as_ref().map(|r| r.text.as_str()).unwrap_or_default();
+                let _ = writeln!(s, "- {} → {}", escape_markdown(&question.text), escape_markdown(resolution));
+            }
+            s.push('\n');
+        }
+        if !self.counter_deltas.is_empty() {
+            s.push_str("### Counters\n\n");
+            for (name, delta) in &self.counter_deltas {
+                let _ = writeln!(s, "- {}: {:+}", name, delta);
+            }
+            s.push('\n');
+        }
+        s.pop();
+        s
+    }
+}
+
+fn dimensions(q: &Qualia) -> [(&'static str, f32); 8] {
+    [
+        ("novelty", q.novelty),
+        ("effort", q.effort),
+        ("satisfaction", q.satisfaction),
+        ("confusion", q.confusion),
+        ("surprise", q.surprise),
+        ("valence", q.valence),
+        ("arousal", q.arousal),
+        ("confidence", q.confidence),
+    ]
+}
+
+/// Longest-common-subsequence token diff: (removed, added)
+fn token_diff(before: &[&str], after: &[&str]) -> (Vec<String>, Vec<String>) {
+    let (n, m) = (before.len(), after.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if before[i] == after[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if before[i] == after[j] {
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            removed.push(before[i].to_string());
+            i += 1;
+        } else {
+            added.push(after[j].to_string());
+            j += 1;
+        }
+    }
+    removed.extend(before[i..].iter().map(|t| t.to_string()));
+    added.extend(after[j..].iter().map(|t| t.to_string()));
+    (removed, added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cognitive::GateState;
+    use crate::learning::moment::{LinkKind, MomentBuilder};
+    use crate::learning::LearningSession;
+    
+    #[test]
+    fn test_diff_with_self_is_empty() {
+        let moment = MomentBuilder::new("s", "retry with backoff").breakthrough().qualia(0.7, 0.4, 0.9).build();
+        let diff = moment.diff(&moment);
+        assert!(diff.is_empty());
+        assert_eq!(diff.fingerprint_similarity, 1.0);
+        assert_eq!(diff.to_report(), "No differences\n");
+    }
+    
+    #[test]
+    fn test_failed_vs_successful_attempt() {
+        let failed = MomentBuilder::new("s", "call the API without retry and hope")
+            .failure()
+            .qualia(0.4, 0.8, 0.2)
+            .meta("attempt", "1").unwrap()
+            .meta("ticket", "OPS-9").unwrap()
+            .build();
+        let fixed = MomentBuilder::new("s", "call the API with retry and backoff")
+            .breakthrough()
+            .qualia(0.4, 0.5, 0.9)
+            .meta("attempt", "2").unwrap()
+            .meta("ticket", "OPS-9").unwrap()
+            .link(LinkKind::Resolves, &failed.id)
+            .build();
+        
+        let diff = failed.diff(&fixed);
+        assert_eq!(diff.removed_tokens, vec!["without", "hope"]);
+        assert_eq!(diff.added_tokens, vec!["with", "backoff"]);
+        assert_eq!(diff.type_change, Some((MomentType::Failure, MomentType::Breakthrough)));
+        let deltas: Vec<&str> = diff.qualia_deltas.iter().map(|(name, _)| *name).collect();
+        assert_eq!(deltas, vec!["effort", "satisfaction"]);
+        assert!((diff.qualia_deltas[0].1 + 0.3).abs() < 1e-6);
+        assert!((diff.qualia_deltas[1].1 - 0.7).abs() < 1e-6);
+        assert_eq!(diff.links_added.len(), 1);
+        assert!(diff.links_removed.is_empty());
+        assert_eq!(diff.metadata_changes, vec![MetadataChange {
+            key: "attempt".to_string(),
+            before: Some("1".to_string()),
+            after: Some("2".to_string()),
+        }]);
+        
+        let report = diff.to_report();
+        assert!(report.contains("Type: Failure -> Breakthrough\n"));
+        assert!(report.contains("  - without hope\n  + with backoff\n"));
+        assert!(report.contains("  satisfaction: +0.70\n"));
+        assert!(report.contains("  attempt: 1 -> 2\n"));
+    }
+    
+    #[test]
+    fn test_identical_content_different_feeling() {
+        let a = MomentBuilder::new("s", "same words").struggle().qualia(0.3, 0.9, 0.1).build();
+        let b = MomentBuilder::new("s", "same words").struggle().qualia(0.3, 0.9, 0.6).build();
+        let diff = a.diff(&b);
+        assert!(diff.added_tokens.is_empty() && diff.removed_tokens.is_empty());
+        assert_eq!(diff.fingerprint_similarity, 1.0);
+        assert_eq!(diff.type_change, None);
+        assert!(diff.to_report().contains("Content: identical\n"));
+    }
+    
+    #[test]
+    fn test_identical_snapshots_give_an_empty_diff() {
+        let mut bb = Blackboard::new("s-1", "task", "desc");
+        bb.record_decision("lexer", "Track byte offsets", "cheap", GateState::Flow);
+        let snapshot = bb.snapshot();
+        let diff = bb.diff(&snapshot);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_markdown(), "## Blackboard Changes\n\nNo changes since the snapshot.\n");
+        bb.note("notes are not tracked");
+        assert!(bb.diff(&snapshot).is_empty());
+    }
+    
+    #[test]
+    fn test_decisions_new_and_superseded() {
+        let mut bb = Blackboard::new("s-1", "task", "desc");
+        bb.record_decision("lexer", "Track char offsets", "simplest", GateState::Flow);
+        bb.record_decision("cli", "Read stdin", "pipes", GateState::Hold);
+        let first = bb.decisions[0].id.clone();
+        let snapshot = bb.snapshot();
+        let second = bb.revise_decision(&first, "Track byte offsets", "chars are slow").unwrap();
+        bb.revise_decision(&second, "Track `u32` byte offsets", "halves memory").unwrap();
+        
+        let diff = bb.diff(&snapshot);
+        let new: Vec<&str> = diff.new_decisions.iter().map(|d| d.choice.as_str()).collect();
+        assert_eq!(new, ["Track byte offsets", "Track `u32` byte offsets"]);
+        let superseded: Vec<&str> = diff.superseded_decisions.iter().map(|d| d.choice.as_str()).collect();
+        assert_eq!(superseded, ["Track char offsets"], "a decision new since the snapshot is only listed as new");
+        assert!(diff.counter_deltas.is_empty() && diff.state_changes.is_empty() && diff.resolved_questions.is_empty());
+        assert_eq!(diff.to_markdown(), concat!(
+            "## Blackboard Changes\n",
+            "\n",
+            "### Decisions\n",
+            "\n",
+            "- New: Track byte offsets (lexer)\n",
+            "- New: Track \\`u32\\` byte offsets (lexer)\n",
+            "- Superseded: Track char offsets\n",
+        ));
+    }
+    
+    #[test]
+    fn test_resolved_questions_and_counters() {
+        let mut bb = Blackboard::new("s-1", "task", "desc");
+        let tabs = bb.add_question("Do tabs count as one column?");
+        let bom = bb.add_question("Strip a leading BOM?");
+        bb.resolve_question(&bom, "yes", None).unwrap();
+        let snapshot = bb.snapshot();
+        bb.resolve_question(&tabs, "no, they expand to 4", Some("m-9")).unwrap();
+        bb.add_question("What about CRLF?");
+        bb.moment_types.insert("Struggle".to_string(), 3);
+        bb.concepts_extracted += 2;
+        
+        let diff = bb.diff(&snapshot);
+        let resolved: Vec<&QuestionId> = diff.resolved_questions.iter().map(|q| &q.id).collect();
+        assert_eq!(resolved, [&tabs]);
+        assert_eq!(diff.counter_deltas, [("moments", 3), ("concepts extracted", 2)]);
+        let markdown = diff.to_markdown();
+        assert!(markdown.contains("### Resolved Questions\n\n- Do tabs count as one column? → no, they expand to 4\n\n"));
+        assert!(markdown.ends_with("### Counters\n\n- moments: +3\n- concepts extracted: +2\n"), "{markdown}");
+        
+        let snapshot = bb.snapshot();
+        let crlf = bb.unresolved_questions().next().unwrap().id.clone();
+        bb.resolve_question(&crlf, "normalize first", None).unwrap();
+        assert_eq!(bb.diff(&snapshot).counter_deltas, [("open questions", -1)]);
+    }
+    
+    #[test]
+    fn test_state_text_changes() {
+        let mut session = LearningSession::new("task");
+        let mut bb = Blackboard::new(&session.id, "task", "desc");
+        bb.update_from_session(&session.state());
+        let snapshot = bb.snapshot();
+        session.struggle("offsets mixed up", 0.8, 0.7);
+        bb.update_from_session(&session.state());
+        
+        let diff = bb.diff(&snapshot);
+        let fields: Vec<&str> = diff.state_changes.iter().map(|change| change.field).collect();
+        assert_eq!(fields, ["phase", "gate", "last open struggle"]);
+        assert_eq!(diff.state_changes[0], StateChange { field: "phase", before: "Initialize".to_string(), after: "Struggle".to_string() });
+        assert_eq!(diff.counter_deltas, [("moments", 1), ("resonance captures", 1)]);
+        assert!(diff.to_markdown().contains("### State\n\n- phase: Initialize → Struggle\n- gate: (none) → "));
+    }
+}
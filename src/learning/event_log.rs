@@ -0,0 +1,151 @@
+//! Event log — a session's events appended as JSON lines
+
+use std::io::{self, Write};
+use serde::{Serialize, Deserialize};
+use crate::learning::session::{SessionOutcome, SessionPhase};
+
+/// Version written on every event line; bump on incompatible changes
+pub const EVENT_LOG_SCHEMA_VERSION: u32 = 1;
+
+/// One line of the event log, without its envelope
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "payload", rename_all = "snake_case")]
+pub enum SessionEvent {
+    MomentCreated { id: String, seq: u64, moment_type: String, content: String },
+    MomentEdited { id: String, revision: u32, content: String },
+    PhaseChanged { from: SessionPhase, to: SessionPhase },
+    IceCaked { layer_id: u32, moment_id: String, rationale: String },
+    Thawed { layer_id: u32, moment_id: String, reason: String },
+    Closed { outcome: SessionOutcome },
+}
+
+/// A line as written: the event with its envelope
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventLine {
+    pub schema_version: u32,
+    pub session_id: String,
+    /// Session clock time of the event
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: SessionEvent,
+}
+
+/// Writer attached with `LearningSession::attach_event_log`
+pub(crate) struct EventLog {
+    writer: Box<dyn Write + Send>,
+    last_error: Option<io::Error>,
+}
+
+impl EventLog {
+    pub(crate) fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self { writer, last_error: None }
+    }
+    
+    /// Write and flush one line; a failure is kept for `last_error` and the
+    /// next event is tried all the same
+    pub(crate) fn append(&mut self, line: &EventLine) {
+        if let Err(error) = self.try_append(line) {
+            self.last_error = Some(error);
+        }
+    }
+    
+    fn try_append(&mut self, line: &EventLine) -> io::Result<()> {
+        let mut json = serde_json::to_string(line)?;
+        json.push('\n');
+        self.writer.write_all(json.as_bytes())?;
+        self.writer.flush()
+    }
+    
+    pub(crate) fn last_error(&self) -> Option<&io::Error> {
+        self.last_error.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::learning::{LearningSession, MockClock};
+    
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+    
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    
+    struct FailingWriter;
+    
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+        
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[test]
+    fn test_event_sequence() {
+        let clock = MockClock::new(1_000);
+        let mut session = LearningSession::with_clock("parser", Arc::new(clock.clone()));
+        let buffer = SharedBuffer::default();
+        session.attach_event_log(Box::new(buffer.clone()));
+        let struggle = session.struggle("offsets mixed up", 0.6, 0.5).id.clone();
+        clock.advance(500);
+        session.edit_moment(&struggle, "byte and char offsets mixed up").unwrap();
+        let fix = session.breakthrough("track byte offsets only", 0.9).id.clone();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        session.thaw(1, "chars are needed for columns").unwrap();
+        session.close(SessionOutcome::Completed).unwrap();
+        
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<EventLine> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert!(lines.iter().all(|line| line.schema_version == EVENT_LOG_SCHEMA_VERSION && line.session_id == session.id));
+        let names: Vec<String> = text.lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["event"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names, [
+            "moment_created", "phase_changed", "moment_edited", "moment_created", "phase_changed",
+            "phase_changed", "ice_caked", "thawed", "moment_created", "phase_changed", "closed",
+        ]);
+        assert_eq!(lines[0].timestamp, 1_000);
+        assert_eq!(lines[2].timestamp, 1_500);
+        assert_eq!(lines[2].event, SessionEvent::MomentEdited {
+            id: struggle,
+            revision: 1,
+            content: "byte and char offsets mixed up".to_string(),
+        });
+        assert_eq!(lines[1].event, SessionEvent::PhaseChanged { from: SessionPhase::Initialize, to: SessionPhase::Struggle });
+        assert_eq!(lines[7].event, SessionEvent::Thawed {
+            layer_id: 1,
+            moment_id: fix,
+            reason: "chars are needed for columns".to_string(),
+        });
+        assert_eq!(lines[10].event, SessionEvent::Closed { outcome: SessionOutcome::Completed });
+        assert!(session.last_log_error().is_none());
+    }
+    
+    #[test]
+    fn test_failing_writer_does_not_stop_the_session() {
+        let mut session = LearningSession::new("parser");
+        session.attach_event_log(Box::new(FailingWriter));
+        session.encounter("parser rejects UTF-8 input");
+        session.struggle("offsets mixed up", 0.6, 0.5);
+        assert_eq!(session.moments.len(), 2);
+        assert_eq!(session.last_log_error().unwrap().to_string(), "disk full");
+        
+        let buffer = SharedBuffer::default();
+        session.attach_event_log(Box::new(buffer.clone()));
+        assert!(session.last_log_error().is_none());
+        session.encounter("back on disk");
+        assert_eq!(String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap().lines().count(), 2, "moment_created and phase_changed");
+    }
+}
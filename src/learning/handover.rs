@@ -0,0 +1,728 @@
+//! HandoverTemplate — which sections a blackboard's handover summary shows,
+//! in which order
+
+use serde::{Serialize, Deserialize};
+use crate::learning::blackboard::{Blackboard, OrderBy, DEFAULT_LOAD_BEARING_LAYERS, DEFAULT_LOG_EXPORT_ENTRIES};
+use crate::learning::session::SessionOutcome;
+
+/// A part of the handover summary; a section with nothing to show is left out
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Section {
+    Outcome,
+    CurrentTask,
+    OpenQuestions,
+    ResolvedQuestions,
+    /// Current decisions, with a count of superseded ones
+    Decisions,
+    /// `Decisions`, with each decision's revision chain
+    DecisionHistory,
+    DecisionConflicts,
+    /// Live layers by confidence, `DEFAULT_LOAD_BEARING_LAYERS` unless
+    /// `max_items` is set, and the layer count per phase
+    LoadBearing,
+    IceCaked,
+    PhaseOverrides,
+    KeyMoments,
+    Moments,
+    PhaseMetrics,
+    ThinkingStyles,
+    ConceptReuse,
+    Tags,
+    Context,
+    LearningCurve,
+    Artifacts,
+    ExpectedDecisions,
+    /// Pending steps, most urgent first
+    NextSteps,
+    /// Blockers, then open risks, most severe first
+    Risks,
+    /// `Blackboard::contributions_by_agent`, when more than one agent wrote
+    Contributions,
+    ActivityLog,
+    Stats,
+    /// A section name this version does not know, e.g. from a newer
+    /// template file; renders nothing
+    #[serde(other)]
+    Unknown,
+}
+
+/// A section with its options
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SectionSpec {
+    pub section: Section,
+    /// Items listed before the rest are counted in a final line; for the
+    /// activity log, the latest entries shown instead of
+    /// `DEFAULT_LOG_EXPORT_ENTRIES`
+    #[serde(default)]
+    pub max_items: Option<usize>,
+    /// Characters of free text kept per item, ellipsis included
+    #[serde(default)]
+    pub truncate: Option<usize>,
+}
+
+impl SectionSpec {
+    fn cut(&self, text: &str) -> String {
+        match self.truncate {
+            Some(max) if text.chars().count() > max => {
+                let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+                format!("{}…", kept.trim_end())
+            }
+            _ => text.to_string(),
+        }
+    }
+    
+    /// The first `max_items` of `items`, and how many were left out
+    fn limit<T>(&self, items: impl IntoIterator<Item = T>) -> (Vec<T>, usize) {
+        let mut items: Vec<T> = items.into_iter().collect();
+        let hidden = items.len().saturating_sub(self.max_items.unwrap_or(usize::MAX));
+        items.truncate(items.len() - hidden);
+        (items, hidden)
+    }
+}
+
+fn more(s: &mut String, hidden: usize) {
+    if hidden > 0 {
+        s.push_str(&format!("- … {} more\n", hidden));
+    }
+}
+
+/// Layout for `Blackboard::handover_with`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HandoverTemplate {
+    pub name: String,
+    pub sections: Vec<SectionSpec>,
+}
+
+impl HandoverTemplate {
+    /// A template without sections; the summary is just its title
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), sections: Vec::new() }
+    }
+    
+    pub fn section(self, section: Section) -> Self {
+        self.section_with(section, None, None)
+    }
+    
+    pub fn section_with(mut self, section: Section, max_items: Option<usize>, truncate: Option<usize>) -> Self {
+        self.sections.push(SectionSpec { section, max_items, truncate });
+        self
+    }
+    
+    /// Every section, as `Blackboard::handover_summary` shows them
+    pub fn standard() -> Self {
+        [
+            Section::Outcome, Section::CurrentTask, Section::NextSteps, Section::Risks, Section::OpenQuestions,
+            Section::ResolvedQuestions, Section::Decisions, Section::DecisionConflicts, Section::LoadBearing,
+            Section::IceCaked, Section::PhaseOverrides, Section::KeyMoments, Section::Moments, Section::PhaseMetrics,
+            Section::ThinkingStyles, Section::ConceptReuse, Section::Tags, Section::Context,
+            Section::LearningCurve, Section::Artifacts, Section::ExpectedDecisions, Section::Contributions,
+            Section::ActivityLog, Section::Stats,
+        ].into_iter().fold(Self::new("default"), Self::section)
+    }
+    
+    /// Where the task stands and what to do next, a few short items each
+    pub fn minimal() -> Self {
+        Self::new("minimal")
+            .section(Section::Outcome)
+            .section(Section::CurrentTask)
+            .section_with(Section::NextSteps, Some(3), Some(100))
+            .section_with(Section::Risks, Some(3), Some(100))
+            .section_with(Section::OpenQuestions, Some(5), Some(100))
+            .section_with(Section::Decisions, Some(5), Some(100))
+            .section_with(Section::LoadBearing, Some(3), Some(100))
+    }
+    
+    /// Decisions with their history and evidence, the longer activity log
+    /// and stats
+    pub fn audit() -> Self {
+        Self::new("audit")
+            .section(Section::CurrentTask)
+            .section(Section::Outcome)
+            .section(Section::NextSteps)
+            .section(Section::Risks)
+            .section(Section::DecisionHistory)
+            .section(Section::DecisionConflicts)
+            .section(Section::IceCaked)
+            .section(Section::ExpectedDecisions)
+            .section(Section::PhaseOverrides)
+            .section(Section::ResolvedQuestions)
+            .section(Section::OpenQuestions)
+            .section(Section::Artifacts)
+            .section(Section::Contributions)
+            .section_with(Section::ActivityLog, Some(50), None)
+            .section(Section::Stats)
+    }
+}
+
+impl Default for HandoverTemplate {
+    fn default() -> Self { Self::standard() }
+}
+
+impl Blackboard {
+    /// The handover summary laid out by `template`
+    pub fn handover_with(&self, template: &HandoverTemplate) -> String {
+        let mut s = format!("# Session Handover: {}\n", self.session_id);
+        for spec in &template.sections {
+            if let Some(section) = self.render_section(spec) {
+                s.push('\n');
+                s.push_str(&section);
+            }
+        }
+        s
+    }
+    
+    fn render_section(&self, spec: &SectionSpec) -> Option<String> {
+        let mut s = String::new();
+        match spec.section {
+            Section::Outcome => {
+                let outcome = self.outcome.as_ref()?;
+                s.push_str("## Outcome\n");
+                match outcome {
+                    SessionOutcome::Completed => s.push_str("- **Completed**\n"),
+                    SessionOutcome::Abandoned { reason } => s.push_str(&format!("- **Abandoned**: {}\n", reason)),
+                    SessionOutcome::Blocked { reason } => s.push_str(&format!("- **Blocked**: {}\n", spec.cut(reason))),
+                }
+                if let Some(struggle) = &self.last_open_struggle {
+                    s.push_str(&format!("- **Last open struggle**: {}\n", spec.cut(struggle)));
+                }
+            }
+            Section::CurrentTask => {
+                s.push_str("## Current Task\n");
+                s.push_str(&format!("- **ID**: {}\n", self.current_task.id));
+                if let Some(predecessor) = &self.predecessor_session_id {
+                    s.push_str(&format!("- **Resumed from**: session {}\n", predecessor));
+                }
+                if let Some(template) = &self.template {
+                    s.push_str(&format!("- **Template**: {}\n", template));
+                }
+                s.push_str(&format!("- **Phase**: {}\n", self.current_task.phase));
+                s.push_str(&format!("- **Progress**: {:.0}%\n", self.current_task.progress * 100.0));
+                if let Some(gate) = &self.current_task.gate_state {
+                    s.push_str(&format!("- **Gate**: {}\n", gate));
+                }
+            }
+            Section::OpenQuestions => {
+                let (open, hidden) = spec.limit(self.all_open_questions());
+                if open.is_empty() {
+                    return None;
+                }
+                s.push_str("## Open Questions\n");
+                for question in open {
+                    s.push_str(&format!("- {}\n", spec.cut(question)));
+                }
+                more(&mut s, hidden);
+            }
+            Section::ResolvedQuestions => {
+                let (resolved, hidden) = spec.limit(self.resolved_questions());
+                if resolved.is_empty() {
+                    return None;
+                }
+                s.push_str("## Resolved Questions\n");
+                for question in resolved {
+                    let resolution = question.resolution.as_ref().expect("resolved");
+                    s.push_str(&format!("- {} → {}\n", spec.cut(&question.text), spec.cut(&resolution.text)));
+                }
+                more(&mut s, hidden);
+            }
+            Section::Decisions | Section::DecisionHistory => {
+                if self.decisions.is_empty() {
+                    return None;
+                }
+                let superseded = self.decisions.len() - self.current_decisions().count();
+                match superseded {
+                    0 => s.push_str("## Decisions\n"),
+                    n => s.push_str(&format!("## Decisions ({} superseded)\n", n)),
+                }
+                let (current, hidden) = spec.limit(self.current_decisions());
+                for decision in current {
+                    s.push_str(&format!("- {} ({}, {})\n", spec.cut(&decision.choice), decision.task, decision.gate_state));
+                    s.push_str(&format!("  Rationale: {}\n", spec.cut(&decision.rationale)));
+                    for provenance in &decision.provenance {
+                        s.push_str(&format!("  {}\n", provenance));
+                    }
+                    let chain = self.decision_history(&decision.id);
+                    if spec.section == Section::DecisionHistory && chain.len() > 1 {
+                        s.push_str("  History:\n");
+                        for (i, older) in chain.iter().enumerate() {
+                            s.push_str(&format!("  {}. {} ({})\n", i + 1, spec.cut(&older.choice), spec.cut(&older.rationale)));
+                        }
+                    }
+                }
+                more(&mut s, hidden);
+            }
+            Section::DecisionConflicts => {
+                let (conflicts, hidden) = spec.limit(&self.decision_conflicts);
+                if conflicts.is_empty() {
+                    return None;
+                }
+                s.push_str("## Decision Conflicts ⚠️\n");
+                for conflict in conflicts {
+                    s.push_str(&format!("- {}:\n", conflict.topic));
+                    for decision in &conflict.decisions {
+                        s.push_str(&format!("  - {} ({})\n", spec.cut(&decision.choice), spec.cut(&decision.rationale)));
+                    }
+                }
+                more(&mut s, hidden);
+            }
+            Section::LoadBearing => {
+                let layers = self.ice_caked_ordered_by(OrderBy::Confidence);
+                if layers.is_empty() {
+                    return None;
+                }
+                let shown = spec.max_items.unwrap_or(DEFAULT_LOAD_BEARING_LAYERS).min(layers.len());
+                s.push_str(&format!("## Load-Bearing Decisions (top {} of {})\n", shown, layers.len()));
+                for (i, layer) in layers.iter().take(shown).enumerate() {
+                    s.push_str(&format!("{}. {} (confidence {:.2}, layer {})\n", i + 1, spec.cut(&layer.content), layer.truth.confidence, layer.layer_id));
+                }
+                let by_phase: Vec<String> = self.ice_caked_by_phase().iter().map(|(phase, n)| format!("{} {}", phase, n)).collect();
+                if !by_phase.is_empty() {
+                    s.push_str(&format!("- Ice-caked by phase: {}\n", by_phase.join(", ")));
+                }
+            }
+            Section::IceCaked => {
+                let (layers, hidden) = spec.limit(&self.ice_cake_layers);
+                if layers.is_empty() {
+                    return None;
+                }
+                s.push_str("## Ice-Caked (Frozen Commitments) ❄️\n");
+                for layer in layers {
+                    s.push_str(&format!("{}. {}\n", layer.layer_id, spec.cut(&layer.content)));
+                    s.push_str(&format!("   Rationale: {}\n", spec.cut(&layer.rationale)));
+                    s.push_str(&format!("   Truth: {} (confidence {:.2})\n", layer.truth, layer.truth.confidence));
+                    if let Some(by) = &layer.superseded_by {
+                        match self.ice_cake_layers.iter().rev().find(|l| l.decision_id == *by) {
+                            Some(newer) => s.push_str(&format!("   Superseded by layer {}\n", newer.layer_id)),
+                            None => s.push_str("   Superseded\n"),
+                        }
+                    }
+                    if !layer.sources.is_empty() {
+                        s.push_str("   Sources:\n");
+                        for source in &layer.sources {
+                            s.push_str(&format!("   - {}\n", source.to_markdown_link()));
+                        }
+                    }
+                }
+                more(&mut s, hidden);
+            }
+            Section::PhaseOverrides => {
+                let (overrides, hidden) = spec.limit(&self.phase_overrides);
+                if overrides.is_empty() {
+                    return None;
+                }
+                s.push_str("## Phase Overrides\n");
+                for o in overrides {
+                    s.push_str(&format!("- {:?} → {:?}: {}\n", o.from, o.to, spec.cut(&o.reason)));
+                }
+                more(&mut s, hidden);
+            }
+            Section::KeyMoments => {
+                let (keys, hidden) = spec.limit(&self.key_moments);
+                if keys.is_empty() {
+                    return None;
+                }
+                s.push_str("## Key Moments\n");
+                for key in keys {
+                    s.push_str(&format!("- {} ({:.2})\n", spec.cut(&key.content), key.importance));
+                }
+                more(&mut s, hidden);
+            }
+            Section::Moments => {
+                let (kinds, hidden) = spec.limit(&self.moment_types);
+                if kinds.is_empty() {
+                    return None;
+                }
+                s.push_str("## Moments\n");
+                for (kind, count) in kinds {
+                    s.push_str(&format!("- {}: {}\n", kind, count));
+                }
+                more(&mut s, hidden);
+            }
+            Section::PhaseMetrics => {
+                let (phases, hidden) = spec.limit(&self.metrics.phases);
+                if phases.is_empty() {
+                    return None;
+                }
+                s.push_str("## Phase Metrics\n| Phase | Moments | Time | Effort (mean/max) | Frustration (mean/max) |\n|---|---|---|---|---|\n");
+                for m in phases {
+                    s.push_str(&format!("| {:?} | {} | {:.1}s | {:.2}/{:.2} | {:.2}/{:.2} |\n",
+                        m.phase, m.moment_count, m.duration_ms as f64 / 1000.0, m.mean_effort, m.max_effort, m.mean_frustration, m.max_frustration));
+                }
+                more(&mut s, hidden);
+                match self.metrics.time_to_first_breakthrough_ms {
+                    Some(ms) => s.push_str(&format!("- Time to first breakthrough: {:.1}s\n", ms as f64 / 1000.0)),
+                    None => s.push_str("- Time to first breakthrough: none yet\n"),
+                }
+                if let Some(ratio) = self.metrics.struggle_to_breakthrough_ratio {
+                    s.push_str(&format!("- Struggles per breakthrough: {:.2}\n", ratio));
+                }
+            }
+            Section::ThinkingStyles => {
+                let (shares, hidden) = spec.limit(&self.style_distribution);
+                if shares.is_empty() {
+                    return None;
+                }
+                let moments: usize = self.style_distribution.iter().map(|share| share.moment_count).sum();
+                let time: u64 = self.style_distribution.iter().map(|share| share.duration_ms).sum();
+                let percent = |part: f64, whole: f64| if whole > 0.0 { part / whole * 100.0 } else { 0.0 };
+                s.push_str("## Thinking Styles\n| Style | Moments | Time |\n|---|---|---|\n");
+                for share in shares {
+                    s.push_str(&format!("| {} | {} ({:.0}%) | {:.1}s ({:.0}%) |\n",
+                        share.axis, share.moment_count, percent(share.moment_count as f64, moments as f64),
+                        share.duration_ms as f64 / 1000.0, percent(share.duration_ms as f64, time as f64)));
+                }
+                more(&mut s, hidden);
+            }
+            Section::ConceptReuse => {
+                let reuse = self.reuse.as_ref()?;
+                s.push_str("## Concept Reuse\n");
+                let (names, hidden) = spec.limit(reuse.concepts.iter().map(|c| c.name.as_str()));
+                let mut reused = if names.is_empty() { "none".to_string() } else { names.join(", ") };
+                if hidden > 0 {
+                    reused.push_str(&format!(" and {} more", hidden));
+                }
+                s.push_str(&format!("- Reused: {}\n", reused));
+                s.push_str(&format!("- Breakthroughs after a resonance hit: {} of {}\n",
+                    reuse.assisted_breakthroughs, reuse.assisted_breakthroughs + reuse.unassisted_breakthroughs));
+                if let (Some(assisted), Some(unassisted), Some(delta)) = (reuse.assisted_effort, reuse.unassisted_effort, reuse.effort_delta) {
+                    s.push_str(&format!("- Effort: {:.2} assisted vs {:.2} unassisted ({:+.2})\n", assisted, unassisted, delta));
+                }
+            }
+            Section::Tags => {
+                let mut tags: Vec<(&String, &usize)> = self.tags.iter().collect();
+                tags.sort_by(|a, b| b.1.cmp(a.1));
+                let (tags, hidden) = spec.limit(tags);
+                if tags.is_empty() {
+                    return None;
+                }
+                s.push_str("## Tags\n| Tag | Moments |\n|---|---|\n");
+                for (tag, count) in tags {
+                    s.push_str(&format!("| #{} | {} |\n", tag, count));
+                }
+                more(&mut s, hidden);
+            }
+            Section::Context => {
+                let (metadata, hidden) = spec.limit(&self.metadata);
+                if metadata.is_empty() {
+                    return None;
+                }
+                s.push_str("## Context\n");
+                for (key, values) in metadata {
+                    s.push_str(&format!("- {}: {}\n", key, spec.cut(&values.join(", "))));
+                }
+                more(&mut s, hidden);
+            }
+            Section::LearningCurve => {
+                if self.learning_curve.is_empty() {
+                    return None;
+                }
+                s.push_str("## Learning Curve\n");
+                for (dimension, line) in &self.learning_curve {
+                    s.push_str(&format!("- {:<12} {}\n", dimension, line));
+                }
+            }
+            Section::Artifacts => {
+                let (artifacts, hidden) = spec.limit(&self.artifacts);
+                if artifacts.is_empty() {
+                    return None;
+                }
+                s.push_str("## Artifacts\n");
+                for attached in artifacts {
+                    s.push_str(&format!("- {}\n", spec.cut(&attached.artifact.to_string())));
+                }
+                more(&mut s, hidden);
+            }
+            Section::ExpectedDecisions => {
+                if self.expected_decisions.is_empty() {
+                    return None;
+                }
+                let pending = self.unmatched_expected_decisions().len();
+                s.push_str(&format!("## Expected Decisions ({} of {} pending)\n", pending, self.expected_decisions.len()));
+                let (expected, hidden) = spec.limit(&self.expected_decisions);
+                for expected in expected {
+                    match expected.matched_layer {
+                        Some(layer_id) => s.push_str(&format!("- [x] {} (layer {})\n", spec.cut(&expected.description), layer_id)),
+                        None => s.push_str(&format!("- [ ] {}\n", spec.cut(&expected.description))),
+                    }
+                }
+                more(&mut s, hidden);
+            }
+            Section::NextSteps => {
+                let (steps, hidden) = spec.limit(self.pending_next_steps());
+                if steps.is_empty() {
+                    return None;
+                }
+                s.push_str("## Next Steps\n");
+                for (i, step) in steps.into_iter().enumerate() {
+                    let suggested = if step.auto_generated { " (suggested)" } else { "" };
+                    s.push_str(&format!("{}. [P{}] {}{}\n", i + 1, step.priority, spec.cut(&step.text), suggested));
+                }
+                more(&mut s, hidden);
+            }
+            Section::Risks => {
+                let blockers = self.blockers.iter().map(|blocker| format!("- **Blocker**: {}\n", spec.cut(blocker)));
+                let risks = self.open_risks().into_iter().map(|risk| {
+                    let auto = if risk.auto_generated { " (auto-generated)" } else { "" };
+                    let mut item = format!("- [{}] {}{}\n", risk.severity, spec.cut(&risk.text), auto);
+                    if let Some(mitigation) = &risk.mitigation {
+                        item.push_str(&format!("  - Mitigation: {}\n", spec.cut(mitigation)));
+                    }
+                    item
+                });
+                let (items, hidden) = spec.limit(blockers.chain(risks));
+                if items.is_empty() {
+                    return None;
+                }
+                s.push_str("## Risks & Blockers\n");
+                s.extend(items);
+                more(&mut s, hidden);
+            }
+            Section::Contributions => {
+                let contributions = self.contributions_by_agent();
+                if contributions.len() < 2 {
+                    return None;
+                }
+                s.push_str("## Contributions\n");
+                let (contributions, hidden) = spec.limit(contributions);
+                for (agent, contributed) in contributions {
+                    s.push_str(&format!("- {}: {}\n", agent, contributed));
+                }
+                more(&mut s, hidden);
+            }
+            Section::ActivityLog => {
+                if self.log.is_empty() {
+                    return None;
+                }
+                s.push_str("## Activity Log\n");
+                for line in self.log_lines(spec.max_items.unwrap_or(DEFAULT_LOG_EXPORT_ENTRIES)) {
+                    s.push_str(&format!("- {}\n", spec.cut(&line)));
+                }
+            }
+            Section::Stats => {
+                s.push_str(&format!("## Stats\n- Resonance Captures: {}\n- Concepts Extracted: {}\n",
+                    self.resonance_captures, self.concepts_extracted));
+                s.push_str(&format!("- Session Duration: {:.1}s active / {:.1}s wall clock\n- Longest Gap: {:.1}s\n",
+                    self.session_duration_ms as f64 / 1000.0, self.wall_clock_ms as f64 / 1000.0, self.longest_gap_ms as f64 / 1000.0));
+            }
+            Section::Unknown => return None,
+        }
+        Some(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::cognitive::GateState;
+    use crate::learning::{Artifact, MockClock, DEFAULT_NEXT_STEP_PRIORITY};
+    
+    fn handover() -> Blackboard {
+        let mut bb = Blackboard::new("s-1", "utf8-parser", "Parser rejects UTF-8 input").with_clock(Arc::new(MockClock::new(0)));
+        bb.current_task.phase = "Consolidate".to_string();
+        bb.current_task.progress = 0.75;
+        bb.outcome = Some(SessionOutcome::Blocked { reason: "column numbers are still counted in bytes".to_string() });
+        bb.record_decision("lexer", "Copy each token", "simplest first", GateState::Flow);
+        let first = bb.decisions[0].id.clone();
+        bb.revise_decision(&first, "Keep &str slices", "copies dominate the profile").unwrap();
+        bb.record_decision("errors", "Report char columns", "editors count chars", GateState::Hold);
+        let tabs = bb.add_question("Do tabs count as one column?");
+        bb.add_question("Should CRLF reset the column?");
+        bb.add_question("Is NFC normalisation in scope?");
+        bb.resolve_question(&tabs, "yes, like rustc", None).unwrap();
+        bb.add_artifact(Artifact::file("src/lexer.rs"));
+        bb.add_next_step("Map byte offsets to char columns in diagnostics", DEFAULT_NEXT_STEP_PRIORITY);
+        bb.add_next_step("Add a CRLF fixture", 1);
+        bb.moment_types.insert("Struggle".to_string(), 2);
+        bb.concepts_extracted = 1;
+        bb.session_duration_ms = 90_000;
+        bb.wall_clock_ms = 120_000;
+        bb
+    }
+    
+    #[test]
+    fn test_default_golden() {
+        let bb = handover();
+        assert_eq!(bb.handover_with(&HandoverTemplate::default()), bb.handover_summary());
+        assert_eq!(bb.handover_summary(), concat!(
+            "# Session Handover: s-1\n",
+            "\n",
+            "## Outcome\n",
+            "- **Blocked**: column numbers are still counted in bytes\n",
+            "\n",
+            "## Current Task\n",
+            "- **ID**: utf8-parser\n",
+            "- **Phase**: Consolidate\n",
+            "- **Progress**: 75%\n",
+            "\n",
+            "## Next Steps\n",
+            "1. [P1] Add a CRLF fixture\n",
+            "2. [P2] Map byte offsets to char columns in diagnostics\n",
+            "\n",
+            "## Open Questions\n",
+            "- Should CRLF reset the column?\n",
+            "- Is NFC normalisation in scope?\n",
+            "\n",
+            "## Resolved Questions\n",
+            "- Do tabs count as one column? → yes, like rustc\n",
+            "\n",
+            "## Decisions (1 superseded)\n",
+            "- Keep &str slices (lexer, Flow)\n",
+            "  Rationale: copies dominate the profile\n",
+            "- Report char columns (errors, Hold)\n",
+            "  Rationale: editors count chars\n",
+            "\n",
+            "## Moments\n",
+            "- Struggle: 2\n",
+            "\n",
+            "## Artifacts\n",
+            "- src/lexer.rs\n",
+            "\n",
+            "## Activity Log\n",
+            "- t=0ms decision: recorded: Copy each token\n",
+            "- t=0ms decision: revised: Keep &str slices (was: Copy each token)\n",
+            "- t=0ms decision: recorded: Report char columns\n",
+            "- t=0ms question: raised: Do tabs count as one column?\n",
+            "- t=0ms question: raised: Should CRLF reset the column?\n",
+            "- t=0ms question: raised: Is NFC normalisation in scope?\n",
+            "- t=0ms question: resolved: Do tabs count as one column? → yes, like rustc\n",
+            "- t=0ms next step: added: Map byte offsets to char columns in diagnostics (P2)\n",
+            "- t=0ms next step: added: Add a CRLF fixture (P1)\n",
+            "\n",
+            "## Stats\n",
+            "- Resonance Captures: 0\n",
+            "- Concepts Extracted: 1\n",
+            "- Session Duration: 90.0s active / 120.0s wall clock\n",
+            "- Longest Gap: 0.0s\n",
+        ));
+    }
+    
+    #[test]
+    fn test_minimal_golden() {
+        assert_eq!(handover().handover_with(&HandoverTemplate::minimal()), concat!(
+            "# Session Handover: s-1\n",
+            "\n",
+            "## Outcome\n",
+            "- **Blocked**: column numbers are still counted in bytes\n",
+            "\n",
+            "## Current Task\n",
+            "- **ID**: utf8-parser\n",
+            "- **Phase**: Consolidate\n",
+            "- **Progress**: 75%\n",
+            "\n",
+            "## Next Steps\n",
+            "1. [P1] Add a CRLF fixture\n",
+            "2. [P2] Map byte offsets to char columns in diagnostics\n",
+            "\n",
+            "## Open Questions\n",
+            "- Should CRLF reset the column?\n",
+            "- Is NFC normalisation in scope?\n",
+            "\n",
+            "## Decisions (1 superseded)\n",
+            "- Keep &str slices (lexer, Flow)\n",
+            "  Rationale: copies dominate the profile\n",
+            "- Report char columns (errors, Hold)\n",
+            "  Rationale: editors count chars\n",
+        ));
+    }
+    
+    #[test]
+    fn test_audit_golden() {
+        assert_eq!(handover().handover_with(&HandoverTemplate::audit()), concat!(
+            "# Session Handover: s-1\n",
+            "\n",
+            "## Current Task\n",
+            "- **ID**: utf8-parser\n",
+            "- **Phase**: Consolidate\n",
+            "- **Progress**: 75%\n",
+            "\n",
+            "## Outcome\n",
+            "- **Blocked**: column numbers are still counted in bytes\n",
+            "\n",
+            "## Next Steps\n",
+            "1. [P1] Add a CRLF fixture\n",
+            "2. [P2] Map byte offsets to char columns in diagnostics\n",
+            "\n",
+            "## Decisions (1 superseded)\n",
+            "- Keep &str slices (lexer, Flow)\n",
+            "  Rationale: copies dominate the profile\n",
+            "  History:\n",
+            "  1. Copy each token (simplest first)\n",
+            "  2. Keep &str slices (copies dominate the profile)\n",
+            "- Report char columns (errors, Hold)\n",
+            "  Rationale: editors count chars\n",
+            "\n",
+            "## Resolved Questions\n",
+            "- Do tabs count as one column? → yes, like rustc\n",
+            "\n",
+            "## Open Questions\n",
+            "- Should CRLF reset the column?\n",
+            "- Is NFC normalisation in scope?\n",
+            "\n",
+            "## Artifacts\n",
+            "- src/lexer.rs\n",
+            "\n",
+            "## Activity Log\n",
+            "- t=0ms decision: recorded: Copy each token\n",
+            "- t=0ms decision: revised: Keep &str slices (was: Copy each token)\n",
+            "- t=0ms decision: recorded: Report char columns\n",
+            "- t=0ms question: raised: Do tabs count as one column?\n",
+            "- t=0ms question: raised: Should CRLF reset the column?\n",
+            "- t=0ms question: raised: Is NFC normalisation in scope?\n",
+            "- t=0ms question: resolved: Do tabs count as one column? → yes, like rustc\n",
+            "- t=0ms next step: added: Map byte offsets to char columns in diagnostics (P2)\n",
+            "- t=0ms next step: added: Add a CRLF fixture (P1)\n",
+            "\n",
+            "## Stats\n",
+            "- Resonance Captures: 0\n",
+            "- Concepts Extracted: 1\n",
+            "- Session Duration: 90.0s active / 120.0s wall clock\n",
+            "- Longest Gap: 0.0s\n",
+        ));
+    }
+    
+    #[test]
+    fn test_custom_order_limits_and_empty_sections() {
+        let template = HandoverTemplate::new("next-steps-first")
+            .section_with(Section::NextSteps, Some(1), Some(11))
+            .section(Section::Unknown)
+            .section(Section::DecisionConflicts)
+            .section_with(Section::OpenQuestions, Some(1), None)
+            .section(Section::CurrentTask);
+        assert_eq!(handover().handover_with(&template), concat!(
+            "# Session Handover: s-1\n",
+            "\n",
+            "## Next Steps\n",
+            "1. [P1] Add a CRLF…\n",
+            "- … 1 more\n",
+            "\n",
+            "## Open Questions\n",
+            "- Should CRLF reset the column?\n",
+            "- … 1 more\n",
+            "\n",
+            "## Current Task\n",
+            "- **ID**: utf8-parser\n",
+            "- **Phase**: Consolidate\n",
+            "- **Progress**: 75%\n",
+        ));
+        assert_eq!(handover().handover_with(&HandoverTemplate::new("empty")), "# Session Handover: s-1\n");
+    }
+    
+    #[test]
+    fn test_unknown_section_names_are_skipped() {
+        let template: HandoverTemplate = serde_yaml::from_str(concat!(
+            "name: team\n",
+            "sections:\n",
+            "  - section: Retrospective\n",
+            "  - section: NextSteps\n",
+            "    max_items: 1\n",
+        )).unwrap();
+        assert_eq!(template.sections[0].section, Section::Unknown);
+        assert_eq!(handover().handover_with(&template), concat!(
+            "# Session Handover: s-1\n",
+            "\n",
+            "## Next Steps\n",
+            "1. [P1] Add a CRLF fixture\n",
+            "- … 1 more\n",
+        ));
+    }
+}
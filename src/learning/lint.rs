@@ -0,0 +1,244 @@
+//! Blackboard lints — what is missing or out of date before a handover
+
+use std::cmp::Reverse;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use crate::learning::blackboard::Blackboard;
+use crate::learning::risk::RiskSeverity;
+
+/// Session cycles a blackboard may lag behind its session before `StaleSync`
+pub const DEFAULT_STALE_AFTER_CYCLES: u64 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    /// Worth fixing before handing over; see `MetaAGI::handover_lints`
+    Error,
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BbLintKind {
+    /// The task has no description
+    EmptyState,
+    /// Neither a decision, a decision conflict nor an ice-caked layer
+    NoDecisions,
+    /// The session has moved on this many cycles since the last
+    /// `update_from_session`
+    StaleSync { cycles_since_sync: u64 },
+    /// `decision_conflicts` left by a merge
+    UnresolvedConflicts { count: usize },
+    /// Unresolved tracked questions nobody was assigned
+    OpenQuestionsWithoutOwner { count: usize },
+    /// Open Critical risks without a mitigation
+    UnmitigatedCriticalRisks { count: usize },
+}
+
+impl BbLintKind {
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            Self::EmptyState | Self::StaleSync { .. } | Self::UnresolvedConflicts { .. } => LintSeverity::Error,
+            Self::NoDecisions | Self::OpenQuestionsWithoutOwner { .. } | Self::UnmitigatedCriticalRisks { .. } => LintSeverity::Warning,
+        }
+    }
+}
+
+/// From `Blackboard::validate`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BbLint {
+    pub kind: BbLintKind,
+    pub severity: LintSeverity,
+}
+
+impl From<BbLintKind> for BbLint {
+    fn from(kind: BbLintKind) -> Self {
+        Self { severity: kind.severity(), kind }
+    }
+}
+
+impl fmt::Display for BbLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.severity)?;
+        match &self.kind {
+            BbLintKind::EmptyState => f.write_str("the task has no description"),
+            BbLintKind::NoDecisions => f.write_str("no decisions recorded"),
+            BbLintKind::StaleSync { cycles_since_sync } => write!(f, "last synced {} cycles ago", cycles_since_sync),
+            BbLintKind::UnresolvedConflicts { count } => write!(f, "{} unresolved decision conflicts", count),
+            BbLintKind::OpenQuestionsWithoutOwner { count } => write!(f, "{} open questions without an owner", count),
+            BbLintKind::UnmitigatedCriticalRisks { count } => write!(f, "{} critical risks without a mitigation", count),
+        }
+    }
+}
+
+/// Thresholds for `Blackboard::validate_with`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BbLintPolicy {
+    pub stale_after_cycles: u64,
+    /// Unowned open questions tolerated before `OpenQuestionsWithoutOwner`
+    pub max_unowned_questions: usize,
+}
+
+impl BbLintPolicy {
+    pub fn new() -> Self {
+        Self { stale_after_cycles: DEFAULT_STALE_AFTER_CYCLES, max_unowned_questions: 0 }
+    }
+}
+
+impl Default for BbLintPolicy {
+    fn default() -> Self { Self::new() }
+}
+
+impl Blackboard {
+    /// `validate_with` the default policy, without a session cycle to check
+    /// staleness against
+    pub fn validate(&self) -> Vec<BbLint> {
+        self.validate_with(&BbLintPolicy::new(), None)
+    }
+    
+    /// What to fix before handing over, most severe first. `StaleSync` needs
+    /// `session_cycle`, the current cycle of the blackboard's session, to
+    /// compare with the cycle of the last sync.
+    pub fn validate_with(&self, policy: &BbLintPolicy, session_cycle: Option<u64>) -> Vec<BbLint> {
+        let mut kinds = Vec::new();
+        if self.current_task.description.trim().is_empty() {
+            kinds.push(BbLintKind::EmptyState);
+        }
+        if self.decisions.is_empty() && self.decision_conflicts.is_empty() && self.ice_cake_layers.is_empty() {
+            kinds.push(BbLintKind::NoDecisions);
+        }
+        let cycles_since_sync = session_cycle.unwrap_or(self.cycle).saturating_sub(self.cycle);
+        if cycles_since_sync > policy.stale_after_cycles {
+            kinds.push(BbLintKind::StaleSync { cycles_since_sync });
+        }
+        if !self.decision_conflicts.is_empty() {
+            kinds.push(BbLintKind::UnresolvedConflicts { count: self.decision_conflicts.len() });
+        }
+        let unowned = self.unresolved_questions().filter(|q| q.owner.is_none()).count();
+        if unowned > policy.max_unowned_questions {
+            kinds.push(BbLintKind::OpenQuestionsWithoutOwner { count: unowned });
+        }
+        let unmitigated = self.open_risks().into_iter()
+            .filter(|risk| risk.severity == RiskSeverity::Critical && risk.mitigation.is_none())
+            .count();
+        if unmitigated > 0 {
+            kinds.push(BbLintKind::UnmitigatedCriticalRisks { count: unmitigated });
+        }
+        let mut lints: Vec<BbLint> = kinds.into_iter().map(BbLint::from).collect();
+        lints.sort_by_key(|lint| Reverse(lint.severity));
+        lints
+    }
+}
+
+/// Block put before a handover with Error lints; empty without one
+pub(crate) fn warning_block(lints: &[BbLint]) -> String {
+    let errors: Vec<&BbLint> = lints.iter().filter(|lint| lint.severity == LintSeverity::Error).collect();
+    if errors.is_empty() {
+        return String::new();
+    }
+    let mut block = String::from("> ⚠️ **Not ready for handover**\n");
+    for lint in errors {
+        block.push_str(&format!("> - {}\n", lint));
+    }
+    block.push('\n');
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cognitive::GateState;
+    use crate::learning::{BbMergePolicy, LearningSession};
+    
+    fn ready() -> Blackboard {
+        let mut bb = Blackboard::new("s-1", "parser", "Parser rejects UTF-8 input");
+        bb.record_decision("lexer", "Track byte offsets", "cheap", GateState::Flow);
+        bb
+    }
+    
+    fn kinds(bb: &Blackboard) -> Vec<BbLintKind> {
+        bb.validate().into_iter().map(|lint| lint.kind).collect()
+    }
+    
+    #[test]
+    fn test_clean_blackboard() {
+        assert!(ready().validate().is_empty());
+        assert!(ready().validate_with(&BbLintPolicy::new(), Some(DEFAULT_STALE_AFTER_CYCLES)).is_empty());
+        assert_eq!(warning_block(&ready().validate()), "");
+    }
+    
+    #[test]
+    fn test_empty_state() {
+        let mut bb = ready();
+        bb.current_task.description = "  ".to_string();
+        assert_eq!(kinds(&bb), [BbLintKind::EmptyState]);
+        assert_eq!(warning_block(&bb.validate()), "> ⚠️ **Not ready for handover**\n> - error: the task has no description\n\n");
+    }
+    
+    #[test]
+    fn test_no_decisions() {
+        let bb = Blackboard::new("s-1", "parser", "Parser rejects UTF-8 input");
+        let lints = bb.validate();
+        assert_eq!(lints, [BbLint { kind: BbLintKind::NoDecisions, severity: LintSeverity::Warning }]);
+        assert_eq!(warning_block(&lints), "", "warnings alone don't block");
+    }
+    
+    #[test]
+    fn test_stale_sync() {
+        let mut session = LearningSession::new("parser");
+        session.encounter("parser rejects UTF-8 input");
+        let mut bb = ready();
+        bb.update_from_session(&session.state());
+        for i in 0..12 {
+            session.encounter(&format!("step {}", i));
+        }
+        let lints = bb.validate_with(&BbLintPolicy::new(), Some(session.cycle));
+        assert_eq!(lints.iter().map(|lint| &lint.kind).collect::<Vec<_>>(), [&BbLintKind::StaleSync { cycles_since_sync: 12 }]);
+        assert_eq!(lints[0].to_string(), "error: last synced 12 cycles ago");
+        let lenient = BbLintPolicy { stale_after_cycles: 12, ..BbLintPolicy::new() };
+        assert!(bb.validate_with(&lenient, Some(session.cycle)).is_empty());
+        assert!(bb.validate().is_empty(), "no session cycle to compare with");
+    }
+    
+    #[test]
+    fn test_unresolved_conflicts() {
+        let mut other = Blackboard::new("s-2", "parser", "Parser rejects UTF-8 input");
+        other.record_decision("lexer", "Track char offsets", "columns", GateState::Flow);
+        let merged = ready().merge(&other, &BbMergePolicy::new()).unwrap();
+        assert_eq!(kinds(&merged), [BbLintKind::UnresolvedConflicts { count: 1 }]);
+    }
+    
+    #[test]
+    fn test_open_questions_without_owner() {
+        let mut bb = ready();
+        let tabs = bb.add_question("Do tabs count as one column?");
+        bb.add_question("Should CRLF reset the column?");
+        assert_eq!(kinds(&bb), [BbLintKind::OpenQuestionsWithoutOwner { count: 2 }]);
+        bb.assign_question(&tabs, "ada").unwrap();
+        let lenient = BbLintPolicy { max_unowned_questions: 1, ..BbLintPolicy::new() };
+        assert!(bb.validate_with(&lenient, None).is_empty());
+    }
+    
+    #[test]
+    fn test_unmitigated_critical_risks() {
+        let mut bb = ready();
+        let locks = bb.add_risk("Migration locks the table", RiskSeverity::Critical, None);
+        bb.add_risk("Deletes cascade into history", RiskSeverity::Critical, Some("Back up first".to_string()));
+        bb.add_risk("Log volume doubles", RiskSeverity::Low, None);
+        let lints = bb.validate();
+        assert_eq!(lints, [BbLint { kind: BbLintKind::UnmitigatedCriticalRisks { count: 1 }, severity: LintSeverity::Warning }]);
+        assert_eq!(lints[0].to_string(), "warning: 1 critical risks without a mitigation");
+        assert_eq!(warning_block(&lints), "");
+        bb.close_risk(&locks, "runs off-hours").unwrap();
+        assert!(bb.validate().is_empty());
+    }
+}
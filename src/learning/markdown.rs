@@ -0,0 +1,599 @@
+//! Markdown export — a session or a handover as a document for PR
+//! descriptions, issues and notes
+
+use std::fmt::Write;
+use crate::learning::blackboard::{Blackboard, Decision, ProvenanceKind, DEFAULT_LOG_EXPORT_ENTRIES};
+use crate::learning::moment::{Artifact, Moment, MomentType};
+use crate::learning::session::{LearningSession, SessionPhase};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarkdownOptions {
+    /// Cut moment content to this many characters with an ellipsis; None
+    /// keeps it whole
+    pub truncate: Option<usize>,
+    /// Type glyphs and emoji markers; off gives plain-text markers
+    pub emoji: bool,
+    /// Character budget for `Blackboard::to_markdown`, met by leaving out
+    /// the least important sections; None renders every section
+    pub compact: Option<usize>,
+}
+
+impl MarkdownOptions {
+    pub fn new() -> Self {
+        Self { truncate: Some(120), emoji: true, compact: None }
+    }
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self { Self::new() }
+}
+
+/// Backslash-escape characters Markdown would read as formatting, so text
+/// renders as written. Newlines become spaces.
+pub fn escape_markdown(text: &str) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut out = String::with_capacity(flat.len());
+    let list_number = flat.find(|c: char| !c.is_ascii_digit())
+        .filter(|&i| i > 0 && flat[i..].starts_with(['.', ')']));
+    for (i, c) in flat.char_indices() {
+        let block_marker = (i == 0 && matches!(c, '-' | '+')) || Some(i) == list_number;
+        if block_marker || matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '&') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn glyph(moment_type: &MomentType) -> &'static str {
+    match moment_type {
+        MomentType::Encounter => "🔍",
+        MomentType::Struggle => "😣",
+        MomentType::Breakthrough => "💡",
+        MomentType::Failure => "💥",
+        MomentType::Application => "🔧",
+        MomentType::MetaReflection => "🪞",
+        MomentType::Hypothesis => "🤔",
+        MomentType::Verification { .. } => "🧪",
+        MomentType::DecisionReversal => "🔙",
+        MomentType::Custom(_) => "📌",
+    }
+}
+
+fn truncate(text: &str, max_chars: Option<usize>, emoji: bool) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match max_chars {
+        Some(max) if flat.chars().count() > max => {
+            let ellipsis = if emoji { "…" } else { "..." };
+            let kept: String = flat.chars().take(max.saturating_sub(ellipsis.chars().count())).collect();
+            format!("{}{}", kept.trim_end(), ellipsis)
+        }
+        _ => flat,
+    }
+}
+
+impl LearningSession {
+    /// Markdown document: a header with task, phase, duration and counts, the
+    /// moments grouped by the phase they were recorded in, the subtask tree,
+    /// the ice-caked decisions and the meta-reflections. Content is escaped.
+    pub fn to_markdown(&self, opts: &MarkdownOptions) -> String {
+        let state = self.state();
+        let mut s = String::new();
+        let _ = writeln!(s, "# Learning Session: {}\n", escape_markdown(&self.task_id));
+        let _ = writeln!(s, "- Session: `{}`", self.id);
+        let _ = writeln!(s, "- Phase: {:?}", state.phase);
+        let _ = writeln!(s, "- Duration: {:.1}s active / {:.1}s wall clock",
+            state.duration_ms as f64 / 1000.0, state.wall_clock_ms as f64 / 1000.0);
+        let counts: Vec<String> = state.moment_types.iter().map(|(kind, n)| format!("{kind} {n}")).collect();
+        if counts.is_empty() {
+            let _ = writeln!(s, "- Moments: 0");
+        } else {
+            let _ = writeln!(s, "- Moments: {} ({})", state.moment_count, counts.join(", "));
+        }
+        let _ = writeln!(s, "- Breakthroughs: {}", state.breakthrough_count);
+        let _ = writeln!(s, "- Ice-caked decisions: {}", self.ice_caked.len());
+        
+        let phase_of = |m: &Moment| self.phase_at_seq(m.seq);
+        let ordered = self.moments_ordered();
+        if !ordered.is_empty() {
+            s.push_str("\n## Timeline\n");
+            let start = ordered.iter().map(|m| m.created_at).min().unwrap_or(0);
+            let mut current = None;
+            for moment in &ordered {
+                let phase = phase_of(moment);
+                if current.as_ref() != Some(&phase) {
+                    let _ = writeln!(s, "\n### {:?}\n", phase);
+                    current = Some(phase);
+                }
+                s.push_str(&self.markdown_line(moment, start, opts));
+            }
+        }
+        
+        let subtasks = self.subtask_tree();
+        if !subtasks.is_empty() {
+            s.push_str("\n## Subtasks\n\n");
+            for summary in &subtasks {
+                let status = summary.outcome.as_ref().map_or("open".to_string(), |outcome| outcome.to_string());
+                let plural = if summary.moment_count == 1 { "" } else { "s" };
+                let _ = write!(s, "{}- {} ({}): {} moment{plural}",
+                    "  ".repeat(summary.depth), escape_markdown(&summary.name), escape_markdown(&status), summary.moment_count);
+                if let Some(q) = &summary.mean_qualia {
+                    let _ = write!(s, " `e={:.2} s={:.2} c={:.2} v={:+.2}`", q.effort, q.satisfaction, q.confusion, q.valence);
+                }
+                s.push('\n');
+            }
+        }
+        
+        if !self.ice_caked.is_empty() {
+            let heading = if opts.emoji { "Ice-Caked Decisions ❄️" } else { "Ice-Caked Decisions" };
+            let _ = writeln!(s, "\n## {heading}\n");
+            for (i, decision) in self.ice_caked.iter().enumerate() {
+                let _ = writeln!(s, "{}. {} ({:?})", i + 1, escape_markdown(&truncate(&decision.content, opts.truncate, opts.emoji)), decision.gate_state);
+                let _ = writeln!(s, "   Rationale: {}", escape_markdown(&decision.rationale));
+            }
+        }
+        
+        let reflections: Vec<&&Moment> = ordered.iter()
+            .filter(|m| m.moment_type == MomentType::MetaReflection || phase_of(m) == SessionPhase::MetaLearn)
+            .collect();
+        if !reflections.is_empty() {
+            s.push_str("\n## Meta-Reflections\n\n");
+            for moment in reflections {
+                let _ = writeln!(s, "- {}", escape_markdown(&truncate(&moment.content, opts.truncate, opts.emoji)));
+            }
+        }
+        s
+    }
+    
+    /// One timeline list item: offset, type, content, qualia badge and markers
+    fn markdown_line(&self, moment: &Moment, start: u64, opts: &MarkdownOptions) -> String {
+        let secs = moment.created_at.saturating_sub(start) / 1000;
+        let q = &moment.qualia;
+        let mut line = format!("- +{}:{:02} ", secs / 60, secs % 60);
+        if opts.emoji {
+            line.push_str(glyph(&moment.moment_type));
+            line.push(' ');
+        }
+        let _ = write!(line, "**{}** {} `n={:.2} e={:.2} s={:.2} v={:+.2}`",
+            escape_markdown(moment.moment_type.name()),
+            escape_markdown(&truncate(&moment.content, opts.truncate, opts.emoji)),
+            q.novelty, q.effort, q.satisfaction, q.valence);
+        let ice_caked = self.ice_caked.iter().any(|d| d.moment_id == moment.id);
+        if moment.is_breakthrough() {
+            line.push_str(if opts.emoji { " ✨" } else { " (breakthrough)" });
+        }
+        if ice_caked {
+            line.push_str(if opts.emoji { " ❄️" } else { " (ice-caked)" });
+        }
+        line.push('\n');
+        line
+    }
+}
+
+impl Blackboard {
+    /// Markdown handover, e.g. for an issue: the task, its pending next
+    /// steps, its current state, a decisions table, the ice-caked layers,
+    /// open questions, artifacts by kind, stats and the latest activity log
+    /// entries. Content is escaped. With `opts.compact`, sections are left
+    /// out, least important first (activity log, stats, artifacts,
+    /// questions, decisions, layers, state, risks, next steps), until the
+    /// document fits; the task header is always kept, cut short if it alone
+    /// is over budget.
+    pub fn to_markdown(&self, opts: &MarkdownOptions) -> String {
+        let cut = |text: &str| escape_markdown(&truncate(text, opts.truncate, opts.emoji));
+        let mut header = String::new();
+        let _ = writeln!(header, "# Handover: {}\n", escape_markdown(&self.current_task.id));
+        if !self.current_task.description.is_empty() {
+            let _ = writeln!(header, "{}\n", escape_markdown(&self.current_task.description));
+        }
+        let _ = writeln!(header, "- Session: `{}`", self.session_id);
+        if self.redacted {
+            let _ = writeln!(header, "- Redacted: ids are pseudonyms");
+        }
+        if let Some(predecessor) = &self.predecessor_session_id {
+            let _ = writeln!(header, "- Resumed from: `{}`", predecessor);
+        }
+        if let Some(template) = &self.template {
+            let _ = writeln!(header, "- Template: {}", escape_markdown(template));
+        }
+        if let Some(outcome) = &self.outcome {
+            let _ = writeln!(header, "- Outcome: {}", escape_markdown(&outcome.to_string()));
+        }
+        
+        // (importance, title, body); higher importance is left out later
+        let mut sections: Vec<(u8, &str, String)> = Vec::new();
+        let mut steps = String::new();
+        for (i, step) in self.pending_next_steps().into_iter().enumerate() {
+            let suggested = if step.auto_generated { " (suggested)" } else { "" };
+            let _ = writeln!(steps, "{}. [P{}] {}{}", i + 1, step.priority, cut(&step.text), suggested);
+        }
+        if !steps.is_empty() {
+            sections.push((7, "Next Steps", steps));
+        }
+        
+        let mut state = String::new();
+        let _ = writeln!(state, "- Phase: {} ({:.0}%)", self.current_task.phase, self.current_task.progress * 100.0);
+        if let Some(gate) = &self.current_task.gate_state {
+            let _ = writeln!(state, "- Gate: {}", gate);
+        }
+        let _ = writeln!(state, "- Thinking style: {} (coherence {:.2})", self.consciousness.thinking_style, self.consciousness.coherence);
+        if let Some(struggle) = &self.last_open_struggle {
+            let _ = writeln!(state, "- Last open struggle: {}", cut(struggle));
+        }
+        for blocker in &self.blockers {
+            let _ = writeln!(state, "- Blocker: {}", cut(blocker));
+        }
+        if !self.files_modified.is_empty() {
+            let files: Vec<String> = self.files_modified.iter().map(|f| format!("`{}`", f.replace('`', "'"))).collect();
+            let _ = writeln!(state, "- Files: {}", files.join(", "));
+        }
+        sections.push((6, "Current State", state));
+        
+        let mut risks = String::new();
+        for risk in self.open_risks() {
+            let auto = if risk.auto_generated { " (auto-generated)" } else { "" };
+            let _ = write!(risks, "- **{}** {}{}", risk.severity, cut(&risk.text), auto);
+            match &risk.mitigation {
+                Some(mitigation) => { let _ = writeln!(risks, " — mitigation: {}", cut(mitigation)); }
+                None => risks.push('\n'),
+            }
+        }
+        if !risks.is_empty() {
+            sections.push((6, "Risks", risks));
+        }
+        
+        if !self.decisions.is_empty() || !self.decision_conflicts.is_empty() || !self.ice_cake_layers.is_empty() {
+            let mut table = String::from("| Decision | Confidence | Status | Provenance |\n|---|---|---|---|\n");
+            for layer in &self.ice_cake_layers {
+                let status = if layer.superseded_by.is_some() { "superseded" } else { "ice-caked" };
+                let mut provenance = format!("layer {}, cycle {}", layer.layer_id, layer.ice_caked_at_cycle);
+                for source in &layer.sources {
+                    let _ = write!(provenance, ", {}", escape_markdown(&source.to_string()));
+                }
+                let _ = writeln!(table, "| {} | {:.2} | {} | {} |", cut(&layer.content), layer.truth.confidence, status, provenance);
+            }
+            let from_layer = |decision: &Decision| decision.provenance.iter().any(|p| {
+                p.kind == ProvenanceKind::IceCake && self.ice_cake_layers.iter().any(|layer| layer.decision_id == p.moment_id)
+            });
+            for decision in self.decisions.iter().filter(|d| !from_layer(d)) {
+                let status = match (&decision.superseded_by, decision.ice_caked) {
+                    (Some(_), _) => "superseded",
+                    (None, true) => "ice-caked",
+                    (None, false) => "recorded",
+                };
+                let mut provenance = format!("{}, cycle {}", escape_markdown(&decision.task), decision.cycle);
+                for source in &decision.provenance {
+                    let _ = write!(provenance, ", {}", escape_markdown(&source.to_string()));
+                }
+                let _ = writeln!(table, "| {} | gate {} | {} | {} |",
+                    cut(&decision.choice), decision.gate_state.to_uppercase(), status, provenance);
+            }
+            for decision in self.decision_conflicts.iter().flat_map(|conflict| &conflict.decisions) {
+                let _ = writeln!(table, "| {} | gate {} | conflict | {}, cycle {} |",
+                    cut(&decision.choice), decision.gate_state.to_uppercase(), escape_markdown(&decision.task), decision.cycle);
+            }
+            sections.push((4, "Decisions", table));
+        }
+        
+        if !self.ice_cake_layers.is_empty() {
+            let mut layers = String::new();
+            for layer in &self.ice_cake_layers {
+                let _ = writeln!(layers, "{}. {} ({})", layer.layer_id, cut(&layer.content), layer.truth);
+                let _ = writeln!(layers, "   Rationale: {}", escape_markdown(&layer.rationale));
+            }
+            sections.push((5, "Ice-Caked Layers", layers));
+        }
+        
+        let mut questions: String = self.all_open_questions().iter().map(|q| format!("- {}\n", cut(q))).collect();
+        for question in self.resolved_questions() {
+            let resolution = question.resolution.as_ref().expect("resolved");
+            let _ = write!(questions, "- ~~{}~~ {}", cut(&question.text), cut(&resolution.text));
+            match &resolution.moment_id {
+                Some(moment_id) => { let _ = writeln!(questions, " (moment `{}`)", moment_id); }
+                None => questions.push('\n'),
+            }
+        }
+        if !questions.is_empty() {
+            sections.push((3, "Open Questions", questions));
+        }
+        
+        let mut artifacts = String::new();
+        for (kind, listed) in self.artifacts_by_kind() {
+            for artifact in listed {
+                match artifact {
+                    Artifact::Url(url) => { let _ = writeln!(artifacts, "- {}: <{}>", kind, url.replace('>', "%3E")); }
+                    Artifact::FilePath { .. } => { let _ = writeln!(artifacts, "- {}: `{}`", kind, artifact.to_string().replace('`', "'")); }
+                    Artifact::Snippet { .. } => { let _ = writeln!(artifacts, "- {}: {}", kind, escape_markdown(&artifact.to_string())); }
+                }
+            }
+        }
+        if !artifacts.is_empty() {
+            sections.push((2, "Artifacts", artifacts));
+        }
+        
+        let mut stats = String::new();
+        let moments: usize = self.moment_types.values().sum();
+        let _ = writeln!(stats, "- Moments: {}", moments);
+        let _ = writeln!(stats, "- Concepts extracted: {}", self.concepts_extracted);
+        let _ = writeln!(stats, "- Duration: {:.1}s active / {:.1}s wall clock",
+            self.session_duration_ms as f64 / 1000.0, self.wall_clock_ms as f64 / 1000.0);
+        let _ = writeln!(stats, "- Cycle: {}", self.cycle);
+        sections.push((1, "Stats", stats));
+        
+        if !self.log.is_empty() {
+            let log: String = self.log_lines(DEFAULT_LOG_EXPORT_ENTRIES).iter().map(|line| format!("- {}\n", cut(line))).collect();
+            sections.push((0, "Activity Log", log));
+        }
+        
+        let render = |sections: &[(u8, &str, String)], omitted: &[&str]| {
+            let mut s = header.clone();
+            for (_, title, body) in sections {
+                let _ = write!(s, "\n## {}\n\n{}", title, body);
+            }
+            if !omitted.is_empty() {
+                let _ = write!(s, "\n_Left out for length: {}_\n", omitted.join(", "));
+            }
+            s
+        };
+        let Some(budget) = opts.compact else { return render(&sections, &[]) };
+        let mut omitted: Vec<&str> = Vec::new();
+        loop {
+            let s = render(&sections, &omitted);
+            if s.chars().count() <= budget {
+                return s;
+            }
+            let Some(least) = sections.iter().enumerate().min_by_key(|(_, (importance, _, _))| *importance).map(|(i, _)| i) else {
+                return truncate(&s, Some(budget), opts.emoji);
+            };
+            omitted.push(sections.remove(least).1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::cognitive::GateState;
+    use crate::learning::{MockClock, SessionOutcome, SourceLocation};
+    use crate::learning::session::IceCakedDecision;
+    use crate::nars::TruthValue;
+    
+    fn scripted() -> LearningSession {
+        let clock = MockClock::new(1_000);
+        let mut session = LearningSession::with_clock("utf8-parser", Arc::new(clock.clone()));
+        session.encounter("Parser rejects naïve UTF-8 input");
+        clock.advance(65_000);
+        session.struggle("Byte offsets vs char offsets in the lexer are mixed up everywhere", 0.8, 0.7);
+        clock.advance(3_600_000);
+        let id = session.breakthrough("Track byte offsets only", 0.9).id.clone();
+        session.ice_cake(&id, "lexer invariant").unwrap();
+        clock.advance(5_000);
+        session.meta_reflect("Write the invariant down before coding");
+        session
+    }
+    
+    #[test]
+    fn test_golden_emoji() {
+        let session = scripted();
+        let opts = MarkdownOptions { truncate: Some(40), ..MarkdownOptions::default() };
+        let expected = format!(concat!(
+            "# Learning Session: utf8-parser\n",
+            "\n",
+            "- Session: `{}`\n",
+            "- Phase: MetaLearn\n",
+            "- Duration: 3670.0s active / 3670.0s wall clock\n",
+            "- Moments: 4 (Breakthrough 1, Encounter 2, Struggle 1)\n",
+            "- Breakthroughs: 2\n",
+            "- Ice-caked decisions: 1\n",
+            "\n",
+            "## Timeline\n",
+            "\n",
+            "### Encounter\n",
+            "\n",
+            "- +0:00 🔍 **Encounter** Parser rejects naïve UTF-8 input `n=0.50 e=0.20 s=0.50 v=+0.00`\n",
+            "\n",
+            "### Struggle\n",
+            "\n",
+            "- +1:05 😣 **Struggle** Byte offsets vs char offsets in the lex… `n=0.30 e=0.80 s=0.30 v=-0.30`\n",
+            "\n",
+            "### Breakthrough\n",
+            "\n",
+            "- +61:05 💡 **Breakthrough** Track byte offsets only `n=0.80 e=0.31 s=0.90 v=+0.80` ✨ ❄️\n",
+            "\n",
+            "### MetaLearn\n",
+            "\n",
+            "- +61:10 🔍 **Encounter** Write the invariant down before coding `n=0.70 e=0.40 s=0.80 v=+0.30` ✨\n",
+            "\n",
+            "## Ice-Caked Decisions ❄️\n",
+            "\n",
+            "1. Track byte offsets only (Flow)\n",
+            "   Rationale: lexer invariant\n",
+            "\n",
+            "## Meta-Reflections\n",
+            "\n",
+            "- Write the invariant down before coding\n",
+        ), session.id);
+        assert_eq!(session.to_markdown(&opts), expected);
+    }
+    
+    #[test]
+    fn test_golden_plain() {
+        let session = scripted();
+        let opts = MarkdownOptions { truncate: None, emoji: false, compact: None };
+        let markdown = session.to_markdown(&opts);
+        assert!(markdown.contains(concat!(
+            "- +1:05 **Struggle** Byte offsets vs char offsets in the lexer are mixed up everywhere `n=0.30 e=0.80 s=0.30 v=-0.30`\n",
+            "\n",
+            "### Breakthrough\n",
+            "\n",
+            "- +61:05 **Breakthrough** Track byte offsets only `n=0.80 e=0.31 s=0.90 v=+0.80` (breakthrough) (ice-caked)\n",
+        )));
+        assert!(markdown.contains("\n## Ice-Caked Decisions\n"));
+        assert!(!markdown.chars().any(|c| c as u32 >= 0x2700));
+        
+        let opts = MarkdownOptions { truncate: Some(20), emoji: false, compact: None };
+        assert!(session.to_markdown(&opts).contains("**Struggle** Byte offsets vs c... `"));
+    }
+    
+    #[test]
+    fn test_content_is_escaped() {
+        assert_eq!(escape_markdown("use `Vec<T>` *not* [T; N] | a_b # ~x & y"),
+            "use \\`Vec\\<T\\>\\` \\*not\\* \\[T; N\\] \\| a\\_b \\# \\~x \\& y");
+        assert_eq!(escape_markdown("- not a list"), "\\- not a list");
+        assert_eq!(escape_markdown("42. not a list\neither"), "42\\. not a list either");
+        assert_eq!(escape_markdown("C:\\path 1.5x - fine"), "C:\\\\path 1.5x - fine");
+        
+        let mut session = LearningSession::new("task_[1]");
+        session.struggle("**why** does `unwrap()` panic?\n# it", 0.5, 0.5);
+        let markdown = session.to_markdown(&MarkdownOptions::default());
+        assert!(markdown.starts_with("# Learning Session: task\\_\\[1\\]\n"));
+        assert!(markdown.contains("**Struggle** \\*\\*why\\*\\* does \\`unwrap()\\` panic? \\# it `"));
+    }
+    
+    #[test]
+    fn test_subtask_tree() {
+        let mut session = LearningSession::new("versioning");
+        let fk = session.begin_subtask("fix the FK constraint").unwrap();
+        session.struggle("FK constraint fails", 0.8, 0.6);
+        let index = session.begin_subtask("add_index").unwrap();
+        session.struggle("which column?", 0.4, 0.2);
+        session.end_subtask(&index, SessionOutcome::Completed).unwrap();
+        session.end_subtask(&fk, SessionOutcome::Blocked { reason: "needs a DBA".to_string() }).unwrap();
+        session.begin_subtask("write docs").unwrap();
+        
+        let markdown = session.to_markdown(&MarkdownOptions::default());
+        assert!(markdown.contains(concat!(
+            "\n## Subtasks\n",
+            "\n",
+            "- fix the FK constraint (blocked: needs a DBA): 2 moments `e=0.60 s=0.30 c=0.40 v=-0.30`\n",
+            "  - add\\_index (completed): 1 moment `e=0.40 s=0.30 c=0.20 v=-0.30`\n",
+            "- write docs (open): 0 moments\n",
+        )), "{markdown}");
+        assert!(!LearningSession::new("empty").to_markdown(&MarkdownOptions::default()).contains("## Subtasks"));
+    }
+    
+    fn handover() -> Blackboard {
+        let mut bb = Blackboard::new("s-1", "utf8-parser", "Parser rejects naïve UTF-8 input").with_clock(Arc::new(MockClock::new(0)));
+        bb.current_task.phase = "Consolidate".to_string();
+        bb.current_task.progress = 0.75;
+        bb.current_task.gate_state = Some("HOLD".to_string());
+        bb.consciousness.coherence = 0.5;
+        bb.last_open_struggle = Some("column numbers count *bytes*".to_string());
+        bb.record_decision("lexer", "Keep `&str` slices", "no copies", GateState::Flow);
+        bb.add_ice_cake(&IceCakedDecision {
+            moment_id: "m-3".to_string(),
+            content: "Track byte offsets only".to_string(),
+            sources: vec![SourceLocation { path: "src/lexer.rs".to_string(), line: Some(42), column: None }],
+            rationale: "lexer invariant".to_string(),
+            gate_state: GateState::Flow,
+            ice_caked_at_cycle: 4,
+            after_reversal: None,
+            truth: TruthValue::new(1.0, 0.9),
+            superseded_by: None,
+            phase: None,
+            prior_moment_id: None,
+        });
+        bb.record_file_modified("src/lexer.rs");
+        bb.add_next_step("Map offsets to columns", 1);
+        bb.open_questions.push("Do tabs count as one column?".to_string());
+        bb.moment_types.insert("Struggle".to_string(), 2);
+        bb.moment_types.insert("Breakthrough".to_string(), 1);
+        bb.concepts_extracted = 1;
+        bb.session_duration_ms = 90_000;
+        bb.wall_clock_ms = 120_000;
+        bb.cycle = 5;
+        bb.note("handed over #2");
+        bb
+    }
+    
+    #[test]
+    fn test_blackboard_golden_full() {
+        let opts = MarkdownOptions { truncate: None, emoji: false, compact: None };
+        assert_eq!(handover().to_markdown(&opts), concat!(
+            "# Handover: utf8-parser\n",
+            "\n",
+            "Parser rejects naïve UTF-8 input\n",
+            "\n",
+            "- Session: `s-1`\n",
+            "\n",
+            "## Next Steps\n",
+            "\n",
+            "1. [P1] Map offsets to columns\n",
+            "\n",
+            "## Current State\n",
+            "\n",
+            "- Phase: Consolidate (75%)\n",
+            "- Gate: HOLD\n",
+            "- Thinking style: analytical (coherence 0.50)\n",
+            "- Last open struggle: column numbers count \\*bytes\\*\n",
+            "- Files: `src/lexer.rs`\n",
+            "\n",
+            "## Decisions\n",
+            "\n",
+            "| Decision | Confidence | Status | Provenance |\n",
+            "|---|---|---|---|\n",
+            "| Track byte offsets only | 0.90 | ice-caked | layer 1, cycle 4, src/lexer.rs:42 |\n",
+            "| Keep \\`\\&str\\` slices | gate FLOW | recorded | lexer, cycle 0 |\n",
+            "\n",
+            "## Ice-Caked Layers\n",
+            "\n",
+            "1. Track byte offsets only (⟨100%, 90%⟩)\n",
+            "   Rationale: lexer invariant\n",
+            "\n",
+            "## Open Questions\n",
+            "\n",
+            "- Do tabs count as one column?\n",
+            "\n",
+            "## Stats\n",
+            "\n",
+            "- Moments: 3\n",
+            "- Concepts extracted: 1\n",
+            "- Duration: 90.0s active / 120.0s wall clock\n",
+            "- Cycle: 5\n",
+            "\n",
+            "## Activity Log\n",
+            "\n",
+            "- t=0ms decision: recorded: Keep \\`\\&str\\` slices\n",
+            "- t=0ms decision: ice-caked layer 1: Track byte offsets only\n",
+            "- t=0ms next step: added: Map offsets to columns (P1)\n",
+            "- t=0ms note: handed over \\#2\n",
+        ));
+    }
+    
+    #[test]
+    fn test_blackboard_golden_compact() {
+        let opts = MarkdownOptions { compact: Some(560), ..MarkdownOptions::default() };
+        assert_eq!(handover().to_markdown(&opts), concat!(
+            "# Handover: utf8-parser\n",
+            "\n",
+            "Parser rejects naïve UTF-8 input\n",
+            "\n",
+            "- Session: `s-1`\n",
+            "\n",
+            "## Next Steps\n",
+            "\n",
+            "1. [P1] Map offsets to columns\n",
+            "\n",
+            "## Current State\n",
+            "\n",
+            "- Phase: Consolidate (75%)\n",
+            "- Gate: HOLD\n",
+            "- Thinking style: analytical (coherence 0.50)\n",
+            "- Last open struggle: column numbers count \\*bytes\\*\n",
+            "- Files: `src/lexer.rs`\n",
+            "\n",
+            "## Ice-Caked Layers\n",
+            "\n",
+            "1. Track byte offsets only (⟨100%, 90%⟩)\n",
+            "   Rationale: lexer invariant\n",
+            "\n",
+            "_Left out for length: Activity Log, Stats, Open Questions, Decisions_\n",
+        ));
+        
+        let opts = MarkdownOptions { compact: Some(30), ..MarkdownOptions::default() };
+        let markdown = handover().to_markdown(&opts);
+        assert!(markdown.chars().count() <= 30, "{markdown}");
+        assert!(markdown.starts_with("# Handover: utf8-parser"));
+    }
+}
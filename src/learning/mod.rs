@@ -5,9 +5,75 @@ pub mod session;
 pub mod blackboard;
 pub mod resonance;
 pub mod concept;
+pub mod trajectory;
+pub mod diff;
+pub mod redact;
+pub mod calibration;
+pub mod timeline;
+pub mod archive;
+pub mod markdown;
+pub mod replay;
+pub mod observer;
+pub mod template;
+pub mod retrospective;
+pub mod search;
+pub mod event_log;
+pub mod reuse;
+pub mod handover;
+pub mod lint;
+pub mod budget;
+pub mod export;
+pub mod agent;
+pub mod risk;
+pub mod cypher;
 
-pub use moment::{Moment, MomentType, Qualia, MomentBuilder};
-pub use session::{LearningSession, SessionState, SessionPhase};
-pub use blackboard::{Blackboard, Decision, IceCakedLayer};
+pub use crate::core::{Clock, SystemClock, MockClock};
+pub use moment::{
+    Moment, MomentType, Qualia, MomentBuilder, MetadataError, MAX_META_KEY_LEN,
+    LinkKind, MomentLink, Artifact, MAX_SNIPPET_CHARS, QUALIA_DIMENSIONS, ImportanceWeights,
+    MomentBuildError, MAX_CONTENT_CHARS, normalize_tag, MAX_EDIT_HISTORY, summarize_content,
+    SourceLocation,
+};
+pub use session::{
+    LearningSession, SessionState, SessionPhase, DanglingLinks, KeyMoment, ResolutionPair, EditError, IceCakeError,
+    DEFAULT_RESOLUTION_SIMILARITY, SessionConfig, DEFAULT_MAX_CONTENT_CHARS,
+    DedupPolicy, AddOutcome, SessionIoError, SESSION_SCHEMA_VERSION, SessionPaused,
+    SessionOutcome, AbandonReason, SessionClosedError, CaptureError, SessionReport, MergePolicy, MergeError, BranchMerge,
+    IceCakeConflict, merge_sessions, PhaseTransitionError, PhaseOverride, ThawTarget, ThawError,
+    DEFAULT_SUPERSEDE_SIMILARITY, StuckReport, DEFAULT_STUCK_SIMILARITY,
+    SubtaskId, Subtask, SubtaskSummary, SubtaskError, SessionMetrics, PhaseMetrics,
+    Suggestion, DEFAULT_SUGGESTION_RESONANCE, UndoError, MAX_UNDO_DEPTH, StyleShare,
+    CheckpointId, RestoredReport, RestoreError, StruggleGateConfig, DEFAULT_STRUGGLE_GATE_WINDOW,
+    DEFAULT_FAILURE_FRUSTRATION,
+};
+pub use blackboard::{
+    Blackboard, Decision, Provenance, ProvenanceKind, IceCakedLayer, OrderBy,
+    DEFAULT_ICE_CAKED_SEARCH_SIMILARITY, DEFAULT_LOAD_BEARING_LAYERS, AttachedArtifact, ExpectedDecision,
+    DEFAULT_EXPECTED_DECISION_SIMILARITY, BLACKBOARD_SCHEMA_VERSION, BlackboardParseError, ReviseDecisionError,
+    QuestionId, Question, QuestionResolution, QuestionError,
+    NextStepId, NextStep, NextStepError, DEFAULT_NEXT_STEP_PRIORITY,
+    LogCategory, LogEntry, LogSummary, DEFAULT_LOG_CAPACITY, DEFAULT_LOG_EXPORT_ENTRIES,
+    BbMergePolicy, BbMergeError, DecisionConflict, DEFAULT_DECISION_TOPIC_SIMILARITY,
+};
 pub use resonance::{ResonanceCapture, SimilarMoment, ResonanceStats, find_sweet_spot, mexican_hat_resonance};
 pub use concept::{ConceptExtractor, ExtractedConcept, RelationType, ConceptRelation};
+pub use trajectory::QualiaTrajectory;
+pub use diff::{MomentDiff, MetadataChange, BlackboardSnapshot, BlackboardDiff, StateChange};
+pub use redact::{Redactor, RedactionPattern, RedactionError};
+pub use calibration::QualiaCalibration;
+pub use timeline::{TimelineOptions, format_timeline};
+pub use archive::{ArchivedMoment, ArchivedFingerprint, DEFAULT_ARCHIVE_SUMMARY_CHARS};
+pub use markdown::{MarkdownOptions, escape_markdown};
+pub use cypher::escape_cypher;
+pub use handover::{HandoverTemplate, Section, SectionSpec};
+pub use export::ExportFormat;
+pub use agent::{AgentId, AgentContributions, UNATTRIBUTED_AGENT};
+pub use risk::{Risk, RiskId, RiskSeverity, RiskClosure, RiskError, DEFAULT_RISK_FRUSTRATION};
+pub use lint::{BbLint, BbLintKind, BbLintPolicy, LintSeverity, DEFAULT_STALE_AFTER_CYCLES};
+pub use replay::{SessionReplay, ReplayEvent, ReplayKind};
+pub use observer::{SessionObserver, Subscription};
+pub use template::SessionTemplate;
+pub use retrospective::{retrospective, Retrospective, PastMatch, DEFAULT_RETROSPECTIVE_SIMILARITY};
+pub use search::{SessionMatch, session_match_score, MIN_SESSION_MATCH_SCORE};
+pub use event_log::{SessionEvent, EventLine, EVENT_LOG_SCHEMA_VERSION};
+pub use reuse::{ReuseReport, ReusedConcept, ResonanceHit, DEFAULT_REUSE_WINDOW};
@@ -1,12 +1,19 @@
 //! Moment — Atomic unit of learning capture
 
-use std::time::{SystemTime, UNIX_EPOCH};
-use crate::core::Fingerprint;
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+use crate::core::{Clock, Fingerprint, SystemClock, FINGERPRINT_BITS};
+use crate::learning::archive::{ArchivedMoment, DEFAULT_ARCHIVE_SUMMARY_CHARS};
 use crate::nars::TruthValue;
-use crate::cognitive::ThinkingStyle;
+use crate::cognitive::{ThinkingStyle, GateState};
 
 /// Qualia — The felt quality of a learning moment
-#[derive(Clone, Debug, Default)]
+///
+/// Missing fields deserialize to their `Default` values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Qualia {
     pub novelty: f32,
     pub effort: f32,
@@ -14,6 +21,33 @@ pub struct Qualia {
     pub confusion: f32,
     pub surprise: f32,
     pub qidx: u8,
+    /// How good it felt, -1.0 (bad) to 1.0 (good)
+    pub valence: f32,
+    /// How activating it felt, 0.0 (calm) to 1.0 (intense)
+    pub arousal: f32,
+    /// Self-assessed confidence in the moment's content, 0.0 to 1.0
+    pub confidence: f32,
+}
+
+/// Qualia dimensions in `Qualia::to_fingerprint`, one reserved segment each
+pub const QUALIA_DIMENSIONS: usize = 8;
+const QUALIA_SEGMENT_BITS: usize = FINGERPRINT_BITS / QUALIA_DIMENSIONS;
+
+/// Neutral valence, no arousal, middling confidence
+impl Default for Qualia {
+    fn default() -> Self {
+        Self {
+            novelty: 0.0,
+            effort: 0.0,
+            satisfaction: 0.0,
+            confusion: 0.0,
+            surprise: 0.0,
+            qidx: 0,
+            valence: 0.0,
+            arousal: 0.0,
+            confidence: 0.5,
+        }
+    }
 }
 
 impl Qualia {
@@ -24,14 +58,108 @@ impl Qualia {
             novelty: novelty.clamp(0.0, 1.0),
             effort: effort.clamp(0.0, 1.0),
             satisfaction: satisfaction.clamp(0.0, 1.0),
-            confusion: 0.0,
-            surprise: 0.0,
+            ..Self::default()
+        };
+        q.compute_qidx();
+        q
+    }
+    
+    pub fn with_affect(mut self, valence: f32, arousal: f32, confidence: f32) -> Self {
+        self.valence = valence.clamp(-1.0, 1.0);
+        self.arousal = arousal.clamp(0.0, 1.0);
+        self.confidence = confidence.clamp(0.0, 1.0);
+        self
+    }
+    
+    /// First dimension outside its range (NaN included): valence in
+    /// [-1, 1], everything else in [0, 1]
+    pub fn out_of_range(&self) -> Option<(&'static str, f32)> {
+        let unit = [
+            ("novelty", self.novelty),
+            ("effort", self.effort),
+            ("satisfaction", self.satisfaction),
+            ("confusion", self.confusion),
+            ("surprise", self.surprise),
+            ("arousal", self.arousal),
+            ("confidence", self.confidence),
+        ];
+        unit.into_iter()
+            .find(|(_, v)| !(0.0..=1.0).contains(v))
+            .or_else(|| (!(-1.0..=1.0).contains(&self.valence)).then_some(("valence", self.valence)))
+    }
+    
+    /// Every dimension clamped into range, NaN mapped to 0. `qidx` is left as is.
+    pub fn clamped(&self) -> Qualia {
+        let unit = |v: f32| if v.is_nan() { 0.0 } else { v.clamp(0.0, 1.0) };
+        Qualia {
+            novelty: unit(self.novelty),
+            effort: unit(self.effort),
+            satisfaction: unit(self.satisfaction),
+            confusion: unit(self.confusion),
+            surprise: unit(self.surprise),
+            qidx: self.qidx,
+            valence: if self.valence.is_nan() { 0.0 } else { self.valence.clamp(-1.0, 1.0) },
+            arousal: unit(self.arousal),
+            confidence: unit(self.confidence),
+        }
+    }
+    
+    /// Every dimension mapped onto [0, 1]; valence is rescaled from [-1, 1]
+    pub fn normalized(&self) -> [f32; QUALIA_DIMENSIONS] {
+        [
+            self.novelty,
+            self.effort,
+            self.satisfaction,
+            self.confusion,
+            self.surprise,
+            (self.valence + 1.0) / 2.0,
+            self.arousal,
+            self.confidence,
+        ].map(|v| if v.is_nan() { 0.0 } else { v.clamp(0.0, 1.0) })
+    }
+    
+    /// Inverse of `normalized`: values are clamped to [0, 1], valence is
+    /// mapped back onto [-1, 1] and `qidx` is recomputed
+    pub fn from_normalized(values: [f32; QUALIA_DIMENSIONS]) -> Qualia {
+        let [novelty, effort, satisfaction, confusion, surprise, valence, arousal, confidence] =
+            values.map(|v| if v.is_nan() { 0.0 } else { v.clamp(0.0, 1.0) });
+        let mut q = Qualia {
+            novelty,
+            effort,
+            satisfaction,
+            confusion,
+            surprise,
             qidx: 0,
+            valence: valence * 2.0 - 1.0,
+            arousal,
+            confidence,
         };
         q.compute_qidx();
         q
     }
     
+    /// Thermometer code: each dimension owns a segment of the fingerprint and
+    /// sets its first `value × segment` bits, so nearby feelings share most
+    /// bits regardless of content.
+    pub fn to_fingerprint(&self) -> Fingerprint {
+        let mut fp = Fingerprint::zero();
+        for (dim, value) in self.normalized().iter().enumerate() {
+            let lit = (value * QUALIA_SEGMENT_BITS as f32).round() as usize;
+            for bit in 0..lit {
+                fp.set_bit(dim * QUALIA_SEGMENT_BITS + bit, true);
+            }
+        }
+        fp
+    }
+    
+    /// 1 − mean absolute difference over the normalized dimensions. Equals
+    /// the Hamming similarity of `to_fingerprint` up to bit quantization.
+    pub fn similarity(&self, other: &Qualia) -> f32 {
+        let (a, b) = (self.normalized(), other.normalized());
+        let l1: f32 = a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).sum();
+        1.0 - l1 / QUALIA_DIMENSIONS as f32
+    }
+    
     pub fn compute_qidx(&mut self) {
         let breakthrough = (self.novelty * self.satisfaction * 15.0) as u8;
         let clean_effort = (self.effort * (1.0 - self.confusion) * 15.0) as u8;
@@ -48,18 +176,271 @@ impl Qualia {
     
     pub fn weight_fingerprint(&self, fp: &Fingerprint) -> Fingerprint {
         let qualia_sig = Fingerprint::from_content(&format!(
-            "qualia:{}:{}:{}:{}:{}",
+            "qualia:{}:{}:{}:{}:{}:{}:{}:{}",
             (self.novelty * 100.0) as u32,
             (self.effort * 100.0) as u32,
             (self.satisfaction * 100.0) as u32,
             (self.confusion * 100.0) as u32,
             (self.surprise * 100.0) as u32,
+            (self.valence * 100.0) as i32,
+            (self.arousal * 100.0) as u32,
+            (self.confidence * 100.0) as u32,
         ));
         fp.bind(&qualia_sig)
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// Longest accepted metadata key, in characters
+pub const MAX_META_KEY_LEN: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum MetadataError {
+    #[error("metadata key must not be empty")]
+    EmptyKey,
+    #[error("metadata key {key:?} is longer than {MAX_META_KEY_LEN} characters")]
+    KeyTooLong { key: String },
+}
+
+fn validate_meta_key(key: &str) -> Result<(), MetadataError> {
+    if key.trim().is_empty() {
+        return Err(MetadataError::EmptyKey);
+    }
+    if key.chars().count() > MAX_META_KEY_LEN {
+        return Err(MetadataError::KeyTooLong { key: key.to_string() });
+    }
+    Ok(())
+}
+
+/// Canonical form of a tag: trimmed, without a leading `#`, lowercase.
+/// "#Auth", "auth" and " AUTH " are the same tag.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').trim().to_lowercase()
+}
+
+fn push_tag(tags: &mut Vec<String>, tag: &str) -> bool {
+    let tag = normalize_tag(tag);
+    if tag.is_empty() || tags.contains(&tag) {
+        return false;
+    }
+    tags.push(tag);
+    true
+}
+
+/// Earlier contents `Moment::edit_content` keeps
+pub const MAX_EDIT_HISTORY: usize = 16;
+
+/// Head-and-tail summary of content longer than `max_chars`, or None if it
+/// fits. Multi-line text keeps its first and last line around a marker
+/// counting the omitted lines; anything still too long keeps the first and
+/// last characters around " … ". The omitted middle never affects the result.
+pub fn summarize_content(content: &str, max_chars: usize) -> Option<String> {
+    if content.chars().count() <= max_chars {
+        return None;
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() > 2 {
+        let summary = format!("{}\n… {} lines omitted …\n{}", lines[0], lines.len() - 2, lines[lines.len() - 1]);
+        if summary.chars().count() <= max_chars {
+            return Some(summary);
+        }
+    }
+    let budget = max_chars.saturating_sub(3);
+    let head: String = content.chars().take(budget - budget / 2).collect();
+    let tail: Vec<char> = content.chars().rev().take(budget / 2).collect();
+    Some(format!("{} … {}", head, tail.into_iter().rev().collect::<String>()))
+}
+
+/// Longest content `MomentBuilder::try_build` accepts, in characters
+pub const MAX_CONTENT_CHARS: usize = 64 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Error)]
+#[non_exhaustive]
+pub enum MomentBuildError {
+    #[error("moment content is empty")]
+    EmptyContent,
+    #[error("qualia {field} = {value} is out of range")]
+    QualiaOutOfRange { field: &'static str, value: f32 },
+    #[error("moment type was not set")]
+    MissingType,
+    #[error("moment content is {len} characters, limit is {MAX_CONTENT_CHARS}")]
+    ContentTooLong { len: usize },
+}
+
+/// Longest snippet kept on a moment, in characters; longer text is truncated
+pub const MAX_SNIPPET_CHARS: usize = 4_096;
+
+/// Evidence attached to a moment
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Artifact {
+    /// Inclusive 1-based line range, if the evidence is a specific region
+    FilePath { path: String, line_range: Option<(u32, u32)> },
+    Snippet { language: String, text: String },
+    Url(String),
+}
+
+impl Artifact {
+    pub fn file(path: &str) -> Self {
+        Self::FilePath { path: path.to_string(), line_range: None }
+    }
+    
+    pub fn file_lines(path: &str, start: u32, end: u32) -> Self {
+        Self::FilePath { path: path.to_string(), line_range: Some((start.min(end), start.max(end))) }
+    }
+    
+    /// Snippet truncated to `MAX_SNIPPET_CHARS`
+    pub fn snippet(language: &str, text: &str) -> Self {
+        let text = match text.char_indices().nth(MAX_SNIPPET_CHARS) {
+            Some((cut, _)) => &text[..cut],
+            None => text,
+        };
+        Self::Snippet { language: language.to_string(), text: text.to_string() }
+    }
+    
+    pub fn url(url: &str) -> Self {
+        Self::Url(url.to_string())
+    }
+    
+    /// "file", "snippet" or "url"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::FilePath { .. } => "file",
+            Self::Snippet { .. } => "snippet",
+            Self::Url(_) => "url",
+        }
+    }
+}
+
+impl fmt::Display for Artifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FilePath { path, line_range: None } => write!(f, "{}", path),
+            Self::FilePath { path, line_range: Some((start, end)) } if start == end => write!(f, "{}:{}", path, start),
+            Self::FilePath { path, line_range: Some((start, end)) } => write!(f, "{}:{}-{}", path, start, end),
+            Self::Snippet { language, text } => write!(f, "{} snippet ({} lines)", language, text.lines().count()),
+            Self::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// Where in the source tree a moment happened
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub path: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+impl SourceLocation {
+    pub fn new(path: &str, line: Option<u32>, column: Option<u32>) -> Self {
+        Self { path: path.to_string(), line, column }
+    }
+    
+    /// The Rust source location of the (outermost `#[track_caller]`) caller
+    #[track_caller]
+    pub fn caller() -> Self {
+        let location = std::panic::Location::caller();
+        Self::new(location.file(), Some(location.line()), Some(location.column()))
+    }
+    
+    /// Whether the location is in `path`; "./src/a.rs" matches "src/a.rs"
+    pub fn is_in(&self, path: &str) -> bool {
+        fn parts(path: &str) -> impl Iterator<Item = std::path::Component<'_>> {
+            std::path::Path::new(path).components().filter(|c| *c != std::path::Component::CurDir)
+        }
+        parts(&self.path).eq(parts(path))
+    }
+    
+    /// Markdown link to the location, e.g. `[src/lib.rs:42](src/lib.rs#L42)`
+    pub fn to_markdown_link(&self) -> String {
+        match self.line {
+            Some(line) => format!("[{}]({}#L{})", self, self.path, line),
+            None => format!("[{}]({})", self, self.path),
+        }
+    }
+}
+
+/// "path", "path:line" or "path:line:column"
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Weights for `Moment::importance_with`. Importance is unitless; only
+/// the ordering it produces matters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportanceWeights {
+    pub breakthrough: f32,
+    pub failure: f32,
+    pub struggle: f32,
+    pub encounter: f32,
+    /// Application, meta-reflection and custom types
+    pub other: f32,
+    /// Scales the larger of novelty and frustration
+    pub extremity: f32,
+    pub ice_caked: f32,
+    /// Scales d / (d + 1) for link degree d, so links saturate
+    pub links: f32,
+}
+
+impl Default for ImportanceWeights {
+    fn default() -> Self {
+        Self {
+            breakthrough: 1.0,
+            failure: 0.7,
+            struggle: 0.5,
+            encounter: 0.2,
+            other: 0.3,
+            extremity: 0.5,
+            ice_caked: 1.0,
+            links: 0.5,
+        }
+    }
+}
+
+impl ImportanceWeights {
+    pub fn type_weight(&self, moment_type: &MomentType) -> f32 {
+        match moment_type {
+            MomentType::Breakthrough => self.breakthrough,
+            MomentType::Failure | MomentType::DecisionReversal => self.failure,
+            MomentType::Struggle => self.struggle,
+            MomentType::Encounter => self.encounter,
+            MomentType::Application
+            | MomentType::MetaReflection
+            | MomentType::Hypothesis
+            | MomentType::Verification { .. }
+            | MomentType::Custom(_) => self.other,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LinkKind {
+    CausedBy,
+    Resolves,
+    Follows,
+    Contradicts,
+    /// Verification → the hypothesis it checked
+    Verifies,
+}
+
+/// Typed edge from one moment to another, stored on the source moment
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MomentLink {
+    pub target_id: String,
+    pub kind: LinkKind,
+}
+
+/// Serialized as the bare variant name, e.g. `"Breakthrough"`; custom
+/// types as `{"Custom": "Review"}`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MomentType {
     Encounter,
     Struggle,
@@ -67,40 +448,145 @@ pub enum MomentType {
     Failure,
     Application,
     MetaReflection,
+    /// A guess to be verified; its `truth` tracks the accumulated evidence
+    Hypothesis,
+    /// Outcome of checking a hypothesis, linked to it with `LinkKind::Verifies`
+    Verification { confirmed: bool },
+    /// Retraction of an ice-caked decision, linked to the decision's moment
+    /// with `LinkKind::Contradicts`
+    DecisionReversal,
+    /// User-defined kind, e.g. "Review" or "Handoff"
+    Custom(String),
 }
 
-#[derive(Clone, Debug)]
+impl MomentType {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Encounter => "Encounter",
+            Self::Struggle => "Struggle",
+            Self::Breakthrough => "Breakthrough",
+            Self::Failure => "Failure",
+            Self::Application => "Application",
+            Self::MetaReflection => "MetaReflection",
+            Self::Hypothesis => "Hypothesis",
+            Self::Verification { .. } => "Verification",
+            Self::DecisionReversal => "DecisionReversal",
+            Self::Custom(name) => name,
+        }
+    }
+    
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom(_))
+    }
+}
+
+/// A captured learning moment.
+///
+/// JSON form (field names are stable):
+///
+/// ```text
+/// {
+///   "id": "…", "session_id": "…", "seq": 1, "created_at": 1700000000000,
+///   "moment_type": "Breakthrough",
+///   "content": "…",
+///   "fingerprint": "<compact base64>", "resonance_vector": "<compact base64>",
+///   "qualia": { "novelty": 0.9, "effort": 0.5, "satisfaction": 0.8,
+///               "confusion": 0.0, "surprise": 0.0, "qidx": 167,
+///               "valence": 0.8, "arousal": 0.7, "confidence": 0.8 },
+///   "thinking_style": { "analytical": 0.0, "creative": 0.0, "focused": 0.0, "exploratory": 0.0 },
+///   "truth": { "f": 0.5, "c": 0.0 },
+///   "duration_ms": null,
+///   "tags": [], "parent_id": null, "subtask_id": null, "related_files": [],
+///   "links": [{ "target_id": "…", "kind": "Resolves" }],
+///   "artifacts": [{ "FilePath": { "path": "version.rb", "line_range": [42, 42] } },
+///                 { "Snippet": { "language": "ruby", "text": "…" } },
+///                 { "Url": "https://…" }],
+///   "metadata": { "commit": "3f2a9c1" },
+///   "revision": 1, "edit_history": [[0, "teh typo"]]
+/// }
+/// ```
+///
+/// Fingerprints use `Fingerprint::to_compact_string`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Moment {
     pub id: String,
     pub session_id: String,
-    pub timestamp_ms: u64,
+    /// Position within the session, assigned on recording and never reused;
+    /// 0 until the moment is recorded
+    #[serde(default)]
+    pub seq: u64,
+    /// Epoch milliseconds; accepts the older `timestamp_ms` name when deserializing
+    #[serde(alias = "timestamp_ms")]
+    pub created_at: u64,
     pub moment_type: MomentType,
+    /// What the fingerprints are computed from; a summary when `raw_content` is set
     pub content: String,
+    /// Full text of content that was summarized on capture
+    #[serde(default)]
+    pub raw_content: Option<String>,
     pub fingerprint: Fingerprint,
     pub resonance_vector: Fingerprint,
     pub qualia: Qualia,
     pub thinking_style: ThinkingStyle,
     pub truth: TruthValue,
+    /// Set when the moment stands for a span of work rather than an instant
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// Lightweight labels, normalized with `normalize_tag` and deduplicated
     pub tags: Vec<String>,
     pub parent_id: Option<String>,
+    /// Innermost subtask open when the moment was recorded; see
+    /// `LearningSession::begin_subtask`
+    #[serde(default)]
+    pub subtask_id: Option<String>,
     pub related_files: Vec<String>,
+    /// Opt-in for custom moment types to count as breakthroughs; see `is_breakthrough`
+    #[serde(default)]
+    pub breakthrough_eligible: bool,
+    /// Free-form context: commit hash, file under investigation, ticket id
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub links: Vec<MomentLink>,
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    /// Where the moment happened, for querying by file
+    #[serde(default)]
+    pub source: Option<SourceLocation>,
+    /// Times the same moment was logged again and merged into this one
+    #[serde(default)]
+    pub repeat_count: u32,
+    /// Bumped by every `edit_content`
+    #[serde(default)]
+    pub revision: u32,
+    /// Earlier contents as (revision, content), oldest first
+    #[serde(default)]
+    pub edit_history: Vec<(u32, String)>,
+    /// Set once `MetaAGI::capture_moment` has stored the moment in its
+    /// resonance store; such a moment can no longer be undone
+    #[serde(default)]
+    pub captured_externally: bool,
+    /// Collapse gate read off the latest struggles when this struggle was
+    /// recorded; see `StruggleGateConfig`
+    #[serde(default)]
+    pub gate_state: Option<GateState>,
 }
 
 impl Moment {
     pub fn new(session_id: &str, content: &str, moment_type: MomentType) -> Self {
+        Self::new_at(session_id, content, moment_type, SystemClock.now_millis())
+    }
+    
+    pub fn new_at(session_id: &str, content: &str, moment_type: MomentType, created_at: u64) -> Self {
         let fingerprint = Fingerprint::from_content(content);
         let qualia = Qualia::default();
         let resonance_vector = qualia.weight_fingerprint(&fingerprint);
         
-        let timestamp_ms = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_millis() as u64)
-            .unwrap_or(0);
-        
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             session_id: session_id.to_string(),
-            timestamp_ms,
+            seq: 0,
+            created_at,
             moment_type,
             content: content.to_string(),
             fingerprint,
@@ -108,10 +594,72 @@ impl Moment {
             qualia,
             thinking_style: ThinkingStyle::default(),
             truth: TruthValue::unknown(),
+            duration_ms: None,
             tags: Vec::new(),
             parent_id: None,
+            subtask_id: None,
             related_files: Vec::new(),
+            breakthrough_eligible: false,
+            metadata: BTreeMap::new(),
+            links: Vec::new(),
+            artifacts: Vec::new(),
+            raw_content: None,
+            source: None,
+            repeat_count: 0,
+            revision: 0,
+            edit_history: Vec::new(),
+            captured_externally: false,
+            gate_state: None,
+        }
+    }
+    
+    /// Fold a repeat of this moment in: `repeat_count` goes up and the qualia
+    /// become the mean over every occurrence
+    pub fn merge_repeat(&mut self, repeat: &Moment) {
+        let occurrences = (self.repeat_count + 1) as f32;
+        let (mine, theirs) = (self.qualia.normalized(), repeat.qualia.normalized());
+        let mut mean = [0.0; QUALIA_DIMENSIONS];
+        for (dim, value) in mean.iter_mut().enumerate() {
+            *value = (mine[dim] * occurrences + theirs[dim]) / (occurrences + 1.0);
+        }
+        self.qualia = Qualia::from_normalized(mean);
+        self.resonance_vector = self.qualia.weight_fingerprint(&self.fingerprint);
+        self.repeat_count += 1;
+    }
+    
+    /// Replace the content, recomputing both fingerprints, and keep the old
+    /// content in `edit_history` (at most `MAX_EDIT_HISTORY` entries). Any
+    /// `raw_content` is dropped along with the summary it belonged to.
+    pub fn edit_content(&mut self, new_content: &str) {
+        self.edit_content_keeping(new_content, MAX_EDIT_HISTORY);
+    }
+    
+    /// `edit_content` with an explicit history cap; older entries are dropped first
+    pub fn edit_content_keeping(&mut self, new_content: &str, max_history: usize) {
+        let previous = std::mem::replace(&mut self.content, new_content.to_string());
+        self.raw_content = None;
+        self.edit_history.push((self.revision, previous));
+        if self.edit_history.len() > max_history {
+            let excess = self.edit_history.len() - max_history;
+            self.edit_history.drain(..excess);
         }
+        self.revision += 1;
+        self.fingerprint = Fingerprint::from_content(&self.content);
+        self.resonance_vector = self.qualia.weight_fingerprint(&self.fingerprint);
+    }
+    
+    pub fn links_of(&self, kind: LinkKind) -> impl Iterator<Item = &str> {
+        self.links.iter().filter(move |l| l.kind == kind).map(|l| l.target_id.as_str())
+    }
+    
+    /// Set a metadata entry, returning the previous value
+    pub fn set_meta(&mut self, key: &str, value: &str) -> Result<Option<String>, MetadataError> {
+        validate_meta_key(key)?;
+        Ok(self.metadata.insert(key.to_string(), value.to_string()))
+    }
+    
+    pub fn meta(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
     }
     
     pub fn with_qualia(mut self, qualia: Qualia) -> Self {
@@ -126,28 +674,95 @@ impl Moment {
     }
     
     pub fn with_tag(mut self, tag: &str) -> Self {
-        self.tags.push(tag.to_string());
+        self.add_tag(tag);
         self
     }
     
+    /// Returns false if the tag is empty or already present
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        push_tag(&mut self.tags, tag)
+    }
+    
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        self.tags.contains(&tag)
+    }
+    
+    /// Content bound to how it felt: two moments score high only when both
+    /// content and qualia are close. Use `Qualia::to_fingerprint` alone to
+    /// match on feeling regardless of content.
+    pub fn feeling_fingerprint(&self) -> Fingerprint {
+        self.fingerprint.bind(&self.qualia.to_fingerprint())
+    }
+    
+    /// Importance from the moment alone: default weights, not ice-caked,
+    /// outgoing links only. `LearningSession::importance` adds session context.
+    pub fn importance(&self) -> f32 {
+        self.importance_with(&ImportanceWeights::default(), false, self.links.len())
+    }
+    
+    pub fn importance_with(&self, weights: &ImportanceWeights, ice_caked: bool, link_degree: usize) -> f32 {
+        let extremity = self.qualia.novelty.max(self.qualia.confusion).clamp(0.0, 1.0);
+        let degree = link_degree as f32;
+        weights.type_weight(&self.moment_type)
+            + weights.extremity * extremity
+            + if ice_caked { weights.ice_caked } else { 0.0 }
+            + weights.links * degree / (degree + 1.0)
+    }
+    
+    /// When the moment's span ends: `created_at` plus its duration, if any
+    pub fn ends_at(&self) -> u64 {
+        self.created_at.saturating_add(self.duration_ms.unwrap_or(0))
+    }
+    
+    /// Breakthrough by type or by qualia. Custom moments are never
+    /// breakthroughs by type, and only count by qualia when they opted in
+    /// with `MomentBuilder::breakthrough_eligible`; the same rule gates
+    /// concept extraction.
     pub fn is_breakthrough(&self) -> bool {
-        self.moment_type == MomentType::Breakthrough || self.qualia.is_breakthrough()
+        match &self.moment_type {
+            MomentType::Breakthrough => true,
+            MomentType::Custom(_) => self.breakthrough_eligible && self.qualia.is_breakthrough(),
+            _ => self.qualia.is_breakthrough(),
+        }
     }
     
     pub fn resonance(&self, other: &Moment) -> f32 {
         self.resonance_vector.similarity(&other.resonance_vector)
     }
+    
+    /// Compact form for long-term storage, with the content summary capped
+    /// at `DEFAULT_ARCHIVE_SUMMARY_CHARS`
+    pub fn to_archive(&self) -> ArchivedMoment {
+        ArchivedMoment::from_moment(self, DEFAULT_ARCHIVE_SUMMARY_CHARS)
+    }
 }
 
+/// Builds moments. `build()` accepts anything, clamping qualia and
+/// defaulting the type to `Encounter`; `try_build()` rejects empty or
+/// overlong content, out-of-range qualia and a missing type instead.
 pub struct MomentBuilder {
     session_id: String,
     content: String,
-    moment_type: MomentType,
+    moment_type: Option<MomentType>,
+    /// Stored clamped; the first out-of-range input is kept for `try_build`
     qualia: Option<Qualia>,
+    qualia_violation: Option<(&'static str, f32)>,
+    lenient: bool,
     style: Option<ThinkingStyle>,
     tags: Vec<String>,
     parent_id: Option<String>,
     files: Vec<String>,
+    breakthrough_eligible: bool,
+    metadata: BTreeMap<String, String>,
+    created_at: Option<u64>,
+    duration_ms: Option<u64>,
+    links: Vec<MomentLink>,
+    artifacts: Vec<Artifact>,
+    truth: Option<TruthValue>,
+    summarize_over: Option<usize>,
+    raw_content: Option<String>,
+    source: Option<SourceLocation>,
 }
 
 impl MomentBuilder {
@@ -155,32 +770,184 @@ impl MomentBuilder {
         Self {
             session_id: session_id.to_string(),
             content: content.to_string(),
-            moment_type: MomentType::Encounter,
+            moment_type: None,
             qualia: None,
+            qualia_violation: None,
+            lenient: false,
             style: None,
             tags: Vec::new(),
             parent_id: None,
             files: Vec::new(),
+            breakthrough_eligible: false,
+            metadata: BTreeMap::new(),
+            created_at: None,
+            duration_ms: None,
+            links: Vec::new(),
+            artifacts: Vec::new(),
+            truth: None,
+            summarize_over: None,
+            raw_content: None,
+            source: None,
         }
     }
     
-    pub fn encounter(mut self) -> Self { self.moment_type = MomentType::Encounter; self }
-    pub fn struggle(mut self) -> Self { self.moment_type = MomentType::Struggle; self }
-    pub fn breakthrough(mut self) -> Self { self.moment_type = MomentType::Breakthrough; self }
-    pub fn failure(mut self) -> Self { self.moment_type = MomentType::Failure; self }
+    pub fn moment_type(mut self, moment_type: MomentType) -> Self { self.moment_type = Some(moment_type); self }
+    pub fn encounter(self) -> Self { self.moment_type(MomentType::Encounter) }
+    pub fn struggle(self) -> Self { self.moment_type(MomentType::Struggle) }
+    pub fn breakthrough(self) -> Self { self.moment_type(MomentType::Breakthrough) }
+    pub fn failure(self) -> Self { self.moment_type(MomentType::Failure) }
+    pub fn meta_reflection(self) -> Self { self.moment_type(MomentType::MetaReflection) }
+    pub fn custom(self, name: &str) -> Self { self.moment_type(MomentType::Custom(name.to_string())) }
+    pub fn hypothesis(self) -> Self { self.moment_type(MomentType::Hypothesis) }
+    pub fn verification(self, confirmed: bool) -> Self { self.moment_type(MomentType::Verification { confirmed }) }
+    pub fn decision_reversal(self) -> Self { self.moment_type(MomentType::DecisionReversal) }
+    
+    /// Let a custom moment count as a breakthrough when its qualia say so
+    pub fn breakthrough_eligible(mut self) -> Self {
+        self.breakthrough_eligible = true;
+        self
+    }
     
     pub fn qualia(mut self, novelty: f32, effort: f32, satisfaction: f32) -> Self {
-        self.qualia = Some(Qualia::from_metrics(novelty, effort, satisfaction));
+        let raw = Qualia { novelty, effort, satisfaction, ..Qualia::default() };
+        self.note_violation(&raw);
+        let affect = self.qualia.take().unwrap_or_default();
+        self.qualia = Some(Qualia::from_metrics(novelty, effort, satisfaction)
+            .with_affect(affect.valence, affect.arousal, affect.confidence));
+        self
+    }
+    
+    pub fn affect(mut self, valence: f32, arousal: f32, confidence: f32) -> Self {
+        let raw = Qualia { valence, arousal, confidence, ..Qualia::default() };
+        self.note_violation(&raw);
+        self.qualia = Some(self.qualia.take().unwrap_or_default().with_affect(valence, arousal, confidence));
+        self
+    }
+    
+    /// Use these qualia as given (clamped by `build`, checked by `try_build`)
+    pub fn with_qualia(mut self, qualia: Qualia) -> Self {
+        self.note_violation(&qualia);
+        self.qualia = Some(qualia.clamped());
+        self
+    }
+    
+    fn note_violation(&mut self, qualia: &Qualia) {
+        if self.qualia_violation.is_none() {
+            self.qualia_violation = qualia.out_of_range();
+        }
+    }
+    
+    /// Let `try_build` accept what `build` would: qualia are clamped, content
+    /// over `MAX_CONTENT_CHARS` is truncated and empty content is allowed.
+    /// Only a missing type is still an error.
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+    
+    /// Replace content over `max_chars` with `summarize_content`, keeping the
+    /// full text in `raw_content`. Length checks apply to the summary.
+    pub fn summarize_over(mut self, max_chars: usize) -> Self {
+        self.summarize_over = Some(max_chars);
+        self
+    }
+    
+    fn summarize(&mut self) {
+        let Some(max_chars) = self.summarize_over.take() else { return };
+        if let Some(summary) = summarize_content(&self.content, max_chars) {
+            self.raw_content = Some(std::mem::replace(&mut self.content, summary));
+        }
+    }
+    
+    pub fn style(mut self, style: ThinkingStyle) -> Self {
+        self.style = Some(style);
         self
     }
     
     pub fn tag(mut self, tag: &str) -> Self {
-        self.tags.push(tag.to_string());
+        push_tag(&mut self.tags, tag);
         self
     }
     
-    pub fn build(self) -> Moment {
-        let mut moment = Moment::new(&self.session_id, &self.content, self.moment_type);
+    pub fn link(mut self, kind: LinkKind, target_id: &str) -> Self {
+        self.links.push(MomentLink { target_id: target_id.to_string(), kind });
+        self
+    }
+    
+    pub fn caused_by(self, target_id: &str) -> Self { self.link(LinkKind::CausedBy, target_id) }
+    pub fn resolves(self, target_id: &str) -> Self { self.link(LinkKind::Resolves, target_id) }
+    pub fn follows(self, target_id: &str) -> Self { self.link(LinkKind::Follows, target_id) }
+    
+    pub fn artifact(mut self, artifact: Artifact) -> Self {
+        self.artifacts.push(artifact);
+        self
+    }
+    
+    pub fn file(self, path: &str) -> Self { self.artifact(Artifact::file(path)) }
+    pub fn file_lines(self, path: &str, start: u32, end: u32) -> Self { self.artifact(Artifact::file_lines(path, start, end)) }
+    pub fn snippet(self, language: &str, text: &str) -> Self { self.artifact(Artifact::snippet(language, text)) }
+    pub fn url(self, url: &str) -> Self { self.artifact(Artifact::url(url)) }
+    
+    /// Creation time in epoch millis; defaults to the system clock
+    pub fn created_at(mut self, ms: u64) -> Self {
+        self.created_at = Some(ms);
+        self
+    }
+    
+    pub fn duration_ms(mut self, ms: u64) -> Self {
+        self.duration_ms = Some(ms);
+        self
+    }
+    
+    pub fn source(mut self, source: SourceLocation) -> Self {
+        self.source = Some(source);
+        self
+    }
+    
+    pub fn source_at(self, path: &str, line: u32) -> Self {
+        self.source(SourceLocation::new(path, Some(line), None))
+    }
+    
+    pub fn truth(mut self, truth: TruthValue) -> Self {
+        self.truth = Some(truth);
+        self
+    }
+    
+    pub fn meta(mut self, key: &str, value: &str) -> Result<Self, MetadataError> {
+        validate_meta_key(key)?;
+        self.metadata.insert(key.to_string(), value.to_string());
+        Ok(self)
+    }
+    
+    pub fn try_build(mut self) -> Result<Moment, MomentBuildError> {
+        self.summarize();
+        let len = self.content.chars().count();
+        if self.lenient {
+            if let Some((cut, _)) = self.content.char_indices().nth(MAX_CONTENT_CHARS) {
+                self.content.truncate(cut);
+            }
+        } else {
+            if self.content.trim().is_empty() {
+                return Err(MomentBuildError::EmptyContent);
+            }
+            if len > MAX_CONTENT_CHARS {
+                return Err(MomentBuildError::ContentTooLong { len });
+            }
+            if let Some((field, value)) = self.qualia_violation {
+                return Err(MomentBuildError::QualiaOutOfRange { field, value });
+            }
+        }
+        if self.moment_type.is_none() {
+            return Err(MomentBuildError::MissingType);
+        }
+        Ok(self.build())
+    }
+    
+    pub fn build(mut self) -> Moment {
+        self.summarize();
+        let created_at = self.created_at.unwrap_or_else(|| SystemClock.now_millis());
+        let moment_type = self.moment_type.unwrap_or(MomentType::Encounter);
+        let mut moment = Moment::new_at(&self.session_id, &self.content, moment_type, created_at);
         if let Some(q) = self.qualia {
             moment = moment.with_qualia(q);
         }
@@ -190,6 +957,408 @@ impl MomentBuilder {
         moment.tags = self.tags;
         moment.parent_id = self.parent_id;
         moment.related_files = self.files;
+        moment.breakthrough_eligible = self.breakthrough_eligible;
+        moment.metadata = self.metadata;
+        moment.duration_ms = self.duration_ms;
+        moment.links = self.links;
+        moment.artifacts = self.artifacts;
+        if let Some(truth) = self.truth {
+            moment.truth = truth;
+        }
+        moment.raw_content = self.raw_content;
+        moment.source = self.source;
         moment
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn all_types() -> [MomentType; 9] {
+        [
+            MomentType::Encounter,
+            MomentType::Struggle,
+            MomentType::Breakthrough,
+            MomentType::Failure,
+            MomentType::Application,
+            MomentType::MetaReflection,
+            MomentType::Hypothesis,
+            MomentType::Verification { confirmed: false },
+            MomentType::Custom("Review".to_string()),
+        ]
+    }
+    
+    fn assert_same(a: &Moment, b: &Moment) {
+        assert_eq!(a.id, b.id);
+        assert_eq!(a.session_id, b.session_id);
+        assert_eq!(a.seq, b.seq);
+        assert_eq!(a.created_at, b.created_at);
+        assert_eq!(a.duration_ms, b.duration_ms);
+        assert_eq!(a.moment_type, b.moment_type);
+        assert_eq!(a.content, b.content);
+        assert_eq!(a.raw_content, b.raw_content);
+        assert_eq!(a.fingerprint, b.fingerprint);
+        assert_eq!(a.resonance_vector, b.resonance_vector);
+        assert_eq!(a.qualia, b.qualia);
+        assert_eq!(a.thinking_style, b.thinking_style);
+        assert_eq!(a.truth.frequency, b.truth.frequency);
+        assert_eq!(a.truth.confidence, b.truth.confidence);
+        assert_eq!(a.tags, b.tags);
+        assert_eq!(a.parent_id, b.parent_id);
+        assert_eq!(a.related_files, b.related_files);
+        assert_eq!(a.breakthrough_eligible, b.breakthrough_eligible);
+        assert_eq!(a.metadata, b.metadata);
+        assert_eq!(a.links, b.links);
+        assert_eq!(a.artifacts, b.artifacts);
+        assert_eq!(a.source, b.source);
+        assert_eq!(a.repeat_count, b.repeat_count);
+        assert_eq!(a.revision, b.revision);
+        assert_eq!(a.edit_history, b.edit_history);
+    }
+    
+    fn samples() -> Vec<Moment> {
+        let extreme = Qualia {
+            novelty: 1.0,
+            effort: 0.0,
+            satisfaction: f32::MIN_POSITIVE,
+            confusion: 0.999_999_9,
+            surprise: 1.0,
+            qidx: u8::MAX,
+            valence: -1.0,
+            arousal: 1.0,
+            confidence: 0.0,
+        };
+        all_types().into_iter().flat_map(|t| {
+            let full = MomentBuilder::new("s1", "borrow checker finally clicked")
+                .qualia(0.9, 0.4, 0.8)
+                .tag("rust")
+                .meta("commit", "3f2a9c1").unwrap()
+                .meta("ticket", "LRN-42").unwrap()
+                .duration_ms(90_000)
+                .resolves("struggle-1")
+                .caused_by("failure-1")
+                .file_lines("lib/version.rb", 42, 42)
+                .snippet("ruby", "VERSION = \"1.2.3\"")
+                .url("https://example.com/issues/7")
+                .source(SourceLocation::new("src/lexer.rs", Some(42), Some(7)))
+                .build();
+            let mut full = Moment { moment_type: t.clone(), ..full };
+            full.parent_id = Some("parent".to_string());
+            full.seq = u64::MAX;
+            full.truth = TruthValue::new(0.9, 0.8);
+            full.edit_content("borrow checker finally clicked!");
+            let empty = Moment::new("", "", t).with_qualia(extreme.clone());
+            [full, empty]
+        }).collect()
+    }
+    
+    #[test]
+    fn test_moment_type_is_externally_tagged() {
+        assert_eq!(serde_json::to_string(&MomentType::MetaReflection).unwrap(), "\"MetaReflection\"");
+    }
+    
+    #[test]
+    fn test_custom_type_serializes_tagged() {
+        let json = serde_json::to_string(&MomentType::Custom("Handoff".to_string())).unwrap();
+        assert_eq!(json, r#"{"Custom":"Handoff"}"#);
+    }
+    
+    #[test]
+    fn test_custom_breakthrough_is_opt_in() {
+        let plain = MomentBuilder::new("s", "review went great").custom("Review").qualia(0.9, 0.3, 0.9).build();
+        assert!(!plain.is_breakthrough());
+        
+        let eligible = MomentBuilder::new("s", "review went great")
+            .custom("Review")
+            .qualia(0.9, 0.3, 0.9)
+            .breakthrough_eligible()
+            .build();
+        assert!(eligible.is_breakthrough());
+        assert_eq!(eligible.moment_type.name(), "Review");
+    }
+    
+    #[test]
+    fn test_tags_are_normalized_and_deduplicated() {
+        let moment = MomentBuilder::new("s", "login loops")
+            .tag("Auth")
+            .tag("#auth")
+            .tag(" AUTH ")
+            .tag("#")
+            .tag("Performance")
+            .build();
+        assert_eq!(moment.tags, vec!["auth".to_string(), "performance".to_string()]);
+        assert!(moment.has_tag("#Auth"));
+        
+        let mut moment = moment.with_tag("auth");
+        assert_eq!(moment.tags.len(), 2);
+        assert!(!moment.add_tag("PERFORMANCE"));
+        assert!(moment.add_tag("#db"));
+        assert_eq!(moment.tags.last().map(String::as_str), Some("db"));
+    }
+    
+    #[test]
+    fn test_tags_export_in_yaml() {
+        let moment = MomentBuilder::new("s", "token expired").tag("#Auth").build();
+        let yaml = serde_yaml::to_string(&moment).unwrap();
+        assert!(yaml.contains("tags:\n- auth\n"));
+        let back: Moment = serde_yaml::from_str(&yaml).unwrap();
+        assert!(back.has_tag("auth"));
+    }
+    
+    #[test]
+    fn test_edit_content_recomputes_fingerprints() {
+        let mut moment = MomentBuilder::new("s", "teh cache is stale").qualia(0.6, 0.4, 0.5).build();
+        let fixed = MomentBuilder::new("s", "the cache is stale").qualia(0.6, 0.4, 0.5).build();
+        moment.edit_content("the cache is stale");
+        
+        assert_eq!(moment.content, "the cache is stale");
+        assert_eq!(moment.fingerprint, fixed.fingerprint);
+        assert_eq!(moment.resonance_vector, fixed.resonance_vector);
+        assert_eq!(moment.revision, 1);
+        assert_eq!(moment.edit_history, vec![(0, "teh cache is stale".to_string())]);
+    }
+    
+    #[test]
+    fn test_edit_history_is_capped() {
+        let mut moment = Moment::new("s", "v0", MomentType::Encounter);
+        for i in 1..=5 {
+            moment.edit_content_keeping(&format!("v{}", i), 3);
+        }
+        assert_eq!(moment.revision, 5);
+        let kept: Vec<(u32, &str)> = moment.edit_history.iter().map(|(r, c)| (*r, c.as_str())).collect();
+        assert_eq!(kept, vec![(2, "v2"), (3, "v3"), (4, "v4")]);
+    }
+    
+    #[test]
+    fn test_summarize_content_boundary() {
+        assert_eq!(summarize_content("abcdef", 6), None);
+        assert_eq!(summarize_content("abcdefg", 6).as_deref(), Some("ab … g"));
+        let trace = "panicked at src/main.rs:3\nframe 1\nframe 2\nframe 3\nend of trace";
+        assert_eq!(summarize_content(trace, 60).as_deref(), Some("panicked at src/main.rs:3\n… 3 lines omitted …\nend of trace"));
+        assert!(summarize_content(trace, 20).unwrap().chars().count() <= 20);
+    }
+    
+    #[test]
+    fn test_summarized_content_keeps_raw_text() {
+        let trace = format!("error: boom\n{}at main", "frame\n".repeat(100));
+        let moment = MomentBuilder::new("s", &trace).struggle().summarize_over(100).try_build().unwrap();
+        assert_eq!(moment.content, "error: boom\n… 100 lines omitted …\nat main");
+        assert_eq!(moment.raw_content.as_deref(), Some(trace.as_str()));
+        assert_eq!(moment.fingerprint, Fingerprint::from_content(&moment.content));
+        
+        let short = MomentBuilder::new("s", "error: boom").summarize_over(100).build();
+        assert_eq!(short.raw_content, None);
+    }
+    
+    #[test]
+    fn test_source_location() {
+        let here = SourceLocation::caller();
+        assert_eq!(here.path, file!());
+        assert_eq!(here.line, Some(line!() - 2));
+        
+        let source = SourceLocation::new("./src/lexer.rs", Some(42), Some(7));
+        assert_eq!(source.to_string(), "./src/lexer.rs:42:7");
+        assert_eq!(source.to_markdown_link(), "[./src/lexer.rs:42:7](./src/lexer.rs#L42)");
+        assert!(source.is_in("src/lexer.rs"));
+        assert!(!source.is_in("src/lexer.rs.bak"));
+        assert_eq!(SourceLocation::new("Cargo.toml", None, Some(3)).to_string(), "Cargo.toml");
+    }
+    
+    #[test]
+    fn test_meta_key_validation() {
+        let builder = MomentBuilder::new("s", "c");
+        assert_eq!(builder.meta("", "x").err(), Some(MetadataError::EmptyKey));
+        
+        let long = "k".repeat(MAX_META_KEY_LEN + 1);
+        let mut moment = Moment::new("s", "c", MomentType::Encounter);
+        assert_eq!(moment.set_meta(&long, "x"), Err(MetadataError::KeyTooLong { key: long.clone() }));
+        assert_eq!(moment.set_meta(&long[1..], "x"), Ok(None));
+        assert_eq!(moment.set_meta("file", "a.rs"), Ok(None));
+        assert_eq!(moment.set_meta("file", "b.rs"), Ok(Some("a.rs".to_string())));
+        assert_eq!(moment.meta("file"), Some("b.rs"));
+    }
+    
+    #[test]
+    fn test_legacy_timestamp_field_name() {
+        let mut value = serde_json::to_value(Moment::new_at("s", "c", MomentType::Encounter, 42)).unwrap();
+        let object = value.as_object_mut().unwrap();
+        let created_at = object.remove("created_at").unwrap();
+        object.insert("timestamp_ms".to_string(), created_at);
+        object.remove("duration_ms");
+        let moment: Moment = serde_json::from_value(value).unwrap();
+        assert_eq!(moment.created_at, 42);
+        assert_eq!(moment.duration_ms, None);
+    }
+    
+    #[test]
+    fn test_affect_defaults() {
+        let q = Qualia::from_metrics(0.5, 0.5, 0.5);
+        assert_eq!((q.valence, q.arousal, q.confidence), (0.0, 0.0, 0.5));
+        
+        let old: Qualia = serde_json::from_str(r#"{"novelty":0.9,"effort":0.1,"satisfaction":0.8,"confusion":0.0,"surprise":0.0,"qidx":7}"#).unwrap();
+        assert_eq!(old.novelty, 0.9);
+        assert_eq!((old.valence, old.arousal, old.confidence), (0.0, 0.0, 0.5));
+        
+        let clamped = Qualia::new().with_affect(-3.0, 2.0, -1.0);
+        assert_eq!((clamped.valence, clamped.arousal, clamped.confidence), (-1.0, 1.0, 0.0));
+    }
+    
+    #[test]
+    fn test_affect_changes_resonance_vector() {
+        let calm = MomentBuilder::new("s", "same content").affect(0.5, 0.1, 0.5).build();
+        let tense = MomentBuilder::new("s", "same content").affect(-0.5, 0.9, 0.5).build();
+        assert_ne!(calm.resonance_vector, tense.resonance_vector);
+        
+        let ordered = MomentBuilder::new("s", "c").affect(-0.4, 0.6, 0.3).qualia(0.9, 0.2, 0.8).build();
+        assert_eq!(ordered.qualia.valence, -0.4);
+        assert_eq!(ordered.qualia.novelty, 0.9);
+    }
+    
+    #[test]
+    fn test_qualia_similarity_matches_fingerprint() {
+        let a = Qualia::from_metrics(0.9, 0.2, 0.7).with_affect(0.5, 0.4, 0.8);
+        let b = Qualia::from_metrics(0.3, 0.6, 0.1).with_affect(-0.9, 0.9, 0.2);
+        let direct = a.similarity(&b);
+        let encoded = a.to_fingerprint().similarity(&b.to_fingerprint());
+        assert!((direct - encoded).abs() < 1e-3, "{} vs {}", direct, encoded);
+        assert_eq!(a.similarity(&a), 1.0);
+        assert_eq!(a.to_fingerprint().similarity(&a.to_fingerprint()), 1.0);
+    }
+    
+    #[test]
+    fn test_qualia_similarity_is_monotone() {
+        let base = Qualia::from_metrics(0.2, 0.5, 0.5);
+        let mut last = 1.0;
+        for step in 1..=8 {
+            let mut other = base.clone();
+            other.novelty = 0.2 + step as f32 * 0.1;
+            let direct = base.similarity(&other);
+            let encoded = base.to_fingerprint().similarity(&other.to_fingerprint());
+            assert!(direct < last);
+            assert!((direct - encoded).abs() < 1e-3);
+            last = direct;
+        }
+    }
+    
+    #[test]
+    fn test_feeling_fingerprint_needs_both() {
+        let calm = Qualia::from_metrics(0.3, 0.2, 0.8);
+        let tense = Qualia::from_metrics(0.3, 0.9, 0.1).with_affect(-0.8, 0.9, 0.2);
+        let a = Moment::new("s", "same words", MomentType::Encounter).with_qualia(calm.clone());
+        let b = Moment::new("s", "same words", MomentType::Encounter).with_qualia(tense);
+        let c = Moment::new("s", "other words", MomentType::Encounter).with_qualia(calm);
+        let self_sim = a.feeling_fingerprint().similarity(&a.feeling_fingerprint());
+        let same_content = a.feeling_fingerprint().similarity(&b.feeling_fingerprint());
+        let same_feeling = a.feeling_fingerprint().similarity(&c.feeling_fingerprint());
+        assert_eq!(self_sim, 1.0);
+        assert!(same_content < 0.8);
+        assert!(same_feeling < 0.6);
+    }
+    
+    fn valid() -> MomentBuilder {
+        MomentBuilder::new("s", "the cache key ignored the locale")
+            .breakthrough()
+            .qualia(0.9, 0.4, 0.8)
+            .affect(0.6, 0.5, 0.7)
+            .tag("cache")
+            .created_at(1_000)
+    }
+    
+    #[test]
+    fn test_try_build_matches_build() {
+        let checked = valid().try_build().unwrap();
+        let unchecked = valid().build();
+        assert_same(&checked, &Moment { id: checked.id.clone(), ..unchecked });
+    }
+    
+    #[test]
+    fn test_try_build_errors() {
+        assert_eq!(MomentBuilder::new("s", "  ").encounter().try_build().err(), Some(MomentBuildError::EmptyContent));
+        assert_eq!(MomentBuilder::new("s", "no type").try_build().err(), Some(MomentBuildError::MissingType));
+        
+        let long = "x".repeat(MAX_CONTENT_CHARS + 1);
+        assert_eq!(
+            MomentBuilder::new("s", &long).encounter().try_build().err(),
+            Some(MomentBuildError::ContentTooLong { len: MAX_CONTENT_CHARS + 1 }),
+        );
+        assert_eq!(
+            valid().qualia(1.5, 0.2, 0.3).try_build().err(),
+            Some(MomentBuildError::QualiaOutOfRange { field: "novelty", value: 1.5 }),
+        );
+        let mut q = Qualia::new();
+        q.valence = -2.0;
+        assert_eq!(
+            valid().with_qualia(q).try_build().err(),
+            Some(MomentBuildError::QualiaOutOfRange { field: "valence", value: -2.0 }),
+        );
+        match valid().affect(0.0, f32::NAN, 0.5).try_build() {
+            Err(MomentBuildError::QualiaOutOfRange { field: "arousal", value }) => assert!(value.is_nan()),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_build_and_lenient_clamp() {
+        let mut q = Qualia::from_metrics(0.5, 0.5, 0.5);
+        q.confusion = 3.0;
+        let built = MomentBuilder::new("s", "c").with_qualia(q.clone()).build();
+        assert_eq!(built.qualia.confusion, 1.0);
+        assert_eq!(built.moment_type, MomentType::Encounter);
+        
+        let long = "y".repeat(MAX_CONTENT_CHARS + 5);
+        let lenient = MomentBuilder::new("s", &long).struggle().with_qualia(q).lenient().try_build().unwrap();
+        assert_eq!(lenient.content.len(), MAX_CONTENT_CHARS);
+        assert_eq!(lenient.qualia.confusion, 1.0);
+        assert!(MomentBuilder::new("s", "").encounter().lenient().try_build().is_ok());
+        assert_eq!(MomentBuilder::new("s", "").lenient().try_build().err(), Some(MomentBuildError::MissingType));
+    }
+    
+    #[test]
+    fn test_artifact_builder() {
+        let moment = MomentBuilder::new("s", "Found the pattern in version.rb line 42")
+            .file_lines("version.rb", 44, 42)
+            .file("Gemfile")
+            .snippet("ruby", "def bump!\n  @minor += 1\nend")
+            .url("https://example.com/pr/12")
+            .build();
+        let shown: Vec<String> = moment.artifacts.iter().map(ToString::to_string).collect();
+        assert_eq!(shown, vec![
+            "version.rb:42-44",
+            "Gemfile",
+            "ruby snippet (3 lines)",
+            "https://example.com/pr/12",
+        ]);
+        assert_eq!(Artifact::file_lines("a.rs", 7, 7).to_string(), "a.rs:7");
+    }
+    
+    #[test]
+    fn test_snippet_truncated_at_limit() {
+        let exact = "x".repeat(MAX_SNIPPET_CHARS);
+        assert_eq!(Artifact::snippet("txt", &exact), Artifact::Snippet { language: "txt".to_string(), text: exact.clone() });
+        
+        let long = "é".repeat(MAX_SNIPPET_CHARS + 10);
+        match Artifact::snippet("txt", &long) {
+            Artifact::Snippet { text, .. } => assert_eq!(text.chars().count(), MAX_SNIPPET_CHARS),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_json_round_trip() {
+        for moment in samples() {
+            let json = serde_json::to_string(&moment).unwrap();
+            let back: Moment = serde_json::from_str(&json).unwrap();
+            assert_same(&moment, &back);
+        }
+    }
+    
+    #[test]
+    fn test_yaml_round_trip() {
+        for moment in samples() {
+            let yaml = serde_yaml::to_string(&moment).unwrap();
+            let back: Moment = serde_yaml::from_str(&yaml).unwrap();
+            assert_same(&moment, &back);
+        }
+    }
+}
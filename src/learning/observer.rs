@@ -0,0 +1,106 @@
+//! SessionObserver — synchronous callbacks for session events
+
+use crate::learning::blackboard::IceCakedLayer;
+use crate::learning::moment::Moment;
+use crate::learning::session::SessionPhase;
+
+/// Receives a session's events as they happen, after the session has
+/// finished the change. Every method defaults to doing nothing.
+pub trait SessionObserver: Send {
+    /// A moment was recorded; repeats merged into an existing moment are not
+    fn on_moment(&mut self, _moment: &Moment) {}
+    
+    /// A breakthrough was recorded, right after its `on_moment`
+    fn on_breakthrough(&mut self, _moment: &Moment) {}
+    
+    fn on_phase_change(&mut self, _from: &SessionPhase, _to: &SessionPhase) {}
+    
+    /// A decision was ice-caked; `layer_id` is its 1-based position in
+    /// `LearningSession::ice_caked`
+    fn on_ice_cake(&mut self, _layer: &IceCakedLayer) {}
+}
+
+/// Handle from `LearningSession::subscribe`, for `unsubscribe`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Subscription(pub(crate) u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::learning::LearningSession;
+    
+    #[derive(Clone, Default)]
+    struct Counter {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+    
+    impl SessionObserver for Counter {
+        fn on_moment(&mut self, moment: &Moment) {
+            self.events.lock().unwrap().push(format!("moment {}", moment.seq));
+        }
+        
+        fn on_breakthrough(&mut self, moment: &Moment) {
+            self.events.lock().unwrap().push(format!("breakthrough {}", moment.seq));
+        }
+        
+        fn on_phase_change(&mut self, from: &SessionPhase, to: &SessionPhase) {
+            self.events.lock().unwrap().push(format!("phase {:?} -> {:?}", from, to));
+        }
+        
+        fn on_ice_cake(&mut self, layer: &IceCakedLayer) {
+            self.events.lock().unwrap().push(format!("ice cake {} {}", layer.layer_id, layer.rationale));
+        }
+    }
+    
+    struct Panicker;
+    
+    impl SessionObserver for Panicker {
+        fn on_moment(&mut self, _moment: &Moment) {
+            panic!("observer bug");
+        }
+    }
+    
+    #[test]
+    fn test_observer_sees_scripted_session() {
+        let counter = Counter::default();
+        let mut session = LearningSession::new("parser");
+        let subscription = session.subscribe(counter.clone());
+        session.encounter("parser rejects UTF-8 input");
+        let struggle = session.struggle("byte offsets and char offsets are mixed up", 0.8, 0.7).id.clone();
+        let fix = session.breakthrough_resolving(&struggle, "track byte offsets only", 0.9).id.clone();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        session.complete().unwrap();
+        
+        let expected = [
+            "moment 1", "phase Initialize -> Encounter",
+            "moment 2", "phase Encounter -> Struggle",
+            "moment 3", "breakthrough 3", "phase Struggle -> Breakthrough",
+            "phase Breakthrough -> Consolidate", "ice cake 1 lexer invariant",
+            "phase Consolidate -> Complete",
+        ];
+        assert_eq!(*counter.events.lock().unwrap(), expected);
+        
+        assert!(session.unsubscribe(subscription));
+        assert!(!session.unsubscribe(subscription));
+        session.meta_reflect("write invariants down");
+        assert_eq!(counter.events.lock().unwrap().len(), expected.len());
+    }
+    
+    #[test]
+    fn test_panicking_observer_is_dropped() {
+        let counter = Counter::default();
+        let mut session = LearningSession::new("parser");
+        let panicker = session.subscribe(Panicker);
+        session.subscribe(counter.clone());
+        
+        let first = session.encounter("first").id.clone();
+        session.struggle("second", 0.5, 0.5);
+        
+        assert_eq!(session.moments.len(), 2);
+        assert_eq!(session.get_moment(&first).map(|m| m.seq), Some(1));
+        assert_eq!(session.phase, SessionPhase::Struggle);
+        assert_eq!(counter.events.lock().unwrap().iter().filter(|e| e.starts_with("moment")).count(), 2);
+        assert!(!session.unsubscribe(panicker), "a panicking observer is unsubscribed");
+    }
+}
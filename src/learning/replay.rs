@@ -0,0 +1,175 @@
+//! SessionReplay — a recorded session re-emitted as timed events
+
+use crate::MetaAGI;
+use crate::learning::moment::Moment;
+use crate::learning::session::{LearningSession, SessionPhase, IceCakedDecision};
+
+#[derive(Clone, Debug)]
+pub enum ReplayKind<'a> {
+    Moment(&'a Moment),
+    PhaseEntered(&'a SessionPhase),
+    IceCaked(&'a IceCakedDecision),
+}
+
+#[derive(Clone, Debug)]
+pub struct ReplayEvent<'a> {
+    /// Time since the session's first moment
+    pub offset_ms: u64,
+    /// Time since the previous event; 0 for the first
+    pub since_previous_ms: u64,
+    pub kind: ReplayKind<'a>,
+}
+
+/// Iterator over a session's events in recording order, from
+/// `LearningSession::replay`
+pub struct SessionReplay<'a> {
+    events: std::vec::IntoIter<ReplayEvent<'a>>,
+}
+
+impl<'a> Iterator for SessionReplay<'a> {
+    type Item = ReplayEvent<'a>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.next()
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.events.size_hint()
+    }
+}
+
+impl ExactSizeIterator for SessionReplay<'_> {}
+
+impl LearningSession {
+    /// Moments by `seq`, each phase change just before the first moment
+    /// recorded in it, and each ice-caked decision just after the moment
+    /// that brought the session to its cycle. Phase changes are timed by the
+    /// clock; ice-caked decisions take the time of the event before them.
+    pub fn replay(&self) -> SessionReplay<'_> {
+        let moments = self.moments_ordered();
+        let mut phases: Vec<&(SessionPhase, u64, u64)> = self.phase_log().iter().collect();
+        phases.sort_by_key(|&&(_, _, first_seq)| first_seq);
+        let mut decisions: Vec<&IceCakedDecision> = self.ice_caked.iter().collect();
+        decisions.sort_by_key(|d| d.ice_caked_at_cycle);
+        
+        let mut timed: Vec<(u64, ReplayKind)> = Vec::new();
+        let (mut phases, mut decisions) = (phases.into_iter().peekable(), decisions.into_iter().peekable());
+        for (i, moment) in moments.iter().enumerate() {
+            while let Some((phase, at, _)) = phases.next_if(|&&(_, _, first_seq)| first_seq <= moment.seq) {
+                timed.push((*at, ReplayKind::PhaseEntered(phase)));
+            }
+            timed.push((moment.created_at, ReplayKind::Moment(moment)));
+            while let Some(decision) = decisions.next_if(|d| d.ice_caked_at_cycle <= i as u64 + 1) {
+                timed.push((moment.created_at, ReplayKind::IceCaked(decision)));
+            }
+        }
+        let end = moments.last().map_or(0, |m| m.created_at);
+        timed.extend(phases.map(|(phase, at, _)| (*at, ReplayKind::PhaseEntered(phase))));
+        timed.extend(decisions.map(|decision| (end, ReplayKind::IceCaked(decision))));
+        
+        let start = moments.first().map(|m| m.created_at)
+            .or_else(|| timed.first().map(|&(at, _)| at))
+            .unwrap_or(0);
+        let mut previous = 0;
+        let events: Vec<ReplayEvent> = timed.into_iter().enumerate()
+            .map(|(i, (at, kind))| {
+                let offset_ms = at.saturating_sub(start).max(previous);
+                let since_previous_ms = if i == 0 { 0 } else { offset_ms - previous };
+                previous = offset_ms;
+                ReplayEvent { offset_ms, since_previous_ms, kind }
+            })
+            .collect();
+        SessionReplay { events: events.into_iter() }
+    }
+    
+    /// Feed every moment, in replay order, through `agi.capture_moment_of`,
+    /// e.g. to rebuild a resonance store from a saved session
+    pub fn replay_into(&self, agi: &mut MetaAGI) {
+        for event in self.replay() {
+            if let ReplayKind::Moment(moment) = event.kind {
+                agi.capture_moment_of(moment, self);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::learning::MockClock;
+    
+    fn scripted() -> LearningSession {
+        let clock = MockClock::new(1_000);
+        let mut session = LearningSession::with_clock("parser", Arc::new(clock.clone()));
+        session.encounter("parser rejects UTF-8 input");
+        clock.advance(5_000);
+        let struggle = session.struggle("byte offsets and char offsets are mixed up in the lexer", 0.8, 0.7).id.clone();
+        clock.advance(10_000);
+        let fix = session.breakthrough_resolving(&struggle, "track byte offsets in the lexer, not char offsets", 0.9).id.clone();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        clock.advance(2_000);
+        session.breakthrough("cache the compiled grammar", 0.9);
+        session
+    }
+    
+    #[test]
+    fn test_replay_order_and_timing() {
+        let session = scripted();
+        let events: Vec<ReplayEvent> = session.replay().collect();
+        let summary: Vec<(u64, u64, String)> = events.iter()
+            .map(|e| {
+                let what = match &e.kind {
+                    ReplayKind::Moment(m) => format!("moment {}", m.seq),
+                    ReplayKind::PhaseEntered(phase) => format!("phase {:?}", phase),
+                    ReplayKind::IceCaked(d) => format!("ice cake {}", d.rationale),
+                };
+                (e.offset_ms, e.since_previous_ms, what)
+            })
+            .collect();
+        let expected = [
+            (0, 0, "phase Encounter"),
+            (0, 0, "moment 1"),
+            (5_000, 5_000, "phase Struggle"),
+            (5_000, 0, "moment 2"),
+            (15_000, 10_000, "phase Breakthrough"),
+            (15_000, 0, "moment 3"),
+            (15_000, 0, "ice cake lexer invariant"),
+            (15_000, 0, "phase Consolidate"),
+            (17_000, 2_000, "phase Breakthrough"),
+            (17_000, 0, "moment 4"),
+        ];
+        assert_eq!(summary, expected.map(|(offset, since, what)| (offset, since, what.to_string())));
+        assert_eq!(session.replay().len(), expected.len());
+        assert_eq!(LearningSession::new("empty").replay().count(), 0);
+    }
+    
+    #[test]
+    fn test_replay_saved_session_reproduces_captures() {
+        let mut original = MetaAGI::new();
+        original.start_session("parser", "UTF-8 handling");
+        *original.session_mut().unwrap() = scripted();
+        let moments = original.session().unwrap().moments.clone();
+        for moment in &moments {
+            original.capture_moment(moment);
+        }
+        
+        let path = std::env::temp_dir().join(format!("ladybug-replay-{}.json", uuid::Uuid::new_v4()));
+        original.session().unwrap().save_json(&path).unwrap();
+        let saved = LearningSession::load_json(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        let mut replayed = MetaAGI::new();
+        saved.replay_into(&mut replayed);
+        
+        assert_eq!(replayed.resonance.stats().total_captures, original.resonance.stats().total_captures);
+        let names = |agi: &MetaAGI| {
+            let mut names: Vec<String> = agi.concepts.all().map(|c| c.name.clone()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&replayed).len(), 2);
+        assert_eq!(names(&replayed), names(&original));
+        assert_eq!(replayed.stats().global_cycle, original.stats().global_cycle);
+    }
+}
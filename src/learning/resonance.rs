@@ -1,25 +1,75 @@
 //! ResonanceCapture — "Felt this before" via Hamming similarity
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use serde::{Serialize, Deserialize};
 use crate::core::Fingerprint;
-use crate::learning::moment::{Moment, Qualia};
+use crate::learning::archive::{ArchivedMoment, DEFAULT_ARCHIVE_SUMMARY_CHARS};
+use crate::learning::moment::{Moment, MomentType, Qualia, normalize_tag, summarize_content};
+use crate::nars::{TruthValue, RANDOM_SIMILARITY};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SimilarMoment {
     pub moment_id: String,
+    #[serde(default)]
+    pub session_id: String,
+    /// The moment's `seq` within its session
+    #[serde(default)]
+    pub seq: u64,
+    pub moment_type: MomentType,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The moment's content, summarized like an archived moment's
+    #[serde(default)]
+    pub summary: String,
+    /// Set by `ResonanceCapture::attribute_task`
+    #[serde(default)]
+    pub task_id: Option<String>,
+    /// `ThinkingStyle::dominant` of the moment's style; None if it had none
+    /// or was captured from its archived form
+    #[serde(default)]
+    pub dominant_style: Option<String>,
     pub resonance: f32,
     pub content_similarity: f32,
     pub qualia_distance: f32,
     pub cycle_delta: u64,
 }
 
+impl SimilarMoment {
+    /// How strongly the resonance supports "this is like that"
+    pub fn truth(&self) -> TruthValue {
+        TruthValue::from_similarity(self.resonance, RANDOM_SIMILARITY)
+    }
+}
+
 #[derive(Clone)]
 struct StoredResonance {
     content_fp: Fingerprint,
     resonance_fp: Fingerprint,
+    qualia_fp: Fingerprint,
+    feeling_fp: Fingerprint,
     qualia: Qualia,
+    /// `qualia` normalized against the capturer's calibration, if any
+    calibrated_qualia: Option<Qualia>,
+    moment_type: MomentType,
+    metadata: BTreeMap<String, String>,
+    tags: Vec<String>,
+    seq: u64,
     cycle: u64,
     session_id: String,
+    summary: String,
+    task_id: Option<String>,
+    dominant_style: Option<&'static str>,
+}
+
+/// What a search matches: `query` against the `indexed` fingerprint of each
+/// stored moment, with `qualia_distance` measured from `reference`
+struct Search<'a> {
+    query: &'a Fingerprint,
+    indexed: fn(&StoredResonance) -> &Fingerprint,
+    reference: &'a Qualia,
+    tag: Option<&'a str>,
 }
 
 pub struct ResonanceCapture {
@@ -42,33 +92,162 @@ impl ResonanceCapture {
     }
     
     pub fn capture(&mut self, moment: &Moment, cycle: u64) {
+        self.store(moment, None, cycle);
+    }
+    
+    /// Capture alongside the moment's qualia normalized by a `QualiaCalibration`
+    pub fn capture_calibrated(&mut self, moment: &Moment, calibrated: Qualia, cycle: u64) {
+        self.store(moment, Some(calibrated), cycle);
+    }
+    
+    /// Capture a moment from its archived form, e.g. when loading an old
+    /// session; it is found exactly as the full moment would be
+    pub fn capture_archived(&mut self, archived: &ArchivedMoment, cycle: u64) {
+        let content_fp = archived.content_fingerprint();
+        let qualia_fp = archived.qualia.to_fingerprint();
+        let resonance_fp = archived.qualia.weight_fingerprint(&content_fp);
         let stored = StoredResonance {
-            content_fp: moment.fingerprint.clone(),
-            resonance_fp: moment.resonance_vector.clone(),
-            qualia: moment.qualia.clone(),
+            feeling_fp: content_fp.bind(&qualia_fp),
+            content_fp,
+            resonance_fp: resonance_fp.clone(),
+            qualia_fp,
+            qualia: archived.qualia.clone(),
+            calibrated_qualia: None,
+            moment_type: archived.moment_type.clone(),
+            metadata: archived.metadata.clone(),
+            tags: archived.tags.clone(),
+            seq: archived.seq,
             cycle,
-            session_id: moment.session_id.clone(),
+            session_id: archived.session_id.clone(),
+            summary: archived.summary.clone(),
+            task_id: None,
+            dominant_style: None,
+        };
+        self.fingerprints.insert(archived.id.clone(), stored);
+        self.batch_vectors.push((archived.id.clone(), resonance_fp));
+        self.total_captures += 1;
+    }
+    
+    /// Captured moments recorded in the session `session_id`
+    pub fn captures_for(&self, session_id: &str) -> usize {
+        self.fingerprints.values().filter(|s| s.session_id == session_id).count()
+    }
+    
+    /// Raw and calibrated qualia of a captured moment
+    pub fn qualia_of(&self, moment_id: &str) -> Option<(&Qualia, Option<&Qualia>)> {
+        let stored = self.fingerprints.get(moment_id)?;
+        Some((&stored.qualia, stored.calibrated_qualia.as_ref()))
+    }
+    
+    /// Refresh the stored fingerprints of an already captured moment, e.g.
+    /// after `Moment::edit_content`. The capture cycle, calibrated qualia and
+    /// task are kept. Returns false if the moment was never captured.
+    pub fn recapture(&mut self, moment: &Moment) -> bool {
+        let Some(previous) = self.fingerprints.get(&moment.id) else { return false };
+        let stored = StoredResonance {
+            task_id: previous.task_id.clone(),
+            ..Self::stored(moment, previous.calibrated_qualia.clone(), previous.cycle)
         };
-        
         self.fingerprints.insert(moment.id.clone(), stored);
+        for (id, vector) in &mut self.batch_vectors {
+            if *id == moment.id {
+                *vector = moment.resonance_vector.clone();
+            }
+        }
+        true
+    }
+    
+    fn store(&mut self, moment: &Moment, calibrated_qualia: Option<Qualia>, cycle: u64) {
+        self.fingerprints.insert(moment.id.clone(), Self::stored(moment, calibrated_qualia, cycle));
         self.batch_vectors.push((moment.id.clone(), moment.resonance_vector.clone()));
         self.total_captures += 1;
     }
     
-    pub fn find_resonant(&mut self, query: &Fingerprint, threshold: f32, limit: usize, current_cycle: u64) -> Vec<SimilarMoment> {
+    /// Record the task a captured moment was worked on under, for
+    /// `SimilarMoment::task_id`. Returns false if the moment was never captured.
+    pub fn attribute_task(&mut self, moment_id: &str, task_id: &str) -> bool {
+        let Some(stored) = self.fingerprints.get_mut(moment_id) else { return false };
+        stored.task_id = Some(task_id.to_string());
+        true
+    }
+    
+    fn stored(moment: &Moment, calibrated_qualia: Option<Qualia>, cycle: u64) -> StoredResonance {
+        StoredResonance {
+            content_fp: moment.fingerprint.clone(),
+            resonance_fp: moment.resonance_vector.clone(),
+            qualia_fp: moment.qualia.to_fingerprint(),
+            feeling_fp: moment.feeling_fingerprint(),
+            qualia: moment.qualia.clone(),
+            calibrated_qualia,
+            moment_type: moment.moment_type.clone(),
+            metadata: moment.metadata.clone(),
+            tags: moment.tags.clone(),
+            seq: moment.seq,
+            cycle,
+            session_id: moment.session_id.clone(),
+            summary: summarize_content(&moment.content, DEFAULT_ARCHIVE_SUMMARY_CHARS).unwrap_or_else(|| moment.content.clone()),
+            task_id: None,
+            dominant_style: (!moment.thinking_style.is_unset()).then(|| moment.thinking_style.dominant()),
+        }
+    }
+    
+    /// Moments resonating with `query`; with `tag` set, only moments carrying it
+    pub fn find_resonant(
+        &mut self,
+        query: &Fingerprint,
+        threshold: f32,
+        limit: usize,
+        current_cycle: u64,
+        tag: Option<&str>,
+    ) -> Vec<SimilarMoment> {
+        let tag = tag.map(normalize_tag);
+        let reference = Qualia::default();
+        let search = Search { query, indexed: |s| &s.resonance_fp, reference: &reference, tag: tag.as_deref() };
+        self.search(search, threshold, limit, current_cycle)
+    }
+    
+    /// Moments that felt like `qualia`, regardless of content. `resonance`
+    /// is the qualia fingerprint similarity and `qualia_distance` is measured
+    /// from `qualia`.
+    pub fn find_felt_like(&mut self, qualia: &Qualia, threshold: f32, limit: usize, current_cycle: u64) -> Vec<SimilarMoment> {
+        let query = qualia.to_fingerprint();
+        let search = Search { query: &query, indexed: |s| &s.qualia_fp, reference: qualia, tag: None };
+        self.search(search, threshold, limit, current_cycle)
+    }
+    
+    /// Match against `Moment::feeling_fingerprint`: similar content that felt similar
+    pub fn find_by_feeling(&mut self, query: &Fingerprint, threshold: f32, limit: usize, current_cycle: u64) -> Vec<SimilarMoment> {
+        let reference = Qualia::default();
+        let search = Search { query, indexed: |s| &s.feeling_fp, reference: &reference, tag: None };
+        self.search(search, threshold, limit, current_cycle)
+    }
+    
+    fn search(&mut self, search: Search, threshold: f32, limit: usize, current_cycle: u64) -> Vec<SimilarMoment> {
         self.total_queries += 1;
+        let Search { query, indexed, reference, tag } = search;
         
         let mut results: Vec<SimilarMoment> = self.batch_vectors.iter()
-            .filter_map(|(id, fp)| {
-                let resonance = query.similarity(fp);
+            .filter_map(|(id, _)| {
+                let stored = self.fingerprints.get(id)?;
+                if tag.is_some_and(|tag| !stored.tags.iter().any(|t| t == tag)) {
+                    return None;
+                }
+                let resonance = query.similarity(indexed(stored));
                 if resonance >= threshold {
-                    let stored = self.fingerprints.get(id)?;
                     let content_similarity = query.similarity(&stored.content_fp);
-                    let qualia_distance = Self::qualia_distance(&stored.qualia, &Qualia::default());
+                    let qualia_distance = Self::qualia_distance(&stored.qualia, reference);
                     let cycle_delta = current_cycle.saturating_sub(stored.cycle);
                     
                     Some(SimilarMoment {
                         moment_id: id.clone(),
+                        session_id: stored.session_id.clone(),
+                        seq: stored.seq,
+                        moment_type: stored.moment_type.clone(),
+                        metadata: stored.metadata.clone(),
+                        tags: stored.tags.clone(),
+                        summary: stored.summary.clone(),
+                        task_id: stored.task_id.clone(),
+                        dominant_style: stored.dominant_style.map(str::to_string),
                         resonance,
                         content_similarity,
                         qualia_distance,
@@ -91,7 +270,11 @@ impl ResonanceCapture {
         let ds = (a.satisfaction - b.satisfaction).powi(2);
         let dc = (a.confusion - b.confusion).powi(2);
         let dsu = (a.surprise - b.surprise).powi(2);
-        ((dn + de + ds + dc + dsu) / 5.0).sqrt()
+        // valence spans [-1, 1]; halve it so every axis contributes on [0, 1]
+        let dv = ((a.valence - b.valence) / 2.0).powi(2);
+        let da = (a.arousal - b.arousal).powi(2);
+        let dco = (a.confidence - b.confidence).powi(2);
+        ((dn + de + ds + dc + dsu + dv + da + dco) / 8.0).sqrt()
     }
     
     pub fn stats(&self) -> ResonanceStats {
@@ -129,7 +312,7 @@ pub fn mexican_hat_resonance(distances: &[f32], center: f32, width: f32) -> Vec<
 }
 
 pub fn find_sweet_spot(store: &mut ResonanceCapture, query: &Fingerprint, current_cycle: u64) -> Option<SimilarMoment> {
-    let candidates = store.find_resonant(query, 0.6, 20, current_cycle);
+    let candidates = store.find_resonant(query, 0.6, 20, current_cycle, None);
     
     let scored: Vec<(SimilarMoment, f32)> = candidates.into_iter()
         .map(|m| {
@@ -142,3 +325,145 @@ pub fn find_sweet_spot(store: &mut ResonanceCapture, query: &Fingerprint, curren
         .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
         .map(|(m, _)| m)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_find_felt_like_ignores_content() {
+        let frustrated = Qualia::from_metrics(0.2, 0.9, 0.1).with_affect(-0.8, 0.9, 0.2);
+        let elated = Qualia::from_metrics(0.9, 0.4, 0.95).with_affect(0.9, 0.8, 0.9);
+        let mut capture = ResonanceCapture::new();
+        let a = Moment::new("s", "linker errors", MomentType::Struggle).with_qualia(frustrated.clone());
+        let b = Moment::new("s", "flaky CI", MomentType::Struggle).with_qualia(frustrated.clone());
+        let c = Moment::new("s", "cache works", MomentType::Breakthrough).with_qualia(elated);
+        for (i, m) in [&a, &b, &c].into_iter().enumerate() {
+            capture.capture(m, i as u64);
+        }
+        
+        let found = capture.find_felt_like(&frustrated, 0.9, 5, 3);
+        let ids: Vec<&str> = found.iter().map(|f| f.moment_id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&a.id.as_str()) && ids.contains(&b.id.as_str()));
+        assert_eq!(found[0].qualia_distance, 0.0);
+        
+        let by_feeling = capture.find_by_feeling(&a.feeling_fingerprint(), 0.9, 5, 3);
+        assert_eq!(by_feeling.len(), 1);
+        assert_eq!(by_feeling[0].moment_id, a.id);
+    }
+    
+    #[test]
+    fn test_similar_moment_serde_round_trip() {
+        let similar = SimilarMoment {
+            moment_id: "m1".to_string(),
+            session_id: "s1".to_string(),
+            seq: 7,
+            moment_type: MomentType::Custom("Experiment".to_string()),
+            metadata: BTreeMap::from([("ticket".to_string(), "LRN-7".to_string())]),
+            tags: vec!["auth".to_string()],
+            summary: "token refresh races the logout".to_string(),
+            task_id: Some("auth".to_string()),
+            dominant_style: Some("focused".to_string()),
+            resonance: 0.82,
+            content_similarity: 0.5,
+            qualia_distance: 0.0,
+            cycle_delta: u64::MAX,
+        };
+        let json = serde_json::to_string(&similar).unwrap();
+        assert_eq!(serde_json::from_str::<SimilarMoment>(&json).unwrap(), similar);
+        let yaml = serde_yaml::to_string(&similar).unwrap();
+        assert_eq!(serde_yaml::from_str::<SimilarMoment>(&yaml).unwrap(), similar);
+    }
+    
+    #[test]
+    fn test_type_and_metadata_survive_capture() {
+        let moment = crate::learning::MomentBuilder::new("s", "handed off the parser work")
+            .custom("Handoff")
+            .meta("commit", "9e1d0b2").unwrap()
+            .build();
+        let mut capture = ResonanceCapture::new();
+        capture.capture(&moment, 1);
+        let found = capture.find_resonant(&moment.resonance_vector, 0.9, 5, 1, None);
+        assert_eq!(found[0].moment_type, MomentType::Custom("Handoff".to_string()));
+        assert_eq!(found[0].metadata.get("commit").map(String::as_str), Some("9e1d0b2"));
+    }
+    
+    #[test]
+    fn test_find_resonant_filters_by_tag() {
+        let auth = crate::learning::MomentBuilder::new("s", "session cookie dropped").tag("Auth").build();
+        let other = crate::learning::MomentBuilder::new("s", "session cookie dropped").tag("perf").build();
+        let mut capture = ResonanceCapture::new();
+        capture.capture(&auth, 1);
+        capture.capture(&other, 1);
+        
+        assert_eq!(capture.find_resonant(&auth.resonance_vector, 0.9, 5, 1, None).len(), 2);
+        let tagged = capture.find_resonant(&auth.resonance_vector, 0.9, 5, 1, Some("#AUTH"));
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].moment_id, auth.id);
+        assert_eq!(tagged[0].tags, vec!["auth".to_string()]);
+        assert!(capture.find_resonant(&auth.resonance_vector, 0.9, 5, 1, Some("db")).is_empty());
+    }
+    
+    #[test]
+    fn test_recapture_follows_edited_content() {
+        let mut moment = crate::learning::MomentBuilder::new("s", "teh cache is stale").build();
+        let mut capture = ResonanceCapture::new();
+        capture.capture(&moment, 1);
+        
+        moment.edit_content("the cache is stale");
+        assert!(capture.find_resonant(&moment.resonance_vector, 0.9, 5, 2, None).is_empty());
+        
+        assert!(capture.recapture(&moment));
+        let found = capture.find_resonant(&moment.resonance_vector, 0.9, 5, 2, None);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].resonance, 1.0);
+        assert_eq!(found[0].cycle_delta, 1);
+        assert_eq!(capture.find_by_feeling(&moment.feeling_fingerprint(), 0.9, 5, 2).len(), 1);
+        
+        let unknown = crate::learning::MomentBuilder::new("s", "never captured").build();
+        assert!(!capture.recapture(&unknown));
+    }
+    
+    #[test]
+    fn test_similar_moment_carries_seq_and_session() {
+        let mut session = crate::learning::LearningSession::new("task");
+        session.encounter("first");
+        let moment = session.encounter("second").clone();
+        let mut capture = ResonanceCapture::new();
+        capture.capture(&moment, 1);
+        let found = capture.find_resonant(&moment.resonance_vector, 0.9, 5, 1, None);
+        assert_eq!((found[0].session_id.as_str(), found[0].seq), (session.id.as_str(), 2));
+    }
+    
+    #[test]
+    fn test_archived_moments_resonate_like_full_ones() {
+        let mut session = crate::learning::LearningSession::new("task");
+        session.struggle("lexer mixes byte and char offsets", 0.8, 0.7);
+        session.breakthrough("track byte offsets only", 0.9);
+        session.tag(&session.moments[1].id.clone(), "lexer");
+        let archived: Vec<ArchivedMoment> = session.moments.iter()
+            .map(|m| serde_json::from_str(&serde_json::to_string(&m.to_archive()).unwrap()).unwrap())
+            .collect();
+        
+        let mut full = ResonanceCapture::new();
+        let mut cheap = ResonanceCapture::new();
+        for (moment, archived) in session.moments.iter().zip(&archived) {
+            full.capture(moment, 1);
+            cheap.capture_archived(archived, 1);
+        }
+        
+        let query = &session.moments[1];
+        assert_eq!(
+            full.find_resonant(&query.resonance_vector, 0.0, 5, 3, None),
+            cheap.find_resonant(&query.resonance_vector, 0.0, 5, 3, None),
+        );
+        assert_eq!(
+            full.find_resonant(&query.resonance_vector, 0.0, 5, 3, Some("lexer")),
+            cheap.find_resonant(&query.resonance_vector, 0.0, 5, 3, Some("lexer")),
+        );
+        assert_eq!(full.find_felt_like(&query.qualia, 0.0, 5, 3), cheap.find_felt_like(&query.qualia, 0.0, 5, 3));
+        let feeling = query.feeling_fingerprint();
+        assert_eq!(full.find_by_feeling(&feeling, 0.0, 5, 3), cheap.find_by_feeling(&feeling, 0.0, 5, 3));
+    }
+}
@@ -0,0 +1,206 @@
+//! Retrospective — a closed session measured against similar past ones
+
+use crate::core::Fingerprint;
+use crate::learning::session::SessionReport;
+
+/// `Fingerprint::from_words` similarity of task descriptions at which a past
+/// session counts as a similar task
+pub const DEFAULT_RETROSPECTIVE_SIMILARITY: f32 = 0.6;
+
+/// Relative changes smaller than this read as "about the same"
+const SAME_WITHIN: f32 = 0.05;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PastMatch {
+    pub session_id: String,
+    pub task_id: String,
+    pub similarity: f32,
+}
+
+/// How a session compares with the mean of similar past sessions. Relative
+/// deltas are fractions of the past mean: -0.4 is 40% less. Each is None
+/// when there is nothing to compare, e.g. no breakthrough on one side.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Retrospective {
+    /// Similar past sessions, most similar first
+    pub matches: Vec<PastMatch>,
+    /// Change in active time to the first breakthrough
+    pub time_to_breakthrough_delta: Option<f32>,
+    /// Struggles this session minus the past mean
+    pub struggle_delta: Option<f32>,
+    /// Change in area under the effort curve
+    pub effort_auc_delta: Option<f32>,
+    pub summary: String,
+}
+
+fn description_of(report: &SessionReport) -> &str {
+    if report.description.is_empty() { &report.task_id } else { &report.description }
+}
+
+fn struggles(report: &SessionReport) -> f32 {
+    report.moment_types.get("Struggle").copied().unwrap_or(0) as f32
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
+
+/// `(current - past) / past`; None without a positive past value
+fn relative(current: Option<f32>, past: Option<f32>) -> Option<f32> {
+    match (current, past) {
+        (Some(current), Some(past)) if past > 0.0 => Some((current - past) / past),
+        _ => None,
+    }
+}
+
+fn percent(delta: f32) -> String {
+    format!("{:.0}%", delta.abs() * 100.0)
+}
+
+/// Compare `current` with the reports in `past` whose task description (the
+/// task id when there is none) reaches `DEFAULT_RETROSPECTIVE_SIMILARITY`.
+/// The current session itself is skipped if it is in `past`.
+pub fn retrospective(current: &SessionReport, past: &[SessionReport]) -> Retrospective {
+    let words = Fingerprint::from_words(description_of(current));
+    let mut matched: Vec<(&SessionReport, f32)> = past.iter()
+        .filter(|report| report.session_id != current.session_id)
+        .map(|report| (report, words.similarity(&Fingerprint::from_words(description_of(report)))))
+        .filter(|&(_, similarity)| similarity >= DEFAULT_RETROSPECTIVE_SIMILARITY)
+        .collect();
+    matched.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let matches: Vec<PastMatch> = matched.iter()
+        .map(|(report, similarity)| PastMatch {
+            session_id: report.session_id.clone(),
+            task_id: report.task_id.clone(),
+            similarity: *similarity,
+        })
+        .collect();
+    if matched.is_empty() {
+        return Retrospective {
+            matches,
+            time_to_breakthrough_delta: None,
+            struggle_delta: None,
+            effort_auc_delta: None,
+            summary: "No similar past sessions to compare with.".to_string(),
+        };
+    }
+    
+    let time = |report: &SessionReport| report.metrics.time_to_first_breakthrough_ms.map(|ms| ms as f32);
+    let past_times: Vec<f32> = matched.iter().filter_map(|(report, _)| time(report)).collect();
+    let past_struggles: Vec<f32> = matched.iter().map(|(report, _)| struggles(report)).collect();
+    let past_effort: Vec<f32> = matched.iter().map(|(report, _)| report.trajectory.effort_auc).collect();
+    let time_to_breakthrough_delta = relative(time(current), mean(&past_times));
+    let struggle_delta = mean(&past_struggles).map(|past| struggles(current) - past);
+    let effort_auc_delta = relative(Some(current.trajectory.effort_auc), mean(&past_effort));
+    
+    let mut findings: Vec<String> = Vec::new();
+    match time_to_breakthrough_delta {
+        Some(delta) if delta.abs() < SAME_WITHIN => findings.push("reached a breakthrough in about the same time".to_string()),
+        Some(delta) if delta < 0.0 => findings.push(format!("reached a breakthrough {} faster", percent(delta))),
+        Some(delta) => findings.push(format!("reached a breakthrough {} slower", percent(delta))),
+        None if time(current).is_none() && !past_times.is_empty() => findings.push("did not reach a breakthrough".to_string()),
+        None => {}
+    }
+    match struggle_delta {
+        Some(delta) if delta.abs() < 0.5 => findings.push("struggled about as often".to_string()),
+        Some(delta) => {
+            let count = if delta.fract() == 0.0 { format!("{:.0}", delta.abs()) } else { format!("{:.1}", delta.abs()) };
+            let noun = if delta.abs() == 1.0 { "struggle" } else { "struggles" };
+            findings.push(format!("had {} {} {}", count, if delta < 0.0 { "fewer" } else { "more" }, noun));
+        }
+        None => {}
+    }
+    match effort_auc_delta {
+        Some(delta) if delta.abs() < SAME_WITHIN => findings.push("spent about the same effort".to_string()),
+        Some(delta) => findings.push(format!("spent {} {} effort", percent(delta), if delta < 0.0 { "less" } else { "more" })),
+        None => {}
+    }
+    
+    let sessions = if matches.len() == 1 { "session" } else { "sessions" };
+    let summary = format!("Compared with {} similar past {} (closest: {}), you {}.",
+        matches.len(), sessions, matches[0].task_id, join_findings(&findings));
+    Retrospective { matches, time_to_breakthrough_delta, struggle_delta, effort_auc_delta, summary }
+}
+
+/// "a", "a and b", "a, b and c"
+fn join_findings(findings: &[String]) -> String {
+    match findings {
+        [] => "left nothing to compare".to_string(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::{SessionMetrics, SessionOutcome, QualiaTrajectory};
+    use std::collections::BTreeMap;
+    
+    fn report(task_id: &str, description: &str, time_ms: Option<u64>, struggles: usize, effort_auc: f32) -> SessionReport {
+        SessionReport {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            description: description.to_string(),
+            outcome: SessionOutcome::Completed,
+            closed_at: 0,
+            moment_count: struggles + 1,
+            breakthrough_count: usize::from(time_ms.is_some()),
+            moment_types: BTreeMap::from([("Struggle".to_string(), struggles)]),
+            duration_ms: 0,
+            wall_clock_ms: 0,
+            trajectory: QualiaTrajectory { effort_auc, ..QualiaTrajectory::default() },
+            ice_caked: Vec::new(),
+            metrics: SessionMetrics { time_to_first_breakthrough_ms: time_ms, ..SessionMetrics::default() },
+            tags: BTreeMap::new(),
+            reuse: None,
+        }
+    }
+    
+    #[test]
+    fn test_no_similar_past_session() {
+        let current = report("sprints", "project scoped sprint versioning", Some(1_000), 1, 1.0);
+        let past = [report("ci", "speed up the linker on ci", Some(5_000), 4, 3.0), current.clone()];
+        let retro = retrospective(&current, &past);
+        assert!(retro.matches.is_empty());
+        assert_eq!(retro.time_to_breakthrough_delta, None);
+        assert_eq!(retro.summary, "No similar past sessions to compare with.");
+    }
+    
+    #[test]
+    fn test_single_match_improvement() {
+        let current = report("sprints", "project scoped sprint versioning", Some(6_000), 1, 1.5);
+        let past = [
+            report("versions", "project scoped milestone versioning", Some(10_000), 3, 2.0),
+            report("ci", "speed up the linker on ci", Some(1_000), 0, 0.5),
+        ];
+        let retro = retrospective(&current, &past);
+        assert_eq!(retro.matches.len(), 1);
+        assert_eq!(retro.matches[0].task_id, "versions");
+        assert!((retro.time_to_breakthrough_delta.unwrap() + 0.4).abs() < 1e-6);
+        assert_eq!(retro.struggle_delta, Some(-2.0));
+        assert!((retro.effort_auc_delta.unwrap() + 0.25).abs() < 1e-6);
+        assert_eq!(retro.summary, concat!(
+            "Compared with 1 similar past session (closest: versions), you reached a breakthrough ",
+            "40% faster, had 2 fewer struggles and spent 25% less effort.",
+        ));
+    }
+    
+    #[test]
+    fn test_regression_phrasing() {
+        let past = [
+            report("versions", "project scoped milestone versioning", Some(10_000), 1, 2.0),
+            report("releases", "project scoped release versioning", Some(20_000), 2, 2.0),
+        ];
+        let slower = report("sprints", "project scoped sprint versioning", Some(30_000), 3, 2.02);
+        let retro = retrospective(&slower, &past);
+        assert_eq!(retro.matches.len(), 2);
+        assert!(retro.summary.starts_with("Compared with 2 similar past sessions"));
+        assert!(retro.summary.ends_with("you reached a breakthrough 100% slower, had 1.5 more struggles and spent about the same effort."));
+        
+        let stuck = report("sprints", "project scoped sprint versioning", None, 2, 4.0);
+        let retro = retrospective(&stuck, &past);
+        assert_eq!(retro.time_to_breakthrough_delta, None);
+        assert!(retro.summary.ends_with("you did not reach a breakthrough, had 0.5 more struggles and spent 100% more effort."));
+    }
+}
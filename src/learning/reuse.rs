@@ -0,0 +1,123 @@
+//! Reuse report — how much a closed session drew on earlier sessions' concepts
+
+use serde::{Serialize, Deserialize};
+use crate::learning::concept::ConceptExtractor;
+use crate::learning::session::LearningSession;
+
+/// Moments before a breakthrough within which a resonance hit counts as
+/// having assisted it
+pub const DEFAULT_REUSE_WINDOW: u64 = 3;
+
+/// A past moment surfaced to a session, e.g. by `MetaAGI::suggest`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResonanceHit {
+    pub moment_id: String,
+    /// Session the surfaced moment was recorded in
+    pub session_id: String,
+    pub resonance: f32,
+    /// `seq` of the session's latest moment when the hit was surfaced; 0
+    /// before any moment
+    pub after_seq: u64,
+}
+
+/// A concept whose source moment, or the struggle it resolved, was surfaced
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReusedConcept {
+    pub concept_id: String,
+    pub name: String,
+    /// The surfaced moment
+    pub moment_id: String,
+}
+
+/// From `ReuseReport::build`, kept in `SessionReport::reuse`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReuseReport {
+    /// In the order first surfaced
+    pub concepts: Vec<ReusedConcept>,
+    /// Resonance hits surfaced to the session
+    pub hits: usize,
+    /// Breakthroughs with a hit surfaced within the window before them
+    pub assisted_breakthroughs: usize,
+    pub unassisted_breakthroughs: usize,
+    /// Mean effort of assisted breakthroughs; None without one
+    pub assisted_effort: Option<f32>,
+    pub unassisted_effort: Option<f32>,
+    /// Assisted minus unassisted mean effort; None unless both exist
+    pub effort_delta: Option<f32>,
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
+
+impl ReuseReport {
+    /// Attribute `hits` to `session`'s breakthroughs: one is assisted when a
+    /// hit was surfaced after its `window` preceding moments began, i.e. at
+    /// `after_seq` from `seq - window` up to just before it. Hits on the
+    /// session's own moments are ignored.
+    pub fn build(session: &LearningSession, hits: &[ResonanceHit], concepts: &ConceptExtractor, window: u64) -> Self {
+        let hits: Vec<&ResonanceHit> = hits.iter().filter(|hit| hit.session_id != session.id).collect();
+        let mut reused: Vec<ReusedConcept> = Vec::new();
+        for hit in &hits {
+            let mut found: Vec<ReusedConcept> = concepts.all()
+                .filter(|c| c.source_moment_id == hit.moment_id || c.problem_moment_id.as_deref() == Some(hit.moment_id.as_str()))
+                .filter(|c| !reused.iter().any(|r| r.concept_id == c.id))
+                .map(|c| ReusedConcept { concept_id: c.id.clone(), name: c.name.clone(), moment_id: hit.moment_id.clone() })
+                .collect();
+            found.sort_by(|a, b| a.name.cmp(&b.name));
+            reused.extend(found);
+        }
+        
+        let (mut assisted, mut unassisted) = (Vec::new(), Vec::new());
+        for moment in session.moments_ordered().into_iter().filter(|m| m.is_breakthrough()) {
+            let from = moment.seq.saturating_sub(window);
+            if hits.iter().any(|hit| hit.after_seq >= from && hit.after_seq < moment.seq) {
+                assisted.push(moment.qualia.effort);
+            } else {
+                unassisted.push(moment.qualia.effort);
+            }
+        }
+        let assisted_effort = mean(&assisted);
+        let unassisted_effort = mean(&unassisted);
+        Self {
+            concepts: reused,
+            hits: hits.len(),
+            assisted_breakthroughs: assisted.len(),
+            unassisted_breakthroughs: unassisted.len(),
+            assisted_effort,
+            unassisted_effort,
+            effort_delta: assisted_effort.zip(unassisted_effort).map(|(a, u)| a - u),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn hit(session_id: &str, after_seq: u64) -> ResonanceHit {
+        ResonanceHit { moment_id: "past".to_string(), session_id: session_id.to_string(), resonance: 0.8, after_seq }
+    }
+    
+    #[test]
+    fn test_window_and_own_hits() {
+        let mut session = LearningSession::new("task");
+        session.struggle("offsets mixed up", 0.6, 0.5);
+        for step in ["a", "b", "c"] {
+            session.encounter(step);
+        }
+        session.breakthrough("track byte offsets", 0.9);
+        let concepts = ConceptExtractor::new();
+        
+        let in_window = ReuseReport::build(&session, &[hit("earlier", 2)], &concepts, DEFAULT_REUSE_WINDOW);
+        assert_eq!((in_window.hits, in_window.assisted_breakthroughs, in_window.unassisted_breakthroughs), (1, 1, 0));
+        assert_eq!(in_window.effort_delta, None);
+        
+        let too_early = ReuseReport::build(&session, &[hit("earlier", 1)], &concepts, DEFAULT_REUSE_WINDOW);
+        assert_eq!(too_early.assisted_breakthroughs, 0);
+        
+        let own = ReuseReport::build(&session, &[hit(&session.id, 4)], &concepts, DEFAULT_REUSE_WINDOW);
+        assert_eq!((own.hits, own.assisted_breakthroughs), (0, 0));
+        assert!(own.concepts.is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+//! Risks — what could go wrong for whoever picks the task up, apart from
+//! the questions still open
+
+use std::cmp::Reverse;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+use crate::learning::blackboard::{Blackboard, LogCategory};
+use crate::learning::agent::AgentId;
+use crate::learning::moment::MomentType;
+use crate::learning::session::LearningSession;
+
+/// Frustration (`Qualia::confusion`) of a failure at which
+/// `Blackboard::promote_failure_risks` raises a risk
+pub const DEFAULT_RISK_FRUSTRATION: f32 = 0.7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RiskSeverity {
+    Low,
+    Medium,
+    High,
+    /// Linted when open without a mitigation
+    Critical,
+}
+
+impl fmt::Display for RiskSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+/// Handle returned by `Blackboard::add_risk`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RiskId(pub String);
+
+impl RiskId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RiskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Risk {
+    pub id: RiskId,
+    pub text: String,
+    pub severity: RiskSeverity,
+    #[serde(default)]
+    pub mitigation: Option<String>,
+    /// Promoted from a failure by `Blackboard::promote_failure_risks`
+    #[serde(default)]
+    pub auto_generated: bool,
+    /// The failure a promoted risk came from
+    #[serde(default)]
+    pub moment_id: Option<String>,
+    #[serde(default)]
+    pub closed: Option<RiskClosure>,
+    /// Agent that added or promoted it
+    #[serde(default, skip_serializing_if = "AgentId::is_unattributed")]
+    pub agent: AgentId,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RiskClosure {
+    /// Why the risk no longer applies
+    pub note: String,
+    pub cycle: u64,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RiskError {
+    #[error("no risk {0} on the blackboard")]
+    UnknownRisk(RiskId),
+    #[error("risk {0} is already closed")]
+    AlreadyClosed(RiskId),
+}
+
+pub(crate) fn default_risk_frustration() -> f32 {
+    DEFAULT_RISK_FRUSTRATION
+}
+
+impl Blackboard {
+    /// Add a risk for the handover, after those at least as severe so
+    /// `risks` stays most severe first
+    pub fn add_risk(&mut self, text: &str, severity: RiskSeverity, mitigation: Option<String>) -> RiskId {
+        let risk = Risk {
+            id: RiskId(uuid::Uuid::new_v4().to_string()),
+            text: text.to_string(),
+            severity,
+            mitigation,
+            auto_generated: false,
+            moment_id: None,
+            closed: None,
+            agent: self.agent.clone(),
+        };
+        self.append_log(LogCategory::Risk, &format!("added: {} ({})", text, severity));
+        let id = risk.id.clone();
+        self.insert_risk(risk);
+        id
+    }
+    
+    pub(crate) fn insert_risk(&mut self, risk: Risk) {
+        let at = self.risks.iter().position(|known| known.severity < risk.severity).unwrap_or(self.risks.len());
+        self.risks.insert(at, risk);
+    }
+    
+    pub fn close_risk(&mut self, id: &RiskId, note: &str) -> Result<(), RiskError> {
+        let cycle = self.cycle;
+        let risk = self.risks.iter_mut()
+            .find(|risk| risk.id == *id)
+            .ok_or_else(|| RiskError::UnknownRisk(id.clone()))?;
+        if risk.closed.is_some() {
+            return Err(RiskError::AlreadyClosed(id.clone()));
+        }
+        risk.closed = Some(RiskClosure { note: note.to_string(), cycle });
+        let text = format!("closed: {} ({})", risk.text, note);
+        self.append_log(LogCategory::Risk, &text);
+        Ok(())
+    }
+    
+    /// Risks not closed, most severe first, then in the order added
+    pub fn open_risks(&self) -> Vec<&Risk> {
+        let mut open: Vec<&Risk> = self.risks.iter().filter(|risk| risk.closed.is_none()).collect();
+        open.sort_by_key(|risk| Reverse(risk.severity));
+        open
+    }
+    
+    /// Raise a High risk per failure of `session` at least as frustrating
+    /// as `risk_frustration_threshold`, once per moment
+    pub fn promote_failure_risks(&mut self, session: &LearningSession) {
+        let threshold = self.risk_frustration_threshold;
+        let failures = session.moments_ordered().into_iter()
+            .filter(|m| m.moment_type == MomentType::Failure && m.qualia.confusion >= threshold);
+        for moment in failures {
+            if self.risks.iter().any(|risk| risk.moment_id.as_deref() == Some(moment.id.as_str())) {
+                continue;
+            }
+            self.append_log(LogCategory::Risk, &format!("promoted: {} (high)", moment.content));
+            self.insert_risk(Risk {
+                id: RiskId(uuid::Uuid::new_v4().to_string()),
+                text: moment.content.clone(),
+                severity: RiskSeverity::High,
+                mitigation: None,
+                auto_generated: true,
+                moment_id: Some(moment.id.clone()),
+                closed: None,
+                agent: self.agent.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_ordered_by_severity() {
+        let mut bb = Blackboard::new("s-1", "sync", "Sync drops rows");
+        bb.add_risk("Replica lag hides writes", RiskSeverity::Medium, None);
+        bb.add_risk("Deletes cascade into history", RiskSeverity::Critical, Some("Back up first".to_string()));
+        bb.add_risk("Log volume doubles", RiskSeverity::Low, None);
+        bb.add_risk("Migration locks the table", RiskSeverity::Critical, None);
+        let texts = |risks: Vec<&Risk>| -> Vec<String> { risks.iter().map(|risk| risk.text.clone()).collect() };
+        let expected = ["Deletes cascade into history", "Migration locks the table", "Replica lag hides writes", "Log volume doubles"];
+        assert_eq!(texts(bb.risks.iter().collect()), expected);
+        assert_eq!(texts(bb.open_risks()), expected);
+        
+        let exported = Blackboard::from_yaml(&bb.to_yaml()).unwrap();
+        assert_eq!(exported.risks, bb.risks);
+        let summary = bb.handover_summary();
+        assert!(summary.contains(concat!(
+            "## Risks & Blockers\n",
+            "- [critical] Deletes cascade into history\n",
+            "  - Mitigation: Back up first\n",
+            "- [critical] Migration locks the table\n",
+            "- [medium] Replica lag hides writes\n",
+            "- [low] Log volume doubles\n",
+        )), "{}", summary);
+    }
+    
+    #[test]
+    fn test_close_risk() {
+        let mut bb = Blackboard::new("s-1", "sync", "Sync drops rows");
+        let lag = bb.add_risk("Replica lag hides writes", RiskSeverity::Medium, None);
+        bb.add_risk("Log volume doubles", RiskSeverity::Low, None);
+        bb.cycle = 4;
+        bb.close_risk(&lag, "reads go to the primary").unwrap();
+        assert_eq!(bb.open_risks().len(), 1);
+        assert_eq!(bb.risks[0].closed, Some(RiskClosure { note: "reads go to the primary".to_string(), cycle: 4 }));
+        assert_eq!(bb.close_risk(&lag, "again"), Err(RiskError::AlreadyClosed(lag.clone())));
+        let missing = RiskId("missing".to_string());
+        assert_eq!(bb.close_risk(&missing, "n/a"), Err(RiskError::UnknownRisk(missing)));
+        assert_eq!(bb.log.last().unwrap().text, "closed: Replica lag hides writes (reads go to the primary)");
+        assert!(bb.handover_summary().contains("## Risks & Blockers\n- [low] Log volume doubles\n\n"));
+    }
+    
+    #[test]
+    fn test_failures_promoted_once() {
+        let mut session = LearningSession::new("sync");
+        session.encounter("sync drops rows");
+        let calm = session.fail_with_frustration("retry loop gave up", "raise the limit", 0.3).id.clone();
+        let bad = session.fail("migration deleted history rows", "back up first").id.clone();
+        let mut bb = Blackboard::new(&session.id, "sync", "Sync drops rows");
+        bb.add_risk("Log volume doubles", RiskSeverity::Low, None);
+        bb.promote_failure_risks(&session);
+        bb.promote_failure_risks(&session);
+        
+        assert_eq!(bb.risks.len(), 2);
+        let promoted = &bb.risks[0];
+        let text = "migration deleted history rows | Lesson: back up first";
+        assert_eq!((promoted.text.as_str(), promoted.severity, promoted.auto_generated), (text, RiskSeverity::High, true));
+        assert_eq!(promoted.moment_id.as_deref(), Some(bad.as_str()));
+        assert!(bb.handover_summary().contains(&format!("- [high] {} (auto-generated)\n", text)));
+        
+        bb.risk_frustration_threshold = 0.2;
+        bb.promote_failure_risks(&session);
+        assert_eq!(bb.risks[1].moment_id.as_deref(), Some(calm.as_str()), "a lower threshold promotes the calmer failure");
+    }
+}
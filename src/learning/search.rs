@@ -0,0 +1,81 @@
+//! Session search — rank sessions by task description and tags
+
+use std::collections::BTreeMap;
+use crate::core::Fingerprint;
+use crate::learning::moment::normalize_tag;
+use crate::learning::session::{LearningSession, SessionOutcome, SessionReport};
+
+/// Sessions scoring below this are left out of `MetaAGI::find_sessions`
+pub const MIN_SESSION_MATCH_SCORE: f32 = 0.1;
+
+/// A session found by `MetaAGI::find_sessions`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionMatch {
+    pub task_id: String,
+    pub session_id: String,
+    /// None while the session is open
+    pub outcome: Option<SessionOutcome>,
+    pub moment_count: usize,
+    pub breakthrough_count: usize,
+    /// From `session_match_score`, in [0, 1]
+    pub score: f32,
+}
+
+impl SessionMatch {
+    pub fn from_session(session: &LearningSession, query: &str, tags: &[String]) -> Self {
+        Self {
+            task_id: session.task_id.clone(),
+            session_id: session.id.clone(),
+            outcome: session.report().map(|report| report.outcome.clone()),
+            moment_count: session.moment_count(),
+            breakthrough_count: session.breakthrough_count(),
+            score: session_match_score(query, tags, &session.description, &session.tag_counts()),
+        }
+    }
+    
+    pub fn from_report(report: &SessionReport, query: &str, tags: &[String]) -> Self {
+        Self {
+            task_id: report.task_id.clone(),
+            session_id: report.session_id.clone(),
+            outcome: Some(report.outcome.clone()),
+            moment_count: report.moment_count,
+            breakthrough_count: report.breakthrough_count,
+            score: session_match_score(query, tags, &report.description, &report.tags),
+        }
+    }
+}
+
+/// Mean of the description score and the tag score, counting only the parts
+/// of the query that were given. The description score is the
+/// `Fingerprint::from_words` similarity rescaled so unrelated texts (about
+/// 0.5) score 0 and the same words score 1; the tag score is the share of
+/// `tags` found on the session's moments.
+pub fn session_match_score(query: &str, tags: &[String], description: &str, session_tags: &BTreeMap<String, usize>) -> f32 {
+    let mut scores = Vec::new();
+    if !query.trim().is_empty() {
+        let similarity = Fingerprint::from_words(query).similarity(&Fingerprint::from_words(description));
+        scores.push(((similarity - 0.5) * 2.0).clamp(0.0, 1.0));
+    }
+    if !tags.is_empty() {
+        let found = tags.iter().filter(|tag| session_tags.contains_key(&normalize_tag(tag))).count();
+        scores.push(found as f32 / tags.len() as f32);
+    }
+    if scores.is_empty() { 0.0 } else { scores.iter().sum::<f32>() / scores.len() as f32 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_match_score_parts() {
+        let session_tags = BTreeMap::from([("orm".to_string(), 2), ("postgres".to_string(), 1)]);
+        let description = "scope primary keys per project";
+        
+        assert_eq!(session_match_score(description, &[], description, &session_tags), 1.0);
+        assert_eq!(session_match_score("", &["#ORM".to_string(), "redis".to_string()], description, &session_tags), 0.5);
+        assert_eq!(session_match_score(description, &["redis".to_string()], description, &session_tags), 0.5);
+        assert!(session_match_score("speed up the linker on ci", &[], description, &session_tags) < MIN_SESSION_MATCH_SCORE);
+        assert_eq!(session_match_score("  ", &[], description, &session_tags), 0.0);
+    }
+}
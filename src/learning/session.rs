@@ -1,13 +1,45 @@
 //! LearningSession — 6-phase learning loop lifecycle
 
-use std::collections::HashMap;
-use std::time::{Instant, Duration};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
 
-use crate::core::Fingerprint;
-use crate::cognitive::{ThinkingStyle, GateState, evaluate_gate};
-use crate::learning::moment::{Moment, MomentType, MomentBuilder, Qualia};
+use crate::core::{Clock, Fingerprint, SystemClock};
+use crate::cognitive::{ThinkingStyle, GateState, evaluate_gate_with_clock, calculate_sd, SD_FLOW_THRESHOLD, SD_BLOCK_THRESHOLD};
+use crate::nars::TruthValue;
+use crate::learning::trajectory::QualiaTrajectory;
+use crate::learning::timeline::{TimelineOptions, format_timeline};
+use crate::learning::blackboard::{gate_from_label, Blackboard, Decision, IceCakedLayer, ProvenanceKind};
+use crate::learning::resonance::{ResonanceCapture, SimilarMoment};
+use crate::learning::observer::{SessionObserver, Subscription};
+use crate::learning::reuse::ReuseReport;
+use crate::learning::event_log::{EventLog, EventLine, SessionEvent, EVENT_LOG_SCHEMA_VERSION};
+use crate::learning::moment::{
+    Moment, MomentBuilder, MomentType, MomentLink, LinkKind, Qualia, Artifact, ImportanceWeights,
+    MAX_CONTENT_CHARS, MAX_EDIT_HISTORY, QUALIA_DIMENSIONS, SourceLocation,
+};
+use crate::learning::archive::ArchivedMoment;
 
-#[derive(Clone, Debug, PartialEq)]
+/// Where a session is in the learning loop. Moves checked by
+/// `LearningSession::advance_phase` follow this graph:
+///
+/// ```text
+/// Initialize   → Encounter
+/// Encounter    → Struggle, Breakthrough
+/// Struggle     → Encounter, Breakthrough
+/// Breakthrough → Struggle, Consolidate
+/// Consolidate  → Apply, MetaLearn
+/// Apply        → Struggle, MetaLearn, Complete
+/// MetaLearn    → Encounter, Complete
+/// Complete     → (none)
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SessionPhase {
     Initialize, Encounter, Struggle, Breakthrough, Consolidate, Apply, MetaLearn, Complete,
 }
@@ -25,9 +57,43 @@ impl SessionPhase {
             Self::Complete => None,
         }
     }
+    
+    /// Phases `advance_phase` may move to from this one
+    pub fn legal_targets(&self) -> &'static [SessionPhase] {
+        use SessionPhase::*;
+        match self {
+            Initialize => &[Encounter],
+            Encounter => &[Struggle, Breakthrough],
+            Struggle => &[Encounter, Breakthrough],
+            Breakthrough => &[Struggle, Consolidate],
+            Consolidate => &[Apply, MetaLearn],
+            Apply => &[Struggle, MetaLearn, Complete],
+            MetaLearn => &[Encounter, Complete],
+            Complete => &[],
+        }
+    }
+    
+    /// Whether `to` is a legal target; staying put always is
+    pub fn can_advance_to(&self, to: &SessionPhase) -> bool {
+        self == to || self.legal_targets().contains(to)
+    }
+    
+    /// Position in the phase order, `Initialize` first
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Initialize => 0,
+            Self::Encounter => 1,
+            Self::Struggle => 2,
+            Self::Breakthrough => 3,
+            Self::Consolidate => 4,
+            Self::Apply => 5,
+            Self::MetaLearn => 6,
+            Self::Complete => 7,
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SessionState {
     pub session_id: String,
     pub task_id: String,
@@ -36,46 +102,1033 @@ pub struct SessionState {
     pub thinking_style: ThinkingStyle,
     pub coherence: f32,
     pub ice_cake_layers: u32,
+    /// The decisions behind `ice_cake_layers`, in layer order
+    pub ice_caked: Vec<IceCakedDecision>,
     pub moment_count: usize,
     pub breakthrough_count: usize,
+    /// Moment count per type name, custom types included
+    pub moment_types: BTreeMap<String, usize>,
+    /// Moment count per tag
+    pub tags: BTreeMap<String, usize>,
+    /// Distinct metadata values per key, in first-seen order
+    pub metadata: BTreeMap<String, Vec<String>>,
+    /// From the first moment's creation to the end of the latest one, minus paused time
+    pub duration_ms: u64,
+    /// The same span including paused time
+    pub wall_clock_ms: u64,
+    /// Longest stretch between moments, minus paused time
+    pub longest_gap_ms: u64,
+    pub paused: bool,
+    /// Every attached artifact, as (moment id, artifact)
+    pub artifacts: Vec<(String, Artifact)>,
+    pub trajectory: QualiaTrajectory,
+    /// Most important moments, highest first
+    pub key_moments: Vec<KeyMoment>,
+    /// Content of hypotheses not yet verified
+    pub open_hypotheses: Vec<String>,
+    /// Phase changes forced past the transition graph
+    pub phase_overrides: Vec<PhaseOverride>,
+    pub metrics: SessionMetrics,
+    pub style_distribution: Vec<StyleShare>,
+    /// Set once the session is closed
+    pub outcome: Option<SessionOutcome>,
+    /// Content of the latest struggle no breakthrough resolved
+    pub last_open_struggle: Option<String>,
+    /// From `LearningSession::current_gate`
+    pub gate_state: Option<GateState>,
+    pub predecessor_session_id: Option<String>,
     pub cycle: u64,
 }
 
-#[derive(Clone, Debug)]
+/// One phase's share of a session, from `LearningSession::metrics`.
+/// Frustration is the `confusion` dimension, as in `QualiaTrajectory`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PhaseMetrics {
+    pub phase: SessionPhase,
+    pub moment_count: usize,
+    /// Time in the phase over every visit, minus pauses
+    pub duration_ms: u64,
+    pub mean_effort: f32,
+    pub max_effort: f32,
+    pub mean_frustration: f32,
+    pub max_frustration: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionMetrics {
+    /// One entry per phase entered, in order of first entry; moments
+    /// recorded before any phase count under `Initialize`
+    pub phases: Vec<PhaseMetrics>,
+    /// From the first moment to the first breakthrough, minus pauses
+    pub time_to_first_breakthrough_ms: Option<u64>,
+    /// Struggles per breakthrough; None without breakthroughs
+    pub struggle_to_breakthrough_ratio: Option<f32>,
+}
+
+/// Moments and time under one dominant thinking-style dimension, from
+/// `LearningSession::style_distribution`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StyleShare {
+    /// `ThinkingStyle::dominant`, or "unset" for moments without a style
+    pub axis: String,
+    pub moment_count: usize,
+    /// Time from each of the moments to the next one, minus pauses
+    pub duration_ms: u64,
+}
+
+/// Struggle→breakthrough pair found by `LearningSession::infer_resolutions`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolutionPair {
+    pub struggle_id: String,
+    pub breakthrough_id: String,
+    /// `Fingerprint::from_words` similarity of the two contents
+    pub similarity: f32,
+    /// Moments recorded between the two, a rough proxy for effort
+    pub intervening: usize,
+}
+
+/// Word-fingerprint similarity above which a breakthrough is taken to
+/// resolve a struggle; unrelated texts sit near 0.5
+pub const DEFAULT_RESOLUTION_SIMILARITY: f32 = 0.6;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyMoment {
+    pub moment_id: String,
+    pub content: String,
+    pub importance: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct IceCakedDecision {
     pub moment_id: String,
     pub content: String,
+    /// Source of the moment, then of the moments it links to
+    pub sources: Vec<SourceLocation>,
     pub rationale: String,
     pub gate_state: GateState,
     pub ice_caked_at_cycle: u64,
+    /// The `DecisionReversal` moment that thawed this moment's previous
+    /// decision, when it is frozen again
+    #[serde(default)]
+    pub after_reversal: Option<String>,
+    /// How sure the decision is; `ice_cake` freezes at `TruthValue::certain_true`
+    #[serde(default = "TruthValue::certain_true")]
+    pub truth: TruthValue,
+    /// Moment id of the later decision on the same topic that replaced this one
+    #[serde(default)]
+    pub superseded_by: Option<String>,
+    /// Phase the frozen moment was recorded in; None when saved before
+    /// phases were kept
+    #[serde(default)]
+    pub phase: Option<SessionPhase>,
+    /// Moment the decision was frozen under in the predecessor session,
+    /// when `resume_from_blackboard` froze it again
+    #[serde(default)]
+    pub prior_moment_id: Option<String>,
+}
+
+/// Links whose target is not a moment of the session, as (source id, link)
+#[derive(Clone, Debug, PartialEq, Error)]
+#[error("{} link(s) point at unknown moments", .links.len())]
+pub struct DanglingLinks {
+    pub links: Vec<(String, MomentLink)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum EditError {
+    #[error("no moment with id {0}")]
+    UnknownMoment(String),
+    #[error("moment content must not be empty")]
+    EmptyContent,
+    #[error("moment content is {len} characters, over the limit of {MAX_CONTENT_CHARS}")]
+    ContentTooLong { len: usize },
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+}
+
+#[derive(Clone, Debug, PartialEq, Error)]
+pub enum PhaseTransitionError {
+    #[error("cannot move from {from:?} to {to:?}; legal targets: {}", phase_list(legal))]
+    Illegal { from: SessionPhase, to: SessionPhase, legal: Vec<SessionPhase> },
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+}
+
+fn phase_list(phases: &[SessionPhase]) -> String {
+    if phases.is_empty() {
+        return "none".to_string();
+    }
+    phases.iter().map(|p| format!("{p:?}")).collect::<Vec<_>>().join(", ")
+}
+
+/// A phase change made with `LearningSession::force_phase`, outside the graph
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PhaseOverride {
+    pub from: SessionPhase,
+    pub to: SessionPhase,
+    pub reason: String,
+    /// `seq` of the latest moment when the override was made
+    pub seq: u64,
+}
+
+/// Ice-caked decision to thaw: a 1-based layer id (position in
+/// `LearningSession::ice_caked`) or the decision's moment id
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ThawTarget {
+    Layer(u32),
+    Moment(String),
+}
+
+impl From<u32> for ThawTarget {
+    fn from(layer_id: u32) -> Self { ThawTarget::Layer(layer_id) }
+}
+
+impl From<&str> for ThawTarget {
+    fn from(moment_id: &str) -> Self { ThawTarget::Moment(moment_id.to_string()) }
+}
+
+impl From<&String> for ThawTarget {
+    fn from(moment_id: &String) -> Self { ThawTarget::Moment(moment_id.clone()) }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum IceCakeError {
+    #[error("no moment with id {0}")]
+    UnknownMoment(String),
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+}
+
+/// Why `LearningSession::undo_last` kept the latest moment
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum UndoError {
+    #[error("there is no moment to undo")]
+    Empty,
+    #[error("moment {0} is ice-caked; thaw it first")]
+    IceCaked(String),
+    #[error("moment {id} is the target of a link from {source_id}")]
+    LinkTarget { id: String, source_id: String },
+    #[error("moment {0} was captured into a resonance store")]
+    CapturedExternally(String),
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+}
+
+/// Why `LearningSession::restore_checkpoint` kept the session as it is
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum RestoreError {
+    #[error("no checkpoint {0}")]
+    UnknownCheckpoint(CheckpointId),
+    #[error("moment {0} is ice-caked; thaw it first")]
+    IceCaked(String),
+    #[error("moment {id} is the target of a link from {source_id}")]
+    LinkTarget { id: String, source_id: String },
+    #[error("moment {0} was captured into a resonance store")]
+    CapturedExternally(String),
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ThawError {
+    #[error("no ice-caked layer {0}")]
+    UnknownLayer(u32),
+    #[error("moment {0} is not ice-caked")]
+    NotIceCaked(String),
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+    /// The reversal moment cannot be recorded while paused
+    #[error(transparent)]
+    Paused(#[from] SessionPaused),
+}
+
+/// Handle returned by `LearningSession::begin_subtask`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SubtaskId(pub String);
+
+impl SubtaskId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SubtaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Handle returned by `LearningSession::checkpoint`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CheckpointId(pub String);
+
+impl CheckpointId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CheckpointId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// What `LearningSession::restore_checkpoint` discarded
+#[derive(Clone, Debug)]
+pub struct RestoredReport {
+    pub checkpoint: CheckpointId,
+    pub name: String,
+    /// Live moments recorded after the checkpoint, oldest first
+    pub discarded: Vec<Moment>,
+    /// Moments recorded after the checkpoint that had already been archived
+    pub discarded_archived: Vec<ArchivedMoment>,
+    /// Names of the later checkpoints that no longer exist
+    pub invalidated: Vec<String>,
+    /// Decisions ice-caked since the checkpoint, net of thaws; they stay
+    pub ice_caked_since: usize,
+}
+
+/// A piece of the session's task, e.g. a fix needed along the way. Moments
+/// recorded while it is the innermost open subtask carry its id.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Subtask {
+    pub id: SubtaskId,
+    pub name: String,
+    /// The subtask that was innermost when this one began
+    pub parent: Option<SubtaskId>,
+    pub started_at: u64,
+    /// Clock time of `end_subtask`; None while open
+    pub ended_at: Option<u64>,
+    pub outcome: Option<SessionOutcome>,
+}
+
+impl Subtask {
+    pub fn is_open(&self) -> bool {
+        self.outcome.is_none()
+    }
+}
+
+/// Moments and mean qualia of a subtask, counting its nested subtasks
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubtaskSummary {
+    pub id: SubtaskId,
+    pub name: String,
+    /// 0 for a subtask begun with none open
+    pub depth: usize,
+    pub outcome: Option<SessionOutcome>,
+    pub moment_count: usize,
+    pub breakthrough_count: usize,
+    /// Mean of each qualia dimension; None without moments
+    pub mean_qualia: Option<Qualia>,
+    pub peak_frustration: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum SubtaskError {
+    #[error("no subtask {0}")]
+    Unknown(SubtaskId),
+    #[error("subtask {id} is not the innermost open subtask (innermost: {})", top.as_ref().map_or("none".to_string(), SubtaskId::to_string))]
+    NotOnTop { id: SubtaskId, top: Option<SubtaskId> },
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+}
+
+/// Version written by `LearningSession::save_json`; bump on incompatible changes
+pub const SESSION_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum SessionIoError {
+    #[error("session file I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("session file is not valid session JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("session file has schema version {found}, newer than the supported {SESSION_SCHEMA_VERSION}; upgrade to load it")]
+    UnsupportedVersion { found: u32 },
+}
+
+/// On-disk form of a session, minus the clock and in-memory indexes
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    schema_version: u32,
+    id: String,
+    task_id: String,
+    #[serde(default)]
+    description: String,
+    phase: SessionPhase,
+    progress: f32,
+    cycle: u64,
+    next_seq: u64,
+    phase_log: Vec<(SessionPhase, u64)>,
+    /// `seq` of the first moment of each `phase_log` entry
+    #[serde(default)]
+    phase_seqs: Vec<u64>,
+    #[serde(default)]
+    pauses: Vec<(u64, Option<u64>)>,
+    #[serde(default)]
+    phase_overrides: Vec<PhaseOverride>,
+    #[serde(default)]
+    report: Option<SessionReport>,
+    #[serde(default)]
+    subtasks: Vec<Subtask>,
+    #[serde(default)]
+    style_changes: Vec<(u64, ThinkingStyle)>,
+    #[serde(default)]
+    predecessor_session_id: Option<String>,
+    moments: Vec<Moment>,
+    #[serde(default)]
+    archive: Vec<ArchivedMoment>,
+    ice_caked: Vec<IceCakedDecision>,
+}
+
+/// Just enough of a session file to check its version before parsing the rest
+#[derive(Deserialize)]
+struct SchemaVersion {
+    schema_version: u32,
+}
+
+/// The report of a session file; serde skips the moments without building them
+#[derive(Deserialize)]
+struct ReportOnly {
+    schema_version: u32,
+    #[serde(default)]
+    report: Option<SessionReport>,
+}
+
+/// `Fingerprint::from_words` similarity at which two struggles count as the
+/// same problem restated, for `LearningSession::stuck_report`
+pub const DEFAULT_STUCK_SIMILARITY: f32 = 0.7;
+
+/// Resonance at which a captured moment is suggested by `stuck_report_with`
+const STUCK_SUGGESTION_RESONANCE: f32 = 0.6;
+
+/// A session going in circles, from `LearningSession::stuck_report`
+#[derive(Clone, Debug, PartialEq)]
+pub struct StuckReport {
+    /// The struggle closest to all the others in the window
+    pub representative_id: String,
+    pub representative: String,
+    /// Trailing struggles and failures restating the representative; at
+    /// least the window
+    pub repetitions: usize,
+    /// Confusion over the window, oldest first; never decreasing
+    pub frustration: Vec<f32>,
+    /// Captured moments resonating with the representative, outside the window
+    pub suggestions: Vec<SimilarMoment>,
+}
+
+/// Resonance a past moment needs with the latest struggle for
+/// `LearningSession::suggest_from_resonance` to offer it
+pub const DEFAULT_SUGGESTION_RESONANCE: f32 = 0.6;
+
+/// How many suggestions `suggest_from_resonance` returns at most
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A past moment that resonates with the struggle at hand
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub moment_id: String,
+    pub session_id: String,
+    /// Task the moment was captured under, when the capturer recorded it
+    pub task_id: Option<String>,
+    pub moment_type: MomentType,
+    /// The past moment's content summary
+    pub summary: String,
+    pub resonance: f32,
+    /// `TruthValue::from_similarity` of the resonance against chance
+    pub confidence: TruthValue,
+}
+
+/// `Fingerprint::from_words` similarity of two rationales above which
+/// `ice_cake_with_confidence` takes them to decide the same topic
+pub const DEFAULT_SUPERSEDE_SIMILARITY: f32 = 0.7;
+
+/// Content longer than this is summarized on capture; see `summarize_content`
+pub const DEFAULT_MAX_CONTENT_CHARS: usize = 2_000;
+
+/// Struggles `StruggleGateConfig::new` reads the gate over, the new one included
+pub const DEFAULT_STRUGGLE_GATE_WINDOW: usize = 5;
+
+/// Frustration (`confusion`) `fail` records a failure with
+pub const DEFAULT_FAILURE_FRUSTRATION: f32 = 0.8;
+
+/// How a struggle's collapse gate is read off the struggles before it: the
+/// larger SD of frustration (`confusion`) and of uncertainty (1 -
+/// `confidence`) over the latest `window` live struggles maps to Flow below
+/// `flow_below`, Block above `block_above` and Hold in between
+#[derive(Clone, Debug, PartialEq)]
+pub struct StruggleGateConfig {
+    pub window: usize,
+    pub flow_below: f32,
+    pub block_above: f32,
+}
+
+impl StruggleGateConfig {
+    pub fn new() -> Self {
+        Self {
+            window: DEFAULT_STRUGGLE_GATE_WINDOW,
+            flow_below: SD_FLOW_THRESHOLD,
+            block_above: SD_BLOCK_THRESHOLD,
+        }
+    }
+    
+    pub fn gate(&self, sd: f32) -> GateState {
+        if sd < self.flow_below {
+            GateState::Flow
+        } else if sd > self.block_above {
+            GateState::Block
+        } else {
+            GateState::Hold
+        }
+    }
+}
+
+impl Default for StruggleGateConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionConfig {
+    /// Helper-recorded content over this many characters is stored as a
+    /// head-and-tail summary, with the full text in `Moment::raw_content`
+    pub max_content_chars: usize,
+    /// Merge repeated moments recorded by helpers; None (the default) keeps every moment
+    pub dedup: Option<DedupPolicy>,
+    /// Whether recording a moment while paused resumes the session (the
+    /// default). When false, recording while paused fails with
+    /// `CaptureError::Paused`, and the helpers without `try_` panic.
+    pub capture_while_paused: bool,
+    /// Let helpers move the phase only forward, so that an encounter logged
+    /// mid-struggle leaves the session in `Struggle`. The one step back
+    /// allowed is `Breakthrough` to `Struggle`, for a new struggle after a
+    /// breakthrough. Off by default: each helper enters its phase outright.
+    pub auto_phase: bool,
+    /// Rationale similarity at which `ice_cake_with_confidence` supersedes
+    /// an earlier decision
+    pub supersede_similarity: f32,
+    /// Style stamped on helper-recorded moments and reported by `state()`
+    pub thinking_style: Option<ThinkingStyle>,
+    /// Tags added to every helper-recorded moment
+    pub default_tags: Vec<String>,
+    /// Live moments kept in `LearningSession::moments`. Recording past the
+    /// cap moves the least important moments, oldest first, to
+    /// `LearningSession::archive`; ice-caked and linked moments are never
+    /// moved. None (the default) keeps every moment live.
+    pub max_live_moments: Option<usize>,
+    /// Stamps each recorded struggle with `Moment::gate_state`; None turns
+    /// the reading off
+    pub struggle_gate: Option<StruggleGateConfig>,
+}
+
+impl SessionConfig {
+    pub fn new() -> Self {
+        Self {
+            max_content_chars: DEFAULT_MAX_CONTENT_CHARS,
+            dedup: None,
+            capture_while_paused: true,
+            auto_phase: false,
+            supersede_similarity: DEFAULT_SUPERSEDE_SIMILARITY,
+            thinking_style: None,
+            default_tags: Vec::new(),
+            max_live_moments: None,
+            struggle_gate: Some(StruggleGateConfig::new()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("session is paused and does not capture while paused")]
+pub struct SessionPaused;
+
+/// Why a session was given up
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AbandonReason {
+    /// Cannot go on without something that is not coming
+    Blocked,
+    /// Other work took priority
+    Deprioritized,
+    /// The approach itself turned out wrong
+    WrongApproach,
+    /// Outside circumstances, e.g. the requirement went away
+    External,
+}
+
+impl AbandonReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Blocked => "blocked",
+            Self::Deprioritized => "deprioritized",
+            Self::WrongApproach => "wrong approach",
+            Self::External => "external",
+        }
+    }
+}
+
+impl fmt::Display for AbandonReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How a session ended
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionOutcome {
+    Completed,
+    Abandoned { reason: AbandonReason },
+    Blocked { reason: String },
+}
+
+impl fmt::Display for SessionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Completed => write!(f, "completed"),
+            Self::Abandoned { reason } => write!(f, "abandoned: {}", reason),
+            Self::Blocked { reason } => write!(f, "blocked: {}", reason),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("session is closed ({outcome})")]
+pub struct SessionClosedError {
+    pub outcome: SessionOutcome,
+}
+
+/// Why a moment was not recorded, from the `try_` recording helpers and
+/// `add_moment_deduped`; the helpers without `try_` panic with it
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum CaptureError {
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+    /// Paused with `SessionConfig::capture_while_paused` off
+    #[error(transparent)]
+    Paused(#[from] SessionPaused),
+}
+
+/// How `LearningSession::merge_branch` folds a branch's moments in
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergePolicy {
+    /// Merge branch moments into parent moments they repeat, by the same
+    /// rule as `add_moment_deduped`; None keeps every branch moment
+    pub dedup_threshold: Option<f32>,
+    /// Give merged moments fresh seqs after the parent's latest (the
+    /// default). When false, branch seqs are kept, which fails if the parent
+    /// has recorded moments since the fork.
+    pub renumber: bool,
+    /// Let `merge_sessions` combine sessions of different tasks
+    pub allow_cross_task: bool,
+}
+
+impl MergePolicy {
+    pub fn new() -> Self {
+        Self { dedup_threshold: None, renumber: true, allow_cross_task: false }
+    }
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self { Self::new() }
+}
+
+#[derive(Clone, Debug, PartialEq, Error)]
+pub enum MergeError {
+    #[error("session was not forked from this one")]
+    NotABranch,
+    #[error("branch moment seq {seq} is already used by the parent; merge with renumbering")]
+    SeqConflict { seq: u64 },
+    #[error("sessions belong to different tasks ({a} and {b})")]
+    TaskMismatch { a: String, b: String },
+    #[error(transparent)]
+    Closed(#[from] SessionClosedError),
+}
+
+/// What `merge_branch` did with the branch's moments
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BranchMerge {
+    /// Ids of moments added to the parent, in branch order
+    pub added: Vec<String>,
+    /// (branch moment id, parent moment id) for moments merged as repeats
+    pub merged: Vec<(String, String)>,
+    /// Ice-caked decisions carried over from the branch
+    pub ice_caked: usize,
+}
+
+/// Different ice-caked decisions on the same moment, found by `merge_sessions`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IceCakeConflict {
+    pub moment_id: String,
+    pub decisions: Vec<IceCakedDecision>,
+}
+
+/// How many recorded moments `LearningSession::undo_last` can roll the
+/// phase back across
+pub const MAX_UNDO_DEPTH: usize = 64;
+
+/// Phase state from just before a moment was recorded by `add_moment`
+#[derive(Clone, Debug)]
+struct UndoPoint {
+    moment_id: String,
+    phase: SessionPhase,
+    progress: f32,
+    phase_log_len: usize,
+}
+
+/// Session state from when `LearningSession::checkpoint` was called
+#[derive(Clone, Debug)]
+struct Checkpoint {
+    id: CheckpointId,
+    name: String,
+    /// Moments with this `seq` or later were recorded after the checkpoint
+    next_seq: u64,
+    cycle: u64,
+    phase: SessionPhase,
+    progress: f32,
+    phase_log_len: usize,
+    ice_caked_len: usize,
+}
+
+/// Where a forked session came from
+#[derive(Clone, Debug)]
+struct BranchOrigin {
+    name: String,
+    parent_id: String,
+    /// `seq` the branch's own moments start at
+    next_seq_at_fork: u64,
+    /// Ice-caked decisions the branch started with
+    ice_caked_at_fork: usize,
+}
+
+/// Final account of a session, computed once by `LearningSession::close`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionReport {
+    pub session_id: String,
+    pub task_id: String,
+    /// The session's `description`
+    #[serde(default)]
+    pub description: String,
+    pub outcome: SessionOutcome,
+    /// Clock time of the close
+    pub closed_at: u64,
+    pub moment_count: usize,
+    pub breakthrough_count: usize,
+    pub moment_types: BTreeMap<String, usize>,
+    /// Active time, excluding pauses
+    pub duration_ms: u64,
+    pub wall_clock_ms: u64,
+    pub trajectory: QualiaTrajectory,
+    pub ice_caked: Vec<IceCakedDecision>,
+    #[serde(default)]
+    pub metrics: SessionMetrics,
+    /// Moment count per tag
+    #[serde(default)]
+    pub tags: BTreeMap<String, usize>,
+    /// Set by `MetaAGI::end_session`
+    #[serde(default)]
+    pub reuse: Option<ReuseReport>,
+}
+
+impl SessionReport {
+    /// The report saved in a session file by `save_json`, without building
+    /// the session or its moments; None if the session was saved open
+    pub fn load_json(path: &Path) -> Result<Option<Self>, SessionIoError> {
+        let json = std::fs::read_to_string(path)?;
+        let ReportOnly { schema_version, report } = serde_json::from_str(&json)?;
+        if schema_version > SESSION_SCHEMA_VERSION {
+            return Err(SessionIoError::UnsupportedVersion { found: schema_version });
+        }
+        Ok(report)
+    }
+}
+
+/// When `add_moment_deduped` treats an incoming moment as a repeat
+#[derive(Clone, Debug, PartialEq)]
+pub struct DedupPolicy {
+    /// `Fingerprint::from_words` similarity at or above which same-type moments merge
+    pub similarity_threshold: f32,
+    /// How many of the latest moments are checked
+    pub window: usize,
+}
+
+impl DedupPolicy {
+    pub fn new() -> Self {
+        Self { similarity_threshold: 0.95, window: 20 }
+    }
+}
+
+impl Default for DedupPolicy {
+    fn default() -> Self { Self::new() }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddOutcome {
+    /// Recorded as a new moment with this id
+    Added(String),
+    /// Merged into an existing moment
+    Merged { id: String, repeat_count: u32 },
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self { Self::new() }
+}
+
+/// Session history behind `LearningSession::estimate_effort`
+struct EffortInputs {
+    struggles: usize,
+    failures: usize,
+    frustration: f32,
+    moments: usize,
+}
+
+impl EffortInputs {
+    fn effort(&self) -> f32 {
+        let load = 0.15 * self.struggles as f32
+            + 0.2 * self.failures as f32
+            + 0.25 * self.frustration
+            + 0.02 * self.moments as f32;
+        1.0 - (-load).exp()
+    }
+    
+    fn annotate(&self, builder: MomentBuilder) -> MomentBuilder {
+        [
+            ("effort.struggles", self.struggles.to_string()),
+            ("effort.failures", self.failures.to_string()),
+            ("effort.frustration", format!("{:.2}", self.frustration)),
+            ("effort.moments", self.moments.to_string()),
+        ].into_iter().fold(builder, |b, (key, value)| b.meta(key, &value).expect("effort keys are valid"))
+    }
 }
 
+/// How many top moments `state()` reports
+pub const KEY_MOMENTS: usize = 3;
+
 pub struct LearningSession {
     pub id: String,
     pub task_id: String,
+    /// What the task is about, e.g. the blackboard's task description; empty
+    /// unless set
+    pub description: String,
+    /// Session this one resumed, from `resume_from_blackboard`
+    pub predecessor_session_id: Option<String>,
     pub phase: SessionPhase,
     pub progress: f32,
     pub moments: Vec<Moment>,
+    /// Moments moved out of `moments` by `config.max_live_moments`, in the
+    /// order they were archived
+    pub archive: Vec<ArchivedMoment>,
     moment_index: HashMap<String, usize>,
     pub ice_caked: Vec<IceCakedDecision>,
+    /// Decisions `merge_sessions` could not reconcile; none of them is in `ice_caked`
+    pub ice_cake_conflicts: Vec<IceCakeConflict>,
     pub cycle: u64,
-    pub started_at: Instant,
-    pub last_activity: Instant,
+    /// Clock time the session was created
+    pub started_at: u64,
+    /// Clock time of the latest change to the moments
+    pub last_activity: u64,
+    pub importance_weights: ImportanceWeights,
+    pub config: SessionConfig,
+    /// History cap `edit_moment` passes to `Moment::edit_content_keeping`
+    pub max_edit_history: usize,
+    clock: Arc<dyn Clock>,
+    /// Affect override for the next recorded moment, set by `with_affect`
+    next_affect: Option<(f32, f32, f32)>,
+    /// Each phase entered, with the clock time it was entered at and the
+    /// `seq` of its first moment
+    phase_log: Vec<(SessionPhase, u64, u64)>,
+    /// Moves made by `force_phase`
+    phase_overrides: Vec<PhaseOverride>,
+    /// Every subtask begun, in order; the open ones form the subtask stack
+    subtasks: Vec<Subtask>,
+    /// Styles set with `set_style`, with the clock time of each change
+    style_changes: Vec<(u64, ThinkingStyle)>,
+    /// `seq` for the next recorded moment; only ever grows
+    next_seq: u64,
+    /// Paused intervals as (start, end) clock times; the last may still be open
+    pauses: Vec<(u64, Option<u64>)>,
+    /// Set by `close`; the session is read-only from then on
+    report: Option<SessionReport>,
+    /// Set on sessions created by `fork`
+    branch: Option<BranchOrigin>,
+    observers: Vec<(Subscription, Box<dyn SessionObserver>)>,
+    next_subscription: u64,
+    /// Phase state before each of the latest recorded moments, newest last
+    undo_points: Vec<UndoPoint>,
+    /// Checkpoints still valid for `restore_checkpoint`, oldest first
+    checkpoints: Vec<Checkpoint>,
+    event_log: Option<EventLog>,
 }
 
 impl LearningSession {
     pub fn new(task_id: &str) -> Self {
+        Self::with_clock(task_id, Arc::new(SystemClock))
+    }
+    
+    pub fn with_config(task_id: &str, config: SessionConfig) -> Self {
+        Self { config, ..Self::new(task_id) }
+    }
+    
+    /// Rebuild a session from saved moments, e.g. after deserializing them.
+    /// Moments are ordered by `seq`; any without one (saved before seqs
+    /// existed) are numbered after the rest in the order given. New moments
+    /// continue past the highest seq, so seqs never repeat across save/load.
+    pub fn restore(task_id: &str, mut moments: Vec<Moment>) -> Self {
+        let mut session = Self::new(task_id);
+        if let Some(first) = moments.first() {
+            session.id = first.session_id.clone();
+        }
+        let mut next_seq = moments.iter().map(|m| m.seq).max().unwrap_or(0) + 1;
+        for moment in moments.iter_mut().filter(|m| m.seq == 0) {
+            moment.seq = next_seq;
+            next_seq += 1;
+        }
+        moments.sort_by_key(|m| m.seq);
+        debug_assert!(moments.windows(2).all(|w| w[0].seq < w[1].seq), "duplicate moment seq");
+        session.moment_index = moments.iter().enumerate().map(|(idx, m)| (m.id.clone(), idx)).collect();
+        session.cycle = moments.len() as u64;
+        session.moments = moments;
+        session.next_seq = next_seq;
+        session
+    }
+    
+    /// Write moments, ice-caked decisions, phase, ids and counters as JSON.
+    /// Configuration and the clock are not saved.
+    pub fn save_json(&self, path: &Path) -> Result<(), SessionIoError> {
+        let file = SessionFile {
+            schema_version: SESSION_SCHEMA_VERSION,
+            id: self.id.clone(),
+            task_id: self.task_id.clone(),
+            description: self.description.clone(),
+            phase: self.phase.clone(),
+            progress: self.progress,
+            cycle: self.cycle,
+            next_seq: self.next_seq,
+            phase_log: self.phase_log.iter().map(|(phase, at, _)| (phase.clone(), *at)).collect(),
+            phase_seqs: self.phase_log.iter().map(|&(_, _, seq)| seq).collect(),
+            pauses: self.pauses.clone(),
+            phase_overrides: self.phase_overrides.clone(),
+            report: self.report.clone(),
+            subtasks: self.subtasks.clone(),
+            style_changes: self.style_changes.clone(),
+            predecessor_session_id: self.predecessor_session_id.clone(),
+            moments: self.moments.clone(),
+            archive: self.archive.clone(),
+            ice_caked: self.ice_caked.clone(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+    
+    /// Load a session written by `save_json`, with the default config and clock
+    pub fn load_json(path: &Path) -> Result<Self, SessionIoError> {
+        let json = std::fs::read_to_string(path)?;
+        let SchemaVersion { schema_version } = serde_json::from_str(&json)?;
+        if schema_version > SESSION_SCHEMA_VERSION {
+            return Err(SessionIoError::UnsupportedVersion { found: schema_version });
+        }
+        let file: SessionFile = serde_json::from_str(&json)?;
+        let mut session = Self::new(&file.task_id);
+        session.id = file.id;
+        session.description = file.description;
+        session.phase = file.phase;
+        session.progress = file.progress;
+        session.cycle = file.cycle;
+        session.next_seq = file.next_seq;
+        session.phase_log = file.phase_log.into_iter().enumerate()
+            .map(|(i, (phase, at))| (phase, at, file.phase_seqs.get(i).copied().unwrap_or(0)))
+            .collect();
+        session.pauses = file.pauses;
+        session.phase_overrides = file.phase_overrides;
+        session.report = file.report;
+        session.subtasks = file.subtasks;
+        session.style_changes = file.style_changes;
+        session.predecessor_session_id = file.predecessor_session_id;
+        session.moment_index = file.moments.iter().enumerate().map(|(idx, m)| (m.id.clone(), idx)).collect();
+        session.moments = file.moments;
+        session.archive = file.archive;
+        session.ice_caked = file.ice_caked;
+        Ok(session)
+    }
+    
+    /// New session continuing the one `bb` was handed over from. Each
+    /// recorded decision and ice-caked layer becomes a `PriorDecision` custom
+    /// moment, with the old rationale under `prior.rationale`, and layers
+    /// not superseded are ice-caked again, keeping their gate and phase and
+    /// pointing at the moment they were frozen under as `prior_moment_id`.
+    /// A decision ice-caked from a layer's moment comes back with the layer
+    /// only. The session then sits in
+    /// `MetaLearn` if the predecessor had reached it or `Complete`, and in
+    /// `Consolidate` otherwise.
+    pub fn resume_from_blackboard(bb: &Blackboard, clock: Arc<dyn Clock>) -> Self {
+        let mut session = Self::with_clock(&bb.current_task.id, clock);
+        session.description = bb.current_task.description.clone();
+        session.predecessor_session_id = Some(bb.session_id.clone());
+        let qualia = Qualia::from_metrics(0.1, 0.2, 0.6).with_affect(0.2, 0.2, 0.7);
+        let prior = |session: &Self, content: &str, rationale: &str| session.builder(content)
+            .custom("PriorDecision")
+            .meta("prior.session_id", &bb.session_id).expect("key is valid")
+            .meta("prior.rationale", rationale).expect("key is valid")
+            .with_qualia(qualia.clone());
+        let from_layer = |decision: &&Decision| decision.provenance.iter().any(|p| {
+            p.kind == ProvenanceKind::IceCake && bb.ice_cake_layers.iter().any(|layer| layer.decision_id == p.moment_id)
+        });
+        for decision in bb.decisions.iter().filter(|d| !from_layer(d)) {
+            let builder = prior(&session, &decision.choice, &decision.rationale);
+            session.try_record(builder).expect("session is open");
+        }
+        for layer in &bb.ice_cake_layers {
+            let builder = prior(&session, &layer.content, &layer.rationale);
+            let id = session.try_record(builder).expect("session is open").id.clone();
+            if layer.superseded_by.is_none() {
+                session.ice_cake_with_confidence(&id, &layer.rationale, layer.truth.clone()).expect("session is open");
+                let refrozen = session.ice_caked.last_mut().expect("layer was just ice-caked");
+                refrozen.gate_state = gate_from_label(&layer.gate_state).unwrap_or(refrozen.gate_state);
+                refrozen.phase = layer.phase.clone();
+                refrozen.prior_moment_id = Some(layer.decision_id.clone());
+            }
+        }
+        let phase = match bb.current_task.phase.as_str() {
+            "MetaLearn" | "Complete" => SessionPhase::MetaLearn,
+            _ => SessionPhase::Consolidate,
+        };
+        session.transition_to(phase, session.next_seq);
+        session
+    }
+    
+    /// Session whose moments are timestamped by `clock`
+    pub fn with_clock(task_id: &str, clock: Arc<dyn Clock>) -> Self {
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             task_id: task_id.to_string(),
+            description: String::new(),
+            predecessor_session_id: None,
             phase: SessionPhase::Initialize,
             progress: 0.0,
             moments: Vec::new(),
+            archive: Vec::new(),
             moment_index: HashMap::new(),
             ice_caked: Vec::new(),
+            ice_cake_conflicts: Vec::new(),
             cycle: 0,
-            started_at: Instant::now(),
-            last_activity: Instant::now(),
+            started_at: clock.now_millis(),
+            last_activity: clock.now_millis(),
+            importance_weights: ImportanceWeights::default(),
+            config: SessionConfig::default(),
+            max_edit_history: MAX_EDIT_HISTORY,
+            clock,
+            next_affect: None,
+            phase_log: Vec::new(),
+            phase_overrides: Vec::new(),
+            subtasks: Vec::new(),
+            style_changes: Vec::new(),
+            next_seq: 1,
+            pauses: Vec::new(),
+            report: None,
+            branch: None,
+            observers: Vec::new(),
+            next_subscription: 1,
+            undo_points: Vec::new(),
+            checkpoints: Vec::new(),
+            event_log: None,
         }
     }
     
@@ -85,110 +1138,1342 @@ impl LearningSession {
             task_id: self.task_id.clone(),
             phase: self.phase.clone(),
             progress: self.progress,
-            thinking_style: ThinkingStyle::default(),
+            thinking_style: self.config.thinking_style.clone().unwrap_or_default(),
             coherence: 0.5,
             ice_cake_layers: self.ice_caked.len() as u32,
-            moment_count: self.moments.len(),
-            breakthrough_count: self.moments.iter().filter(|m| m.is_breakthrough()).count(),
+            ice_caked: self.ice_caked.clone(),
+            moment_count: self.moment_count(),
+            breakthrough_count: self.breakthrough_count(),
+            moment_types: self.moment_type_counts(),
+            tags: self.tag_counts(),
+            metadata: self.metadata_values(),
+            duration_ms: self.active_ms(),
+            wall_clock_ms: self.span_ms(),
+            longest_gap_ms: self.longest_gap_ms(),
+            paused: self.is_paused(),
+            artifacts: self.moments.iter()
+                .flat_map(|m| m.artifacts.iter().map(move |a| (m.id.clone(), a.clone())))
+                .collect(),
+            trajectory: self.qualia_trajectory(),
+            key_moments: self.top_moments(KEY_MOMENTS).into_iter()
+                .map(|(m, importance)| KeyMoment { moment_id: m.id.clone(), content: m.content.clone(), importance })
+                .collect(),
+            open_hypotheses: self.open_hypotheses().into_iter().map(|m| m.content.clone()).collect(),
+            phase_overrides: self.phase_overrides.clone(),
+            metrics: self.metrics(),
+            style_distribution: self.style_distribution(),
+            outcome: self.report.as_ref().map(|report| report.outcome.clone()),
+            last_open_struggle: self.unresolved_struggles().last().map(|m| m.content.clone()),
+            gate_state: self.current_gate(),
+            predecessor_session_id: self.predecessor_session_id.clone(),
             cycle: self.cycle,
         }
     }
     
     pub fn encounter(&mut self, content: &str) -> &Moment {
-        self.transition_to(SessionPhase::Encounter);
-        let moment = MomentBuilder::new(&self.id, content)
-            .encounter()
-            .qualia(0.5, 0.2, 0.5)
-            .build();
-        self.add_moment(moment)
+        recorded(self.try_encounter(content))
+    }
+    
+    /// `encounter`, failing with `CaptureError` instead of panicking
+    pub fn try_encounter(&mut self, content: &str) -> Result<&Moment, CaptureError> {
+        let builder = self.builder(content).encounter().qualia(0.5, 0.2, 0.5);
+        self.try_record_in(SessionPhase::Encounter, builder)
+    }
+    
+    /// `encounter` with the calling Rust source location as the moment's source
+    #[track_caller]
+    pub fn encounter_here(&mut self, content: &str) -> &Moment {
+        recorded(self.try_encounter_here(content))
+    }
+    
+    /// `encounter_here`, failing with `CaptureError` instead of panicking
+    #[track_caller]
+    pub fn try_encounter_here(&mut self, content: &str) -> Result<&Moment, CaptureError> {
+        let source = SourceLocation::caller();
+        let builder = self.builder(content).encounter().qualia(0.5, 0.2, 0.5).source(source);
+        self.try_record_in(SessionPhase::Encounter, builder)
     }
     
     pub fn struggle(&mut self, content: &str, effort: f32, confusion: f32) -> &Moment {
-        self.transition_to(SessionPhase::Struggle);
-        let mut qualia = Qualia::from_metrics(0.3, effort, 0.3);
+        recorded(self.try_struggle(content, effort, confusion))
+    }
+    
+    /// `struggle`, failing with `CaptureError` instead of panicking
+    pub fn try_struggle(&mut self, content: &str, effort: f32, confusion: f32) -> Result<&Moment, CaptureError> {
+        let mut qualia = Qualia::from_metrics(0.3, effort, 0.3).with_affect(-0.3, 0.6, 0.3);
         qualia.confusion = confusion;
-        let moment = MomentBuilder::new(&self.id, content)
+        let builder = self.builder(content)
             .struggle()
-            .build()
             .with_qualia(qualia);
-        self.add_moment(moment)
+        self.try_record_in(SessionPhase::Struggle, builder)
     }
     
+    /// `fail_with_frustration` at `DEFAULT_FAILURE_FRUSTRATION`
     pub fn fail(&mut self, content: &str, lesson: &str) -> &Moment {
-        let mut qualia = Qualia::from_metrics(0.4, 0.8, 0.2);
+        recorded(self.try_fail(content, lesson))
+    }
+    
+    /// `fail`, failing with `CaptureError` instead of panicking
+    pub fn try_fail(&mut self, content: &str, lesson: &str) -> Result<&Moment, CaptureError> {
+        self.try_fail_with_frustration(content, lesson, DEFAULT_FAILURE_FRUSTRATION)
+    }
+    
+    /// Failure with `frustration` as its `confusion`, which
+    /// `Blackboard::promote_failure_risks` compares with its threshold
+    pub fn fail_with_frustration(&mut self, content: &str, lesson: &str, frustration: f32) -> &Moment {
+        recorded(self.try_fail_with_frustration(content, lesson, frustration))
+    }
+    
+    /// `fail_with_frustration`, failing with `CaptureError` instead of panicking
+    pub fn try_fail_with_frustration(&mut self, content: &str, lesson: &str, frustration: f32) -> Result<&Moment, CaptureError> {
+        let mut qualia = Qualia::from_metrics(0.4, 0.8, 0.2).with_affect(-0.7, 0.7, 0.4);
         qualia.surprise = 0.6;
-        let moment = MomentBuilder::new(&self.id, &format!("{} | Lesson: {}", content, lesson))
+        qualia.confusion = frustration;
+        let builder = self.builder(&format!("{} | Lesson: {}", content, lesson))
             .failure()
-            .build()
             .with_qualia(qualia);
-        self.add_moment(moment)
+        self.try_record(builder)
     }
     
+    /// Breakthrough whose effort is `estimate_effort`; the inputs are kept
+    /// in the moment's metadata under `effort.*`
     pub fn breakthrough(&mut self, content: &str, satisfaction: f32) -> &Moment {
-        self.transition_to(SessionPhase::Breakthrough);
-        let qualia = Qualia::from_metrics(0.8, 0.6, satisfaction);
-        let moment = MomentBuilder::new(&self.id, content)
-            .breakthrough()
-            .build()
-            .with_qualia(qualia);
-        self.add_moment(moment)
+        recorded(self.try_breakthrough(content, satisfaction))
     }
     
-    pub fn ice_cake(&mut self, moment_id: &str, rationale: &str) -> Option<&IceCakedDecision> {
-        self.transition_to(SessionPhase::Consolidate);
-        let moment = self.get_moment(moment_id)?;
+    /// `breakthrough`, failing with `CaptureError` instead of panicking
+    pub fn try_breakthrough(&mut self, content: &str, satisfaction: f32) -> Result<&Moment, CaptureError> {
+        let inputs = self.effort_inputs();
+        let qualia = Qualia::from_metrics(0.8, inputs.effort(), satisfaction).with_affect(0.8, 0.7, 0.8);
+        let builder = inputs.annotate(self.builder(content).breakthrough().with_qualia(qualia));
+        self.try_record_in(SessionPhase::Breakthrough, builder)
+    }
+    
+    /// Breakthrough with explicit qualia, effort included
+    pub fn breakthrough_with_qualia(&mut self, content: &str, qualia: Qualia) -> &Moment {
+        recorded(self.try_breakthrough_with_qualia(content, qualia))
+    }
+    
+    /// `breakthrough_with_qualia`, failing with `CaptureError` instead of panicking
+    pub fn try_breakthrough_with_qualia(&mut self, content: &str, qualia: Qualia) -> Result<&Moment, CaptureError> {
+        let builder = self.builder(content).breakthrough().with_qualia(qualia);
+        self.try_record_in(SessionPhase::Breakthrough, builder)
+    }
+    
+    /// Breakthrough linked as the resolution of an earlier struggle, with
+    /// effort estimated like `breakthrough`
+    pub fn breakthrough_resolving(&mut self, struggle_id: &str, content: &str, novelty: f32) -> &Moment {
+        recorded(self.try_breakthrough_resolving(struggle_id, content, novelty))
+    }
+    
+    /// `breakthrough_resolving`, failing with `CaptureError` instead of panicking
+    pub fn try_breakthrough_resolving(&mut self, struggle_id: &str, content: &str, novelty: f32) -> Result<&Moment, CaptureError> {
+        let inputs = self.effort_inputs();
+        let qualia = Qualia::from_metrics(novelty, inputs.effort(), 0.9).with_affect(0.8, 0.7, 0.8);
+        let builder = self.builder(content)
+            .breakthrough()
+            .resolves(struggle_id)
+            .with_qualia(qualia);
+        self.try_record_in(SessionPhase::Breakthrough, inputs.annotate(builder))
+    }
+    
+    /// Effort behind a breakthrough recorded now, from the moments since the
+    /// last breakthrough (or the session start):
+    ///
+    /// `effort = 1 − exp(−(0.15·struggles + 0.2·failures + 0.25·Σfrustration + 0.02·moments))`
+    ///
+    /// where Σfrustration sums `confusion` over those struggles and failures.
+    /// A single encounter gives about 0.02; five frustrated struggles about 0.85.
+    pub fn estimate_effort(&self) -> f32 {
+        self.effort_inputs().effort()
+    }
+    
+    fn effort_inputs(&self) -> EffortInputs {
+        let since = self.moments.iter()
+            .rposition(|m| m.moment_type == MomentType::Breakthrough)
+            .map_or(0, |idx| idx + 1);
+        let mut inputs = EffortInputs { struggles: 0, failures: 0, frustration: 0.0, moments: 0 };
+        for moment in &self.moments[since..] {
+            inputs.moments += 1;
+            match moment.moment_type {
+                MomentType::Struggle => inputs.struggles += 1,
+                MomentType::Failure => inputs.failures += 1,
+                _ => continue,
+            }
+            inputs.frustration += moment.qualia.confusion;
+        }
+        inputs
+    }
+    
+    /// Add a link from one recorded moment to another. Returns false if the
+    /// source is unknown; the target is checked by `validate_links`.
+    pub fn link(&mut self, source_id: &str, kind: LinkKind, target_id: &str) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        match self.moment_index.get(source_id) {
+            Some(&idx) => {
+                self.moments[idx].links.push(MomentLink { target_id: target_id.to_string(), kind });
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// (struggle, breakthrough) pairs where the breakthrough resolves the struggle
+    pub fn resolution_pairs(&self) -> Vec<(&Moment, &Moment)> {
+        self.moments.iter()
+            .filter(|m| m.moment_type == MomentType::Breakthrough)
+            .flat_map(|b| b.links_of(LinkKind::Resolves).map(move |target| (target, b)))
+            .filter_map(|(target, b)| {
+                let struggle = self.get_moment(target)?;
+                (struggle.moment_type == MomentType::Struggle).then_some((struggle, b))
+            })
+            .collect()
+    }
+    
+    /// Whether the session is going in circles: the last `window` moments
+    /// (at least two) are all struggles or failures, every pair of them
+    /// reaches `DEFAULT_STUCK_SIMILARITY` by `Fingerprint::from_words`, and
+    /// their confusion never decreases.
+    pub fn stuck_report(&self, window: usize) -> Option<StuckReport> {
+        if window < 2 {
+            return None;
+        }
+        let ordered = self.moments_ordered();
+        let recent = &ordered[ordered.len().checked_sub(window)?..];
+        let stuck_type = |m: &Moment| matches!(m.moment_type, MomentType::Struggle | MomentType::Failure);
+        if !recent.iter().all(|m| stuck_type(m)) {
+            return None;
+        }
+        if recent.windows(2).any(|pair| pair[1].qualia.confusion < pair[0].qualia.confusion) {
+            return None;
+        }
+        let words: Vec<Fingerprint> = recent.iter().map(|m| Fingerprint::from_words(&m.content)).collect();
+        let mut closeness = vec![0.0f32; window];
+        for i in 0..window {
+            for j in i + 1..window {
+                let similarity = words[i].similarity(&words[j]);
+                if similarity < DEFAULT_STUCK_SIMILARITY {
+                    return None;
+                }
+                closeness[i] += similarity;
+                closeness[j] += similarity;
+            }
+        }
+        let best = (0..window).max_by(|&a, &b| closeness[a].total_cmp(&closeness[b]).then(b.cmp(&a)))?;
+        let representative = recent[best];
+        let repetitions = ordered.iter().rev()
+            .take_while(|m| stuck_type(m) && words[best].similarity(&Fingerprint::from_words(&m.content)) >= DEFAULT_STUCK_SIMILARITY)
+            .count();
+        Some(StuckReport {
+            representative_id: representative.id.clone(),
+            representative: representative.content.clone(),
+            repetitions,
+            frustration: recent.iter().map(|m| m.qualia.confusion).collect(),
+            suggestions: Vec::new(),
+        })
+    }
+    
+    /// `stuck_report` with up to three suggestions from `resonance`: moments
+    /// resonating with the representative struggle, other than the window's
+    pub fn stuck_report_with(&self, window: usize, resonance: &mut ResonanceCapture, current_cycle: u64) -> Option<StuckReport> {
+        let mut report = self.stuck_report(window)?;
+        let representative = self.get_moment(&report.representative_id)?;
+        let window_ids: Vec<&str> = self.moments_ordered().iter().rev().take(window).map(|m| m.id.as_str()).collect();
+        report.suggestions = resonance
+            .find_resonant(&representative.resonance_vector, STUCK_SUGGESTION_RESONANCE, window + 3, current_cycle, None)
+            .into_iter()
+            .filter(|s| !window_ids.contains(&s.moment_id.as_str()))
+            .take(3)
+            .collect();
+        Some(report)
+    }
+    
+    /// Past moments from other sessions resonating with the latest struggle,
+    /// strongest first; empty without a struggle
+    pub fn suggest_from_resonance(&self, resonance: &mut ResonanceCapture, cycle: u64) -> Vec<Suggestion> {
+        let Some(struggle) = self.moments_ordered().into_iter().rev().find(|m| m.moment_type == MomentType::Struggle) else {
+            return Vec::new();
+        };
+        resonance
+            .find_resonant(&struggle.resonance_vector, DEFAULT_SUGGESTION_RESONANCE, usize::MAX, cycle, None)
+            .into_iter()
+            .filter(|similar| similar.session_id != self.id)
+            .take(MAX_SUGGESTIONS)
+            .map(|similar| Suggestion {
+                confidence: similar.truth(),
+                moment_id: similar.moment_id,
+                session_id: similar.session_id,
+                task_id: similar.task_id,
+                moment_type: similar.moment_type,
+                summary: similar.summary,
+                resonance: similar.resonance,
+            })
+            .collect()
+    }
+    
+    /// Record a meta-reflection on a stuck report, linked to its
+    /// representative struggle. Does not change the session phase.
+    pub fn reflect_on_stuck(&mut self, report: &StuckReport) -> &Moment {
+        recorded(self.try_reflect_on_stuck(report))
+    }
+    
+    /// `reflect_on_stuck`, failing with `CaptureError` instead of panicking
+    pub fn try_reflect_on_stuck(&mut self, report: &StuckReport) -> Result<&Moment, CaptureError> {
+        let content = format!("Going in circles: \"{}\" restated {} times", report.representative, report.repetitions);
+        let qualia = Qualia::from_metrics(0.3, 0.6, 0.2).with_affect(-0.3, 0.5, 0.5);
+        let builder = self.builder(&content)
+            .meta_reflection()
+            .link(LinkKind::CausedBy, &report.representative_id)
+            .meta("stuck.repetitions", &report.repetitions.to_string()).expect("key is valid")
+            .with_qualia(qualia);
+        self.try_record(builder)
+    }
+    
+    /// Pair each breakthrough with the struggle it resolved. An explicit
+    /// `Resolves` link wins; otherwise the breakthrough takes the most similar
+    /// earlier struggle that is not yet resolved, if any reaches
+    /// `min_similarity`. Pairs come in breakthrough order.
+    pub fn infer_resolutions(&self, min_similarity: f32) -> Vec<ResolutionPair> {
+        let words: Vec<Fingerprint> = self.moments.iter().map(|m| Fingerprint::from_words(&m.content)).collect();
+        let pair = |s: usize, b: usize| ResolutionPair {
+            struggle_id: self.moments[s].id.clone(),
+            breakthrough_id: self.moments[b].id.clone(),
+            similarity: words[s].similarity(&words[b]),
+            intervening: b.saturating_sub(s + 1),
+        };
+        
+        let mut resolved: Vec<usize> = self.resolution_pairs().iter()
+            .filter_map(|(struggle, _)| self.moment_index.get(&struggle.id).copied())
+            .collect();
+        let mut pairs = Vec::new();
+        for (b, moment) in self.moments.iter().enumerate() {
+            if moment.moment_type != MomentType::Breakthrough {
+                continue;
+            }
+            let linked: Vec<usize> = moment.links_of(LinkKind::Resolves)
+                .filter_map(|target| self.moment_index.get(target).copied())
+                .filter(|&s| self.moments[s].moment_type == MomentType::Struggle)
+                .collect();
+            if !linked.is_empty() {
+                pairs.extend(linked.into_iter().map(|s| pair(s, b)));
+                continue;
+            }
+            let best = (0..b)
+                .filter(|&s| self.moments[s].moment_type == MomentType::Struggle && !resolved.contains(&s))
+                .map(|s| (s, words[s].similarity(&words[b])))
+                .filter(|&(_, similarity)| similarity >= min_similarity)
+                .max_by(|x, y| x.1.total_cmp(&y.1));
+            if let Some((s, _)) = best {
+                resolved.push(s);
+                pairs.push(pair(s, b));
+            }
+        }
+        pairs
+    }
+    
+    /// Struggles `infer_resolutions` pairs with no breakthrough at
+    /// `DEFAULT_RESOLUTION_SIMILARITY`, in recording order
+    pub fn unresolved_struggles(&self) -> Vec<&Moment> {
+        let resolved: Vec<String> = self.infer_resolutions(DEFAULT_RESOLUTION_SIMILARITY).into_iter()
+            .map(|pair| pair.struggle_id)
+            .collect();
+        self.moments_ordered().into_iter()
+            .filter(|m| m.moment_type == MomentType::Struggle && !resolved.contains(&m.id))
+            .collect()
+    }
+    
+    /// The unresolved struggle with the most effort plus frustration, the
+    /// later one on a tie; what an abandoned session's anti-pattern is
+    /// extracted from
+    pub fn dominant_unresolved_struggle(&self) -> Option<&Moment> {
+        self.unresolved_struggles().into_iter()
+            .max_by(|a, b| (a.qualia.effort + a.qualia.confusion).total_cmp(&(b.qualia.effort + b.qualia.confusion)))
+    }
+    
+    pub fn validate_links(&self) -> Result<(), DanglingLinks> {
+        let links: Vec<(String, MomentLink)> = self.moments.iter()
+            .flat_map(|m| m.links.iter().map(move |l| (m.id.clone(), l.clone())))
+            .filter(|(_, l)| !self.moment_index.contains_key(&l.target_id))
+            .collect();
+        if links.is_empty() { Ok(()) } else { Err(DanglingLinks { links }) }
+    }
+    
+    /// Freeze a moment as a committed decision. Fails without touching the
+    /// session if no moment has `moment_id`.
+    pub fn ice_cake(&mut self, moment_id: &str, rationale: &str) -> Result<&IceCakedDecision, IceCakeError> {
+        self.freeze(moment_id, rationale, TruthValue::certain_true(), false)
+    }
+    
+    /// `ice_cake` with an explicit truth. Earlier live decisions whose
+    /// rationale reaches `config.supersede_similarity` are marked superseded
+    /// by this one; those that agree with it (frequency on the same side of
+    /// 0.5) are revised into its truth.
+    pub fn ice_cake_with_confidence(&mut self, moment_id: &str, rationale: &str, truth: TruthValue) -> Result<&IceCakedDecision, IceCakeError> {
+        self.freeze(moment_id, rationale, truth, true)
+    }
+    
+    fn freeze(&mut self, moment_id: &str, rationale: &str, truth: TruthValue, supersede: bool) -> Result<&IceCakedDecision, IceCakeError> {
+        self.ensure_open()?;
+        if !self.moment_index.contains_key(moment_id) {
+            return Err(IceCakeError::UnknownMoment(moment_id.to_string()));
+        }
+        self.enter(SessionPhase::Consolidate, self.next_seq);
+        let moment = self.get_moment(moment_id).expect("moment id was checked");
         let scores = vec![moment.qualia.satisfaction, 1.0 - moment.qualia.confusion];
-        let decision = evaluate_gate(&scores, false);
+        let decision = evaluate_gate_with_clock(&scores, false, self.clock.as_ref());
+        let phase = self.phase_at_seq(moment.seq);
+        
+        let mut sources: Vec<SourceLocation> = Vec::new();
+        let linked = moment.links.iter().filter_map(|l| self.get_moment(&l.target_id));
+        for source in std::iter::once(moment).chain(linked).filter_map(|m| m.source.as_ref()) {
+            if !sources.contains(source) {
+                sources.push(source.clone());
+            }
+        }
         
-        let ice_caked = IceCakedDecision {
+        let mut ice_caked = IceCakedDecision {
             moment_id: moment_id.to_string(),
             content: moment.content.clone(),
+            sources,
             rationale: rationale.to_string(),
             gate_state: decision.state,
             ice_caked_at_cycle: self.cycle,
+            after_reversal: self.reversals_of(moment_id).last().map(|m| m.id.clone()),
+            truth,
+            superseded_by: None,
+            phase: Some(phase),
+            prior_moment_id: None,
         };
-        
+        if supersede {
+            let words = Fingerprint::from_words(rationale);
+            for older in self.ice_caked.iter_mut().filter(|d| d.superseded_by.is_none()) {
+                if words.similarity(&Fingerprint::from_words(&older.rationale)) < self.config.supersede_similarity {
+                    continue;
+                }
+                if (older.truth.frequency >= 0.5) == (ice_caked.truth.frequency >= 0.5) {
+                    ice_caked.truth = older.truth.revision(&ice_caked.truth);
+                }
+                older.superseded_by = Some(moment_id.to_string());
+            }
+        }
+        let layer = IceCakedLayer { layer_id: self.ice_caked.len() as u32 + 1, ..IceCakedLayer::from(&ice_caked) };
         self.ice_caked.push(ice_caked);
-        self.ice_caked.last()
+        self.log_event(|_| SessionEvent::IceCaked {
+            layer_id: layer.layer_id,
+            moment_id: layer.decision_id.clone(),
+            rationale: layer.rationale.clone(),
+        });
+        self.notify(|observer, _| observer.on_ice_cake(&layer));
+        Ok(self.ice_caked.last().expect("decision was just pushed"))
+    }
+    
+    /// Take back an ice-caked decision: it leaves `ice_caked` (later layers
+    /// move up one) and a `DecisionReversal` moment with `reason` as content
+    /// is recorded, linked to the decision's moment and carrying the old
+    /// rationale under `thawed_rationale`. Returns the layer as it was.
+    pub fn thaw(&mut self, target: impl Into<ThawTarget>, reason: &str) -> Result<IceCakedLayer, ThawError> {
+        self.ensure_open()?;
+        self.ensure_active()?;
+        let idx = match target.into() {
+            ThawTarget::Layer(layer_id) => (layer_id as usize).checked_sub(1)
+                .filter(|&idx| idx < self.ice_caked.len())
+                .ok_or(ThawError::UnknownLayer(layer_id))?,
+            ThawTarget::Moment(moment_id) => self.ice_caked.iter()
+                .position(|d| d.moment_id == moment_id)
+                .ok_or(ThawError::NotIceCaked(moment_id))?,
+        };
+        let decision = self.ice_caked.remove(idx);
+        let mut layer = IceCakedLayer::from(&decision);
+        layer.layer_id = idx as u32 + 1;
+        self.log_event(|_| SessionEvent::Thawed {
+            layer_id: layer.layer_id,
+            moment_id: decision.moment_id.clone(),
+            reason: reason.to_string(),
+        });
+        
+        let qualia = Qualia::from_metrics(0.4, 0.5, 0.3).with_affect(-0.2, 0.5, 0.6);
+        let builder = self.builder(reason)
+            .decision_reversal()
+            .link(LinkKind::Contradicts, &decision.moment_id)
+            .meta("thawed_rationale", &decision.rationale).expect("key is valid")
+            .with_qualia(qualia);
+        self.try_record(builder).expect("session is open");
+        Ok(layer)
+    }
+    
+    /// `DecisionReversal` moments that thawed a decision on `moment_id`, in
+    /// recording order
+    pub fn reversals_of(&self, moment_id: &str) -> Vec<&Moment> {
+        self.moments_ordered().into_iter()
+            .filter(|m| m.moment_type == MomentType::DecisionReversal)
+            .filter(|m| m.links.iter().any(|l| l.kind == LinkKind::Contradicts && l.target_id == moment_id))
+            .collect()
     }
     
     pub fn apply(&mut self, content: &str, success: bool) -> &Moment {
-        self.transition_to(SessionPhase::Apply);
+        recorded(self.try_apply(content, success))
+    }
+    
+    /// `apply`, failing with `CaptureError` instead of panicking
+    pub fn try_apply(&mut self, content: &str, success: bool) -> Result<&Moment, CaptureError> {
         let satisfaction = if success { 0.9 } else { 0.4 };
-        let qualia = Qualia::from_metrics(0.2, 0.3, satisfaction);
-        let moment = MomentBuilder::new(&self.id, content).build().with_qualia(qualia);
-        self.add_moment(moment)
+        let valence = if success { 0.6 } else { -0.3 };
+        let confidence = if success { 0.7 } else { 0.4 };
+        let qualia = Qualia::from_metrics(0.2, 0.3, satisfaction).with_affect(valence, 0.3, confidence);
+        let builder = self.builder(content).encounter().with_qualia(qualia);
+        self.try_record_in(SessionPhase::Apply, builder)
     }
     
     pub fn meta_reflect(&mut self, reflection: &str) -> &Moment {
-        self.transition_to(SessionPhase::MetaLearn);
+        recorded(self.try_meta_reflect(reflection))
+    }
+    
+    /// `meta_reflect`, failing with `CaptureError` instead of panicking
+    pub fn try_meta_reflect(&mut self, reflection: &str) -> Result<&Moment, CaptureError> {
         let breakthrough_count = self.moments.iter().filter(|m| m.is_breakthrough()).count();
         let novelty = if breakthrough_count > 0 { 0.7 } else { 0.3 };
-        let qualia = Qualia::from_metrics(novelty, 0.4, 0.8);
-        let moment = MomentBuilder::new(&self.id, reflection).build().with_qualia(qualia);
-        self.add_moment(moment)
+        let qualia = Qualia::from_metrics(novelty, 0.4, 0.8).with_affect(0.3, 0.2, 0.6);
+        let builder = self.builder(reflection).encounter().with_qualia(qualia);
+        self.try_record_in(SessionPhase::MetaLearn, builder)
+    }
+    
+    /// Record a user-defined moment kind. Does not change the session phase.
+    pub fn custom_moment(&mut self, kind: &str, content: &str, qualia: Qualia) -> &Moment {
+        recorded(self.try_custom_moment(kind, content, qualia))
+    }
+    
+    /// `custom_moment`, failing with `CaptureError` instead of panicking
+    pub fn try_custom_moment(&mut self, kind: &str, content: &str, qualia: Qualia) -> Result<&Moment, CaptureError> {
+        let builder = self.builder(content)
+            .custom(kind)
+            .with_qualia(qualia);
+        self.try_record(builder)
+    }
+    
+    /// Record a guess with a prior belief in it. Does not change the session phase.
+    pub fn hypothesize(&mut self, content: &str, prior: TruthValue) -> &Moment {
+        recorded(self.try_hypothesize(content, prior))
+    }
+    
+    /// `hypothesize`, failing with `CaptureError` instead of panicking
+    pub fn try_hypothesize(&mut self, content: &str, prior: TruthValue) -> Result<&Moment, CaptureError> {
+        let qualia = Qualia::from_metrics(0.6, 0.3, 0.4).with_affect(0.1, 0.4, prior.confidence);
+        let builder = self.builder(content)
+            .hypothesis()
+            .truth(prior)
+            .with_qualia(qualia);
+        self.try_record(builder)
+    }
+    
+    /// Record the outcome of checking a hypothesis and revise the hypothesis'
+    /// truth with it as one unit of positive or negative evidence. Returns
+    /// None if `hypothesis_id` is not a recorded hypothesis or the session
+    /// cannot capture.
+    pub fn verify(&mut self, hypothesis_id: &str, confirmed: bool, note: &str) -> Option<&Moment> {
+        self.ensure_open().ok()?;
+        self.ensure_active().ok()?;
+        let &idx = self.moment_index.get(hypothesis_id)?;
+        if self.moments[idx].moment_type != MomentType::Hypothesis {
+            return None;
+        }
+        let observation = if confirmed {
+            TruthValue::from_evidence(1.0, 0.0)
+        } else {
+            TruthValue::from_evidence(0.0, 1.0)
+        };
+        let hypothesis = &mut self.moments[idx];
+        hypothesis.truth = hypothesis.truth.revision(&observation);
+        
+        let qualia = if confirmed {
+            Qualia::from_metrics(0.3, 0.4, 0.8).with_affect(0.6, 0.4, 0.7)
+        } else {
+            let mut refuted = Qualia::from_metrics(0.5, 0.4, 0.2).with_affect(-0.4, 0.6, 0.5);
+            refuted.surprise = 0.6;
+            refuted
+        };
+        let builder = self.builder(note)
+            .verification(confirmed)
+            .link(LinkKind::Verifies, hypothesis_id)
+            .truth(observation)
+            .with_qualia(qualia);
+        self.try_record(builder).ok()
+    }
+    
+    /// Hypotheses no verification points at yet, in recording order
+    pub fn open_hypotheses(&self) -> Vec<&Moment> {
+        self.moments.iter()
+            .filter(|m| m.moment_type == MomentType::Hypothesis)
+            .filter(|h| !self.moments.iter().any(|m| m.links_of(LinkKind::Verifies).any(|t| t == h.id)))
+            .collect()
+    }
+    
+    /// Convenience helpers fail only with `CaptureError`: they build
+    /// leniently, so out-of-range qualia (e.g. a `confusion` above 1 passed
+    /// to `struggle`) are clamped, content over `config.max_content_chars`
+    /// is summarized and empty content is accepted.
+    fn try_record(&mut self, builder: MomentBuilder) -> Result<&Moment, CaptureError> {
+        let moment = builder.lenient().try_build().expect("helpers always set a moment type");
+        match self.config.dedup.clone() {
+            Some(policy) => {
+                let id = match self.dedupe(moment, policy.similarity_threshold, policy.window)? {
+                    AddOutcome::Added(id) | AddOutcome::Merged { id, .. } => id,
+                };
+                Ok(&self.moments[self.moment_index[&id]])
+            }
+            None => self.add_moment(moment),
+        }
+    }
+    
+    /// Record a moment unless it repeats a recent one: a moment of the same
+    /// type among the last `config.dedup` window (or `DedupPolicy::default`
+    /// window) whose content reaches `similarity_threshold` by
+    /// `Fingerprint::from_words`. A repeat is merged into the most similar
+    /// match with `Moment::merge_repeat`.
+    pub fn add_moment_deduped(&mut self, moment: Moment, similarity_threshold: f32) -> Result<AddOutcome, CaptureError> {
+        let window = self.config.dedup.as_ref().map_or(DedupPolicy::new().window, |p| p.window);
+        self.dedupe(moment, similarity_threshold, window)
+    }
+    
+    fn dedupe(&mut self, moment: Moment, similarity_threshold: f32, window: usize) -> Result<AddOutcome, CaptureError> {
+        self.ensure_open()?;
+        self.ensure_active()?;
+        let moment = self.with_next_affect(moment);
+        Ok(match self.find_repeat(&moment, similarity_threshold, window) {
+            Some(idx) => {
+                let existing = &mut self.moments[idx];
+                existing.merge_repeat(&moment);
+                self.last_activity = self.clock.now_millis();
+                AddOutcome::Merged { id: existing.id.clone(), repeat_count: existing.repeat_count }
+            }
+            None => AddOutcome::Added(self.add_moment(moment)?.id.clone()),
+        })
+    }
+    
+    /// Index of the moment among the last `window` that `moment` repeats
+    fn find_repeat(&self, moment: &Moment, similarity_threshold: f32, window: usize) -> Option<usize> {
+        let words = Fingerprint::from_words(&moment.content);
+        let recent = self.moments.len().saturating_sub(window);
+        (recent..self.moments.len())
+            .filter(|&idx| self.moments[idx].moment_type == moment.moment_type)
+            .map(|idx| (idx, words.similarity(&Fingerprint::from_words(&self.moments[idx].content))))
+            .filter(|&(_, similarity)| similarity >= similarity_threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(idx, _)| idx)
+    }
+    
+    fn builder(&self, content: &str) -> MomentBuilder {
+        let builder = MomentBuilder::new(&self.id, content)
+            .created_at(self.clock.now_millis())
+            .summarize_over(self.config.max_content_chars);
+        let builder = match &self.config.thinking_style {
+            Some(style) => builder.style(style.clone()),
+            None => builder,
+        };
+        self.config.default_tags.iter().fold(builder, |b, tag| b.tag(tag))
+    }
+    
+    /// Override valence, arousal and confidence for the next moment recorded
+    /// by any helper, e.g. `session.with_affect(-0.5, 0.9, 0.4).breakthrough(..)`
+    /// for a breakthrough that felt bad but important.
+    pub fn with_affect(&mut self, valence: f32, arousal: f32, confidence: f32) -> &mut Self {
+        self.next_affect = Some((valence, arousal, confidence));
+        self
+    }
+    
+    fn with_next_affect(&mut self, moment: Moment) -> Moment {
+        match self.next_affect.take() {
+            Some((valence, arousal, confidence)) => {
+                let qualia = moment.qualia.clone().with_affect(valence, arousal, confidence);
+                moment.with_qualia(qualia)
+            }
+            None => moment,
+        }
+    }
+    
+    fn add_moment(&mut self, moment: Moment) -> Result<&Moment, CaptureError> {
+        self.ensure_open()?;
+        self.ensure_active()?;
+        let mut moment = self.with_next_affect(moment);
+        moment.seq = self.next_seq;
+        moment.subtask_id = self.open_subtasks().last().map(|s| s.id.0.clone());
+        if moment.moment_type == MomentType::Struggle && moment.gate_state.is_none() {
+            moment.gate_state = self.struggle_gate(&moment.qualia);
+        }
+        if let Some(cap) = self.config.max_live_moments {
+            let targets: Vec<&str> = moment.links.iter().map(|l| l.target_id.as_str()).collect();
+            self.archive_down_to(cap.saturating_sub(1), &targets);
+        }
+        if self.undo_points.len() == MAX_UNDO_DEPTH {
+            self.undo_points.remove(0);
+        }
+        self.undo_points.push(UndoPoint {
+            moment_id: moment.id.clone(),
+            phase: self.phase.clone(),
+            progress: self.progress,
+            phase_log_len: self.phase_log.len(),
+        });
+        Ok(self.insert_moment(moment))
+    }
+    
+    /// Gate for a struggle with `qualia` recorded now, per `config.struggle_gate`
+    fn struggle_gate(&self, qualia: &Qualia) -> Option<GateState> {
+        let config = self.config.struggle_gate.as_ref()?;
+        let mut struggles: Vec<&Moment> = self.moments.iter().filter(|m| m.moment_type == MomentType::Struggle).collect();
+        struggles.sort_by_key(|m| m.seq);
+        let earlier = &struggles[struggles.len().saturating_sub(config.window.saturating_sub(1))..];
+        let frustration: Vec<f32> = earlier.iter().map(|m| m.qualia.confusion).chain([qualia.confusion]).collect();
+        let uncertainty: Vec<f32> = earlier.iter().map(|m| 1.0 - m.qualia.confidence).chain([1.0 - qualia.confidence]).collect();
+        Some(config.gate(calculate_sd(&frustration).max(calculate_sd(&uncertainty))))
+    }
+    
+    /// Gate of the latest live struggle; None without one or with
+    /// `config.struggle_gate` off when it was recorded
+    pub fn current_gate(&self) -> Option<GateState> {
+        self.moments.iter()
+            .filter(|m| m.moment_type == MomentType::Struggle)
+            .max_by_key(|m| m.seq)
+            .and_then(|m| m.gate_state)
+    }
+    
+    /// Remove the most recent moment, e.g. a capture typed by mistake, and
+    /// return it. The session's cycle and next `seq` go back by one, and for
+    /// the latest `MAX_UNDO_DEPTH` moments recorded here, the phase and
+    /// progress go back to what they were before the moment was recorded,
+    /// dropping any phase change made since. Moments archived to make room
+    /// for it stay archived. Refused if the moment is ice-caked, linked to
+    /// from another moment, or was captured with `MetaAGI::capture_moment`.
+    pub fn undo_last(&mut self) -> Result<Moment, UndoError> {
+        self.ensure_open()?;
+        let latest = self.moments.iter().max_by_key(|m| m.seq).ok_or(UndoError::Empty)?;
+        let id = latest.id.clone();
+        if self.is_ice_caked(&id) {
+            return Err(UndoError::IceCaked(id));
+        }
+        if let Some(source) = self.moments.iter().find(|m| m.id != id && m.links.iter().any(|l| l.target_id == id)) {
+            return Err(UndoError::LinkTarget { id, source_id: source.id.clone() });
+        }
+        if latest.captured_externally {
+            return Err(UndoError::CapturedExternally(id));
+        }
+        
+        let idx = self.moment_index[&id];
+        let moment = self.moments.remove(idx);
+        self.moment_index = self.moments.iter().enumerate().map(|(idx, m)| (m.id.clone(), idx)).collect();
+        self.cycle = self.cycle.saturating_sub(1);
+        if self.next_seq == moment.seq + 1 {
+            self.next_seq = moment.seq;
+            for entry in self.phase_log.iter_mut().filter(|(_, _, first_seq)| *first_seq > moment.seq) {
+                entry.2 = moment.seq;
+            }
+        }
+        if self.undo_points.last().is_some_and(|point| point.moment_id == id) {
+            let point = self.undo_points.pop().expect("last was checked");
+            self.phase_log.truncate(point.phase_log_len);
+            self.phase = point.phase;
+            self.progress = point.progress;
+        } else {
+            self.undo_points.clear();
+        }
+        self.last_activity = self.clock.now_millis();
+        Ok(moment)
+    }
+    
+    /// Remember the session's current moments, phase and counters under
+    /// `name`, e.g. before trying something that may go nowhere. Checkpoints
+    /// live in memory only and are not saved or forked.
+    pub fn checkpoint(&mut self, name: &str) -> Result<CheckpointId, SessionClosedError> {
+        self.ensure_open()?;
+        let id = CheckpointId(uuid::Uuid::new_v4().to_string());
+        self.checkpoints.push(Checkpoint {
+            id: id.clone(),
+            name: name.to_string(),
+            next_seq: self.next_seq,
+            cycle: self.cycle,
+            phase: self.phase.clone(),
+            progress: self.progress,
+            phase_log_len: self.phase_log.len(),
+            ice_caked_len: self.ice_caked.len(),
+        });
+        Ok(id)
+    }
+    
+    /// Names of the checkpoints `restore_checkpoint` accepts, oldest first
+    pub fn checkpoint_names(&self) -> Vec<&str> {
+        self.checkpoints.iter().map(|c| c.name.as_str()).collect()
+    }
+    
+    /// Go back to checkpoint `id`: moments recorded since, live or archived,
+    /// are discarded, and the phase, progress, cycle and next `seq` return
+    /// to what they were. Checkpoints taken after `id` are invalidated; `id`
+    /// itself stays usable. Refused, changing nothing, if a moment to discard
+    /// is protected the way `undo_last` protects one. Decisions ice-caked
+    /// since on older moments, subtasks and style changes are kept.
+    pub fn restore_checkpoint(&mut self, id: &CheckpointId) -> Result<RestoredReport, RestoreError> {
+        self.ensure_open()?;
+        let idx = self.checkpoints.iter().position(|c| c.id == *id).ok_or_else(|| RestoreError::UnknownCheckpoint(id.clone()))?;
+        let watermark = self.checkpoints[idx].next_seq;
+        for moment in self.moments.iter().filter(|m| m.seq >= watermark) {
+            if self.is_ice_caked(&moment.id) {
+                return Err(RestoreError::IceCaked(moment.id.clone()));
+            }
+            if let Some(source) = self.moments.iter().find(|m| m.seq < watermark && m.links.iter().any(|l| l.target_id == moment.id)) {
+                return Err(RestoreError::LinkTarget { id: moment.id.clone(), source_id: source.id.clone() });
+            }
+            if moment.captured_externally {
+                return Err(RestoreError::CapturedExternally(moment.id.clone()));
+            }
+        }
+        
+        let invalidated = self.checkpoints.drain(idx + 1..).map(|c| c.name).collect();
+        let checkpoint = self.checkpoints[idx].clone();
+        let (mut discarded, kept): (Vec<Moment>, Vec<Moment>) = std::mem::take(&mut self.moments).into_iter().partition(|m| m.seq >= watermark);
+        discarded.sort_by_key(|m| m.seq);
+        self.moments = kept;
+        self.moment_index = self.moments.iter().enumerate().map(|(idx, m)| (m.id.clone(), idx)).collect();
+        let (discarded_archived, archive) = std::mem::take(&mut self.archive).into_iter().partition(|m| m.seq >= watermark);
+        self.archive = archive;
+        self.undo_points.retain(|point| self.moment_index.contains_key(&point.moment_id));
+        self.next_seq = watermark;
+        self.cycle = checkpoint.cycle;
+        self.phase_log.truncate(checkpoint.phase_log_len);
+        self.phase = checkpoint.phase;
+        self.progress = checkpoint.progress;
+        self.last_activity = self.clock.now_millis();
+        Ok(RestoredReport {
+            checkpoint: checkpoint.id,
+            name: checkpoint.name,
+            discarded,
+            discarded_archived,
+            invalidated,
+            ice_caked_since: self.ice_caked.len().saturating_sub(checkpoint.ice_caked_len),
+        })
+    }
+    
+    /// Fill in `SessionReport::reuse` of a closed session
+    pub(crate) fn set_reuse_report(&mut self, reuse: ReuseReport) {
+        if let Some(report) = &mut self.report {
+            report.reuse = Some(reuse);
+        }
+    }
+    
+    /// Mark a moment as stored in a resonance store, so `undo_last` keeps it
+    pub(crate) fn mark_captured(&mut self, moment_id: &str) {
+        if let Some(&idx) = self.moment_index.get(moment_id) {
+            self.moments[idx].captured_externally = true;
+        }
+    }
+    
+    /// Move the least important unprotected moments, oldest first among
+    /// equals, to `archive` until at most `live` remain. Ice-caked moments,
+    /// moments with links in or out and the `linked` ones, targets of a
+    /// moment about to be recorded, stay.
+    fn archive_down_to(&mut self, live: usize, linked: &[&str]) {
+        let excess = self.moments.len().saturating_sub(live);
+        if excess == 0 {
+            return;
+        }
+        let mut protected: Vec<&str> = self.ice_caked.iter().map(|d| d.moment_id.as_str()).chain(linked.iter().copied()).collect();
+        for moment in self.moments.iter().filter(|m| !m.links.is_empty()) {
+            protected.push(&moment.id);
+            protected.extend(moment.links.iter().map(|l| l.target_id.as_str()));
+        }
+        let mut candidates: Vec<(usize, f32)> = self.moments.iter().enumerate()
+            .filter(|(_, m)| !protected.contains(&m.id.as_str()))
+            .map(|(idx, m)| (idx, m.importance_with(&self.importance_weights, false, 0)))
+            .collect();
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1).then(self.moments[a.0].seq.cmp(&self.moments[b.0].seq)));
+        let mut evicted: Vec<usize> = candidates.into_iter().take(excess).map(|(idx, _)| idx).collect();
+        if evicted.is_empty() {
+            return;
+        }
+        evicted.sort_unstable();
+        
+        let moments = std::mem::take(&mut self.moments);
+        for (idx, moment) in moments.into_iter().enumerate() {
+            if evicted.binary_search(&idx).is_ok() {
+                self.archive.push(moment.to_archive());
+            } else {
+                self.moments.push(moment);
+            }
+        }
+        self.moment_index = self.moments.iter().enumerate().map(|(idx, m)| (m.id.clone(), idx)).collect();
+    }
+    
+    /// Moments recorded, live and archived
+    pub fn moment_count(&self) -> usize {
+        self.moments.len() + self.archive.len()
+    }
+    
+    /// Breakthroughs recorded, live and archived
+    pub fn breakthrough_count(&self) -> usize {
+        self.moments.iter().filter(|m| m.is_breakthrough()).count()
+            + self.archive.iter().filter(|a| a.is_breakthrough()).count()
+    }
+    
+    /// Archived moments whose resonance vector reaches `threshold` against
+    /// `query`, most similar first
+    pub fn find_archived(&self, query: &Fingerprint, threshold: f32) -> Vec<(&ArchivedMoment, f32)> {
+        let mut found: Vec<(&ArchivedMoment, f32)> = self.archive.iter()
+            .map(|archived| (archived, archived.similarity(query)))
+            .filter(|&(_, similarity)| similarity >= threshold)
+            .collect();
+        found.sort_by(|a, b| b.1.total_cmp(&a.1));
+        found
     }
     
-    fn add_moment(&mut self, moment: Moment) -> &Moment {
+    /// Append a moment that already has its seq
+    fn insert_moment(&mut self, moment: Moment) -> &Moment {
+        debug_assert!(self.moments.iter().all(|m| m.seq < moment.seq), "moment seq reused");
+        self.next_seq = self.next_seq.max(moment.seq + 1);
         let idx = self.moments.len();
         self.moment_index.insert(moment.id.clone(), idx);
         self.cycle += 1;
         self.moments.push(moment);
-        self.last_activity = Instant::now();
+        self.last_activity = self.clock.now_millis();
+        self.log_event(|session| {
+            let moment = &session.moments[idx];
+            SessionEvent::MomentCreated {
+                id: moment.id.clone(),
+                seq: moment.seq,
+                moment_type: moment.moment_type.name().to_string(),
+                content: moment.content.clone(),
+            }
+        });
+        self.notify(|observer, session| observer.on_moment(&session.moments[idx]));
+        if self.moments[idx].is_breakthrough() {
+            self.notify(|observer, session| observer.on_breakthrough(&session.moments[idx]));
+        }
         &self.moments[idx]
     }
     
+    /// Fix a recorded moment's content. Fingerprints are recomputed and the
+    /// old content is kept in the moment's `edit_history`; recapture the
+    /// moment in any `ResonanceCapture` holding it.
+    pub fn edit_moment(&mut self, id: &str, new_content: &str) -> Result<&Moment, EditError> {
+        self.ensure_open()?;
+        if new_content.trim().is_empty() {
+            return Err(EditError::EmptyContent);
+        }
+        let len = new_content.chars().count();
+        if len > MAX_CONTENT_CHARS {
+            return Err(EditError::ContentTooLong { len });
+        }
+        let &idx = self.moment_index.get(id).ok_or_else(|| EditError::UnknownMoment(id.to_string()))?;
+        self.moments[idx].edit_content_keeping(new_content, self.max_edit_history);
+        self.last_activity = self.clock.now_millis();
+        self.log_event(|session| SessionEvent::MomentEdited {
+            id: session.moments[idx].id.clone(),
+            revision: session.moments[idx].revision,
+            content: session.moments[idx].content.clone(),
+        });
+        Ok(&self.moments[idx])
+    }
+    
+    /// Moments by `seq`, i.e. in recording order, whatever happened to `moments`
+    pub fn moments_ordered(&self) -> Vec<&Moment> {
+        let mut ordered: Vec<&Moment> = self.moments.iter().collect();
+        ordered.sort_by_key(|m| m.seq);
+        ordered
+    }
+    
     pub fn get_moment(&self, id: &str) -> Option<&Moment> {
         self.moment_index.get(id).map(|&idx| &self.moments[idx])
     }
     
-    fn transition_to(&mut self, new_phase: SessionPhase) {
+    /// Same as `get_moment`
+    pub fn find_moment(&self, id: &str) -> Option<&Moment> {
+        self.get_moment(id)
+    }
+    
+    /// `try_record`, then `enter` the helper's phase
+    fn try_record_in(&mut self, phase: SessionPhase, builder: MomentBuilder) -> Result<&Moment, CaptureError> {
+        let first_seq = self.next_seq;
+        let id = self.try_record(builder)?.id.clone();
+        self.enter(phase, first_seq);
+        Ok(&self.moments[self.moment_index[&id]])
+    }
+    
+    /// Move to a helper's phase: always, or with `config.auto_phase` only to
+    /// a later phase or from a breakthrough back to a struggle
+    fn enter(&mut self, phase: SessionPhase, first_seq: u64) {
+        let allowed = !self.config.auto_phase
+            || phase.rank() > self.phase.rank()
+            || (self.phase == SessionPhase::Breakthrough && phase == SessionPhase::Struggle);
+        if allowed {
+            self.transition_to(phase, first_seq);
+        }
+    }
+    
+    pub fn current_phase(&self) -> &SessionPhase {
+        &self.phase
+    }
+    
+    /// Move to `to` if the `SessionPhase` graph allows it from the current phase
+    pub fn advance_phase(&mut self, to: SessionPhase) -> Result<(), PhaseTransitionError> {
+        self.ensure_open()?;
+        if !self.phase.can_advance_to(&to) {
+            return Err(PhaseTransitionError::Illegal {
+                from: self.phase.clone(),
+                to,
+                legal: self.phase.legal_targets().to_vec(),
+            });
+        }
+        self.transition_to(to, self.next_seq);
+        Ok(())
+    }
+    
+    /// Move to `to` whatever the graph says. Any actual move is kept in
+    /// `phase_overrides` and the session state, and so on the blackboard.
+    pub fn force_phase(&mut self, to: SessionPhase, reason: &str) -> Result<(), SessionClosedError> {
+        self.ensure_open()?;
+        if self.phase != to {
+            self.phase_overrides.push(PhaseOverride {
+                from: self.phase.clone(),
+                to: to.clone(),
+                reason: reason.to_string(),
+                seq: self.next_seq - 1,
+            });
+            self.transition_to(to, self.next_seq);
+        }
+        Ok(())
+    }
+    
+    pub fn phase_overrides(&self) -> &[PhaseOverride] {
+        &self.phase_overrides
+    }
+    
+    /// Enter `new_phase`, whose first moment is `first_seq`
+    fn transition_to(&mut self, new_phase: SessionPhase, first_seq: u64) {
+        self.assert_open();
         if self.phase != new_phase {
-            self.phase = new_phase;
+            self.phase_log.push((new_phase.clone(), self.clock.now_millis(), first_seq));
+            let from = std::mem::replace(&mut self.phase, new_phase);
             self.progress = 0.0;
+            self.log_event(|session| SessionEvent::PhaseChanged { from: from.clone(), to: session.phase.clone() });
+            self.notify(|observer, session| observer.on_phase_change(&from, &session.phase));
+        }
+    }
+    
+    /// Time spent in each phase entered, in order, minus paused time. A
+    /// phase lasts until the next one starts; the current phase lasts until
+    /// the latest moment ends.
+    pub fn phase_durations(&self) -> Vec<(SessionPhase, u64)> {
+        let end = self.moments.iter().map(Moment::ends_at).max().unwrap_or(0);
+        self.phase_log.iter().enumerate()
+            .map(|(i, (phase, start, _))| {
+                let until = self.phase_log.get(i + 1).map_or(end, |(_, next, _)| *next);
+                let active = until.saturating_sub(*start).saturating_sub(self.paused_between(*start, until));
+                (phase.clone(), active)
+            })
+            .collect()
+    }
+    
+    /// Each phase entered as (phase, clock time, first moment's seq)
+    pub(crate) fn phase_log(&self) -> &[(SessionPhase, u64, u64)] {
+        &self.phase_log
+    }
+    
+    /// Each phase entered, in order, with the `seq` of its first moment:
+    /// the one that brought it on, or for `ice_cake`, `complete` and explicit
+    /// moves the next one recorded. A phase left before any moment was
+    /// recorded in it shares that seq with the phase after it.
+    pub fn phase_history(&self) -> Vec<(SessionPhase, u64)> {
+        self.phase_log.iter().map(|(phase, _, seq)| (phase.clone(), *seq)).collect()
+    }
+    
+    /// Phase the moment with `seq` was recorded in, by `phase_history`
+    pub(crate) fn phase_at_seq(&self, seq: u64) -> SessionPhase {
+        self.phase_log.iter().rev()
+            .find(|&&(_, _, first_seq)| first_seq <= seq)
+            .map_or(SessionPhase::Initialize, |(phase, _, _)| phase.clone())
+    }
+    
+    /// Per-phase moment counts, time, effort and frustration, plus how long
+    /// the first breakthrough took and how many struggles each one cost
+    pub fn metrics(&self) -> SessionMetrics {
+        let ordered = self.moments_ordered();
+        // Moments before the first phase entry, e.g. custom ones, count under
+        // Initialize until that entry
+        let before_phases = ordered.first()
+            .filter(|m| self.phase_at_seq(m.seq) == SessionPhase::Initialize)
+            .map(|m| {
+                let end = match self.phase_log.first() {
+                    Some(&(_, at, _)) => at,
+                    None => self.moments.iter().map(Moment::ends_at).max().unwrap_or(0),
+                };
+                let active = end.saturating_sub(m.created_at).saturating_sub(self.paused_between(m.created_at, end));
+                (SessionPhase::Initialize, active)
+            });
+        let mut phases: Vec<PhaseMetrics> = Vec::new();
+        for (phase, duration_ms) in before_phases.into_iter().chain(self.phase_durations()) {
+            match phases.iter_mut().find(|m| m.phase == phase) {
+                Some(metrics) => metrics.duration_ms += duration_ms,
+                None => phases.push(PhaseMetrics {
+                    phase,
+                    moment_count: 0,
+                    duration_ms,
+                    mean_effort: 0.0,
+                    max_effort: 0.0,
+                    mean_frustration: 0.0,
+                    max_frustration: 0.0,
+                }),
+            }
+        }
+        for moment in &ordered {
+            let phase = self.phase_at_seq(moment.seq);
+            let Some(metrics) = phases.iter_mut().find(|m| m.phase == phase) else { continue };
+            metrics.moment_count += 1;
+            metrics.mean_effort += moment.qualia.effort;
+            metrics.max_effort = metrics.max_effort.max(moment.qualia.effort);
+            metrics.mean_frustration += moment.qualia.confusion;
+            metrics.max_frustration = metrics.max_frustration.max(moment.qualia.confusion);
+        }
+        for metrics in phases.iter_mut().filter(|m| m.moment_count > 0) {
+            metrics.mean_effort /= metrics.moment_count as f32;
+            metrics.mean_frustration /= metrics.moment_count as f32;
+        }
+        
+        let first = ordered.first().map(|m| m.created_at);
+        let first_breakthrough = ordered.iter().find(|m| m.is_breakthrough()).map(|m| m.created_at);
+        let breakthroughs = ordered.iter().filter(|m| m.is_breakthrough()).count();
+        let struggles = ordered.iter().filter(|m| m.moment_type == MomentType::Struggle).count();
+        SessionMetrics {
+            phases,
+            time_to_first_breakthrough_ms: first.zip(first_breakthrough)
+                .map(|(start, end)| end.saturating_sub(start).saturating_sub(self.paused_between(start, end))),
+            struggle_to_breakthrough_ratio: (breakthroughs > 0).then(|| struggles as f32 / breakthroughs as f32),
+        }
+    }
+    
+    /// Make `style` the active thinking style: helpers stamp it on every
+    /// moment they record from now on, and `state()` reports it. The change
+    /// is logged in `style_changes`. False on a closed session.
+    pub fn set_style(&mut self, style: ThinkingStyle) -> bool {
+        if self.ensure_open().is_err() {
+            return false;
+        }
+        self.style_changes.push((self.clock.now_millis(), style.clone()));
+        self.config.thinking_style = Some(style);
+        true
+    }
+    
+    /// Each `set_style`, as (clock time, style), oldest first
+    pub fn style_changes(&self) -> &[(u64, ThinkingStyle)] {
+        &self.style_changes
+    }
+    
+    /// Moments and active time per dominant style dimension of the moments'
+    /// stamped styles, most moments first. Each moment is credited with the
+    /// time until the next moment starts, minus pauses; the latest one with
+    /// its own duration.
+    pub fn style_distribution(&self) -> Vec<StyleShare> {
+        let mut moments: Vec<&Moment> = self.moments.iter().collect();
+        moments.sort_by_key(|m| (m.created_at, m.seq));
+        let mut shares: Vec<StyleShare> = Vec::new();
+        for (i, moment) in moments.iter().enumerate() {
+            let end = moments.get(i + 1).map_or(moment.ends_at(), |next| next.created_at.max(moment.created_at));
+            let duration_ms = end.saturating_sub(moment.created_at).saturating_sub(self.paused_between(moment.created_at, end));
+            let axis = if moment.thinking_style.is_unset() { "unset" } else { moment.thinking_style.dominant() };
+            match shares.iter_mut().find(|share| share.axis == axis) {
+                Some(share) => {
+                    share.moment_count += 1;
+                    share.duration_ms += duration_ms;
+                }
+                None => shares.push(StyleShare { axis: axis.to_string(), moment_count: 1, duration_ms }),
+            }
+        }
+        shares.sort_by_key(|share| std::cmp::Reverse(share.moment_count));
+        shares
+    }
+    
+    /// Open a subtask inside the innermost open one, if any. Moments recorded
+    /// until it ends are stamped with its id.
+    pub fn begin_subtask(&mut self, name: &str) -> Result<SubtaskId, SessionClosedError> {
+        self.ensure_open()?;
+        let id = SubtaskId(uuid::Uuid::new_v4().to_string());
+        self.subtasks.push(Subtask {
+            id: id.clone(),
+            name: name.to_string(),
+            parent: self.open_subtasks().last().map(|s| s.id.clone()),
+            started_at: self.clock.now_millis(),
+            ended_at: None,
+            outcome: None,
+        });
+        Ok(id)
+    }
+    
+    /// End the innermost open subtask, which must be `id`
+    pub fn end_subtask(&mut self, id: &SubtaskId, outcome: SessionOutcome) -> Result<(), SubtaskError> {
+        self.ensure_open()?;
+        if !self.subtasks.iter().any(|s| s.id == *id) {
+            return Err(SubtaskError::Unknown(id.clone()));
+        }
+        let top = self.open_subtasks().last().map(|s| s.id.clone());
+        if top.as_ref() != Some(id) {
+            return Err(SubtaskError::NotOnTop { id: id.clone(), top });
+        }
+        let now = self.clock.now_millis();
+        let subtask = self.subtasks.iter_mut().find(|s| s.id == *id).expect("subtask exists");
+        subtask.ended_at = Some(now);
+        subtask.outcome = Some(outcome);
+        Ok(())
+    }
+    
+    pub fn subtasks(&self) -> &[Subtask] {
+        &self.subtasks
+    }
+    
+    /// The subtask stack, outermost first
+    pub fn open_subtasks(&self) -> Vec<&Subtask> {
+        self.subtasks.iter().filter(|s| s.is_open()).collect()
+    }
+    
+    /// Moments recorded in `id` or any subtask nested in it, in recording order
+    pub fn moments_in_subtask(&self, id: &SubtaskId) -> Vec<&Moment> {
+        let within: Vec<&str> = self.subtasks.iter()
+            .filter(|s| self.subtask_ancestry(s).any(|a| a.id == *id))
+            .map(|s| s.id.as_str())
+            .collect();
+        self.moments_ordered().into_iter()
+            .filter(|m| m.subtask_id.as_deref().is_some_and(|sid| within.contains(&sid)))
+            .collect()
+    }
+    
+    /// `subtask` followed by the subtasks it is nested in, innermost first
+    fn subtask_ancestry<'a>(&'a self, subtask: &'a Subtask) -> impl Iterator<Item = &'a Subtask> + 'a {
+        std::iter::successors(Some(subtask), move |s| {
+            s.parent.as_ref().and_then(|parent| self.subtasks.iter().find(|p| p.id == *parent))
+        })
+    }
+    
+    /// Summary of `id`; None for an unknown subtask
+    pub fn subtask_summary(&self, id: &SubtaskId) -> Option<SubtaskSummary> {
+        let subtask = self.subtasks.iter().find(|s| s.id == *id)?;
+        let moments = self.moments_in_subtask(id);
+        let mean_qualia = (!moments.is_empty()).then(|| {
+            let mut mean = [0.0; QUALIA_DIMENSIONS];
+            for moment in &moments {
+                for (dim, value) in moment.qualia.normalized().iter().enumerate() {
+                    mean[dim] += value / moments.len() as f32;
+                }
+            }
+            Qualia::from_normalized(mean)
+        });
+        Some(SubtaskSummary {
+            id: id.clone(),
+            name: subtask.name.clone(),
+            depth: self.subtask_ancestry(subtask).count() - 1,
+            outcome: subtask.outcome.clone(),
+            moment_count: moments.len(),
+            breakthrough_count: moments.iter().filter(|m| m.is_breakthrough()).count(),
+            mean_qualia,
+            peak_frustration: moments.iter().map(|m| m.qualia.confusion).fold(0.0, f32::max),
+        })
+    }
+    
+    /// A summary of every subtask in tree order: each one followed by the
+    /// subtasks nested in it, siblings in the order they began
+    pub fn subtask_tree(&self) -> Vec<SubtaskSummary> {
+        let mut ordered = Vec::with_capacity(self.subtasks.len());
+        let mut pending: Vec<&Subtask> = self.subtasks.iter()
+            .filter(|s| s.parent.as_ref().is_none_or(|parent| self.subtasks.iter().all(|p| p.id != *parent)))
+            .rev()
+            .collect();
+        while let Some(subtask) = pending.pop() {
+            ordered.extend(self.subtask_summary(&subtask.id));
+            pending.extend(self.subtasks.iter().filter(|s| s.parent.as_ref() == Some(&subtask.id)).rev());
+        }
+        ordered
+    }
+    
+    /// Stop the clock for time-based statistics, e.g. overnight. Returns
+    /// false if already paused.
+    pub fn pause(&mut self) -> bool {
+        if self.is_paused() || self.is_closed() {
+            return false;
+        }
+        self.pauses.push((self.clock.now_millis(), None));
+        true
+    }
+    
+    /// End the current pause. Returns false if the session was not paused.
+    pub fn resume(&mut self) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        let now = self.clock.now_millis();
+        match self.pauses.last_mut() {
+            Some((_, end @ None)) => {
+                *end = Some(now);
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    pub fn is_paused(&self) -> bool {
+        matches!(self.pauses.last(), Some((_, None)))
+    }
+    
+    /// Paused intervals as (start, end); an ongoing pause has no end
+    pub fn pauses(&self) -> &[(u64, Option<u64>)] {
+        &self.pauses
+    }
+    
+    /// Make sure the session can capture: resumes it if paused and
+    /// `config.capture_while_paused` allows, otherwise fails while paused
+    pub fn ensure_active(&mut self) -> Result<(), SessionPaused> {
+        if !self.is_paused() {
+            return Ok(());
+        }
+        if !self.config.capture_while_paused {
+            return Err(SessionPaused);
+        }
+        self.resume();
+        Ok(())
+    }
+    
+    /// Paused time overlapping `start..end`; an ongoing pause runs to now
+    fn paused_between(&self, start: u64, end: u64) -> u64 {
+        let now = self.clock.now_millis();
+        self.pauses.iter()
+            .map(|&(from, to)| to.unwrap_or(now).min(end).saturating_sub(from.max(start)))
+            .sum()
+    }
+    
+    /// From the first moment's creation to the end of the latest one
+    pub fn span_ms(&self) -> u64 {
+        let start = self.moments.iter().map(|m| m.created_at).min();
+        let end = self.moments.iter().map(Moment::ends_at).max();
+        match (start, end) {
+            (Some(start), Some(end)) => end.saturating_sub(start),
+            _ => 0,
         }
     }
     
+    /// `span_ms` minus the time spent paused within the span
+    pub fn active_ms(&self) -> u64 {
+        let Some(start) = self.moments.iter().map(|m| m.created_at).min() else { return 0 };
+        let end = self.moments.iter().map(Moment::ends_at).max().unwrap_or(start);
+        end.saturating_sub(start).saturating_sub(self.paused_between(start, end))
+    }
+    
+    /// Longest idle stretch between one moment ending and the next starting,
+    /// not counting paused time
+    pub fn longest_gap_ms(&self) -> u64 {
+        let mut moments: Vec<&Moment> = self.moments.iter().collect();
+        moments.sort_by_key(|m| m.created_at);
+        moments.windows(2)
+            .map(|w| {
+                let gap = w[1].created_at.saturating_sub(w[0].ends_at());
+                gap.saturating_sub(self.paused_between(w[0].ends_at(), w[1].created_at))
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    
     pub fn find_similar(&self, query: &Fingerprint, threshold: f32) -> Vec<(&Moment, f32)> {
         let mut results: Vec<_> = self.moments.iter()
             .map(|m| (m, query.similarity(&m.resonance_vector)))
@@ -202,12 +2487,1906 @@ impl LearningSession {
         self.moments.iter().filter(|m| m.is_breakthrough()).collect()
     }
     
-    pub fn duration(&self) -> Duration {
-        self.started_at.elapsed()
+    pub fn is_ice_caked(&self, moment_id: &str) -> bool {
+        self.ice_caked.iter().any(|d| d.moment_id == moment_id)
     }
     
-    pub fn complete(&mut self) {
-        self.phase = SessionPhase::Complete;
-        self.progress = 1.0;
+    /// Links from and to the moment
+    pub fn link_degree(&self, moment_id: &str) -> usize {
+        self.moments.iter()
+            .map(|m| {
+                let incoming = m.links.iter().filter(|l| l.target_id == moment_id).count();
+                if m.id == moment_id { incoming + m.links.len() } else { incoming }
+            })
+            .sum()
+    }
+    
+    pub fn importance(&self, moment_id: &str) -> Option<f32> {
+        let moment = self.get_moment(moment_id)?;
+        Some(self.importance_of(moment))
+    }
+    
+    fn importance_of(&self, moment: &Moment) -> f32 {
+        moment.importance_with(&self.importance_weights, self.is_ice_caked(&moment.id), self.link_degree(&moment.id))
+    }
+    
+    /// The `k` most important moments, highest first; ties keep recording order
+    pub fn top_moments(&self, k: usize) -> Vec<(&Moment, f32)> {
+        let mut scored: Vec<(&Moment, f32)> = self.moments.iter().map(|m| (m, self.importance_of(m))).collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(k);
+        scored
+    }
+    
+    /// `format_timeline` of the session's moments with its ice-caked ones marked
+    pub fn timeline(&self) -> String {
+        self.timeline_with(TimelineOptions::default())
+    }
+    
+    pub fn timeline_with(&self, mut opts: TimelineOptions) -> String {
+        opts.ice_caked.extend(self.ice_caked.iter().map(|d| d.moment_id.clone()));
+        format_timeline(&self.moments, &opts)
+    }
+    
+    pub fn qualia_trajectory(&self) -> QualiaTrajectory {
+        QualiaTrajectory::from_moments(&self.moments)
+    }
+    
+    pub fn moments_of_type(&self, moment_type: &MomentType) -> Vec<&Moment> {
+        self.moments.iter().filter(|m| &m.moment_type == moment_type).collect()
+    }
+    
+    /// Moments whose source is in `path`
+    pub fn moments_in_file(&self, path: &str) -> Vec<&Moment> {
+        self.moments.iter().filter(|m| m.source.as_ref().is_some_and(|s| s.is_in(path))).collect()
+    }
+    
+    pub fn moments_with_meta(&self, key: &str, value: &str) -> Vec<&Moment> {
+        self.moments.iter().filter(|m| m.meta(key) == Some(value)).collect()
+    }
+    
+    /// Moments carrying the tag; "#Auth" and "auth" match alike
+    pub fn moments_tagged(&self, tag: &str) -> Vec<&Moment> {
+        self.moments.iter().filter(|m| m.has_tag(tag)).collect()
+    }
+    
+    /// Tag a recorded moment. Returns false if the moment is unknown or
+    /// already carries the tag.
+    pub fn tag(&mut self, moment_id: &str, tag: &str) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        match self.moment_index.get(moment_id) {
+            Some(&idx) => self.moments[idx].add_tag(tag),
+            None => false,
+        }
+    }
+    
+    /// Moment count per tag
+    pub fn tag_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for tag in self.moments.iter().flat_map(|m| &m.tags).chain(self.archive.iter().flat_map(|a| &a.tags)) {
+            *counts.entry(tag.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+    
+    fn metadata_values(&self) -> BTreeMap<String, Vec<String>> {
+        let mut values: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (key, value) in self.moments.iter().flat_map(|m| &m.metadata) {
+            let seen = values.entry(key.clone()).or_default();
+            if !seen.contains(value) {
+                seen.push(value.clone());
+            }
+        }
+        values
+    }
+    
+    pub fn moment_type_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        let types = self.moments.iter().map(|m| &m.moment_type).chain(self.archive.iter().map(|a| &a.moment_type));
+        for moment_type in types {
+            *counts.entry(moment_type.name().to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+    
+    /// Clock time since the session was created, paused time included
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.clock.now_millis().saturating_sub(self.started_at))
+    }
+    
+    pub fn complete(&mut self) -> Result<(), SessionClosedError> {
+        self.ensure_open()?;
+        self.transition_to(SessionPhase::Complete, self.next_seq);
+        self.progress = 1.0;
+        Ok(())
+    }
+    
+    /// End the session and freeze its report. A completed session moves to
+    /// the `Complete` phase and an open pause ends. Afterwards `link`, `tag`,
+    /// `pause` and `resume` return false and `verify` returns None; the
+    /// other mutators, `try_` recording helpers included, fail with
+    /// `SessionClosedError` or an error wrapping it, and the recording
+    /// helpers without `try_` panic.
+    pub fn close(&mut self, outcome: SessionOutcome) -> Result<&SessionReport, SessionClosedError> {
+        self.ensure_open()?;
+        self.resume();
+        if outcome == SessionOutcome::Completed {
+            self.complete()?;
+        }
+        let state = self.state();
+        self.report = Some(SessionReport {
+            session_id: self.id.clone(),
+            task_id: self.task_id.clone(),
+            description: self.description.clone(),
+            outcome,
+            closed_at: self.clock.now_millis(),
+            moment_count: state.moment_count,
+            breakthrough_count: state.breakthrough_count,
+            moment_types: state.moment_types,
+            duration_ms: state.duration_ms,
+            wall_clock_ms: state.wall_clock_ms,
+            trajectory: state.trajectory,
+            ice_caked: self.ice_caked.clone(),
+            metrics: state.metrics,
+            tags: state.tags,
+            reuse: None,
+        });
+        self.log_event(|session| SessionEvent::Closed { outcome: session.report.as_ref().expect("report was just set").outcome.clone() });
+        Ok(self.report.as_ref().expect("report was just set"))
+    }
+    
+    /// Branch off to explore, e.g. a risky hypothesis. The branch is a new
+    /// session with the same task and a copy of this one's moments,
+    /// ice-caked decisions, phase and counters. Merge it back with
+    /// `merge_branch`, or drop it to discard everything logged on it.
+    pub fn fork(&self, branch_name: &str) -> LearningSession {
+        let mut branch = Self::with_clock(&self.task_id, self.clock.clone());
+        branch.description = self.description.clone();
+        branch.phase = self.phase.clone();
+        branch.progress = self.progress;
+        branch.moments = self.moments.clone();
+        branch.archive = self.archive.clone();
+        branch.moment_index = self.moment_index.clone();
+        branch.ice_caked = self.ice_caked.clone();
+        branch.cycle = self.cycle;
+        branch.importance_weights = self.importance_weights.clone();
+        branch.config = self.config.clone();
+        branch.max_edit_history = self.max_edit_history;
+        branch.phase_log = self.phase_log.clone();
+        branch.next_seq = self.next_seq;
+        branch.pauses = self.pauses.clone();
+        branch.phase_overrides = self.phase_overrides.clone();
+        branch.subtasks = self.subtasks.clone();
+        branch.style_changes = self.style_changes.clone();
+        branch.predecessor_session_id = self.predecessor_session_id.clone();
+        branch.branch = Some(BranchOrigin {
+            name: branch_name.to_string(),
+            parent_id: self.id.clone(),
+            next_seq_at_fork: self.next_seq,
+            ice_caked_at_fork: self.ice_caked.len(),
+        });
+        branch
+    }
+    
+    /// Name given to `fork`, for branch sessions
+    pub fn branch_name(&self) -> Option<&str> {
+        self.branch.as_ref().map(|b| b.name.as_str())
+    }
+    
+    /// Fold the moments and ice-caked decisions logged on a branch since the
+    /// fork into this session. Merged moments join this session and record
+    /// the branch name under the `branch` metadata key; links and decisions
+    /// pointing at moments merged as repeats are redirected to the parent's
+    /// moment.
+    pub fn merge_branch(&mut self, branch: LearningSession, policy: MergePolicy) -> Result<BranchMerge, MergeError> {
+        self.ensure_open()?;
+        let origin = match &branch.branch {
+            Some(origin) if origin.parent_id == self.id => origin.clone(),
+            _ => return Err(MergeError::NotABranch),
+        };
+        let new_moments: Vec<&Moment> = branch.moments.iter().filter(|m| m.seq >= origin.next_seq_at_fork).collect();
+        let new_archived: Vec<&ArchivedMoment> = branch.archive.iter().filter(|a| a.seq >= origin.next_seq_at_fork).collect();
+        if !policy.renumber {
+            let seqs = new_moments.iter().map(|m| m.seq).chain(new_archived.iter().map(|a| a.seq));
+            if let Some(seq) = seqs.filter(|&seq| seq < self.next_seq).min() {
+                return Err(MergeError::SeqConflict { seq });
+            }
+        }
+        
+        let mut result = BranchMerge::default();
+        let mut redirect: HashMap<String, String> = HashMap::new();
+        for moment in new_moments {
+            let mut moment = moment.clone();
+            moment.session_id = self.id.clone();
+            for link in &mut moment.links {
+                if let Some(target) = redirect.get(&link.target_id) {
+                    link.target_id = target.clone();
+                }
+            }
+            let repeat = policy.dedup_threshold
+                .and_then(|threshold| self.find_repeat(&moment, threshold, self.moments.len()));
+            if let Some(idx) = repeat {
+                self.moments[idx].merge_repeat(&moment);
+                redirect.insert(moment.id.clone(), self.moments[idx].id.clone());
+                result.merged.push((moment.id, self.moments[idx].id.clone()));
+                continue;
+            }
+            moment.metadata.insert("branch".to_string(), origin.name.clone());
+            if policy.renumber {
+                moment.seq = self.next_seq;
+            }
+            result.added.push(self.insert_moment(moment).id.clone());
+        }
+        for archived in new_archived {
+            let mut archived = archived.clone();
+            archived.session_id = self.id.clone();
+            if policy.renumber {
+                archived.seq = self.next_seq;
+            }
+            self.next_seq = self.next_seq.max(archived.seq + 1);
+            self.archive.push(archived);
+        }
+        
+        for decision in &branch.ice_caked[origin.ice_caked_at_fork..] {
+            let mut decision = decision.clone();
+            if let Some(target) = redirect.get(&decision.moment_id) {
+                decision.moment_id = target.clone();
+            }
+            self.ice_caked.push(decision);
+            result.ice_caked += 1;
+        }
+        union_subtasks(&mut self.subtasks, &branch.subtasks);
+        Ok(result)
+    }
+    
+    pub fn is_closed(&self) -> bool {
+        self.report.is_some()
+    }
+    
+    /// The final report, once closed
+    pub fn report(&self) -> Option<&SessionReport> {
+        self.report.as_ref()
+    }
+    
+    pub fn ensure_open(&self) -> Result<(), SessionClosedError> {
+        match &self.report {
+            Some(report) => Err(SessionClosedError { outcome: report.outcome.clone() }),
+            None => Ok(()),
+        }
+    }
+    
+    /// Call `observer` on this session's events until `unsubscribe`. Forks
+    /// and merged sessions start without observers. An observer that panics
+    /// is unsubscribed; the change it was told about stands.
+    pub fn subscribe(&mut self, observer: impl SessionObserver + 'static) -> Subscription {
+        let subscription = Subscription(self.next_subscription);
+        self.next_subscription += 1;
+        self.observers.push((subscription, Box::new(observer)));
+        subscription
+    }
+    
+    /// Drop the observer; false if it was already gone
+    pub fn unsubscribe(&mut self, subscription: Subscription) -> bool {
+        let before = self.observers.len();
+        self.observers.retain(|(s, _)| *s != subscription);
+        self.observers.len() < before
+    }
+    
+    /// Append this session's events to `writer` as JSON lines, one
+    /// `EventLine` each, flushing after every event. Replaces any log
+    /// attached before; forks and merged sessions start without one. A
+    /// failed write never stops the session; see `last_log_error`.
+    pub fn attach_event_log(&mut self, writer: Box<dyn Write + Send>) {
+        self.event_log = Some(EventLog::new(writer));
+    }
+    
+    /// `attach_event_log` to the file at `path`, appending to it if it exists
+    pub fn attach_event_log_file(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        self.attach_event_log(Box::new(file));
+        Ok(())
+    }
+    
+    /// The latest error writing the event log; None if every write so far
+    /// succeeded or no log is attached
+    pub fn last_log_error(&self) -> Option<&io::Error> {
+        self.event_log.as_ref().and_then(EventLog::last_error)
+    }
+    
+    fn log_event(&mut self, event: impl FnOnce(&LearningSession) -> SessionEvent) {
+        if self.event_log.is_none() {
+            return;
+        }
+        let line = EventLine {
+            schema_version: EVENT_LOG_SCHEMA_VERSION,
+            session_id: self.id.clone(),
+            timestamp: self.clock.now_millis(),
+            event: event(self),
+        };
+        self.event_log.as_mut().expect("log was checked").append(&line);
+    }
+    
+    fn notify(&mut self, event: impl Fn(&mut dyn SessionObserver, &LearningSession)) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let mut observers = std::mem::take(&mut self.observers);
+        observers.retain_mut(|(_, observer)| {
+            panic::catch_unwind(AssertUnwindSafe(|| event(observer.as_mut(), self))).is_ok()
+        });
+        self.observers = observers;
+    }
+    
+    fn assert_open(&self) {
+        if let Err(closed) = self.ensure_open() {
+            panic!("{}", closed);
+        }
+    }
+}
+
+/// The moment a recording helper recorded, or a panic with why not
+fn recorded(result: Result<&Moment, CaptureError>) -> &Moment {
+    result.unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Combine two sessions run independently on the same task into a new one.
+/// Moments are interleaved by creation time, then seq, with `a` first on
+/// ties, and renumbered from 1; each records the session it came from under
+/// the `origin_session` metadata key. A moment present in both (same id) is
+/// kept once, and with `policy.dedup_threshold` near-identical moments are
+/// merged as repeats. Ice-caked decisions are unioned; decisions on the same
+/// moment with different rationales become an `IceCakeConflict`. The phase
+/// comes from the session with the last moment and progress is the higher of
+/// the two. Pauses are not carried over and `policy.renumber` is ignored.
+pub fn merge_sessions(a: &LearningSession, b: &LearningSession, policy: &MergePolicy) -> Result<LearningSession, MergeError> {
+    if a.task_id != b.task_id && !policy.allow_cross_task {
+        return Err(MergeError::TaskMismatch { a: a.task_id.clone(), b: b.task_id.clone() });
+    }
+    let mut merged = LearningSession::with_clock(&a.task_id, a.clock.clone());
+    merged.description = a.description.clone();
+    merged.config = a.config.clone();
+    merged.importance_weights = a.importance_weights.clone();
+    merged.max_edit_history = a.max_edit_history;
+    
+    let mut ordered: Vec<(u64, u64, usize, usize, &Moment)> = [a, b].iter().enumerate()
+        .flat_map(|(side, session)| session.moments.iter().enumerate().map(move |(i, m)| (m.created_at, m.seq, side, i, m)))
+        .collect();
+    ordered.sort_by_key(|&(created_at, seq, side, i, _)| (created_at, seq, side, i));
+    
+    let latest = match ordered.last() {
+        Some(&(_, _, 1, _, _)) => b,
+        _ => a,
+    };
+    let mut redirect: HashMap<String, String> = HashMap::new();
+    for (_, _, _, _, original) in ordered {
+        if merged.moment_index.contains_key(&original.id) {
+            continue;
+        }
+        let mut moment = original.clone();
+        moment.metadata.insert("origin_session".to_string(), moment.session_id.clone());
+        moment.session_id = merged.id.clone();
+        let repeat = policy.dedup_threshold
+            .and_then(|threshold| merged.find_repeat(&moment, threshold, merged.moments.len()));
+        if let Some(idx) = repeat {
+            merged.moments[idx].merge_repeat(&moment);
+            redirect.insert(moment.id, merged.moments[idx].id.clone());
+            continue;
+        }
+        moment.seq = merged.next_seq;
+        merged.insert_moment(moment);
+    }
+    for archived in a.archive.iter().chain(&b.archive) {
+        if !merged.archive.iter().any(|known| known.id == archived.id) {
+            let mut archived = archived.clone();
+            archived.session_id = merged.id.clone();
+            merged.archive.push(archived);
+        }
+    }
+    for link in merged.moments.iter_mut().flat_map(|m| m.links.iter_mut()) {
+        if let Some(target) = redirect.get(&link.target_id) {
+            link.target_id = target.clone();
+        }
+    }
+    
+    let mut by_moment: Vec<(String, Vec<IceCakedDecision>)> = Vec::new();
+    for decision in a.ice_caked.iter().chain(&b.ice_caked) {
+        let mut decision = decision.clone();
+        if let Some(target) = redirect.get(&decision.moment_id) {
+            decision.moment_id = target.clone();
+        }
+        match by_moment.iter_mut().find(|(id, _)| *id == decision.moment_id) {
+            Some((_, decisions)) => {
+                if !decisions.iter().any(|d| d.rationale == decision.rationale) {
+                    decisions.push(decision);
+                }
+            }
+            None => by_moment.push((decision.moment_id.clone(), vec![decision])),
+        }
+    }
+    for (moment_id, mut decisions) in by_moment {
+        if decisions.len() == 1 {
+            merged.ice_caked.append(&mut decisions);
+        } else {
+            merged.ice_cake_conflicts.push(IceCakeConflict { moment_id, decisions });
+        }
+    }
+    
+    merged.phase = latest.phase.clone();
+    merged.progress = a.progress.max(b.progress);
+    // Seqs were renumbered; a phase entry points at the first merged moment
+    // created once it was entered
+    merged.phase_log = a.phase_log.iter().chain(&b.phase_log)
+        .map(|(phase, at, _)| {
+            let seq = merged.moments.iter().filter(|m| m.created_at >= *at).map(|m| m.seq).min().unwrap_or(merged.next_seq);
+            (phase.clone(), *at, seq)
+        })
+        .collect();
+    merged.phase_log.sort_by_key(|&(_, at, _)| at);
+    merged.phase_log.dedup_by(|later, earlier| later.0 == earlier.0);
+    merged.phase_overrides = a.phase_overrides.iter().chain(&b.phase_overrides).cloned().collect();
+    union_subtasks(&mut merged.subtasks, &a.subtasks);
+    union_subtasks(&mut merged.subtasks, &b.subtasks);
+    Ok(merged)
+}
+
+/// Add the subtasks `into` lacks, and the end of any it still has open
+fn union_subtasks(into: &mut Vec<Subtask>, from: &[Subtask]) {
+    for subtask in from {
+        match into.iter_mut().find(|s| s.id == subtask.id) {
+            Some(existing) if existing.is_open() => *existing = subtask.clone(),
+            Some(_) => {}
+            None => into.push(subtask.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MockClock;
+    
+    #[test]
+    fn test_custom_moment_capture_and_retrieval() {
+        let mut session = LearningSession::new("task");
+        session.encounter("read the spec");
+        let id = session.custom_moment("Review", "PR review of the parser", Qualia::from_metrics(0.4, 0.3, 0.7)).id.clone();
+        session.custom_moment("Review", "second review pass", Qualia::default());
+        
+        assert_eq!(session.phase, SessionPhase::Encounter);
+        let review = MomentType::Custom("Review".to_string());
+        assert_eq!(session.get_moment(&id).unwrap().moment_type, review);
+        assert_eq!(session.moments_of_type(&review).len(), 2);
+        
+        let state = session.state();
+        assert_eq!(state.moment_types.get("Review"), Some(&2));
+        assert_eq!(state.moment_types.get("Encounter"), Some(&1));
+    }
+    
+    #[test]
+    fn test_moments_with_meta() {
+        let mut session = LearningSession::new("task");
+        let id = session.encounter("read parser.rs").id.clone();
+        session.moments[0].set_meta("file", "parser.rs").unwrap();
+        session.encounter("read lexer.rs");
+        session.moments[1].set_meta("file", "lexer.rs").unwrap();
+        session.struggle("parser.rs again", 0.6, 0.5);
+        session.moments[2].set_meta("file", "parser.rs").unwrap();
+        
+        let hits = session.moments_with_meta("file", "parser.rs");
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].id, id);
+        assert!(session.moments_with_meta("ticket", "parser.rs").is_empty());
+        assert_eq!(session.state().metadata["file"], vec!["parser.rs", "lexer.rs"]);
+    }
+    
+    #[test]
+    fn test_helpers_clamp_instead_of_failing() {
+        let mut session = LearningSession::new("task");
+        let moment = session.struggle("", 1.4, 2.0);
+        assert_eq!(moment.qualia.effort, 1.0);
+        assert_eq!(moment.qualia.confusion, 1.0);
+        assert_eq!(moment.content, "");
+    }
+    
+    #[test]
+    fn test_helpers_set_valence() {
+        let mut session = LearningSession::new("task");
+        assert!(session.breakthrough("it clicked", 0.9).qualia.valence > 0.5);
+        assert!(session.fail("tests red", "read the error").qualia.valence < 0.0);
+        assert!(session.struggle("lost", 0.8, 0.7).qualia.valence < 0.0);
+    }
+    
+    #[test]
+    fn test_with_affect_overrides_next_moment_only() {
+        let mut session = LearningSession::new("task");
+        let felt_bad = session.with_affect(-0.5, 0.9, 0.3).breakthrough("painful but right", 0.9).clone();
+        assert_eq!((felt_bad.qualia.valence, felt_bad.qualia.arousal, felt_bad.qualia.confidence), (-0.5, 0.9, 0.3));
+        assert_eq!(felt_bad.qualia.satisfaction, 0.9);
+        assert_eq!(felt_bad.resonance_vector, felt_bad.qualia.weight_fingerprint(&felt_bad.fingerprint));
+        
+        assert_eq!(session.breakthrough("next", 0.9).qualia.valence, 0.8);
+    }
+    
+    #[test]
+    fn test_ice_caked_breakthrough_outranks_encounter() {
+        let mut session = LearningSession::new("task");
+        let routine = session.encounter("opened the file").id.clone();
+        let key = session.breakthrough("the index was stale", 0.9).id.clone();
+        session.ice_cake(&key, "always rebuild the index").unwrap();
+        
+        assert!(session.importance(&key).unwrap() > session.importance(&routine).unwrap());
+        let top = session.top_moments(1);
+        assert_eq!(top[0].0.id, key);
+        assert_eq!(session.state().key_moments[0].moment_id, key);
+    }
+    
+    #[test]
+    fn test_weights_change_ordering() {
+        let mut session = LearningSession::new("task");
+        let failure = session.fail("migration dropped a column", "back up first").id.clone();
+        let struggle = session.struggle("what is this schema", 0.9, 0.5).id.clone();
+        assert_eq!(session.top_moments(1)[0].0.id, failure);
+        
+        session.importance_weights.failure = 0.0;
+        assert_eq!(session.top_moments(1)[0].0.id, struggle);
+    }
+    
+    #[test]
+    fn test_top_moments_deterministic() {
+        let mut session = LearningSession::new("task");
+        for i in 0..5 {
+            session.encounter(&format!("same kind {}", i));
+        }
+        let first: Vec<String> = session.top_moments(5).iter().map(|(m, _)| m.id.clone()).collect();
+        let again: Vec<String> = session.top_moments(5).iter().map(|(m, _)| m.id.clone()).collect();
+        let recorded: Vec<String> = session.moments.iter().map(|m| m.id.clone()).collect();
+        assert_eq!(first, again);
+        assert_eq!(first, recorded);
+    }
+    
+    #[test]
+    fn test_resolution_pairs() {
+        let mut session = LearningSession::new("task");
+        let first = session.struggle("borrowck rejects the cache", 0.7, 0.6).id.clone();
+        let second = session.struggle("tests flaky", 0.5, 0.5).id.clone();
+        let encounter = session.encounter("read the docs").id.clone();
+        let fix = session.breakthrough_resolving(&first, "split the borrow", 0.8).id.clone();
+        session.breakthrough_resolving(&encounter, "not a struggle", 0.8);
+        session.breakthrough("unrelated insight", 0.9);
+        
+        let pairs = session.resolution_pairs();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].0.id.as_str(), pairs[0].1.id.as_str()), (first.as_str(), fix.as_str()));
+        
+        assert!(session.link(&fix, LinkKind::Resolves, &second));
+        assert_eq!(session.resolution_pairs().len(), 2);
+        assert!(!session.link("missing", LinkKind::Follows, &first));
+    }
+    
+    #[test]
+    fn test_ice_cake_keeps_every_decision() {
+        let mut session = LearningSession::new("task");
+        let cache = session.breakthrough("cache the parsed config", 0.9).id.clone();
+        let lazy = session.breakthrough("load the config lazily", 0.9).id.clone();
+        session.ice_cake(&cache, "parse the config once at startup").unwrap();
+        session.ice_cake(&lazy, "parse the config once at startup").unwrap();
+        
+        assert_eq!(session.ice_caked.len(), 2);
+        assert!(session.ice_caked.iter().all(|d| d.superseded_by.is_none() && d.truth == TruthValue::certain_true()));
+    }
+    
+    #[test]
+    fn test_ice_cake_unknown_moment_fails() {
+        let mut session = LearningSession::new("task");
+        session.encounter("parser rejects UTF-8 input");
+        assert_eq!(session.ice_cake("no-such-moment", "keep").err(), Some(IceCakeError::UnknownMoment("no-such-moment".to_string())));
+        assert_eq!(IceCakeError::UnknownMoment("x".to_string()).to_string(), "no moment with id x");
+        assert!(session.ice_caked.is_empty());
+        assert_eq!(session.phase, SessionPhase::Encounter);
+    }
+    
+    #[test]
+    fn test_moment_ids_are_unique() {
+        let mut session = LearningSession::new("task");
+        for _ in 0..10_000 {
+            session.encounter("x");
+        }
+        let ids: std::collections::HashSet<&str> = session.moments.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids.len(), 10_000);
+        let last = session.moments[9_999].id.clone();
+        assert_eq!(session.find_moment(&last).map(|m| m.seq), Some(10_000));
+        assert!(session.find_moment("no-such-moment").is_none());
+        session.ice_cake(&last, "keep").unwrap();
+    }
+    
+    #[test]
+    fn test_ice_cake_with_confidence_supersedes_same_topic() {
+        let mut session = LearningSession::new("task");
+        let cache = session.breakthrough("cache the parsed config", 0.9).id.clone();
+        let retries = session.breakthrough("retry flaky requests", 0.9).id.clone();
+        let lazy = session.breakthrough("load the config lazily", 0.9).id.clone();
+        session.ice_cake_with_confidence(&cache, "parse the config file once at startup", TruthValue::new(0.9, 0.6)).unwrap();
+        session.ice_cake_with_confidence(&retries, "retry network requests three times", TruthValue::new(0.8, 0.5)).unwrap();
+        let newer = session.ice_cake_with_confidence(&lazy, "parse the config file once, lazily", TruthValue::new(0.8, 0.5)).unwrap();
+        
+        let expected = TruthValue::new(0.9, 0.6).revision(&TruthValue::new(0.8, 0.5));
+        assert_eq!(newer.truth, expected);
+        assert!(newer.truth.confidence > 0.6);
+        assert_eq!(session.ice_caked[0].superseded_by, Some(lazy.clone()));
+        assert_eq!(session.ice_caked[1].superseded_by, None);
+        assert_eq!(session.ice_caked[2].superseded_by, None);
+        
+        let fresh = session.breakthrough("never cache the config", 0.9).id.clone();
+        let disagreeing = session.ice_cake_with_confidence(&fresh, "parse the config file once, never cache", TruthValue::new(0.1, 0.7)).unwrap();
+        assert_eq!(disagreeing.truth, TruthValue::new(0.1, 0.7));
+        assert_eq!(session.ice_caked[2].superseded_by, Some(fresh));
+        assert_eq!(session.ice_caked[0].superseded_by, Some(lazy));
+    }
+    
+    fn circling(session: &mut LearningSession) {
+        session.encounter("cache lookups in the resolver");
+        session.struggle("borrow checker rejects the cache lookup", 0.6, 0.5);
+        let mut qualia = Qualia::from_metrics(0.4, 0.8, 0.2);
+        qualia.confusion = 0.6;
+        let failure = MomentBuilder::new(&session.id, "borrow checker still rejects the cache lookup").failure().with_qualia(qualia);
+        session.add_moment_deduped(failure.build(), 1.1).unwrap();
+        session.struggle("the borrow checker rejects the cache lookup again", 0.8, 0.7);
+    }
+    
+    #[test]
+    fn test_stuck_report_on_restated_struggles() {
+        let mut session = LearningSession::new("task");
+        circling(&mut session);
+        
+        let report = session.stuck_report(3).unwrap();
+        assert_eq!(report.repetitions, 3);
+        assert_eq!(report.frustration.len(), 3);
+        assert!(report.frustration.windows(2).all(|w| w[0] <= w[1]));
+        assert!(report.representative.contains("cache lookup"));
+        assert!(report.suggestions.is_empty());
+        assert!(session.stuck_report(2).is_some_and(|r| r.repetitions == 3));
+        assert!(session.stuck_report(4).is_none(), "the encounter breaks the run");
+        assert!(session.stuck_report(1).is_none());
+        assert!(session.stuck_report(10).is_none());
+        
+        let reflection = session.reflect_on_stuck(&report).clone();
+        assert_eq!(reflection.moment_type, MomentType::MetaReflection);
+        assert_eq!(reflection.links, vec![MomentLink { target_id: report.representative_id.clone(), kind: LinkKind::CausedBy }]);
+        assert_eq!(reflection.meta("stuck.repetitions"), Some("3"));
+        assert!(session.stuck_report(3).is_none());
+    }
+    
+    #[test]
+    fn test_stuck_report_ignores_varied_or_easing_struggles() {
+        let mut session = LearningSession::new("task");
+        session.struggle("borrow checker rejects the cache lookup", 0.6, 0.5);
+        session.struggle("flaky network test on ci", 0.7, 0.6);
+        session.struggle("linker runs out of memory", 0.8, 0.7);
+        assert!(session.stuck_report(3).is_none());
+        
+        let mut session = LearningSession::new("task");
+        session.struggle("borrow checker rejects the cache lookup", 0.6, 0.7);
+        session.struggle("borrow checker still rejects the cache lookup", 0.6, 0.5);
+        session.struggle("the borrow checker rejects the cache lookup again", 0.6, 0.6);
+        assert!(session.stuck_report(3).is_none(), "confusion went down");
+    }
+    
+    #[test]
+    fn test_stuck_report_suggests_resonant_moments() {
+        let mut session = LearningSession::new("task");
+        circling(&mut session);
+        let representative = session.get_moment(&session.stuck_report(3).unwrap().representative_id).unwrap().clone();
+        
+        let mut resonance = ResonanceCapture::new();
+        let past = MomentBuilder::new("earlier", &representative.content)
+            .moment_type(representative.moment_type.clone())
+            .with_qualia(representative.qualia.clone())
+            .build();
+        resonance.capture(&past, 1);
+        for moment in &session.moments {
+            resonance.capture(moment, 2);
+        }
+        let report = session.stuck_report_with(3, &mut resonance, 3).unwrap();
+        let ids: Vec<&str> = report.suggestions.iter().map(|s| s.moment_id.as_str()).collect();
+        assert!(ids.contains(&past.id.as_str()));
+        assert!(report.suggestions.iter().all(|s| session.get_moment(&s.moment_id).is_none_or(|m| m.moment_type == MomentType::Encounter)));
+    }
+    
+    #[test]
+    fn test_thaw_unknown_layer() {
+        let mut session = LearningSession::new("task");
+        let fix = session.breakthrough("track byte offsets", 0.9).id.clone();
+        assert_eq!(session.thaw(1, "nothing frozen yet"), Err(ThawError::UnknownLayer(1)));
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        assert_eq!(session.thaw(0, "ids start at 1"), Err(ThawError::UnknownLayer(0)));
+        assert_eq!(session.thaw(2, "only one layer"), Err(ThawError::UnknownLayer(2)));
+        assert_eq!(session.thaw("missing", "no such moment"), Err(ThawError::NotIceCaked("missing".to_string())));
+        assert_eq!(session.moments.len(), 1);
+        assert_eq!(session.ice_caked.len(), 1);
+    }
+    
+    #[test]
+    fn test_thaw_records_reversal() {
+        let mut session = LearningSession::new("task");
+        let cache = session.breakthrough("cache the parsed config", 0.9).id.clone();
+        let fix = session.breakthrough("track byte offsets", 0.9).id.clone();
+        session.ice_cake(&cache, "startup is slow").unwrap();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        
+        let layer = session.thaw(1, "config changes at runtime").unwrap();
+        assert_eq!((layer.layer_id, layer.decision_id.as_str(), layer.rationale.as_str()), (1, cache.as_str(), "startup is slow"));
+        assert_eq!(session.ice_caked.len(), 1);
+        assert_eq!(session.ice_caked[0].moment_id, fix);
+        
+        let reversal = session.moments.last().unwrap();
+        assert_eq!(reversal.moment_type, MomentType::DecisionReversal);
+        assert_eq!(reversal.content, "config changes at runtime");
+        assert_eq!(reversal.links, vec![MomentLink { target_id: cache.clone(), kind: LinkKind::Contradicts }]);
+        assert_eq!(reversal.meta("thawed_rationale"), Some("startup is slow"));
+        assert!(!reversal.is_breakthrough());
+        let reversal_id = reversal.id.clone();
+        assert_eq!(session.reversals_of(&cache).len(), 1);
+        
+        assert_eq!(session.thaw(&cache, "again"), Err(ThawError::NotIceCaked(cache.clone())));
+        let refrozen = session.ice_cake(&cache, "cache with invalidation").unwrap();
+        assert_eq!(refrozen.after_reversal, Some(reversal_id));
+        assert_eq!(session.ice_caked[0].after_reversal, None);
+        
+        session.close(SessionOutcome::Completed).unwrap();
+        assert!(matches!(session.thaw(&fix, "too late"), Err(ThawError::Closed(_))));
+    }
+    
+    #[test]
+    fn test_verify_links_and_revises_hypothesis() {
+        let mut session = LearningSession::new("task");
+        let prior = TruthValue::new(0.5, 0.2);
+        let id = session.hypothesize("off-by-one in the pager", prior.clone()).id.clone();
+        let phase = session.phase.clone();
+        
+        let check = session.verify(&id, true, "page 2 repeats the last row").unwrap();
+        assert_eq!(check.moment_type, MomentType::Verification { confirmed: true });
+        assert_eq!(check.links_of(LinkKind::Verifies).collect::<Vec<_>>(), vec![id.as_str()]);
+        assert_eq!(session.phase, phase);
+        let confirmed = session.get_moment(&id).unwrap().truth.clone();
+        assert!(confirmed.frequency > prior.frequency);
+        assert!(confirmed.confidence > prior.confidence);
+        
+        session.verify(&id, false, "page 3 is fine");
+        session.verify(&id, false, "page 4 is fine");
+        let refuted = &session.get_moment(&id).unwrap().truth;
+        assert!(refuted.frequency < prior.frequency);
+        assert!(refuted.confidence > confirmed.confidence);
+        
+        let encounter = session.encounter("not a guess").id.clone();
+        assert!(session.verify(&encounter, true, "nothing to verify").is_none());
+        assert!(session.verify("missing", true, "nothing to verify").is_none());
+    }
+    
+    #[test]
+    fn test_open_hypotheses() {
+        let mut session = LearningSession::new("task");
+        let first = session.hypothesize("dns is slow", TruthValue::unknown()).id.clone();
+        let second = session.hypothesize("tls handshake retries", TruthValue::unknown()).id.clone();
+        assert_eq!(session.open_hypotheses().len(), 2);
+        
+        session.verify(&first, false, "dig answers in 2ms");
+        let open: Vec<&str> = session.open_hypotheses().iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(open, vec![second.as_str()]);
+        assert_eq!(session.state().open_hypotheses, vec!["tls handshake retries".to_string()]);
+    }
+    
+    #[test]
+    fn test_moments_tagged() {
+        let mut session = LearningSession::new("task");
+        let login = session.encounter("login loops").id.clone();
+        let slow = session.struggle("token refresh is slow", 0.6, 0.4).id.clone();
+        session.encounter("unrelated");
+        assert!(session.tag(&login, "Auth"));
+        assert!(session.tag(&slow, "#auth"));
+        assert!(session.tag(&slow, "performance"));
+        assert!(!session.tag(&slow, "AUTH"));
+        assert!(!session.tag("missing", "auth"));
+        
+        let tagged: Vec<&str> = session.moments_tagged("#AUTH").iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(tagged, vec![login.as_str(), slow.as_str()]);
+        assert_eq!(session.moments_tagged("performance").len(), 1);
+        assert_eq!(session.state().tags.get("auth"), Some(&2));
+    }
+    
+    #[test]
+    fn test_infer_resolutions() {
+        let mut session = LearningSession::new("task");
+        let borrow = session.struggle("borrowck rejects the cache lookup in the resolver", 0.7, 0.6).id.clone();
+        let pool = session.struggle("integration tests time out waiting for the database pool", 0.6, 0.5).id.clone();
+        session.encounter("read the sqlx docs");
+        let pool_fix = session.breakthrough("database pool was exhausted by integration tests; raise the pool size", 0.9).id.clone();
+        let borrow_fix = session.breakthrough("clone the cache key so the resolver lookup no longer borrows", 0.8).id.clone();
+        let unrelated = session.breakthrough("markdown tables need a header separator row", 0.9).id.clone();
+        
+        let pairs = session.infer_resolutions(DEFAULT_RESOLUTION_SIMILARITY);
+        let found: Vec<(&str, &str, usize)> = pairs.iter()
+            .map(|p| (p.struggle_id.as_str(), p.breakthrough_id.as_str(), p.intervening))
+            .collect();
+        assert_eq!(found, vec![(pool.as_str(), pool_fix.as_str(), 1), (borrow.as_str(), borrow_fix.as_str(), 3)]);
+        assert!(pairs.iter().all(|p| p.similarity >= DEFAULT_RESOLUTION_SIMILARITY));
+        assert!(pairs.iter().all(|p| p.breakthrough_id != unrelated));
+    }
+    
+    #[test]
+    fn test_infer_resolutions_respects_explicit_links() {
+        let mut session = LearningSession::new("task");
+        let stuck = session.struggle("cache invalidation races", 0.7, 0.6).id.clone();
+        let other = session.struggle("cache invalidation races again", 0.7, 0.6).id.clone();
+        let fix = session.breakthrough_resolving(&other, "version the cache entries", 0.9).id.clone();
+        let later = session.breakthrough("cache invalidation races fixed by versioning", 0.9).id.clone();
+        
+        let pairs = session.infer_resolutions(DEFAULT_RESOLUTION_SIMILARITY);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!((pairs[0].struggle_id.as_str(), pairs[0].breakthrough_id.as_str()), (other.as_str(), fix.as_str()));
+        assert_eq!((pairs[1].struggle_id.as_str(), pairs[1].breakthrough_id.as_str()), (stuck.as_str(), later.as_str()));
+    }
+    
+    #[test]
+    fn test_edit_moment() {
+        let mut session = LearningSession::new("task");
+        session.max_edit_history = 2;
+        let id = session.encounter("teh parser").id.clone();
+        
+        let edited = session.edit_moment(&id, "the parser").unwrap();
+        assert_eq!(edited.fingerprint, Fingerprint::from_content("the parser"));
+        assert_eq!(edited.revision, 1);
+        session.edit_moment(&id, "the lexer").unwrap();
+        session.edit_moment(&id, "the tokenizer").unwrap();
+        let moment = session.get_moment(&id).unwrap();
+        assert_eq!(moment.edit_history, vec![(1, "the parser".to_string()), (2, "the lexer".to_string())]);
+        let query = moment.qualia.weight_fingerprint(&Fingerprint::from_content("the tokenizer"));
+        assert_eq!(session.find_similar(&query, 0.99).len(), 1);
+        
+        assert_eq!(session.edit_moment("missing", "x").unwrap_err(), EditError::UnknownMoment("missing".to_string()));
+        assert_eq!(session.edit_moment(&id, "  ").unwrap_err(), EditError::EmptyContent);
+        assert_eq!(session.get_moment(&id).unwrap().revision, 3);
+    }
+    
+    #[test]
+    fn test_content_limit_boundary() {
+        let mut session = LearningSession::with_config("task", SessionConfig { max_content_chars: 10, ..SessionConfig::default() });
+        let fits = session.encounter("0123456789").clone();
+        assert_eq!(fits.content, "0123456789");
+        assert_eq!(fits.raw_content, None);
+        
+        let over = session.struggle("0123456789A", 0.5, 0.5).clone();
+        assert_eq!(over.content, "0123 … 89A");
+        assert_eq!(over.raw_content.as_deref(), Some("0123456789A"));
+    }
+    
+    #[test]
+    fn test_summarized_fingerprints_ignore_the_middle() {
+        let mut session = LearningSession::with_config("task", SessionConfig { max_content_chars: 60, ..SessionConfig::default() });
+        let a = session.struggle("thread 'main' panicked\nframe tokio::runtime\nframe hyper::client\nat src/api.rs:42", 0.7, 0.6).clone();
+        let b = session.struggle("thread 'main' panicked\nframe std::rt\nframe reqwest::blocking\nat src/api.rs:42", 0.7, 0.6).clone();
+        assert_ne!(a.raw_content, b.raw_content);
+        assert_eq!(a.content, b.content);
+        assert_eq!(a.fingerprint, b.fingerprint);
+    }
+    
+    #[test]
+    fn test_exact_duplicates_merge() {
+        let mut session = LearningSession::new("task");
+        let first = session.encounter("GET /health returned 503").id.clone();
+        let retry = MomentBuilder::new(&session.id, "GET /health returned 503").encounter().qualia(0.1, 0.8, 0.1).build();
+        
+        let outcome = session.add_moment_deduped(retry, 0.95).unwrap();
+        assert_eq!(outcome, AddOutcome::Merged { id: first.clone(), repeat_count: 1 });
+        assert_eq!(session.moments.len(), 1);
+        let merged = session.get_moment(&first).unwrap();
+        assert!((merged.qualia.novelty - 0.3).abs() < 1e-6);
+        assert!((merged.qualia.effort - 0.5).abs() < 1e-6);
+        assert_eq!(merged.resonance_vector, merged.qualia.weight_fingerprint(&merged.fingerprint));
+        
+        let other_type = MomentBuilder::new(&session.id, "GET /health returned 503").struggle().build();
+        assert!(matches!(session.add_moment_deduped(other_type, 0.95).unwrap(), AddOutcome::Added(_)));
+    }
+    
+    #[test]
+    fn test_near_duplicates_respect_threshold() {
+        let near = || MomentBuilder::new("s", "retrying upload of report.csv to bucket reports attempt three").encounter().build();
+        let session_with_first = || {
+            let mut session = LearningSession::new("task");
+            let id = session.encounter("retrying upload of report.csv to bucket reports attempt two").id.clone();
+            (session, id)
+        };
+        let (mut strict, _) = session_with_first();
+        let similarity = Fingerprint::from_words(&strict.moments[0].content)
+            .similarity(&Fingerprint::from_words(&near().content));
+        assert!(similarity > 0.7 && similarity < 1.0);
+        
+        assert!(matches!(strict.add_moment_deduped(near(), similarity + 0.01).unwrap(), AddOutcome::Added(_)));
+        assert_eq!(strict.moments.len(), 2);
+        
+        let (mut loose, first) = session_with_first();
+        assert_eq!(loose.add_moment_deduped(near(), similarity - 0.01).unwrap(), AddOutcome::Merged { id: first, repeat_count: 1 });
+        assert_eq!(loose.moments.len(), 1);
+    }
+    
+    #[test]
+    fn test_dedup_policy_switch() {
+        let mut session = LearningSession::new("task");
+        session.encounter("poll queue");
+        session.encounter("poll queue");
+        assert_eq!(session.moments.len(), 2);
+        
+        session.config.dedup = Some(DedupPolicy::default());
+        let id = session.encounter("poll queue").id.clone();
+        assert_eq!(session.moments.len(), 2);
+        assert_eq!(session.get_moment(&id).unwrap().repeat_count, 1);
+        assert!(session.timeline().lines().nth(1).unwrap().ends_with(" ↻1"));
+        
+        session.config.dedup = Some(DedupPolicy { window: 0, ..DedupPolicy::default() });
+        session.encounter("poll queue");
+        assert_eq!(session.moments.len(), 3);
+    }
+    
+    #[test]
+    fn test_seq_is_monotonic_through_merges() {
+        let mut session = LearningSession::new("task");
+        let a = session.encounter("poll queue").id.clone();
+        let b = session.struggle("queue is empty", 0.5, 0.5).id.clone();
+        let repeat = MomentBuilder::new(&session.id, "poll queue").encounter().build();
+        session.add_moment_deduped(repeat, 0.95).unwrap();
+        let c = session.encounter("queue filled").id.clone();
+        
+        let ordered: Vec<(&str, u64)> = session.moments_ordered().iter().map(|m| (m.id.as_str(), m.seq)).collect();
+        assert_eq!(ordered, vec![(a.as_str(), 1), (b.as_str(), 2), (c.as_str(), 3)]);
+    }
+    
+    #[test]
+    fn test_seq_survives_save_and_load() {
+        let mut session = LearningSession::new("task");
+        session.encounter("one");
+        session.encounter("two");
+        let json = serde_json::to_string(&session.moments).unwrap();
+        let mut saved: Vec<Moment> = serde_json::from_str(&json).unwrap();
+        saved.reverse();
+        
+        let mut restored = LearningSession::restore("task", saved);
+        assert_eq!(restored.id, session.id);
+        let seqs: Vec<u64> = restored.moments_ordered().iter().map(|m| m.seq).collect();
+        assert_eq!(seqs, vec![1, 2]);
+        assert_eq!(restored.moments[0].content, "one");
+        assert_eq!(restored.encounter("three").seq, 3);
+        
+        let mut legacy = Moment::new(&session.id, "old", MomentType::Encounter);
+        legacy.seq = 0;
+        let restored = LearningSession::restore("task", vec![restored.moments[2].clone(), legacy]);
+        let seqs: Vec<(&str, u64)> = restored.moments.iter().map(|m| (m.content.as_str(), m.seq)).collect();
+        assert_eq!(seqs, vec![("three", 3), ("old", 4)]);
+    }
+    
+    #[test]
+    fn test_effort_tracks_struggle_history() {
+        let mut easy = LearningSession::new("task");
+        easy.encounter("read the error");
+        let easy_effort = easy.breakthrough("obvious typo", 0.9).qualia.effort;
+        
+        let mut hard = LearningSession::new("task");
+        for i in 0..5 {
+            hard.struggle(&format!("attempt {}", i), 0.9, 0.9);
+        }
+        let expected = hard.estimate_effort();
+        let moment = hard.breakthrough("lifetime variance", 0.9);
+        assert_eq!(moment.qualia.effort, expected);
+        assert!(moment.qualia.effort > 0.8);
+        assert!(easy_effort < 0.1);
+        assert_eq!(moment.meta("effort.struggles"), Some("5"));
+        assert_eq!(moment.meta("effort.frustration"), Some("4.50"));
+        assert_eq!(moment.meta("effort.moments"), Some("5"));
+        
+        // Counting restarts after each breakthrough
+        assert!(hard.estimate_effort() < 0.01);
+        let explicit = Qualia::from_metrics(0.8, 0.3, 0.9);
+        assert_eq!(hard.breakthrough_with_qualia("explicit", explicit).qualia.effort, 0.3);
+    }
+    
+    #[test]
+    fn test_encounter_here_and_moments_in_file() {
+        let mut session = LearningSession::new("task");
+        let line = line!() + 1;
+        let here = session.encounter_here("instrumented").clone();
+        let source = here.source.unwrap();
+        assert_eq!((source.path.as_str(), source.line), (file!(), Some(line)));
+        
+        let builder = MomentBuilder::new(&session.id, "lexer bug").struggle().source_at("src/lexer.rs", 42);
+        session.add_moment_deduped(builder.build(), 1.1).unwrap();
+        session.encounter("no source");
+        
+        assert_eq!(session.moments_in_file(file!()).len(), 1);
+        let lexer = session.moments_in_file("./src/lexer.rs");
+        assert_eq!(lexer.len(), 1);
+        assert_eq!(lexer[0].content, "lexer bug");
+        assert!(session.moments_in_file("src/parser.rs").is_empty());
+    }
+    
+    #[test]
+    fn test_validate_links_reports_dangling() {
+        let mut session = LearningSession::new("task");
+        let failure = session.fail("deploy broke", "pin versions").id.clone();
+        let retry = session.encounter("retry deploy").id.clone();
+        assert!(session.link(&retry, LinkKind::CausedBy, &failure));
+        assert_eq!(session.validate_links(), Ok(()));
+        
+        session.link(&retry, LinkKind::Follows, "gone");
+        let err = session.validate_links().unwrap_err();
+        assert_eq!(err.links, vec![(retry, MomentLink { target_id: "gone".to_string(), kind: LinkKind::Follows })]);
+        assert_eq!(err.to_string(), "1 link(s) point at unknown moments");
+    }
+    
+    #[test]
+    fn test_phase_durations_with_mock_clock() {
+        let clock = MockClock::new(1_000);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("opened the crate");
+        clock.advance(5_000);
+        session.struggle("lifetimes everywhere", 0.7, 0.6);
+        clock.advance(3_000);
+        session.struggle("still stuck", 0.8, 0.7);
+        clock.advance(11_000);
+        session.breakthrough("it's a self-referential struct", 0.9);
+        
+        assert_eq!(session.moments[3].created_at, 20_000);
+        assert_eq!(session.phase_durations(), vec![
+            (SessionPhase::Encounter, 5_000),
+            (SessionPhase::Struggle, 14_000),
+            (SessionPhase::Breakthrough, 0),
+        ]);
+        
+        let state = session.state();
+        assert_eq!(state.duration_ms, 19_000);
+        assert_eq!(state.longest_gap_ms, 11_000);
+    }
+    
+    /// Out-of-order script: struggle, encounter, breakthrough, encounter,
+    /// struggle, breakthrough, ice cake, struggle, reflection, apply
+    fn script_phases(session: &mut LearningSession) {
+        session.struggle("borrowck rejects the cache", 0.7, 0.6);
+        session.encounter("read the nomicon chapter");
+        session.breakthrough("split the borrow", 0.8);
+        session.encounter("looked at the call sites");
+        session.struggle("the iterator still aliases", 0.6, 0.5);
+        let fix = session.breakthrough("collect the keys first", 0.9).id.clone();
+        session.ice_cake(&fix, "no aliasing through iterators").unwrap();
+        session.struggle("one call site left", 0.4, 0.3);
+        session.meta_reflect("draw the ownership first");
+        session.apply("refactored the other cache", true);
+    }
+    
+    #[test]
+    fn test_auto_phase_only_moves_forward() {
+        let config = SessionConfig { auto_phase: true, ..SessionConfig::new() };
+        let mut session = LearningSession::with_config("task", config);
+        script_phases(&mut session);
+        
+        assert_eq!(session.phase_history(), vec![
+            (SessionPhase::Struggle, 1),
+            (SessionPhase::Breakthrough, 3),
+            (SessionPhase::Struggle, 5),
+            (SessionPhase::Breakthrough, 6),
+            (SessionPhase::Consolidate, 7),
+            (SessionPhase::MetaLearn, 8),
+        ]);
+        assert_eq!(session.state().phase, SessionPhase::MetaLearn);
+        
+        let config = SessionConfig { auto_phase: true, ..SessionConfig::new() };
+        let mut session = LearningSession::with_config("task", config);
+        session.meta_reflect("start from what went wrong last time");
+        session.encounter("the same parser bug");
+        session.struggle("offsets again", 0.5, 0.5);
+        session.complete().unwrap();
+        assert_eq!(session.phase_history(), vec![(SessionPhase::MetaLearn, 1), (SessionPhase::Complete, 4)]);
+    }
+    
+    #[test]
+    fn test_manual_phase_follows_every_helper() {
+        let mut session = LearningSession::new("task");
+        script_phases(&mut session);
+        
+        assert_eq!(session.phase_history(), vec![
+            (SessionPhase::Struggle, 1),
+            (SessionPhase::Encounter, 2),
+            (SessionPhase::Breakthrough, 3),
+            (SessionPhase::Encounter, 4),
+            (SessionPhase::Struggle, 5),
+            (SessionPhase::Breakthrough, 6),
+            (SessionPhase::Consolidate, 7),
+            (SessionPhase::Struggle, 7),
+            (SessionPhase::MetaLearn, 8),
+            (SessionPhase::Apply, 9),
+        ]);
+        assert_eq!(session.state().phase, SessionPhase::Apply);
+    }
+    
+    const ALL_PHASES: [SessionPhase; 8] = [
+        SessionPhase::Initialize, SessionPhase::Encounter, SessionPhase::Struggle, SessionPhase::Breakthrough,
+        SessionPhase::Consolidate, SessionPhase::Apply, SessionPhase::MetaLearn, SessionPhase::Complete,
+    ];
+    
+    #[test]
+    fn test_advance_phase_transition_matrix() {
+        use SessionPhase::*;
+        let legal = [
+            (Initialize, Encounter),
+            (Encounter, Struggle), (Encounter, Breakthrough),
+            (Struggle, Encounter), (Struggle, Breakthrough),
+            (Breakthrough, Struggle), (Breakthrough, Consolidate),
+            (Consolidate, Apply), (Consolidate, MetaLearn),
+            (Apply, Struggle), (Apply, MetaLearn), (Apply, Complete),
+            (MetaLearn, Encounter), (MetaLearn, Complete),
+        ];
+        for from in ALL_PHASES {
+            for to in ALL_PHASES {
+                let mut session = LearningSession::new("task");
+                session.phase = from.clone();
+                let result = session.advance_phase(to.clone());
+                if from == to || legal.contains(&(from.clone(), to.clone())) {
+                    assert_eq!(result, Ok(()), "{from:?} -> {to:?}");
+                    assert_eq!(session.current_phase(), &to);
+                } else {
+                    let targets: Vec<SessionPhase> = legal.iter().filter(|(f, _)| *f == from).map(|(_, t)| t.clone()).collect();
+                    assert_eq!(result, Err(PhaseTransitionError::Illegal { from: from.clone(), to: to.clone(), legal: targets }));
+                    assert_eq!(session.current_phase(), &from);
+                }
+            }
+        }
+        
+        let mut session = LearningSession::new("task");
+        session.advance_phase(Encounter).unwrap();
+        let err = session.advance_phase(Consolidate).unwrap_err();
+        assert_eq!(err.to_string(), "cannot move from Encounter to Consolidate; legal targets: Struggle, Breakthrough");
+        session.phase = Complete;
+        assert!(session.advance_phase(Encounter).unwrap_err().to_string().ends_with("legal targets: none"));
+    }
+    
+    #[test]
+    fn test_force_phase_records_override() {
+        let mut session = LearningSession::new("task");
+        session.encounter("parser rejects UTF-8");
+        assert!(session.advance_phase(SessionPhase::Consolidate).is_err());
+        session.force_phase(SessionPhase::Consolidate, "fix was known from the last session").unwrap();
+        session.force_phase(SessionPhase::Consolidate, "already there").unwrap();
+        
+        assert_eq!(session.current_phase(), &SessionPhase::Consolidate);
+        assert_eq!(session.phase_overrides(), [PhaseOverride {
+            from: SessionPhase::Encounter,
+            to: SessionPhase::Consolidate,
+            reason: "fix was known from the last session".to_string(),
+            seq: 1,
+        }]);
+        assert_eq!(session.state().phase_overrides, session.phase_overrides());
+        
+        session.close(SessionOutcome::Completed).unwrap();
+        assert!(matches!(session.advance_phase(SessionPhase::Encounter), Err(PhaseTransitionError::Closed(_))));
+        assert!(session.force_phase(SessionPhase::Encounter, "reopen").is_err());
+        assert_eq!(session.current_phase(), &SessionPhase::Complete);
+    }
+    
+    #[test]
+    fn test_span_moment_extends_phase() {
+        let clock = MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("pairing session");
+        session.moments[0].duration_ms = Some(60_000);
+        clock.advance(70_000);
+        session.struggle("after the call", 0.5, 0.5);
+        
+        assert_eq!(session.longest_gap_ms(), 10_000);
+        assert_eq!(session.phase_durations()[0], (SessionPhase::Encounter, 70_000));
+        assert_eq!(session.span_ms(), 70_000);
+    }
+    
+    #[test]
+    fn test_pause_and_resume_are_idempotent() {
+        let clock = MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        assert!(!session.resume());
+        assert!(session.pause());
+        clock.advance(1_000);
+        assert!(!session.pause());
+        assert!(session.is_paused() && session.state().paused);
+        clock.advance(1_000);
+        assert!(session.resume());
+        assert!(!session.resume());
+        assert_eq!(session.pauses(), &[(0, Some(2_000))]);
+    }
+    
+    #[test]
+    fn test_durations_exclude_paused_time() {
+        let clock = MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("start");
+        clock.advance(5_000);
+        session.pause();
+        clock.advance(3_600_000);
+        session.resume();
+        clock.advance(2_000);
+        session.struggle("stuck", 0.7, 0.6);
+        clock.advance(4_000);
+        session.breakthrough("done", 0.9);
+        
+        let state = session.state();
+        assert_eq!(state.wall_clock_ms, 3_611_000);
+        assert_eq!(state.duration_ms, 11_000);
+        assert_eq!(state.longest_gap_ms, 7_000);
+        assert_eq!(session.phase_durations(), vec![
+            (SessionPhase::Encounter, 7_000),
+            (SessionPhase::Struggle, 4_000),
+            (SessionPhase::Breakthrough, 0),
+        ]);
+        
+        // a pause still running counts up to now
+        session.pause();
+        clock.advance(10_000);
+        session.moments[2].created_at += 10_000;
+        assert_eq!(session.active_ms(), 11_000);
+    }
+    
+    #[test]
+    fn test_capture_while_paused() {
+        let clock = MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.pause();
+        clock.advance(1_000);
+        session.encounter("auto-resumes");
+        assert!(!session.is_paused());
+        assert_eq!(session.pauses(), &[(0, Some(1_000))]);
+        
+        session.config.capture_while_paused = false;
+        session.pause();
+        assert_eq!(session.ensure_active(), Err(SessionPaused));
+        assert_eq!(session.try_encounter("refused").err(), Some(CaptureError::Paused(SessionPaused)));
+        let repeat = MomentBuilder::new(&session.id, "auto-resumes").encounter().build();
+        assert_eq!(session.add_moment_deduped(repeat, 0.9), Err(CaptureError::Paused(SessionPaused)));
+        assert_eq!(session.moments.len(), 1);
+        assert!(session.is_paused());
+    }
+    
+    #[test]
+    fn test_close_freezes_report() {
+        let clock = MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("start");
+        clock.advance(2_000);
+        session.struggle("stuck", 0.8, 0.7);
+        clock.advance(2_000);
+        let fix = session.breakthrough("done", 0.9).id.clone();
+        session.ice_cake(&fix, "keep it").unwrap();
+        session.pause();
+        clock.advance(60_000);
+        
+        let report = session.close(SessionOutcome::Completed).unwrap().clone();
+        assert_eq!(report.outcome, SessionOutcome::Completed);
+        assert_eq!((report.moment_count, report.breakthrough_count), (3, 1));
+        assert_eq!(report.moment_types.get("Struggle"), Some(&1));
+        assert_eq!((report.duration_ms, report.closed_at), (4_000, 64_000));
+        assert_eq!(report.trajectory.peak_frustration_cycle, Some(1));
+        assert_eq!(report.trajectory.time_to_breakthrough, Some(1));
+        assert_eq!(report.ice_caked, session.ice_caked);
+        assert_eq!(session.phase, SessionPhase::Complete);
+        assert!(!session.is_paused());
+        assert_eq!(session.report(), Some(&report));
+    }
+    
+    #[test]
+    fn test_closed_session_rejects_mutation() {
+        let mut session = LearningSession::new("task");
+        let hypothesis = session.hypothesize("cache is stale", TruthValue::new(0.5, 0.2)).id.clone();
+        let abandoned = SessionOutcome::Abandoned { reason: AbandonReason::Deprioritized };
+        session.close(abandoned.clone()).unwrap();
+        
+        let closed = SessionClosedError { outcome: abandoned };
+        assert_eq!(session.close(SessionOutcome::Completed).err(), Some(closed.clone()));
+        assert_eq!(session.ensure_open(), Err(closed.clone()));
+        assert_eq!(closed.to_string(), "session is closed (abandoned: deprioritized)");
+        assert_eq!(session.edit_moment(&hypothesis, "cache is fresh").err(), Some(EditError::Closed(closed.clone())));
+        assert!(!session.tag(&hypothesis, "cache"));
+        assert!(!session.link(&hypothesis, LinkKind::Follows, &hypothesis));
+        assert!(!session.pause());
+        assert!(session.verify(&hypothesis, true, "flushed").is_none());
+        assert_eq!(session.ice_cake(&hypothesis, "no").err(), Some(IceCakeError::Closed(closed.clone())));
+        assert_eq!(session.try_encounter("too late").err(), Some(CaptureError::Closed(closed.clone())));
+        assert_eq!(session.try_fail("too late", "close later").err(), Some(CaptureError::Closed(closed.clone())));
+        let late = MomentBuilder::new(&session.id, "too late").encounter().build();
+        assert_eq!(session.add_moment_deduped(late, 0.9), Err(CaptureError::Closed(closed.clone())));
+        assert_eq!(session.checkpoint("too late"), Err(closed.clone()));
+        assert_eq!(session.begin_subtask("too late"), Err(closed.clone()));
+        assert_eq!(session.complete(), Err(closed));
+        assert_eq!(session.moments.len(), 1);
+        assert_eq!(session.moments[0].content, "cache is stale");
+        assert_eq!(session.phase, SessionPhase::Initialize);
+    }
+    
+    #[test]
+    fn test_merge_branch_appends_after_parent() {
+        let clock = MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("parser rejects UTF-8");
+        let mut branch = session.fork("rewrite-lexer");
+        let mut other = session.fork("other");
+        assert_eq!((branch.task_id.as_str(), branch.moments.len()), ("task", 1));
+        assert_eq!(branch.branch_name(), Some("rewrite-lexer"));
+        
+        clock.advance(1_000);
+        let tried = branch.struggle("rewrite the lexer from scratch", 0.9, 0.5).id.clone();
+        other.encounter("unrelated idea");
+        clock.advance(1_000);
+        session.struggle("patch offsets in place", 0.6, 0.5);
+        clock.advance(1_000);
+        branch.breakthrough("new lexer passes the suite", 0.9);
+        
+        let nested = session.merge_branch(branch.fork("nested"), MergePolicy::new());
+        assert_eq!(nested, Err(MergeError::NotABranch));
+        let keep_seqs = MergePolicy { renumber: false, ..MergePolicy::new() };
+        assert_eq!(session.merge_branch(other, keep_seqs), Err(MergeError::SeqConflict { seq: 2 }));
+        
+        let merged = session.merge_branch(branch, MergePolicy::new()).unwrap();
+        assert_eq!(merged.added.len(), 2);
+        assert_eq!(merged.added[0], tried);
+        let ordered: Vec<(u64, &str)> = session.moments.iter().map(|m| (m.seq, m.content.as_str())).collect();
+        assert_eq!(ordered, vec![
+            (1, "parser rejects UTF-8"),
+            (2, "patch offsets in place"),
+            (3, "rewrite the lexer from scratch"),
+            (4, "new lexer passes the suite"),
+        ]);
+        let from_branch = session.get_moment(&tried).unwrap();
+        assert_eq!(from_branch.meta("branch"), Some("rewrite-lexer"));
+        assert_eq!(from_branch.session_id, session.id);
+        assert_eq!(session.encounter("after merge").seq, 5);
+    }
+    
+    #[test]
+    fn test_merge_branch_keeps_seqs_when_parent_is_idle() {
+        let mut session = LearningSession::new("task");
+        session.encounter("start");
+        let mut branch = session.fork("spike");
+        branch.encounter("spiked it");
+        let keep_seqs = MergePolicy { renumber: false, ..MergePolicy::new() };
+        session.merge_branch(branch, keep_seqs).unwrap();
+        assert_eq!(session.moments[1].seq, 2);
+        assert_eq!(session.encounter("next").seq, 3);
+    }
+    
+    #[test]
+    fn test_merge_branch_dedups_against_parent() {
+        let mut session = LearningSession::new("task");
+        session.encounter("start");
+        let mut branch = session.fork("retry");
+        session.struggle("cache misses on every request", 0.7, 0.5);
+        let parent_struggle = session.moments[1].id.clone();
+        let repeat = branch.struggle("cache misses on every request", 0.9, 0.6).id.clone();
+        let fix = MomentBuilder::new(&branch.id, "key the cache by path").breakthrough().resolves(&repeat).build();
+        let fix = match branch.add_moment_deduped(fix, 1.1).unwrap() { AddOutcome::Added(id) => id, other => panic!("{:?}", other) };
+        branch.ice_cake(&repeat, "cache by path").unwrap();
+        
+        let policy = MergePolicy { dedup_threshold: Some(0.95), ..MergePolicy::new() };
+        let merged = session.merge_branch(branch, policy).unwrap();
+        assert_eq!(merged.merged, vec![(repeat, parent_struggle.clone())]);
+        assert_eq!(merged.added, vec![fix.clone()]);
+        assert_eq!(session.moments[1].repeat_count, 1);
+        assert_eq!(session.get_moment(&fix).unwrap().links[0].target_id, parent_struggle);
+        assert_eq!(session.ice_caked.len(), 1);
+        assert_eq!(session.ice_caked[0].moment_id, parent_struggle);
+        assert!(session.validate_links().is_ok());
+    }
+    
+    #[test]
+    fn test_discarded_branch_leaves_parent_untouched() {
+        let mut session = LearningSession::new("task");
+        let start = session.encounter("start").id.clone();
+        let mut branch = session.fork("dead-end");
+        let idea = branch.hypothesize("it is a race", TruthValue::new(0.5, 0.2)).id.clone();
+        branch.ice_cake(&idea, "assume a race").unwrap();
+        branch.tag(&start, "race");
+        drop(branch);
+        
+        assert_eq!(session.moments.len(), 1);
+        assert!(session.ice_caked.is_empty());
+        assert!(session.moments[0].tags.is_empty());
+        assert_eq!(session.encounter("continue").seq, 2);
+    }
+    
+    fn clocked(start: u64) -> (MockClock, LearningSession) {
+        let clock = MockClock::new(start);
+        let session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        (clock, session)
+    }
+    
+    #[test]
+    fn test_merge_sessions_interleaves_by_time() {
+        let (clock_a, mut a) = clocked(0);
+        let (clock_b, mut b) = clocked(10_000);
+        a.encounter("a0");
+        clock_a.advance(20_000);
+        b.struggle("b10", 0.5, 0.5);
+        clock_b.advance(10_000);
+        a.breakthrough("a20", 0.9);
+        b.struggle("b20", 0.5, 0.5);
+        
+        let merged = merge_sessions(&a, &b, &MergePolicy::new()).unwrap();
+        let order: Vec<(u64, &str)> = merged.moments.iter().map(|m| (m.seq, m.content.as_str())).collect();
+        assert_eq!(order, vec![(1, "a0"), (2, "b10"), (3, "a20"), (4, "b20")]);
+        assert_eq!(merged.moments[1].meta("origin_session"), Some(b.id.as_str()));
+        assert!(merged.moments.iter().all(|m| m.session_id == merged.id));
+        assert_eq!(merged.phase, SessionPhase::Struggle);
+        
+        let other = LearningSession::new("other-task");
+        assert_eq!(
+            merge_sessions(&a, &other, &MergePolicy::new()).err(),
+            Some(MergeError::TaskMismatch { a: "task".to_string(), b: "other-task".to_string() }),
+        );
+        let cross = MergePolicy { allow_cross_task: true, ..MergePolicy::new() };
+        assert_eq!(merge_sessions(&a, &other, &cross).unwrap().task_id, "task");
+    }
+    
+    #[test]
+    fn test_merge_sessions_surfaces_ice_cake_conflicts() {
+        let (_, mut a) = clocked(0);
+        let (_, mut b) = clocked(5_000);
+        let struggle_a = a.struggle("the cache key ignores the locale", 0.8, 0.6).id.clone();
+        let struggle_b = b.struggle("the cache key ignores the locale", 0.7, 0.5).id.clone();
+        a.ice_cake(&struggle_a, "add the locale to the key").unwrap();
+        b.ice_cake(&struggle_b, "drop the cache for localized pages").unwrap();
+        let fix = b.breakthrough_resolving(&struggle_b, "locale-aware cache keys", 0.9).id.clone();
+        b.ice_cake(&fix, "keys include the locale").unwrap();
+        
+        let policy = MergePolicy { dedup_threshold: Some(0.95), ..MergePolicy::new() };
+        let merged = merge_sessions(&a, &b, &policy).unwrap();
+        assert_eq!(merged.moments.len(), 2);
+        assert_eq!(merged.ice_caked.len(), 1);
+        assert_eq!(merged.ice_caked[0].moment_id, fix);
+        assert_eq!(merged.ice_cake_conflicts.len(), 1);
+        let conflict = &merged.ice_cake_conflicts[0];
+        assert_eq!(conflict.moment_id, struggle_a);
+        let rationales: Vec<&str> = conflict.decisions.iter().map(|d| d.rationale.as_str()).collect();
+        assert_eq!(rationales, vec!["add the locale to the key", "drop the cache for localized pages"]);
+        assert_eq!(merged.get_moment(&fix).unwrap().links[0].target_id, struggle_a);
+        
+        let agreed = merge_sessions(&a, &a, &policy).unwrap();
+        assert_eq!((agreed.ice_caked.len(), agreed.ice_cake_conflicts.len()), (1, 0));
+    }
+    
+    #[test]
+    fn test_merge_sessions_recomputes_counters() {
+        let (_, mut a) = clocked(0);
+        let (_, mut b) = clocked(1_000);
+        a.encounter("found the flaky test");
+        a.breakthrough("the test sleeps on a timer", 0.9);
+        b.encounter("found the flaky test");
+        b.struggle("can't reproduce locally", 0.6, 0.7);
+        b.breakthrough("CI has a slower clock", 0.9);
+        
+        let policy = MergePolicy { dedup_threshold: Some(0.95), ..MergePolicy::new() };
+        let mut merged = merge_sessions(&a, &b, &policy).unwrap();
+        let state = merged.state();
+        assert_eq!((state.moment_count, state.breakthrough_count), (4, 2));
+        assert_eq!(state.moment_types.get("Encounter"), Some(&1));
+        assert_eq!(merged.cycle, 4);
+        assert_eq!(merged.moments[0].repeat_count, 1);
+        let seqs: Vec<u64> = merged.moments.iter().map(|m| m.seq).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 4]);
+        assert_eq!(merged.encounter("next").seq, 5);
+    }
+    
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ladybug-{}-{}.json", name, uuid::Uuid::new_v4()))
+    }
+    
+    #[test]
+    fn test_save_and_load_json_round_trip() {
+        let clock = MockClock::new(1_000);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("parser rejects UTF-8");
+        clock.advance(5_000);
+        let struggle = session.struggle("offsets are mixed up", 0.8, 0.7).id.clone();
+        clock.advance(5_000);
+        let fix = session.breakthrough_resolving(&struggle, "track byte offsets", 0.9).id.clone();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        session.tag(&fix, "lexer");
+        
+        let path = temp_path("session");
+        session.save_json(&path).unwrap();
+        let mut loaded = LearningSession::load_json(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        assert_eq!(loaded.id, session.id);
+        assert_eq!(loaded.state(), session.state());
+        assert_eq!(loaded.ice_caked, session.ice_caked);
+        assert_eq!(loaded.phase_durations(), session.phase_durations());
+        assert_eq!(loaded.phase_history(), session.phase_history());
+        let ids = |s: &LearningSession| s.breakthroughs().iter().map(|m| m.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&loaded), ids(&session));
+        assert_eq!(loaded.timeline(), session.timeline());
+        assert_eq!(loaded.get_moment(&fix).map(|m| m.fingerprint.clone()), Some(session.moments[2].fingerprint.clone()));
+        assert_eq!(loaded.encounter("next").seq, 4);
+    }
+    
+    #[test]
+    fn test_load_json_errors() {
+        let path = temp_path("corrupt");
+        std::fs::write(&path, "{\"schema_version\": 1, \"id\": ").unwrap();
+        assert!(matches!(LearningSession::load_json(&path), Err(SessionIoError::Json(_))));
+        
+        std::fs::write(&path, "{\"schema_version\": 99, \"format\": \"from the future\"}").unwrap();
+        let err = LearningSession::load_json(&path).err().unwrap();
+        assert!(matches!(err, SessionIoError::UnsupportedVersion { found: 99 }));
+        assert!(err.to_string().contains("schema version 99"));
+        std::fs::remove_file(&path).unwrap();
+        
+        assert!(matches!(LearningSession::load_json(&path), Err(SessionIoError::Io(_))));
+    }
+    
+    #[test]
+    fn test_subtasks_nest_and_attribute_moments() {
+        let mut session = LearningSession::new("versioning");
+        session.encounter("implement versioning");
+        let fk = session.begin_subtask("fix the FK constraint").unwrap();
+        session.struggle("FK constraint fails on version rows", 0.7, 0.6);
+        let index = session.begin_subtask("add the missing index").unwrap();
+        session.breakthrough("index on version_id", 0.9);
+        
+        assert_eq!(session.open_subtasks().iter().map(|s| &s.id).collect::<Vec<_>>(), vec![&fk, &index]);
+        assert_eq!(session.subtasks()[1].parent.as_ref(), Some(&fk));
+        let err = session.end_subtask(&fk, SessionOutcome::Completed).unwrap_err();
+        assert_eq!(err, SubtaskError::NotOnTop { id: fk.clone(), top: Some(index.clone()) });
+        assert!(err.to_string().ends_with(&format!("(innermost: {index})")));
+        let missing = SubtaskId("missing".to_string());
+        assert_eq!(session.end_subtask(&missing, SessionOutcome::Completed), Err(SubtaskError::Unknown(missing)));
+        
+        session.end_subtask(&index, SessionOutcome::Completed).unwrap();
+        session.apply("migration passes", true);
+        session.end_subtask(&fk, SessionOutcome::Completed).unwrap();
+        assert!(session.end_subtask(&fk, SessionOutcome::Completed).is_err());
+        session.breakthrough("versioning works end to end", 0.8);
+        
+        let stamps: Vec<Option<&str>> = session.moments.iter().map(|m| m.subtask_id.as_deref()).collect();
+        assert_eq!(stamps, vec![None, Some(fk.as_str()), Some(index.as_str()), Some(fk.as_str()), None]);
+        let contents = |id: &SubtaskId| session.moments_in_subtask(id).iter().map(|m| m.content.clone()).collect::<Vec<_>>();
+        assert_eq!(contents(&fk), vec!["FK constraint fails on version rows", "index on version_id", "migration passes"]);
+        assert_eq!(contents(&index), vec!["index on version_id"]);
+        
+        let summary = session.subtask_summary(&fk).unwrap();
+        assert_eq!((summary.depth, summary.moment_count, summary.breakthrough_count), (0, 3, 1));
+        assert_eq!(summary.outcome, Some(SessionOutcome::Completed));
+        assert_eq!(summary.peak_frustration, 0.6);
+        let effort = summary.mean_qualia.unwrap().effort;
+        let expected: f32 = session.moments_in_subtask(&fk).iter().map(|m| m.qualia.effort).sum::<f32>() / 3.0;
+        assert!((effort - expected).abs() < 1e-6);
+        assert_eq!(session.subtask_tree().iter().map(|s| (s.name.as_str(), s.depth)).collect::<Vec<_>>(),
+            vec![("fix the FK constraint", 0), ("add the missing index", 1)]);
+    }
+    
+    #[test]
+    fn test_style_changes_stamp_moments_and_distribution() {
+        let clock = MockClock::new(0);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("no style yet");
+        clock.advance(1_000);
+        assert!(session.set_style(ThinkingStyle::focused()));
+        let focused = session.struggle("trace the lexer", 0.6, 0.5).id.clone();
+        clock.advance(4_000);
+        session.encounter("read the spec");
+        clock.advance(5_000);
+        session.set_style(ThinkingStyle::creative());
+        session.breakthrough("lex bytes, not chars", 0.9);
+        
+        assert_eq!(session.get_moment(&focused).unwrap().thinking_style, ThinkingStyle::focused());
+        assert_eq!(session.style_changes().iter().map(|(at, _)| *at).collect::<Vec<_>>(), [1_000, 10_000]);
+        assert_eq!(session.state().thinking_style, ThinkingStyle::creative());
+        let distribution = session.style_distribution();
+        let shares: Vec<(&str, usize, u64)> = distribution.iter()
+            .map(|share| (share.axis.as_str(), share.moment_count, share.duration_ms))
+            .collect();
+        assert_eq!(shares, [("focused", 2, 9_000), ("unset", 1, 1_000), ("creative", 1, 0)]);
+        
+        let mut resonance = ResonanceCapture::new();
+        resonance.capture(session.get_moment(&focused).unwrap(), 1);
+        let query = session.get_moment(&focused).unwrap().resonance_vector.clone();
+        assert_eq!(resonance.find_resonant(&query, 0.9, 1, 1, None)[0].dominant_style.as_deref(), Some("focused"));
+        
+        session.close(SessionOutcome::Completed).unwrap();
+        assert!(!session.set_style(ThinkingStyle::analytical()));
+    }
+    
+    #[test]
+    fn test_undo_last_rolls_back_moment_and_phase() {
+        let mut session = LearningSession::new("task");
+        session.encounter("parser rejects UTF-8 input");
+        let state = session.state();
+        let struggle = session.struggle("tpyo", 0.5, 0.5).id.clone();
+        assert_eq!(session.phase, SessionPhase::Struggle);
+        
+        let undone = session.undo_last().unwrap();
+        assert_eq!(undone.id, struggle);
+        assert_eq!(session.phase, SessionPhase::Encounter);
+        assert_eq!(session.phase_log.len(), 1);
+        assert_eq!(session.cycle, state.cycle);
+        assert_eq!(session.state().moment_count, 1);
+        assert!(session.get_moment(&struggle).is_none());
+        assert_eq!(session.struggle("byte offsets are mixed up", 0.5, 0.5).seq, 2);
+        
+        session.undo_last().unwrap();
+        session.undo_last().unwrap();
+        assert!(session.moments.is_empty());
+        assert_eq!(session.phase, SessionPhase::Initialize);
+        assert_eq!((session.cycle, session.next_seq), (0, 1));
+        assert_eq!(session.undo_last().err(), Some(UndoError::Empty));
+    }
+    
+    #[test]
+    fn test_undo_last_refusals() {
+        let mut session = LearningSession::new("task");
+        let fix = session.breakthrough("track byte offsets only", 0.9).id.clone();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        assert_eq!(session.undo_last().err(), Some(UndoError::IceCaked(fix.clone())));
+        
+        let struggle = session.struggle("offsets are mixed up", 0.5, 0.5).id.clone();
+        let note = session.encounter("see the lexer docs").id.clone();
+        session.link(&note, LinkKind::Follows, &struggle);
+        session.undo_last().unwrap();
+        let encounter = session.encounter("lexer docs are stale").id.clone();
+        assert!(session.link(&fix, LinkKind::Follows, &encounter));
+        assert_eq!(session.undo_last().err(), Some(UndoError::LinkTarget { id: encounter.clone(), source_id: fix.clone() }));
+        assert_eq!(UndoError::IceCaked("x".to_string()).to_string(), "moment x is ice-caked; thaw it first");
+        
+        let captured = session.encounter("captured").id.clone();
+        session.moments.last_mut().unwrap().captured_externally = true;
+        assert_eq!(session.undo_last().err(), Some(UndoError::CapturedExternally(captured)));
+        assert_eq!(session.moments.len(), 4);
+        
+        session.close(SessionOutcome::Completed).unwrap();
+        assert!(matches!(session.undo_last(), Err(UndoError::Closed(_))));
+    }
+    
+    #[test]
+    fn test_struggle_gate_flow_vs_block() {
+        let mut converging = LearningSession::new("task");
+        for confusion in [0.6, 0.55, 0.5, 0.5, 0.45] {
+            converging.struggle("narrowing the lexer bug down", 0.6, confusion);
+        }
+        assert_eq!(converging.current_gate(), Some(GateState::Flow));
+        assert!(converging.moments.iter().all(|m| m.gate_state == Some(GateState::Flow)));
+        
+        let mut diverging = LearningSession::new("task");
+        let gates: Vec<GateState> = [0.1, 0.9, 0.0, 1.0, 0.2].iter()
+            .map(|&confusion| diverging.struggle("no idea where the bug is", 0.6, confusion).gate_state.unwrap())
+            .collect();
+        assert_eq!(gates, [GateState::Flow, GateState::Block, GateState::Block, GateState::Block, GateState::Block]);
+        diverging.encounter("unrelated note");
+        assert_eq!(diverging.state().gate_state, Some(GateState::Block));
+        
+        let window_of_two = SessionConfig {
+            struggle_gate: Some(StruggleGateConfig { window: 2, ..StruggleGateConfig::new() }),
+            ..SessionConfig::new()
+        };
+        let mut settling = LearningSession::with_config("task", window_of_two);
+        for confusion in [0.0, 1.0, 0.95] {
+            settling.struggle("settling down", 0.6, confusion);
+        }
+        assert_eq!(settling.current_gate(), Some(GateState::Flow));
+        
+        let mut off = LearningSession::with_config("task", SessionConfig { struggle_gate: None, ..SessionConfig::new() });
+        off.struggle("no reading", 0.6, 0.5);
+        assert_eq!(off.current_gate(), None);
+    }
+    
+    #[test]
+    fn test_restore_discards_moments_after_checkpoint() {
+        let mut session = capped(5);
+        session.encounter("parser rejects UTF-8 input");
+        let before = session.state();
+        let phase_log_len = session.phase_log.len();
+        let checkpoint = session.checkpoint("before rewriting the lexer").unwrap();
+        for i in 0..20 {
+            session.struggle(&format!("rewrite attempt {}", i), 0.6, 0.5);
+        }
+        session.breakthrough("lexer rewritten", 0.8);
+        assert!(!session.archive.is_empty());
+        
+        let report = session.restore_checkpoint(&checkpoint).unwrap();
+        assert_eq!(report.name, "before rewriting the lexer");
+        assert_eq!(report.discarded.len() + report.discarded_archived.len(), 21);
+        assert_eq!(report.discarded.last().unwrap().content, "lexer rewritten");
+        assert!(report.discarded.windows(2).all(|w| w[0].seq < w[1].seq));
+        assert_eq!(session.moment_count(), 1);
+        assert!(session.archive.iter().all(|m| m.seq == 1));
+        assert_eq!((session.phase.clone(), session.cycle), (before.phase, before.cycle));
+        assert_eq!(session.phase_log.len(), phase_log_len);
+        assert_eq!(session.struggle("try a smaller change", 0.4, 0.3).seq, 2);
+        assert_eq!(session.restore_checkpoint(&checkpoint).unwrap().discarded.len(), 1);
+    }
+    
+    #[test]
+    fn test_restore_invalidates_later_checkpoints() {
+        let mut session = LearningSession::new("task");
+        let first = session.checkpoint("first").unwrap();
+        session.encounter("one");
+        let second = session.checkpoint("second").unwrap();
+        session.struggle("two", 0.5, 0.5);
+        let third = session.checkpoint("third").unwrap();
+        
+        let report = session.restore_checkpoint(&first).unwrap();
+        assert_eq!(report.invalidated, ["second", "third"]);
+        assert_eq!(report.discarded.len(), 2);
+        assert_eq!(session.checkpoint_names(), ["first"]);
+        assert_eq!(session.restore_checkpoint(&second).err(), Some(RestoreError::UnknownCheckpoint(second.clone())));
+        assert_eq!(RestoreError::UnknownCheckpoint(third.clone()).to_string(), format!("no checkpoint {}", third));
+        assert_eq!(session.phase, SessionPhase::Initialize);
+        assert!(session.moments.is_empty());
+    }
+    
+    #[test]
+    fn test_restore_refused_by_protection() {
+        let mut session = LearningSession::new("task");
+        let early = session.encounter("lexer docs").id.clone();
+        let checkpoint = session.checkpoint("before the fix").unwrap();
+        let fix = session.breakthrough("track byte offsets only", 0.9).id.clone();
+        session.ice_cake(&fix, "lexer invariant").unwrap();
+        assert_eq!(session.restore_checkpoint(&checkpoint).err(), Some(RestoreError::IceCaked(fix.clone())));
+        assert_eq!(session.moments.len(), 2);
+        assert_eq!(session.checkpoint_names(), ["before the fix"]);
+        
+        session.thaw(fix.as_str(), "premature").unwrap();
+        assert!(session.link(&early, LinkKind::Follows, &fix));
+        assert_eq!(session.restore_checkpoint(&checkpoint).err(), Some(RestoreError::LinkTarget { id: fix.clone(), source_id: early }));
+        
+        session.close(SessionOutcome::Completed).unwrap();
+        assert!(matches!(session.restore_checkpoint(&checkpoint), Err(RestoreError::Closed(_))));
+    }
+    
+    fn capped(cap: usize) -> LearningSession {
+        LearningSession::with_config("task", SessionConfig { max_live_moments: Some(cap), ..SessionConfig::new() })
+    }
+    
+    #[test]
+    fn test_live_moment_cap_boundary() {
+        let mut session = capped(3);
+        let first = session.encounter("log line one").id.clone();
+        session.encounter("log line two");
+        session.breakthrough("the retry loop never backs off", 0.9);
+        assert_eq!((session.moments.len(), session.archive.len()), (3, 0));
+        
+        session.encounter("log line three");
+        assert_eq!((session.moments.len(), session.archive.len()), (3, 1));
+        assert_eq!(session.archive[0].id, first);
+        assert!(session.get_moment(&first).is_none());
+        assert_eq!(session.ice_cake(&first, "keep").err(), Some(IceCakeError::UnknownMoment(first.clone())));
+        
+        session.encounter("log line four");
+        let live: Vec<&str> = session.moments.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(live, ["the retry loop never backs off", "log line three", "log line four"]);
+        let query = session.archive[1].resonance_vector();
+        let found = session.find_archived(&query, 0.9);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0.summary, "log line two");
+    }
+    
+    #[test]
+    fn test_live_moment_cap_protects_ice_caked_and_linked() {
+        let mut session = capped(2);
+        let frozen = session.encounter("use a bounded queue").id.clone();
+        session.ice_cake(&frozen, "backpressure").unwrap();
+        let struggle = session.struggle("queue grows without bound", 0.7, 0.6).id.clone();
+        let fix = session.breakthrough_resolving(&struggle, "drop the oldest entries", 0.8).id.clone();
+        assert_eq!(session.moments.len(), 3, "every moment is protected, so the cap gives way");
+        assert!(session.archive.is_empty());
+        
+        session.encounter("metrics dashboard");
+        session.encounter("alert rules");
+        assert_eq!(session.archive.len(), 1);
+        assert_eq!(session.archive[0].summary, "metrics dashboard");
+        for id in [&frozen, &struggle, &fix] {
+            assert!(session.get_moment(id).is_some());
+        }
+    }
+    
+    #[test]
+    fn test_state_counts_archived_moments() {
+        let mut session = capped(1);
+        let struggle = session.struggle("flaky test", 0.6, 0.5).id.clone();
+        session.tag(&struggle, "ci");
+        session.breakthrough("seed the random generator", 0.9);
+        session.encounter("unrelated note");
+        
+        assert_eq!(session.moments.len(), 1);
+        let state = session.state();
+        assert_eq!((state.moment_count, state.breakthrough_count), (3, 1));
+        assert_eq!(state.moment_types.values().sum::<usize>(), 3);
+        assert_eq!(state.tags.get("ci"), Some(&1));
+        
+        let path = temp_path("archive");
+        session.save_json(&path).unwrap();
+        let loaded = LearningSession::load_json(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.archive, session.archive);
+        assert_eq!(loaded.state().moment_count, 3);
+        
+        let mut branch = session.fork("explore");
+        branch.encounter("branch note");
+        session.merge_branch(branch, MergePolicy::default()).unwrap();
+        assert_eq!(session.state().moment_count, 4);
+    }
+    
+    #[test]
+    fn test_subtasks_survive_save_and_fork() {
+        let mut session = LearningSession::new("versioning");
+        let fk = session.begin_subtask("fix the FK constraint").unwrap();
+        session.struggle("FK constraint fails", 0.7, 0.6);
+        
+        let path = temp_path("subtasks");
+        session.save_json(&path).unwrap();
+        let mut loaded = LearningSession::load_json(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.subtasks(), session.subtasks());
+        assert_eq!(loaded.encounter("still inside").subtask_id.as_deref(), Some(fk.as_str()));
+        
+        let mut branch = session.fork("try a trigger");
+        let trigger = branch.begin_subtask("write a trigger").unwrap();
+        branch.end_subtask(&trigger, SessionOutcome::Abandoned { reason: AbandonReason::WrongApproach }).unwrap();
+        branch.end_subtask(&fk, SessionOutcome::Completed).unwrap();
+        session.merge_branch(branch, MergePolicy::default()).unwrap();
+        assert_eq!(session.subtasks().len(), 2);
+        assert!(session.open_subtasks().is_empty());
+    }
+    
+    #[test]
+    fn test_metrics_per_phase() {
+        let clock = MockClock::new(1_000);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("read the spec");
+        clock.advance(4_000);
+        session.struggle("offsets are mixed up", 0.8, 0.6);
+        clock.advance(2_000);
+        session.struggle("offsets are still mixed up", 0.6, 0.4);
+        session.pause();
+        clock.advance(10_000);
+        session.resume();
+        clock.advance(3_000);
+        session.breakthrough("track byte offsets only", 0.9);
+        clock.advance(1_000);
+        session.encounter("read the next chapter");
+        
+        let metrics = session.metrics();
+        let summary: Vec<(SessionPhase, usize, u64)> = metrics.phases.iter()
+            .map(|m| (m.phase.clone(), m.moment_count, m.duration_ms))
+            .collect();
+        assert_eq!(summary, vec![
+            (SessionPhase::Encounter, 2, 4_000),
+            (SessionPhase::Struggle, 2, 5_000),
+            (SessionPhase::Breakthrough, 1, 1_000),
+        ]);
+        let struggle = &metrics.phases[1];
+        assert!((struggle.mean_effort - 0.7).abs() < 1e-6);
+        assert!((struggle.mean_frustration - 0.5).abs() < 1e-6);
+        assert_eq!((struggle.max_effort, struggle.max_frustration), (0.8, 0.6));
+        assert_eq!((metrics.phases[0].mean_effort, metrics.phases[0].max_frustration), (0.2, 0.0));
+        assert_eq!(metrics.time_to_first_breakthrough_ms, Some(9_000));
+        assert_eq!(metrics.struggle_to_breakthrough_ratio, Some(2.0));
+        assert_eq!(session.state().metrics, metrics);
+    }
+    
+    #[test]
+    fn test_metrics_without_breakthroughs() {
+        let clock = MockClock::new(1_000);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.custom_moment("Review", "skim the diff", Qualia::from_metrics(0.2, 0.3, 0.5));
+        clock.advance(2_000);
+        session.struggle("flaky test", 0.5, 0.9);
+        
+        let metrics = session.metrics();
+        assert_eq!(metrics.phases.iter().map(|m| (m.phase.clone(), m.moment_count, m.duration_ms)).collect::<Vec<_>>(),
+            vec![(SessionPhase::Initialize, 1, 2_000), (SessionPhase::Struggle, 1, 0)]);
+        assert_eq!(metrics.time_to_first_breakthrough_ms, None);
+        assert_eq!(metrics.struggle_to_breakthrough_ratio, None);
+        assert_eq!(LearningSession::new("empty").metrics(), SessionMetrics::default());
     }
 }
@@ -0,0 +1,84 @@
+//! SessionTemplate — the expected shape of a recurring kind of task
+
+use serde::{Serialize, Deserialize};
+use crate::cognitive::ThinkingStyle;
+
+/// A recurring task shape, e.g. "add a CRUD feature", to start sessions
+/// from with `MetaAGI::start_session_from_template`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionTemplate {
+    pub name: String,
+    pub description: String,
+    /// Questions the blackboard lists as open from the start
+    #[serde(default)]
+    pub open_questions: Vec<String>,
+    /// Decisions the task usually needs; pending on the blackboard until an
+    /// ice-caked decision matches one
+    #[serde(default)]
+    pub expected_decisions: Vec<String>,
+    /// Stamped on every moment the session's helpers record
+    #[serde(default)]
+    pub default_style: ThinkingStyle,
+    /// Added to every moment the session's helpers record
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl SessionTemplate {
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            open_questions: Vec::new(),
+            expected_decisions: Vec::new(),
+            default_style: ThinkingStyle::default(),
+            tags: Vec::new(),
+        }
+    }
+    
+    pub fn question(mut self, question: &str) -> Self {
+        self.open_questions.push(question.to_string());
+        self
+    }
+    
+    pub fn expect_decision(mut self, decision: &str) -> Self {
+        self.expected_decisions.push(decision.to_string());
+        self
+    }
+    
+    pub fn style(mut self, style: ThinkingStyle) -> Self {
+        self.default_style = style;
+        self
+    }
+    
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn crud() -> SessionTemplate {
+        SessionTemplate::new("crud-feature", "Add a CRUD feature")
+            .question("Who may delete records?")
+            .expect_decision("primary key and foreign key scoping")
+            .expect_decision("pagination strategy for the index endpoint")
+            .style(ThinkingStyle::focused())
+            .tag("crud")
+    }
+    
+    #[test]
+    fn test_template_serde_round_trip() {
+        let template = crud();
+        let json = serde_json::to_string(&template).unwrap();
+        assert_eq!(serde_json::from_str::<SessionTemplate>(&json).unwrap(), template);
+        let yaml = serde_yaml::to_string(&template).unwrap();
+        assert_eq!(serde_yaml::from_str::<SessionTemplate>(&yaml).unwrap(), template);
+        
+        let minimal: SessionTemplate = serde_yaml::from_str("name: flaky-test\ndescription: Debug a flaky test\n").unwrap();
+        assert_eq!(minimal, SessionTemplate::new("flaky-test", "Debug a flaky test"));
+    }
+}
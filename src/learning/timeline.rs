@@ -0,0 +1,203 @@
+//! Timeline — readable, aligned rendering of a sequence of moments
+
+use crate::learning::moment::{Moment, MomentType};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineOptions {
+    /// Plain-ASCII glyphs and markers instead of emoji
+    pub ascii: bool,
+    /// Display columns for content; longer content is cut with an ellipsis
+    pub content_width: usize,
+    /// Append novelty, effort, satisfaction and valence
+    pub show_qualia: bool,
+    /// Moments to mark as ice-caked
+    pub ice_caked: Vec<String>,
+}
+
+impl TimelineOptions {
+    pub fn new() -> Self {
+        Self {
+            ascii: false,
+            content_width: 40,
+            show_qualia: true,
+            ice_caked: Vec::new(),
+        }
+    }
+}
+
+impl Default for TimelineOptions {
+    fn default() -> Self { Self::new() }
+}
+
+fn glyph(moment_type: &MomentType, ascii: bool) -> &'static str {
+    match (moment_type, ascii) {
+        (MomentType::Encounter, false) => "🔍",
+        (MomentType::Struggle, false) => "😣",
+        (MomentType::Breakthrough, false) => "💡",
+        (MomentType::Failure, false) => "💥",
+        (MomentType::Application, false) => "🔧",
+        (MomentType::MetaReflection, false) => "🪞",
+        (MomentType::Hypothesis, false) => "🤔",
+        (MomentType::Verification { .. }, false) => "🧪",
+        (MomentType::DecisionReversal, false) => "🔙",
+        (MomentType::Custom(_), false) => "📌",
+        (MomentType::Encounter, true) => "[E]",
+        (MomentType::Struggle, true) => "[S]",
+        (MomentType::Breakthrough, true) => "[B]",
+        (MomentType::Failure, true) => "[F]",
+        (MomentType::Application, true) => "[A]",
+        (MomentType::MetaReflection, true) => "[M]",
+        (MomentType::Hypothesis, true) => "[H]",
+        (MomentType::Verification { .. }, true) => "[V]",
+        (MomentType::DecisionReversal, true) => "[R]",
+        (MomentType::Custom(_), true) => "[*]",
+    }
+}
+
+/// Terminal columns a character takes: 0 for combining marks and variation
+/// selectors, 2 for East Asian wide characters and emoji, 1 otherwise.
+/// An approximation, but close enough to keep columns lined up.
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1FAFF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+/// Single-line content cut to `width` columns and padded to exactly `width`
+fn fit(content: &str, width: usize, ascii: bool) -> String {
+    let flat: String = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let ellipsis = if ascii { "..." } else { "…" };
+    let mut out = if display_width(&flat) <= width {
+        flat
+    } else {
+        let budget = width.saturating_sub(display_width(ellipsis));
+        let mut kept = String::new();
+        let mut used = 0;
+        for c in flat.chars() {
+            let w = char_width(c);
+            if used + w > budget {
+                break;
+            }
+            used += w;
+            kept.push(c);
+        }
+        let mut cut = kept.trim_end().to_string();
+        cut.push_str(ellipsis);
+        cut
+    };
+    let pad = width.saturating_sub(display_width(&out));
+    out.extend(std::iter::repeat_n(' ', pad));
+    out
+}
+
+/// One line per moment, ordered by creation time: offset from the first
+/// moment, type glyph and name, content, key qualia and markers for
+/// breakthroughs, ice-caked moments and merged repeats.
+pub fn format_timeline(moments: &[Moment], opts: &TimelineOptions) -> String {
+    let mut ordered: Vec<&Moment> = moments.iter().collect();
+    ordered.sort_by_key(|m| m.created_at);
+    let start = ordered.first().map_or(0, |m| m.created_at);
+    let name_width = ordered.iter().map(|m| display_width(m.moment_type.name())).max().unwrap_or(0);
+    let (breakthrough, ice) = if opts.ascii { ("[!]", "[ice]") } else { ("✨", "❄") };
+    
+    let mut out = String::new();
+    for (i, moment) in ordered.iter().enumerate() {
+        let secs = moment.created_at.saturating_sub(start) / 1000;
+        let name = moment.moment_type.name();
+        let mut line = format!(
+            "{:>3} +{:>3}:{:02} {} {}{} {}",
+            i + 1,
+            secs / 60,
+            secs % 60,
+            glyph(&moment.moment_type, opts.ascii),
+            name,
+            " ".repeat(name_width - display_width(name)),
+            fit(&moment.content, opts.content_width, opts.ascii),
+        );
+        if opts.show_qualia {
+            let q = &moment.qualia;
+            line.push_str(&format!(
+                " | n={:.2} e={:.2} s={:.2} v={:+.2}",
+                q.novelty, q.effort, q.satisfaction, q.valence
+            ));
+        }
+        if moment.is_breakthrough() {
+            line.push(' ');
+            line.push_str(breakthrough);
+        }
+        if opts.ice_caked.contains(&moment.id) {
+            line.push(' ');
+            line.push_str(ice);
+        }
+        if moment.repeat_count > 0 {
+            let repeats = if opts.ascii { format!(" [repeat {}]", moment.repeat_count) } else { format!(" ↻{}", moment.repeat_count) };
+            line.push_str(&repeats);
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::learning::{LearningSession, MockClock};
+    
+    fn scripted() -> LearningSession {
+        let clock = MockClock::new(1_000);
+        let mut session = LearningSession::with_clock("task", Arc::new(clock.clone()));
+        session.encounter("Parser rejects naïve UTF-8 input");
+        clock.advance(65_000);
+        session.struggle("Byte offsets vs char offsets in the lexer are mixed up everywhere", 0.8, 0.7);
+        clock.advance(3_600_000);
+        let id = session.breakthrough("Track 字节 offsets only", 0.9).id.clone();
+        session.ice_cake(&id, "lexer invariant").unwrap();
+        session
+    }
+    
+    #[test]
+    fn test_golden_emoji() {
+        let session = scripted();
+        let opts = TimelineOptions { content_width: 30, ..TimelineOptions::default() };
+        assert_eq!(session.timeline_with(opts), concat!(
+            "  1 +  0:00 🔍 Encounter    Parser rejects naïve UTF-8 in… | n=0.50 e=0.20 s=0.50 v=+0.00\n",
+            "  2 +  1:05 😣 Struggle     Byte offsets vs char offsets…  | n=0.30 e=0.80 s=0.30 v=-0.30\n",
+            "  3 + 61:05 💡 Breakthrough Track 字节 offsets only        | n=0.80 e=0.31 s=0.90 v=+0.80 ✨ ❄\n",
+        ));
+    }
+    
+    #[test]
+    fn test_golden_ascii() {
+        let session = scripted();
+        let opts = TimelineOptions { ascii: true, content_width: 30, show_qualia: false, ..TimelineOptions::default() };
+        assert_eq!(session.timeline_with(opts), concat!(
+            "  1 +  0:00 [E] Encounter    Parser rejects naïve UTF-8...\n",
+            "  2 +  1:05 [S] Struggle     Byte offsets vs char offset...\n",
+            "  3 + 61:05 [B] Breakthrough Track 字节 offsets only        [!] [ice]\n",
+        ));
+    }
+    
+    #[test]
+    fn test_fit_counts_wide_characters() {
+        assert_eq!(fit("日本語のテキスト", 10, false), "日本語の… ");
+        assert_eq!(display_width(&fit("日本語のテキスト", 9, false)), 9);
+        assert_eq!(fit("line one\nline two", 20, true), "line one line two   ");
+        assert_eq!(fit("abcdefghij", 6, true), "abc...");
+    }
+}
@@ -0,0 +1,155 @@
+//! QualiaTrajectory — the learning curve of a session, moment by moment
+
+use serde::{Serialize, Deserialize};
+use crate::learning::moment::{Moment, MomentType};
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Per-moment qualia series plus derived features. Positions are moment
+/// indices within the session, in recording order. Frustration is the
+/// `confusion` dimension.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QualiaTrajectory {
+    pub novelty: Vec<f32>,
+    pub effort: Vec<f32>,
+    pub satisfaction: Vec<f32>,
+    pub frustration: Vec<f32>,
+    pub surprise: Vec<f32>,
+    pub valence: Vec<f32>,
+    pub arousal: Vec<f32>,
+    pub confidence: Vec<f32>,
+    /// Index of the most frustrated moment (earliest on ties)
+    pub peak_frustration_cycle: Option<usize>,
+    /// Trapezoidal area under the effort curve, one unit per moment
+    pub effort_auc: f32,
+    /// Moments from the first struggle (or the session start, if there was
+    /// none) to the first breakthrough after it
+    pub time_to_breakthrough: Option<usize>,
+    /// Least-squares slope of satisfaction per moment from the first
+    /// breakthrough on; needs at least two points
+    pub satisfaction_slope_after_breakthrough: Option<f32>,
+}
+
+impl QualiaTrajectory {
+    pub fn from_moments(moments: &[Moment]) -> Self {
+        let series = |f: fn(&Moment) -> f32| moments.iter().map(f).collect::<Vec<f32>>();
+        let mut t = Self {
+            novelty: series(|m| m.qualia.novelty),
+            effort: series(|m| m.qualia.effort),
+            satisfaction: series(|m| m.qualia.satisfaction),
+            frustration: series(|m| m.qualia.confusion),
+            surprise: series(|m| m.qualia.surprise),
+            valence: series(|m| m.qualia.valence),
+            arousal: series(|m| m.qualia.arousal),
+            confidence: series(|m| m.qualia.confidence),
+            ..Self::default()
+        };
+        
+        t.peak_frustration_cycle = t.frustration.iter().enumerate()
+            .fold(None, |best: Option<(usize, f32)>, (i, &v)| match best {
+                Some((_, top)) if top >= v => best,
+                _ => Some((i, v)),
+            })
+            .map(|(i, _)| i);
+        t.effort_auc = t.effort.windows(2).map(|w| (w[0] + w[1]) / 2.0).sum();
+        
+        let start = moments.iter().position(|m| m.moment_type == MomentType::Struggle).unwrap_or(0);
+        let first_breakthrough = moments.iter().skip(start).position(Moment::is_breakthrough);
+        t.time_to_breakthrough = first_breakthrough;
+        t.satisfaction_slope_after_breakthrough = moments.iter().position(Moment::is_breakthrough)
+            .and_then(|b| slope(&t.satisfaction[b..]));
+        t
+    }
+    
+    pub fn len(&self) -> usize {
+        self.effort.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.effort.is_empty()
+    }
+    
+    /// One block character per value, for values in [0, 1]
+    pub fn sparkline(values: &[f32]) -> String {
+        values.iter()
+            .map(|v| {
+                let level = (v.clamp(0.0, 1.0) * (SPARK_LEVELS.len() - 1) as f32).round() as usize;
+                SPARK_LEVELS[level]
+            })
+            .collect()
+    }
+    
+    /// Sparklines for the handover summary: effort, frustration,
+    /// satisfaction and valence (rescaled from [-1, 1])
+    pub fn sparklines(&self) -> Vec<(String, String)> {
+        let valence: Vec<f32> = self.valence.iter().map(|v| (v + 1.0) / 2.0).collect();
+        vec![
+            ("effort".to_string(), Self::sparkline(&self.effort)),
+            ("frustration".to_string(), Self::sparkline(&self.frustration)),
+            ("satisfaction".to_string(), Self::sparkline(&self.satisfaction)),
+            ("valence".to_string(), Self::sparkline(&valence)),
+        ]
+    }
+}
+
+fn slope(values: &[f32]) -> Option<f32> {
+    if values.len() < 2 {
+        return None;
+    }
+    let n = values.len() as f32;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f32>() / n;
+    let (num, den) = values.iter().enumerate().fold((0.0, 0.0), |(num, den), (i, &y)| {
+        let dx = i as f32 - mean_x;
+        (num + dx * (y - mean_y), den + dx * dx)
+    });
+    Some(num / den)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::LearningSession;
+    
+    #[test]
+    fn test_scripted_session_features() {
+        let mut session = LearningSession::new("task");
+        session.encounter("look around");
+        session.struggle("confusing types", 0.6, 0.5);
+        session.struggle("worse", 0.9, 0.8);
+        session.struggle("slightly better", 0.7, 0.3);
+        let estimated = session.breakthrough("got it", 0.7).qualia.effort;
+        session.apply("used it", true);
+        
+        let t = session.qualia_trajectory();
+        assert_eq!(t.len(), 6);
+        assert_eq!(t.frustration, vec![0.0, 0.5, 0.8, 0.3, 0.0, 0.0]);
+        assert_eq!(t.peak_frustration_cycle, Some(2));
+        assert_eq!(t.time_to_breakthrough, Some(3));
+        // effort: 0.2, 0.6, 0.9, 0.7, estimated, 0.3
+        assert!((t.effort_auc - (2.45 + estimated)).abs() < 1e-5);
+        // satisfaction 0.7 then 0.9
+        assert!((t.satisfaction_slope_after_breakthrough.unwrap() - 0.2).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn test_no_breakthrough() {
+        let mut session = LearningSession::new("task");
+        session.encounter("start");
+        session.struggle("stuck", 0.8, 0.9);
+        
+        let t = session.qualia_trajectory();
+        assert_eq!(t.time_to_breakthrough, None);
+        assert_eq!(t.satisfaction_slope_after_breakthrough, None);
+        assert_eq!(t.peak_frustration_cycle, Some(1));
+    }
+    
+    #[test]
+    fn test_empty_and_sparkline() {
+        let t = QualiaTrajectory::from_moments(&[]);
+        assert!(t.is_empty());
+        assert_eq!(t.peak_frustration_cycle, None);
+        assert_eq!(t.effort_auc, 0.0);
+        assert_eq!(QualiaTrajectory::sparkline(&[0.0, 0.5, 1.0, 2.0]), "▁▅██");
+    }
+}
@@ -7,6 +7,9 @@
 //!
 //! The learning curve IS the knowledge.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
 pub mod core;
 pub mod cognitive;
 pub mod nars;
@@ -26,50 +29,317 @@ pub mod prelude {
 }
 
 /// MetaAGI — Unified interface for the learning loop
+///
+/// Holds one session (and blackboard) per task id. One of them is active:
+/// `session`, `end_session`, `handover_summary`, `export_yaml` and
+/// `export_markdown` work on it, and `switch_to` changes which.
 pub struct MetaAGI {
-    pub session: Option<learning::LearningSession>,
+    sessions: HashMap<String, learning::LearningSession>,
+    blackboards: HashMap<String, learning::Blackboard>,
+    /// Task id of the active session
+    active: Option<String>,
     pub resonance: learning::ResonanceCapture,
     pub concepts: learning::ConceptExtractor,
-    pub blackboard: Option<learning::Blackboard>,
+    pub inference: nars::InferenceEngine,
+    /// Per-user qualia baseline; when set, captures also store calibrated qualia
+    pub calibration: Option<learning::QualiaCalibration>,
+    /// When set, `capture_moment` checks the moment's session with
+    /// `stuck_report_with` over this window and records a reflection when
+    /// the session is going in circles
+    pub stuck_window: Option<usize>,
+    /// Reports of closed sessions. When set, `end_session` compares each
+    /// closed session with it, keeps the result in `last_retrospective`, and
+    /// appends the new report.
+    pub report_history: Option<Vec<learning::SessionReport>>,
+    /// Set by `end_session` when `report_history` is set
+    pub last_retrospective: Option<learning::Retrospective>,
+    /// When set, `handover_summary` validates the active blackboard with
+    /// this policy and puts a warning block before the summary when a lint
+    /// is an Error
+    pub handover_lints: Option<learning::BbLintPolicy>,
+    /// Reports of closed sessions read with `load_session_report`, searched
+    /// by `find_sessions` without their moments
+    pub archived_reports: Vec<learning::SessionReport>,
+    /// When set, `export_cypher` follows the concept graph with the decision
+    /// graph of every blackboard, by task id
+    pub cypher_blackboards: bool,
     pub global_cycle: u64,
+    /// Passed to every session `start_session` creates
+    clock: Arc<dyn learning::Clock>,
+    /// Past moments surfaced to each session, by session id
+    resonance_hits: HashMap<String, Vec<learning::ResonanceHit>>,
+    /// Set on the blackboards of sessions started or resumed from now on
+    agent: learning::AgentId,
 }
 
 impl MetaAGI {
     pub fn new() -> Self {
         Self {
-            session: None,
+            sessions: HashMap::new(),
+            blackboards: HashMap::new(),
+            active: None,
             resonance: learning::ResonanceCapture::new(),
             concepts: learning::ConceptExtractor::new(),
-            blackboard: None,
+            inference: nars::InferenceEngine::new(),
+            calibration: None,
+            stuck_window: None,
+            report_history: None,
+            last_retrospective: None,
+            handover_lints: None,
+            archived_reports: Vec::new(),
+            cypher_blackboards: false,
             global_cycle: 0,
+            clock: Arc::new(learning::SystemClock),
+            resonance_hits: HashMap::new(),
+            agent: learning::AgentId::default(),
         }
     }
     
+    /// Time sessions started from now on with `clock`, e.g. a `MockClock`
+    /// in tests. Their blackboards take every time from the session state.
+    pub fn with_clock(mut self, clock: Arc<dyn learning::Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Attribute what sessions started or resumed from now on write to
+    /// their blackboards to `agent`
+    pub fn with_agent(mut self, agent: learning::AgentId) -> Self {
+        self.agent = agent;
+        self
+    }
+    
+    pub fn agent(&self) -> &learning::AgentId {
+        &self.agent
+    }
+    
+    /// Start a session for `task_id` and make it active. Sessions of other
+    /// tasks are kept; an earlier session of the same task is replaced.
     pub fn start_session(&mut self, task_id: &str, description: &str) -> &mut learning::LearningSession {
-        let session = learning::LearningSession::new(task_id);
-        let blackboard = learning::Blackboard::new(&session.id, task_id, description);
+        let mut session = learning::LearningSession::with_clock(task_id, self.clock.clone());
+        session.description = description.to_string();
+        let blackboard = learning::Blackboard::new(&session.id, task_id, description)
+            .with_clock(self.clock.clone())
+            .with_agent(self.agent.clone());
         
-        self.session = Some(session);
-        self.blackboard = Some(blackboard);
+        self.blackboards.insert(task_id.to_string(), blackboard);
+        self.active = Some(task_id.to_string());
+        self.sessions.entry(task_id.to_string())
+            .insert_entry(session)
+            .into_mut()
+    }
+    
+    /// `start_session` shaped by `template`: the blackboard lists its open
+    /// questions and tracks its expected decisions, and the session stamps
+    /// its style and tags on every moment its helpers record
+    pub fn start_session_from_template(&mut self, template: &learning::SessionTemplate, task_id: &str) -> &mut learning::LearningSession {
+        self.start_session(task_id, &template.description);
+        let blackboard = self.blackboards.get_mut(task_id).expect("start_session adds a blackboard");
+        blackboard.template = Some(template.name.clone());
+        blackboard.consciousness.thinking_style = template.default_style.dominant().to_string();
+        blackboard.expected_questions = template.open_questions.clone();
+        blackboard.expected_decisions = template.expected_decisions.iter()
+            .map(|description| learning::ExpectedDecision { description: description.clone(), matched_layer: None })
+            .collect();
         
-        self.session.as_mut().unwrap()
+        let session = self.sessions.get_mut(task_id).expect("start_session adds a session");
+        session.config.thinking_style = Some(template.default_style.clone());
+        session.config.default_tags = template.tags.clone();
+        session
+    }
+    
+    /// Resume the task handed over in `bb`, e.g. one loaded with
+    /// `Blackboard::from_yaml`, as a new active session built by
+    /// `LearningSession::resume_from_blackboard`. Its prior-decision moments
+    /// are captured into the resonance store, and `bb` becomes the session's
+    /// blackboard, pointing at its predecessor. An existing session of the
+    /// same task is replaced.
+    pub fn resume_from_blackboard(&mut self, bb: learning::Blackboard) -> &mut learning::LearningSession {
+        let session = learning::LearningSession::resume_from_blackboard(&bb, self.clock.clone());
+        let task_id = session.task_id.clone();
+        let mut bb = bb.with_clock(self.clock.clone()).with_agent(self.agent.clone());
+        bb.append_log(learning::LogCategory::Sync, &format!("handed over from session {} to {}", bb.session_id, session.id));
+        bb.session_id = session.id.clone();
+        bb.update_from_session(&session.state());
+        let moments = session.moments.clone();
+        self.blackboards.insert(task_id.clone(), bb);
+        self.sessions.insert(task_id.clone(), session);
+        self.active = Some(task_id.clone());
+        for moment in &moments {
+            self.capture_moment(moment);
+        }
+        self.sessions.get_mut(&task_id).expect("session was just inserted")
+    }
+    
+    /// Make the session of `task_id` the active one. Returns false, leaving
+    /// the active session as it was, if there is no session for the task.
+    pub fn switch_to(&mut self, task_id: &str) -> bool {
+        if !self.sessions.contains_key(task_id) {
+            return false;
+        }
+        self.active = Some(task_id.to_string());
+        true
     }
     
+    /// Task id of the active session
+    pub fn active_task(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+    
+    /// Close the active session, sync the blackboards one last time and
+    /// return the session's report. The closed session and its blackboard
+    /// stay in place for inspection and handover until a session for the
+    /// same task is started. None if there is no open active session. With
+    /// `report_history` set, also runs `learning::retrospective`. An
+    /// abandoned session leaves an anti-pattern concept from its dominant
+    /// unresolved struggle. The report's `reuse` and the blackboard's credit
+    /// the concepts of earlier sessions surfaced to this one.
+    pub fn end_session(&mut self, outcome: learning::SessionOutcome) -> Option<learning::SessionReport> {
+        self.active_session_mut()?.close(outcome).ok()?;
+        let task_id = self.active.clone()?;
+        let session = self.sessions.get_mut(&task_id)?;
+        let hits = self.resonance_hits.get(&session.id).map(Vec::as_slice).unwrap_or_default();
+        let reuse = learning::ReuseReport::build(session, hits, &self.concepts, learning::DEFAULT_REUSE_WINDOW);
+        session.set_reuse_report(reuse.clone());
+        let report = session.report().expect("session was just closed").clone();
+        if let Some(bb) = self.blackboards.get_mut(&task_id) {
+            bb.reuse = Some(reuse);
+        }
+        if let learning::SessionOutcome::Abandoned { reason } = report.outcome {
+            let struggle = self.active_session().and_then(|session| session.dominant_unresolved_struggle()).cloned();
+            let extracted = struggle.and_then(|struggle| self.concepts.extract_anti_pattern(&struggle, reason, self.global_cycle));
+            if let (Some(_), Some(bb)) = (extracted, self.active.as_deref().and_then(|task| self.blackboards.get_mut(task))) {
+                bb.concepts_extracted += 1;
+            }
+        }
+        self.sync_blackboard();
+        if let Some(history) = &mut self.report_history {
+            self.last_retrospective = Some(learning::retrospective(&report, history));
+            history.push(report.clone());
+        }
+        Some(report)
+    }
+    
+    pub fn active_session(&self) -> Option<&learning::LearningSession> {
+        self.sessions.get(self.active.as_deref()?)
+    }
+    
+    pub fn active_session_mut(&mut self) -> Option<&mut learning::LearningSession> {
+        self.sessions.get_mut(self.active.as_deref()?)
+    }
+    
+    /// Same as `active_session`
     pub fn session(&self) -> Option<&learning::LearningSession> {
-        self.session.as_ref()
+        self.active_session()
     }
     
+    /// Same as `active_session_mut`
     pub fn session_mut(&mut self) -> Option<&mut learning::LearningSession> {
-        self.session.as_mut()
+        self.active_session_mut()
+    }
+    
+    pub fn session_for(&self, task_id: &str) -> Option<&learning::LearningSession> {
+        self.sessions.get(task_id)
+    }
+    
+    pub fn session_for_mut(&mut self, task_id: &str) -> Option<&mut learning::LearningSession> {
+        self.sessions.get_mut(task_id)
+    }
+    
+    /// Every session, in no particular order
+    pub fn sessions(&self) -> impl Iterator<Item = &learning::LearningSession> {
+        self.sessions.values()
+    }
+    
+    /// Add the report of a closed session saved with `save_json` to
+    /// `archived_reports`, leaving its moments on disk. Returns false if
+    /// the session was saved open and has no report.
+    pub fn load_session_report(&mut self, path: &std::path::Path) -> Result<bool, learning::SessionIoError> {
+        let Some(report) = learning::SessionReport::load_json(path)? else { return Ok(false) };
+        self.archived_reports.push(report);
+        Ok(true)
+    }
+    
+    /// Sessions whose task description matches `query` and whose moments
+    /// carry `tags`, best first, scored by `learning::session_match_score`.
+    /// Searches the sessions held here, open or closed, then
+    /// `report_history` and `archived_reports`; a session found twice is
+    /// listed once. Matches below `learning::MIN_SESSION_MATCH_SCORE` are
+    /// left out.
+    pub fn find_sessions(&self, query: &str, tags: &[String]) -> Vec<learning::SessionMatch> {
+        let held = self.sessions.values().map(|session| learning::SessionMatch::from_session(session, query, tags));
+        let reports = self.report_history.iter().flatten().chain(&self.archived_reports)
+            .map(|report| learning::SessionMatch::from_report(report, query, tags));
+        let mut matches: Vec<learning::SessionMatch> = Vec::new();
+        for found in held.chain(reports) {
+            if found.score >= learning::MIN_SESSION_MATCH_SCORE && !matches.iter().any(|m| m.session_id == found.session_id) {
+                matches.push(found);
+            }
+        }
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches
+    }
+    
+    /// Blackboard of the active session
+    pub fn blackboard(&self) -> Option<&learning::Blackboard> {
+        self.blackboards.get(self.active.as_deref()?)
+    }
+    
+    pub fn blackboard_for(&self, task_id: &str) -> Option<&learning::Blackboard> {
+        self.blackboards.get(task_id)
     }
     
+    /// Capture into the resonance store and, for breakthroughs, extract a
+    /// concept. Attributed to the session the moment was recorded in, active
+    /// or not, where the moment is marked `captured_externally`. With `stuck_window` set, a session found going in circles gets
+    /// a meta-reflection from `reflect_on_stuck`.
     pub fn capture_moment(&mut self, moment: &learning::Moment) {
+        if let Some(session) = self.sessions.values_mut().find(|session| session.id == moment.session_id) {
+            session.mark_captured(&moment.id);
+        }
+        let origin = self.sessions.values().find(|session| session.id == moment.session_id);
+        let problem = origin.and_then(|session| resolved_struggle(session, moment));
+        let task_id = origin.map(|session| session.task_id.clone());
+        self.capture_attributed(moment, problem, task_id.clone());
+        
+        let (Some(window), Some(task_id)) = (self.stuck_window, task_id) else { return };
+        let Some(session) = self.sessions.get_mut(&task_id) else { return };
+        if session.ensure_open().is_err() || session.ensure_active().is_err() {
+            return;
+        }
+        if let Some(report) = session.stuck_report_with(window, &mut self.resonance, self.global_cycle) {
+            session.reflect_on_stuck(&report);
+        }
+    }
+    
+    /// `capture_moment` for a moment of `session`, which need not be one of
+    /// this MetaAGI's sessions, e.g. a saved session being replayed
+    pub fn capture_moment_of(&mut self, moment: &learning::Moment, session: &learning::LearningSession) {
+        let problem = resolved_struggle(session, moment);
+        self.capture_attributed(moment, problem, Some(session.task_id.clone()));
+    }
+    
+    fn capture_attributed(&mut self, moment: &learning::Moment, problem: Option<learning::Moment>, task_id: Option<String>) {
         self.global_cycle += 1;
-        self.resonance.capture(moment, self.global_cycle);
+        match &mut self.calibration {
+            Some(calibration) => {
+                calibration.observe(&moment.qualia);
+                let calibrated = calibration.normalize(&moment.qualia);
+                self.resonance.capture_calibrated(moment, calibrated, self.global_cycle);
+            }
+            None => self.resonance.capture(moment, self.global_cycle),
+        }
+        if let Some(task_id) = &task_id {
+            self.resonance.attribute_task(&moment.id, task_id);
+        }
         
         if moment.is_breakthrough() {
-            if let Some(concept) = self.concepts.extract(moment) {
-                if let Some(bb) = &mut self.blackboard {
+            let extracted = match &problem {
+                Some(struggle) => self.concepts.extract_resolution(struggle, moment, self.global_cycle),
+                None => self.concepts.extract_at(moment, self.global_cycle),
+            };
+            if let Some(concept) = extracted {
+                if let Some(bb) = task_id.and_then(|task_id| self.blackboards.get_mut(&task_id)) {
                     bb.concepts_extracted += 1;
                 }
                 eprintln!("📚 Concept extracted: {} (CAM: {:012x})", 
@@ -78,40 +348,136 @@ impl MetaAGI {
         }
     }
     
+    /// Past moments resonating with the active session's latest struggle,
+    /// from this MetaAGI's resonance store; empty without an active session
+    pub fn suggest(&mut self) -> Vec<learning::Suggestion> {
+        let Some(session) = self.active.as_deref().and_then(|task| self.sessions.get(task)) else {
+            return Vec::new();
+        };
+        let suggestions = session.suggest_from_resonance(&mut self.resonance, self.global_cycle);
+        for suggestion in &suggestions {
+            self.record_hit(&suggestion.moment_id, &suggestion.session_id, suggestion.resonance);
+        }
+        suggestions
+    }
+    
+    /// Resonance store search; hits count as surfaced to the active session
     pub fn find_similar(&mut self, query: &crate::core::Fingerprint, threshold: f32, limit: usize) 
         -> Vec<learning::SimilarMoment> 
     {
-        self.resonance.find_resonant(query, threshold, limit, self.global_cycle)
+        let found = self.resonance.find_resonant(query, threshold, limit, self.global_cycle, None);
+        for similar in &found {
+            self.record_hit(&similar.moment_id, &similar.session_id, similar.resonance);
+        }
+        found
     }
     
     pub fn find_sweet_spot(&mut self, query: &crate::core::Fingerprint) 
         -> Option<learning::SimilarMoment>
     {
-        learning::find_sweet_spot(&mut self.resonance, query, self.global_cycle)
+        let found = learning::find_sweet_spot(&mut self.resonance, query, self.global_cycle);
+        if let Some(similar) = &found {
+            self.record_hit(&similar.moment_id, &similar.session_id, similar.resonance);
+        }
+        found
+    }
+    
+    /// Past moments surfaced to the session `session_id` by `suggest`,
+    /// `find_similar` and `find_sweet_spot` while it was active and open
+    pub fn resonance_hits(&self, session_id: &str) -> &[learning::ResonanceHit] {
+        self.resonance_hits.get(session_id).map(Vec::as_slice).unwrap_or_default()
     }
     
+    fn record_hit(&mut self, moment_id: &str, session_id: &str, resonance: f32) {
+        let Some(session) = self.active_session().filter(|session| !session.is_closed()) else { return };
+        let hit = learning::ResonanceHit {
+            moment_id: moment_id.to_string(),
+            session_id: session_id.to_string(),
+            resonance,
+            after_seq: session.moments.iter().map(|m| m.seq).max().unwrap_or(0),
+        };
+        let session_id = session.id.clone();
+        self.resonance_hits.entry(session_id).or_default().push(hit);
+    }
+    
+    /// Feed extracted concept relations into the inference engine and run
+    /// one forward-chaining step. Relations already in the belief table are not
+    /// re-asserted. Returns the number of derived beliefs.
+    pub fn reason(&mut self) -> usize {
+        for (statement, truth) in self.concepts.relation_statements() {
+            if self.inference.get(&statement).is_none() {
+                self.inference.assert(statement, truth);
+            }
+        }
+        self.inference.step()
+    }
+    
+    /// Raise the question of a `CollapseAction::Clarify` on the active
+    /// session's blackboard; None for other actions or without a session
+    pub fn record_collapse(&mut self, decision: &cognitive::CollapseDecision) -> Option<learning::QuestionId> {
+        let bb = self.active.as_deref().and_then(|task| self.blackboards.get_mut(task))?;
+        bb.record_collapse(decision)
+    }
+    
+    /// Update every session's blackboard from the session, including a
+    /// tracked question per hypothesis, suggested next steps and risks
+    /// promoted from frustrating failures. Resonance captures count that
+    /// session's moments only.
     pub fn sync_blackboard(&mut self) {
-        if let (Some(session), Some(blackboard)) = (&self.session, &mut self.blackboard) {
-            blackboard.update_from_session(&session.state());
-            blackboard.resonance_captures = self.resonance.total_captures;
-            blackboard.concepts_extracted = self.concepts.total_extractions;
+        for (task_id, session) in &self.sessions {
+            if let Some(blackboard) = self.blackboards.get_mut(task_id) {
+                blackboard.update_from_session(&session.state());
+                blackboard.track_hypotheses(session);
+                blackboard.suggest_next_steps(session);
+                blackboard.promote_failure_risks(session);
+                blackboard.resonance_captures = self.resonance.captures_for(&session.id) as u64;
+            }
         }
     }
     
     pub fn handover_summary(&self) -> String {
-        self.blackboard.as_ref()
-            .map(|bb| bb.handover_summary())
-            .unwrap_or_else(|| "No active session".to_string())
+        let Some(bb) = self.blackboard() else { return "No active session".to_string() };
+        match &self.handover_lints {
+            Some(policy) => learning::lint::warning_block(&self.validate_blackboard_with(policy)) + &bb.handover_summary(),
+            None => bb.handover_summary(),
+        }
+    }
+    
+    /// Lints of the active blackboard, checking staleness against its
+    /// session's cycle; empty without an active session
+    pub fn validate_blackboard_with(&self, policy: &learning::BbLintPolicy) -> Vec<learning::BbLint> {
+        let Some(bb) = self.blackboard() else { return Vec::new() };
+        bb.validate_with(policy, self.active_session().map(|session| session.cycle))
+    }
+    
+    /// The active session as Markdown, followed by its blackboard's
+    /// handover summary
+    pub fn export_markdown(&self) -> String {
+        let Some(session) = self.active_session() else { return String::new() };
+        let mut markdown = session.to_markdown(&learning::MarkdownOptions::default());
+        if let Some(bb) = self.blackboard() {
+            markdown.push('\n');
+            markdown.push_str(&bb.handover_summary());
+        }
+        markdown
     }
     
     pub fn export_yaml(&self) -> String {
-        self.blackboard.as_ref()
+        self.blackboard()
             .map(|bb| bb.to_yaml())
             .unwrap_or_default()
     }
     
     pub fn export_cypher(&self) -> String {
-        self.concepts.to_cypher()
+        let mut cypher = self.concepts.to_cypher();
+        if self.cypher_blackboards {
+            let mut tasks: Vec<&String> = self.blackboards.keys().collect();
+            tasks.sort();
+            for task in tasks {
+                cypher.push_str(&self.blackboards[task].to_cypher());
+            }
+        }
+        cypher
     }
     
     pub fn stats(&self) -> MetaAGIStats {
@@ -119,13 +485,15 @@ impl MetaAGI {
             global_cycle: self.global_cycle,
             resonance_stats: self.resonance.stats(),
             total_concepts: self.concepts.all().count(),
-            session_active: self.session.is_some(),
-            session_moments: self.session.as_ref()
-                .map(|s| s.moments.len())
+            session_active: self.active_session().is_some_and(|s| !s.is_closed()),
+            open_sessions: self.sessions.values().filter(|s| !s.is_closed()).count(),
+            session_moments: self.active_session()
+                .map(|s| s.moment_count())
                 .unwrap_or(0),
-            session_breakthroughs: self.session.as_ref()
-                .map(|s| s.breakthroughs().len())
+            session_breakthroughs: self.active_session()
+                .map(|s| s.breakthrough_count())
                 .unwrap_or(0),
+            session_metrics: self.active_session().map(|s| s.metrics()),
         }
     }
 }
@@ -141,9 +509,25 @@ pub struct MetaAGIStats {
     pub global_cycle: u64,
     pub resonance_stats: learning::ResonanceStats,
     pub total_concepts: usize,
+    /// Whether the active session is open
     pub session_active: bool,
+    /// Sessions not yet closed, across all tasks
+    pub open_sessions: usize,
     pub session_moments: usize,
     pub session_breakthroughs: usize,
+    /// Per-phase metrics of the active session
+    pub session_metrics: Option<learning::SessionMetrics>,
+}
+
+/// The struggle a breakthrough of `session` resolves, by `infer_resolutions`
+fn resolved_struggle(session: &learning::LearningSession, moment: &learning::Moment) -> Option<learning::Moment> {
+    if !moment.is_breakthrough() {
+        return None;
+    }
+    let pair = session.infer_resolutions(learning::DEFAULT_RESOLUTION_SIMILARITY)
+        .into_iter()
+        .find(|pair| pair.breakthrough_id == moment.id)?;
+    session.get_moment(&pair.struggle_id).cloned()
 }
 
 #[cfg(test)]
@@ -167,7 +551,7 @@ mod tests {
         let moment_id = agi.session().unwrap().moments.last().unwrap().id.clone();
         
         // Capture moments to resonance
-        let moments: Vec<_> = agi.session().unwrap().moments.iter().cloned().collect();
+        let moments = agi.session().unwrap().moments.to_vec();
         for moment in &moments {
             agi.capture_moment(moment);
         }
@@ -175,7 +559,7 @@ mod tests {
         // Ice cake
         {
             let session = agi.session_mut().unwrap();
-            session.ice_cake(&moment_id, "Always check mod.rs");
+            session.ice_cake(&moment_id, "Always check mod.rs").unwrap();
         }
         
         // Query
@@ -195,4 +579,590 @@ mod tests {
         let summary = agi.handover_summary();
         assert!(summary.contains("test-task"));
     }
+    
+    #[test]
+    fn test_capture_stores_raw_and_calibrated_qualia() {
+        let mut agi = MetaAGI::new();
+        agi.calibration = Some(learning::QualiaCalibration::new());
+        let moments: Vec<Moment> = [0.7, 0.8, 0.9].iter()
+            .map(|&effort| MomentBuilder::new("s", "grinding").qualia(0.5, effort, 0.5).build())
+            .collect();
+        for moment in &moments {
+            agi.capture_moment(moment);
+        }
+        
+        assert_eq!(agi.calibration.as_ref().unwrap().count(), 3);
+        let (raw, calibrated) = agi.resonance.qualia_of(&moments[2].id).unwrap();
+        assert_eq!(raw.effort, 0.9);
+        assert!(calibrated.unwrap().effort > 0.5);
+        let (_, first) = agi.resonance.qualia_of(&moments[0].id).unwrap();
+        assert_eq!(first.unwrap().effort, 0.7);
+    }
+    
+    #[test]
+    fn test_capture_extracts_from_resolution_pairs() {
+        let mut agi = MetaAGI::new();
+        let session = agi.start_session("task", "desc");
+        let struggle = session.struggle("borrowck rejects the cache lookup in the resolver", 0.7, 0.6).id.clone();
+        session.breakthrough("clone the cache key so the resolver lookup no longer borrows", 0.9);
+        session.breakthrough("markdown tables need a header separator row", 0.9);
+        
+        let moments = agi.session().unwrap().moments.to_vec();
+        for moment in &moments {
+            agi.capture_moment(moment);
+        }
+        let paired: Vec<&ExtractedConcept> = agi.concepts.all().filter(|c| c.problem_moment_id.is_some()).collect();
+        assert_eq!(agi.concepts.all().count(), 2);
+        assert_eq!(paired.len(), 1);
+        assert_eq!(paired[0].problem_moment_id.as_deref(), Some(struggle.as_str()));
+        assert!(paired[0].description.starts_with("Problem: borrowck"));
+    }
+    
+    #[test]
+    fn test_end_session_keeps_closed_session_until_next_start() {
+        let mut agi = MetaAGI::new();
+        assert!(agi.end_session(learning::SessionOutcome::Completed).is_none());
+        
+        let session = agi.start_session("task", "desc");
+        session.struggle("stuck on lifetimes", 0.7, 0.6);
+        session.breakthrough("elided lifetimes were wrong", 0.9);
+        let report = agi.end_session(learning::SessionOutcome::Blocked { reason: "waiting on review".to_string() }).unwrap();
+        
+        assert_eq!((report.moment_count, report.breakthrough_count), (2, 1));
+        assert!(agi.session().unwrap().is_closed());
+        assert!(!agi.stats().session_active);
+        assert_eq!(agi.blackboard().unwrap().current_task.phase, "Breakthrough");
+        assert!(agi.handover_summary().contains("task"));
+        assert!(agi.end_session(learning::SessionOutcome::Completed).is_none());
+        
+        agi.start_session("task", "desc");
+        assert!(!agi.session().unwrap().is_closed());
+        assert!(agi.stats().session_active);
+    }
+    
+    #[test]
+    fn test_abandoned_session_leaves_anti_pattern() {
+        let mut agi = MetaAGI::new();
+        let session = agi.start_session("orm", "scope keys per project");
+        session.struggle("patching the ORM to scope keys", 0.9, 0.8);
+        session.struggle("ORM patch conflicts with the upgrade", 0.6, 0.5);
+        let reason = learning::AbandonReason::WrongApproach;
+        agi.end_session(learning::SessionOutcome::Abandoned { reason }).unwrap();
+        
+        let concepts: Vec<&ExtractedConcept> = agi.concepts.all().collect();
+        assert_eq!(concepts.len(), 1);
+        assert!(concepts[0].anti_pattern);
+        assert_eq!(concepts[0].name, "patching the ORM to scope keys");
+        assert_eq!(agi.blackboard().unwrap().concepts_extracted, 1);
+        let summary = agi.handover_summary();
+        assert!(summary.contains("- **Abandoned**: wrong approach\n"));
+        assert!(summary.contains("- **Last open struggle**: ORM patch conflicts with the upgrade\n"));
+        
+        agi.start_session("ci", "speed up CI").struggle("linker is slow", 0.8, 0.7);
+        agi.end_session(learning::SessionOutcome::Blocked { reason: "needs new runners".to_string() });
+        assert_eq!(agi.concepts.all().count(), 1);
+    }
+    
+    #[test]
+    fn test_find_sessions_ranks_by_description_and_tags() {
+        let mut agi = MetaAGI::new();
+        let session = agi.start_session("versions", "project scoped version numbering");
+        let id = session.struggle("versions collide across projects", 0.7, 0.6).id.clone();
+        session.tag(&id, "orm");
+        session.breakthrough("scope the unique index by project", 0.9);
+        agi.end_session(learning::SessionOutcome::Completed);
+        let path = std::env::temp_dir().join(format!("ladybug-search-{}.json", uuid::Uuid::new_v4()));
+        agi.session().unwrap().save_json(&path).unwrap();
+        
+        let mut agi = MetaAGI::new();
+        assert!(agi.load_session_report(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let session = agi.start_session("sprints", "project scoped sprint numbering");
+        let id = session.struggle("sprint numbers restart per board", 0.6, 0.5).id.clone();
+        session.tag(&id, "ui");
+        let session = agi.start_session("ci", "speed up the linker on ci");
+        let id = session.struggle("linking takes ten minutes", 0.8, 0.7).id.clone();
+        session.tag(&id, "orm");
+        
+        let tasks = |matches: Vec<learning::SessionMatch>| matches.into_iter().map(|m| m.task_id).collect::<Vec<_>>();
+        assert_eq!(tasks(agi.find_sessions("project scoped version numbering", &[])), ["versions", "sprints"]);
+        assert_eq!(tasks(agi.find_sessions("project scoped sprint numbering", &[])), ["sprints", "versions"]);
+        assert_eq!(tasks(agi.find_sessions("project scoped numbering", &["orm".to_string()])), ["versions", "ci", "sprints"]);
+        assert_eq!(tasks(agi.find_sessions("", &["#UI".to_string()])), ["sprints"]);
+        
+        let versions = agi.find_sessions("project scoped version numbering", &["orm".to_string()]).remove(0);
+        assert_eq!(versions.outcome, Some(learning::SessionOutcome::Completed));
+        assert_eq!((versions.moment_count, versions.breakthrough_count), (2, 1));
+        assert!(versions.score > 0.99);
+        let sprints = agi.find_sessions("sprint", &[]).remove(0);
+        assert_eq!((sprints.task_id.as_str(), sprints.outcome), ("sprints", None));
+    }
+    
+    #[test]
+    fn test_captured_moment_cannot_be_undone() {
+        let mut agi = MetaAGI::new();
+        let moment = agi.start_session("parser", "fix UTF-8 handling").encounter("parser rejects UTF-8").clone();
+        agi.capture_moment(&moment);
+        let session = agi.session_mut().unwrap();
+        assert!(session.moments[0].captured_externally);
+        assert_eq!(session.undo_last().err(), Some(learning::UndoError::CapturedExternally(moment.id)));
+    }
+    
+    #[test]
+    fn test_concurrent_sessions_stay_separate() {
+        let mut agi = MetaAGI::new();
+        agi.start_session("parser", "fix UTF-8 handling").encounter("parser rejects UTF-8");
+        agi.start_session("ci", "speed up CI").encounter("CI takes 40 minutes");
+        assert_eq!(agi.active_task(), Some("ci"));
+        
+        assert!(agi.switch_to("parser"));
+        assert!(!agi.switch_to("missing"));
+        assert_eq!(agi.active_task(), Some("parser"));
+        let parser_fix = agi.session_mut().unwrap().breakthrough("track byte offsets", 0.9).clone();
+        let ci_fix = agi.session_for_mut("ci").unwrap().breakthrough("cache the toolchain", 0.9).clone();
+        let ci_slow = agi.session_for_mut("ci").unwrap().struggle("linker is slow", 0.6, 0.4).clone();
+        
+        agi.capture_moment(&ci_fix);
+        agi.capture_moment(&parser_fix);
+        agi.capture_moment(&ci_slow);
+        agi.sync_blackboard();
+        
+        let (parser, ci) = (agi.blackboard_for("parser").unwrap(), agi.blackboard_for("ci").unwrap());
+        assert_eq!((parser.resonance_captures, ci.resonance_captures), (1, 2));
+        assert_eq!((parser.concepts_extracted, ci.concepts_extracted), (1, 1));
+        assert_eq!(parser.session_id, agi.session_for("parser").unwrap().id);
+        assert_eq!(ci.moment_types.get("Struggle"), Some(&1));
+        assert!(!parser.moment_types.contains_key("Struggle"));
+        assert!(agi.handover_summary().contains("parser"));
+        
+        let stats = agi.stats();
+        assert_eq!((stats.session_moments, stats.session_breakthroughs, stats.open_sessions), (2, 1, 2));
+        assert_eq!(agi.sessions().count(), 2);
+        
+        agi.end_session(learning::SessionOutcome::Completed);
+        assert!(agi.session_for("parser").unwrap().is_closed());
+        assert!(!agi.session_for("ci").unwrap().is_closed());
+        assert_eq!(agi.stats().open_sessions, 1);
+    }
+    
+    #[test]
+    fn test_export_markdown_combines_session_and_blackboard() {
+        let mut agi = MetaAGI::new();
+        assert_eq!(agi.export_markdown(), "");
+        agi.start_session("parser", "UTF-8 handling").struggle("offsets are mixed up", 0.8, 0.7);
+        agi.sync_blackboard();
+        
+        let markdown = agi.export_markdown();
+        let session = markdown.find("# Learning Session: parser\n").unwrap();
+        let handover = markdown.find("# Session Handover: ").unwrap();
+        assert!(session < handover);
+        assert!(markdown.contains("**Struggle** offsets are mixed up"));
+        assert!(markdown.contains("- **Phase**: Struggle\n"));
+    }
+    
+    #[test]
+    fn test_capture_reflects_on_stuck_session() {
+        let mut agi = MetaAGI::new();
+        agi.stuck_window = Some(3);
+        agi.start_session("resolver", "cache lookups");
+        for (content, confusion) in [
+            ("borrow checker rejects the cache lookup", 0.5),
+            ("the borrow checker rejects the cache lookup again", 0.6),
+            ("borrow checker still rejects the cache lookup", 0.7),
+        ] {
+            let moment = agi.session_mut().unwrap().struggle(content, 0.8, confusion).clone();
+            agi.capture_moment(&moment);
+        }
+        
+        let session = agi.session().unwrap();
+        let reflections: Vec<&learning::Moment> = session.moments.iter()
+            .filter(|m| m.moment_type == learning::MomentType::MetaReflection)
+            .collect();
+        assert_eq!(reflections.len(), 1);
+        assert!(reflections[0].content.starts_with("Going in circles: "));
+        assert_eq!(reflections[0].meta("stuck.repetitions"), Some("3"));
+    }
+    
+    #[test]
+    fn test_stats_include_session_metrics() {
+        let mut agi = MetaAGI::new();
+        assert_eq!(agi.stats().session_metrics, None);
+        agi.start_session("parser", "UTF-8 handling").struggle("offsets are mixed up", 0.8, 0.7);
+        let metrics = agi.stats().session_metrics.unwrap();
+        assert_eq!(metrics.phases.len(), 1);
+        assert_eq!(metrics.phases[0].phase, learning::SessionPhase::Struggle);
+        assert_eq!(metrics.struggle_to_breakthrough_ratio, None);
+    }
+    
+    #[test]
+    fn test_suggest_finds_resolved_struggle_of_earlier_task() {
+        let mut agi = MetaAGI::new();
+        assert!(agi.suggest().is_empty());
+        let problem = "FK constraint fails when a version is deleted";
+        let session = agi.start_session("versions", "Project-scoped versions");
+        let struggle = session.struggle(problem, 0.8, 0.7).id.clone();
+        session.breakthrough_resolving(&struggle, "cascade deletes through the join table", 0.9);
+        let moments = agi.session().unwrap().moments.clone();
+        for moment in &moments {
+            agi.capture_moment(moment);
+        }
+        agi.end_session(learning::SessionOutcome::Completed);
+        
+        agi.start_session("sprints", "Project-scoped sprints").encounter("sprints need the same scoping");
+        assert!(agi.suggest().is_empty(), "no struggle yet");
+        agi.session_mut().unwrap().struggle(problem, 0.8, 0.7);
+        
+        let suggestions = agi.suggest();
+        assert_eq!(suggestions[0].moment_id, struggle);
+        assert_eq!(suggestions[0].summary, problem);
+        assert_eq!(suggestions[0].task_id.as_deref(), Some("versions"));
+        assert_eq!(suggestions[0].session_id, moments[0].session_id);
+        assert_eq!(suggestions[0].resonance, 1.0);
+        assert!(suggestions[0].confidence.frequency == 1.0 && suggestions[0].confidence.confidence > 0.8);
+        assert!(suggestions.iter().all(|s| s.session_id != agi.session().unwrap().id));
+    }
+    
+    #[test]
+    fn test_session_from_template() {
+        let template = learning::SessionTemplate::new("crud-feature", "Add a CRUD feature")
+            .question("Who may delete records?")
+            .expect_decision("primary key and foreign key scoping")
+            .expect_decision("pagination strategy for the index endpoint")
+            .style(cognitive::ThinkingStyle::focused())
+            .tag("crud");
+        let mut agi = MetaAGI::new();
+        let session = agi.start_session_from_template(&template, "versions");
+        let fix = session.breakthrough("scope the foreign key to the project primary key", 0.9).clone();
+        session.ice_cake(&fix.id, "key scoping").unwrap();
+        agi.sync_blackboard();
+        
+        assert_eq!(fix.tags, vec!["crud"]);
+        assert_eq!(fix.thinking_style, cognitive::ThinkingStyle::focused());
+        let bb = agi.blackboard_for("versions").unwrap();
+        assert_eq!(bb.current_task.description, "Add a CRUD feature");
+        assert_eq!(bb.consciousness.thinking_style, "focused");
+        assert_eq!(bb.unmatched_expected_decisions(), vec!["pagination strategy for the index endpoint"]);
+        let summary = agi.handover_summary();
+        assert!(summary.contains("- **Template**: crud-feature\n"));
+        assert!(summary.contains("## Expected Decisions (1 of 2 pending)\n"));
+        assert!(summary.contains("- Who may delete records?\n"));
+    }
+    
+    #[test]
+    fn test_end_session_runs_retrospective() {
+        let mut agi = MetaAGI::new();
+        agi.end_session(learning::SessionOutcome::Completed);
+        agi.start_session("versions", "project scoped milestone versioning").struggle("FK fails", 0.8, 0.7);
+        agi.end_session(learning::SessionOutcome::Completed);
+        assert!(agi.last_retrospective.is_none(), "history is off by default");
+        
+        agi.report_history = Some(Vec::new());
+        agi.start_session("versions", "project scoped milestone versioning").struggle("FK fails", 0.8, 0.7);
+        agi.end_session(learning::SessionOutcome::Completed);
+        assert!(agi.last_retrospective.as_ref().unwrap().matches.is_empty());
+        
+        let session = agi.start_session("sprints", "project scoped sprint versioning");
+        session.struggle("FK fails", 0.8, 0.7);
+        session.struggle("FK still fails", 0.8, 0.7);
+        let report = agi.end_session(learning::SessionOutcome::Completed).unwrap();
+        assert_eq!(report.description, "project scoped sprint versioning");
+        let retro = agi.last_retrospective.as_ref().unwrap();
+        assert_eq!(retro.matches.len(), 1);
+        assert_eq!(retro.matches[0].task_id, "versions");
+        assert_eq!(retro.struggle_delta, Some(1.0));
+        assert!(retro.summary.contains("had 1 more struggle") && !retro.summary.contains("struggles"));
+        assert_eq!(agi.report_history.as_ref().unwrap().len(), 2);
+    }
+    
+    #[test]
+    fn test_deterministic_run_under_mock_clock() {
+        let clock = learning::MockClock::new(10_000);
+        let mut agi = MetaAGI::new().with_clock(Arc::new(clock.clone()));
+        {
+            let session = agi.start_session("lexer", "Fix UTF-8 offsets");
+            session.encounter("parser rejects UTF-8 input");
+            clock.advance(2_000);
+            session.struggle("byte and char offsets mixed up", 0.7, 0.6);
+            session.pause();
+            clock.advance(60_000);
+            session.resume();
+            clock.advance(3_000);
+            session.breakthrough("track byte offsets only", 0.9);
+        }
+        
+        let session = agi.session().unwrap();
+        assert_eq!((session.started_at, session.last_activity), (10_000, 75_000));
+        assert_eq!(session.duration(), std::time::Duration::from_millis(65_000));
+        let state = session.state();
+        assert_eq!((state.duration_ms, state.wall_clock_ms, state.longest_gap_ms), (5_000, 65_000, 3_000));
+        assert_eq!(state.metrics.time_to_first_breakthrough_ms, Some(5_000));
+        agi.sync_blackboard();
+        assert!(agi.handover_summary().contains("- Session Duration: 5.0s active / 65.0s wall clock\n- Longest Gap: 3.0s\n"));
+    }
+    
+    #[test]
+    fn test_end_session_reports_concept_reuse() {
+        let mut agi = MetaAGI::new();
+        let problem = "FK constraint fails when a version is deleted";
+        let session = agi.start_session("versions", "Project-scoped versions");
+        let struggle = session.struggle(problem, 0.8, 0.7).id.clone();
+        session.breakthrough_resolving(&struggle, "cascade deletes through the join table", 0.9);
+        let moments = agi.session().unwrap().moments.clone();
+        for moment in &moments {
+            agi.capture_moment(moment);
+        }
+        let first = agi.end_session(learning::SessionOutcome::Completed).unwrap();
+        assert_eq!(first.reuse.unwrap().hits, 0);
+        let concept = agi.concepts.all().next().unwrap().clone();
+        
+        let session = agi.start_session("sprints", "Project-scoped sprints");
+        session.struggle(problem, 0.8, 0.7);
+        assert_eq!(agi.suggest()[0].moment_id, struggle);
+        let session = agi.session_mut().unwrap();
+        let assisted = session.breakthrough("cascade sprint deletes too", 0.9).qualia.effort;
+        for step in ["board view", "burndown chart", "velocity", "retro notes"] {
+            session.encounter(step);
+        }
+        let unassisted = session.breakthrough("burndown reads from the sprint snapshot", 0.8).qualia.effort;
+        let sprints_id = session.id.clone();
+        
+        let reuse = agi.end_session(learning::SessionOutcome::Completed).unwrap().reuse.unwrap();
+        assert_eq!(agi.resonance_hits(&sprints_id).len(), reuse.hits);
+        assert!(agi.resonance_hits(&sprints_id).iter().all(|hit| hit.after_seq == 1));
+        assert_eq!(reuse.concepts, [learning::ReusedConcept {
+            concept_id: concept.id.clone(),
+            name: concept.name.clone(),
+            moment_id: struggle,
+        }]);
+        assert_eq!((reuse.assisted_breakthroughs, reuse.unassisted_breakthroughs), (1, 1));
+        assert_eq!(reuse.effort_delta, Some(assisted - unassisted));
+        assert!(agi.handover_summary().contains(&format!(
+            "## Concept Reuse\n- Reused: {}\n- Breakthroughs after a resonance hit: 1 of 2\n- Effort: ", concept.name)));
+    }
+    
+    #[test]
+    fn test_resume_from_handed_over_blackboard() {
+        let mut first = MetaAGI::new();
+        let session = first.start_session("versions", "Project-scoped versions");
+        session.struggle("FK constraint fails when a version is deleted", 0.8, 0.7);
+        let fix = session.breakthrough("cascade deletes through the join table", 0.9).id.clone();
+        session.ice_cake(&fix, "deletes must not orphan rows").unwrap();
+        let predecessor = session.id.clone();
+        first.sync_blackboard();
+        let mut bb = Blackboard::from_yaml(&first.export_yaml()).unwrap();
+        bb.record_decision("db", "keep versions in postgres", "joins across projects", GateState::Flow);
+        
+        let mut second = MetaAGI::new();
+        let session = second.resume_from_blackboard(bb);
+        assert_eq!(session.predecessor_session_id.as_deref(), Some(predecessor.as_str()));
+        assert_ne!(session.id, predecessor);
+        assert_eq!((session.task_id.as_str(), session.phase.clone()), ("versions", SessionPhase::Consolidate));
+        assert_eq!(session.moments.len(), 2);
+        assert_eq!(session.moments[1].meta("prior.rationale"), Some("deletes must not orphan rows"));
+        assert_eq!(session.ice_caked.len(), 1);
+        let resumed = session.id.clone();
+        let qualia = session.moments[1].qualia.clone();
+        
+        let query = Moment::new("query", "cascade deletes through the join table", MomentType::Encounter)
+            .with_qualia(qualia)
+            .resonance_vector;
+        let found = second.find_similar(&query, 0.9, 5);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].session_id, resumed);
+        assert_eq!(found[0].summary, "cascade deletes through the join table");
+        assert_eq!(second.blackboard().unwrap().session_id, resumed);
+        assert!(second.handover_summary().contains(&format!("- **ID**: versions\n- **Resumed from**: session {}\n", predecessor)));
+        
+        let frozen = first.blackboard().unwrap().ice_cake_layers[0].clone();
+        second.sync_blackboard();
+        let handed_over = second.blackboard().unwrap().clone();
+        assert_eq!(handed_over.decisions.len(), 2);
+        assert_eq!(handed_over.decisions_from_moment(&frozen.decision_id).len(), 1, "linked to the decision frozen first");
+        let mut third = MetaAGI::new();
+        third.resume_from_blackboard(Blackboard::from_yaml(&second.export_yaml()).unwrap());
+        third.sync_blackboard();
+        let bb = third.blackboard().unwrap();
+        assert_eq!(bb.decisions.len(), handed_over.decisions.len(), "resuming again adds no decisions");
+        assert_eq!(bb.key_moments.len(), handed_over.key_moments.len());
+        assert_eq!(bb.decisions_from_moment(&frozen.decision_id)[0].provenance.len(), 3, "one ice-cake per session");
+        let layer = &bb.ice_cake_layers[0];
+        assert_eq!((layer.gate_state.as_str(), layer.phase.clone()), (frozen.gate_state.as_str(), frozen.phase.clone()));
+    }
+    
+    #[test]
+    fn test_sync_logs_one_state_update() {
+        let clock = learning::MockClock::new(5_000);
+        let mut agi = MetaAGI::new().with_clock(Arc::new(clock.clone()));
+        agi.start_session("cache", "stale reads");
+        agi.start_session("ci", "flaky build");
+        agi.switch_to("cache");
+        agi.active_session_mut().unwrap().struggle("reads are stale", 0.6, 0.5);
+        clock.advance(100);
+        agi.sync_blackboard();
+        
+        let log = &agi.blackboard().unwrap().log;
+        let updates: Vec<&learning::LogEntry> = log.iter().filter(|entry| entry.category == learning::LogCategory::StateUpdate).collect();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].to_string(), "t=5100ms state update: phase Struggle at 0%, moment count 1");
+        assert!(agi.handover_summary().contains("## Activity Log\n- t=5100ms state update: phase Struggle"));
+    }
+    
+    #[test]
+    fn test_questions_raised_on_the_active_blackboard() {
+        let mut agi = MetaAGI::new();
+        let clarify = cognitive::evaluate_gate(&[1.0, 0.0], true);
+        assert!(agi.record_collapse(&clarify).is_none());
+        
+        agi.start_session("cache", "stale reads");
+        agi.active_session_mut().unwrap().hypothesize("TTL is too long", nars::TruthValue::new(0.6, 0.3));
+        let question = agi.record_collapse(&clarify).unwrap();
+        agi.sync_blackboard();
+        let bb = agi.blackboard().unwrap();
+        let open: Vec<&str> = bb.unresolved_questions().map(|q| q.text.as_str()).collect();
+        assert_eq!(open, ["Multiple interpretations possible", "TTL is too long"]);
+        assert_eq!(bb.questions[0].id, question);
+        assert!(agi.handover_summary().contains("## Open Questions\n- Multiple interpretations possible\n- TTL is too long\n\n"));
+    }
+    
+    #[test]
+    fn test_next_steps_suggested_on_sync() {
+        let mut agi = MetaAGI::new();
+        agi.start_session("cache", "stale reads");
+        let session = agi.active_session_mut().unwrap();
+        let ttl = session.hypothesize("TTL is too long", nars::TruthValue::new(0.6, 0.3)).id.clone();
+        session.struggle("reads race the invalidation", 0.7, 0.4);
+        agi.blackboards.get_mut("cache").unwrap().add_next_step("Page the cache owners", 0);
+        agi.sync_blackboard();
+        agi.sync_blackboard();
+        let steps: Vec<(&str, u8, bool)> = agi.blackboard().unwrap().pending_next_steps().iter()
+            .map(|step| (step.text.as_str(), step.priority, step.auto_generated))
+            .collect();
+        assert_eq!(steps, [
+            ("Page the cache owners", 0, false),
+            ("Resolve: reads race the invalidation", 1, true),
+            ("Verify: TTL is too long", 2, true),
+        ]);
+        
+        agi.active_session_mut().unwrap().verify(&ttl, true, "halving it fixed the reads");
+        agi.sync_blackboard();
+        let bb = agi.blackboard().unwrap();
+        assert_eq!(bb.pending_next_steps().len(), 2);
+        assert!(bb.next_steps.iter().any(|step| step.text == "Verify: TTL is too long" && step.completed));
+        
+        let bb = agi.blackboards.get_mut("cache").unwrap();
+        assert_eq!(bb.clear_auto_next_steps(), 1);
+        let left: Vec<&str> = bb.next_steps.iter().map(|step| step.text.as_str()).collect();
+        assert_eq!(left, ["Page the cache owners", "Verify: TTL is too long"]);
+    }
+    
+    #[test]
+    fn test_handover_lints_warn_before_the_summary() {
+        let mut agi = MetaAGI::new();
+        agi.start_session("cache", "");
+        agi.sync_blackboard();
+        assert!(agi.handover_summary().starts_with("# Session Handover"));
+        
+        agi.handover_lints = Some(learning::BbLintPolicy { stale_after_cycles: 1, ..learning::BbLintPolicy::new() });
+        for step in ["read the TTL", "read the invalidation"] {
+            agi.active_session_mut().unwrap().encounter(step);
+        }
+        let summary = agi.handover_summary();
+        assert!(summary.starts_with(concat!(
+            "> ⚠️ **Not ready for handover**\n",
+            "> - error: the task has no description\n",
+            "> - error: last synced 2 cycles ago\n",
+            "\n",
+            "# Session Handover",
+        )), "{summary}");
+        assert!(!summary.contains("no decisions"), "warnings are left to validate");
+        
+        agi.sync_blackboard();
+        assert_eq!(agi.validate_blackboard_with(&learning::BbLintPolicy::new()).len(), 2);
+    }
+    
+    #[test]
+    fn test_ice_caking_records_a_decision_with_provenance() {
+        let mut agi = MetaAGI::new();
+        let session = agi.start_session("versions", "Project-scoped versions");
+        let session_id = session.id.clone();
+        let fix = session.breakthrough("cascade deletes through the join table", 0.9).id.clone();
+        session.ice_cake(&fix, "deletes must not orphan rows").unwrap();
+        agi.sync_blackboard();
+        agi.sync_blackboard();
+        
+        let bb = agi.blackboard().unwrap();
+        assert_eq!(bb.decisions.len(), 1, "one decision per ice-caked moment");
+        let decision = &bb.decisions[0];
+        assert!(decision.ice_caked);
+        assert_eq!((decision.choice.as_str(), decision.task.as_str()), ("cascade deletes through the join table", "versions"));
+        assert_eq!(decision.provenance, [learning::Provenance {
+            session_id: session_id.clone(),
+            moment_id: fix.clone(),
+            kind: learning::ProvenanceKind::IceCake,
+        }]);
+        assert_eq!(bb.decisions_from_moment(&fix), [decision]);
+        assert!(bb.decisions_from_moment("missing").is_empty());
+        assert!(agi.handover_summary().contains(&format!("  ↳ session {} / moment {}…\n", session_id, &fix[..8])));
+    }
+    
+    #[test]
+    fn test_merged_blackboards_keep_agent_attribution() {
+        let planner = learning::AgentId::new("planner").with_instance("1");
+        let coder = learning::AgentId::new("coder").with_instance("2");
+        let mut a = MetaAGI::new().with_agent(planner.clone());
+        let mut b = MetaAGI::new().with_agent(coder.clone());
+        assert_eq!(MetaAGI::new().agent(), &learning::AgentId::unattributed());
+        
+        let session = a.start_session("versions", "Project-scoped versions");
+        let fix = session.breakthrough("cascade deletes through the join table", 0.9).id.clone();
+        session.ice_cake(&fix, "deletes must not orphan rows").unwrap();
+        a.sync_blackboard();
+        b.start_session("versions", "Project-scoped versions");
+        b.active_session_mut().unwrap().struggle("orphaned rows after delete", 0.7, 0.4);
+        b.record_collapse(&cognitive::evaluate_gate(&[1.0, 0.0], true)).unwrap();
+        b.sync_blackboard();
+        
+        let merged = a.blackboard().unwrap().merge(b.blackboard().unwrap(), &learning::BbMergePolicy::new()).unwrap();
+        assert_eq!(merged.agent, planner);
+        assert_eq!(merged.decisions[0].agent, planner);
+        assert_eq!(merged.questions[0].agent, coder);
+        assert_eq!(merged.next_steps[0].agent, coder);
+        let contributions = merged.contributions_by_agent();
+        let counts: Vec<(String, usize, usize, usize)> = contributions.iter()
+            .map(|(agent, c)| (agent.to_string(), c.decisions, c.questions, c.next_steps))
+            .collect();
+        assert_eq!(counts, [("coder#2".to_string(), 0, 1, 1), ("planner#1".to_string(), 1, 0, 0)]);
+        let logged = |agent: &learning::AgentId| merged.log.iter().filter(|entry| &entry.agent == agent).count();
+        assert_eq!(contributions[&coder].log_entries, logged(&coder));
+        assert_eq!(contributions[&planner].log_entries, logged(&planner));
+        assert_eq!(logged(&planner) + logged(&coder), merged.log.len());
+        
+        let summary = merged.handover_summary();
+        assert!(summary.contains(&format!("## Contributions\n- coder#2: 0 decisions, 1 questions, 1 next steps, {} log entries\n", logged(&coder))));
+        assert!(!a.handover_summary().contains("## Contributions"), "a single agent is not listed");
+    }
+    
+    #[test]
+    fn test_export_cypher_appends_blackboard_graphs_when_asked() {
+        let mut agi = MetaAGI::new();
+        agi.start_session("versions", "Project-scoped versions");
+        let session = agi.start_session("cache", "stale reads");
+        session.breakthrough("invalidate on write, not on read", 0.9);
+        session.breakthrough("version keys by project", 0.9);
+        let moments = agi.session().unwrap().moments.to_vec();
+        for moment in &moments {
+            agi.capture_moment(moment);
+        }
+        agi.blackboards.get_mut("cache").unwrap().record_decision("reads", "Invalidate on write", "reads can't race", cognitive::GateState::Flow);
+        
+        let concepts = agi.concepts.to_cypher();
+        assert_eq!(concepts.lines().count(), 2);
+        assert_eq!(agi.export_cypher(), concepts);
+        agi.cypher_blackboards = true;
+        let graphs = agi.blackboard_for("cache").unwrap().to_cypher() + &agi.blackboard_for("versions").unwrap().to_cypher();
+        let script = agi.export_cypher();
+        assert_eq!(script, concepts + &graphs);
+        assert!(script.lines().all(|line| line.ends_with(';')), "{}", script);
+        assert!(graphs.contains("n.choice = 'Invalidate on write', n.rationale = 'reads can\\'t race'"));
+    }
 }
@@ -0,0 +1,96 @@
+//! Multi-hop deduction with per-link provenance
+
+use thiserror::Error;
+use crate::nars::logic::{NalTruth, TruthFunctions};
+use crate::nars::truth::TruthValue;
+
+#[derive(Clone, Debug)]
+pub struct ChainResult {
+    /// Deduction folded over every link
+    pub truth: TruthValue,
+    /// Truth after each link; `steps[0]` is the first link, the last is `truth`
+    pub steps: Vec<TruthValue>,
+    /// Label of the link with the lowest expectation (earliest on ties)
+    pub weakest_link: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ChainError {
+    #[error("deduction chain has no links")]
+    Empty,
+}
+
+/// Fold deduction over A→B, B→C, C→D, … and report which link limits it.
+pub fn deduce_chain(links: &[(String, TruthValue)]) -> Result<ChainResult, ChainError> {
+    deduce_chain_with(links, &NalTruth)
+}
+
+/// `deduce_chain` under another truth logic
+pub fn deduce_chain_with<L: TruthFunctions + ?Sized>(links: &[(String, TruthValue)], logic: &L) -> Result<ChainResult, ChainError> {
+    let ((first_label, first), rest) = links.split_first().ok_or(ChainError::Empty)?;
+    
+    let mut steps = vec![first.clone()];
+    let mut weakest = (first_label, first.expectation());
+    for (label, link) in rest {
+        let next = logic.deduction(&steps[steps.len() - 1], link);
+        steps.push(next);
+        if link.expectation() < weakest.1 {
+            weakest = (label, link.expectation());
+        }
+    }
+    
+    Ok(ChainResult {
+        truth: steps[steps.len() - 1].clone(),
+        weakest_link: weakest.0.clone(),
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn link(label: &str, f: f32, c: f32) -> (String, TruthValue) {
+        (label.to_string(), TruthValue::new(f, c))
+    }
+    
+    #[test]
+    fn test_four_link_chain() {
+        let links = vec![
+            link("versions→project", 0.9, 0.9),
+            link("project→timeline", 0.8, 0.9),
+            link("versions→timeline", 0.6, 0.7),
+            link("timeline→milestones", 0.95, 0.9),
+        ];
+        let result = deduce_chain(&links).unwrap();
+        
+        let nested = links[0].1.deduction(&links[1].1).deduction(&links[2].1).deduction(&links[3].1);
+        assert_eq!(result.truth.frequency, nested.frequency);
+        assert_eq!(result.truth.confidence, nested.confidence);
+        assert_eq!(result.steps.len(), 4);
+        assert_eq!(result.weakest_link, "versions→timeline");
+    }
+    
+    #[test]
+    fn test_single_link_is_identity() {
+        let links = vec![link("only", 0.7, 0.6)];
+        let result = deduce_chain(&links).unwrap();
+        assert_eq!(result.truth.frequency, 0.7);
+        assert_eq!(result.truth.confidence, 0.6);
+        assert_eq!(result.weakest_link, "only");
+    }
+    
+    #[test]
+    fn test_chain_with_product_logic() {
+        let links = vec![link("a→b", 0.9, 0.9), link("b→c", 0.8, 0.9)];
+        let nal = deduce_chain(&links).unwrap();
+        let product = deduce_chain_with(&links, &crate::nars::ProductTruth).unwrap();
+        assert_eq!(nal.truth.frequency, product.truth.frequency);
+        assert!(product.truth.confidence > nal.truth.confidence);
+    }
+    
+    #[test]
+    fn test_empty_chain() {
+        assert!(matches!(deduce_chain(&[]), Err(ChainError::Empty)));
+    }
+}
@@ -0,0 +1,142 @@
+//! DesireValue — how much a statement is wanted, kept apart from belief
+
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use crate::nars::truth::TruthValue;
+
+/// Desire value (frequency, confidence) of a goal.
+///
+/// Same shape as `TruthValue` but a separate type, so a belief can never be
+/// passed where a desire is expected:
+///
+/// ```compile_fail
+/// use ladybug_learning_standalone::nars::{DesireValue, TruthValue};
+///
+/// let desire: DesireValue = TruthValue::new(0.9, 0.9);
+/// ```
+///
+/// ```compile_fail
+/// use ladybug_learning_standalone::nars::{DesireValue, TruthValue};
+///
+/// let belief = TruthValue::new(0.9, 0.9);
+/// let desire = DesireValue::new(0.9, 0.9);
+/// let _ = belief.revision(&desire);
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DesireValue {
+    #[serde(rename = "f")]
+    pub frequency: f32,
+    #[serde(rename = "c")]
+    pub confidence: f32,
+}
+
+impl DesireValue {
+    pub fn new(frequency: f32, confidence: f32) -> Self {
+        let t = TruthValue::new(frequency, confidence);
+        Self { frequency: t.frequency, confidence: t.confidence }
+    }
+    
+    /// Neither wanted nor unwanted
+    pub fn neutral() -> Self {
+        Self { frequency: 0.5, confidence: 0.0 }
+    }
+    
+    /// Expectation: E = c * (f - 0.5) + 0.5
+    pub fn expectation(&self) -> f32 {
+        self.confidence * (self.frequency - 0.5) + 0.5
+    }
+    
+    /// Desire deduction: G!, S⇒G ⊢ S!
+    ///
+    /// A goal and a belief that S leads to G yield S as a subgoal:
+    /// f = f1·f2, c = c1·c2·f2.
+    pub fn deduction(&self, belief: &TruthValue) -> DesireValue {
+        let f = self.frequency * belief.frequency;
+        let c = self.confidence * belief.confidence * belief.frequency;
+        DesireValue::new(f, c)
+    }
+    
+    /// Desire revision: combine independent evidence for the same goal
+    pub fn revision(&self, other: &DesireValue) -> DesireValue {
+        let revised = self.as_truth().revision(&other.as_truth());
+        DesireValue::new(revised.frequency, revised.confidence)
+    }
+    
+    fn as_truth(&self) -> TruthValue {
+        TruthValue::new(self.frequency, self.confidence)
+    }
+}
+
+impl Default for DesireValue {
+    fn default() -> Self {
+        Self::neutral()
+    }
+}
+
+impl fmt::Display for DesireValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "!⟨{:.0}%, {:.0}%⟩", self.frequency * 100.0, self.confidence * 100.0)
+    }
+}
+
+/// A statement the system wants to be true
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Goal {
+    /// `Statement::key()` of the desired statement
+    pub statement_fingerprint: u64,
+    pub desire: DesireValue,
+}
+
+impl Goal {
+    pub fn new(statement_fingerprint: u64, desire: DesireValue) -> Self {
+        Self { statement_fingerprint, desire }
+    }
+    
+    /// Subgoal for the antecedent of a belief `antecedent ⇒ this goal`
+    pub fn subgoal(&self, antecedent_fingerprint: u64, belief: &TruthValue) -> Goal {
+        Goal::new(antecedent_fingerprint, self.desire.deduction(belief))
+    }
+    
+    /// Merge another desire for the same statement
+    pub fn revise(&mut self, desire: &DesireValue) {
+        self.desire = self.desire.revision(desire);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nars::Statement;
+    
+    #[test]
+    fn test_desire_deduction() {
+        let ship = DesireValue::new(1.0, 0.9);
+        let scoping_ships = TruthValue::new(0.8, 0.9);
+        let subgoal = ship.deduction(&scoping_ships);
+        assert!((subgoal.frequency - 0.8).abs() < 1e-6);
+        assert!((subgoal.confidence - 0.648).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_goal_subgoal() {
+        let shipped = Statement::inheritance("versioning", "shipped");
+        let scoped = Statement::inheritance("versions", "project-scoped");
+        let goal = Goal::new(shipped.key(), DesireValue::new(1.0, 0.9));
+        let sub = goal.subgoal(scoped.key(), &TruthValue::new(0.9, 0.9));
+        assert_eq!(sub.statement_fingerprint, scoped.key());
+        assert!(sub.desire.expectation() > 0.5);
+    }
+    
+    #[test]
+    fn test_desire_revision() {
+        let mut goal = Goal::new(1, DesireValue::new(0.8, 0.5));
+        goal.revise(&DesireValue::new(0.8, 0.5));
+        assert!(goal.desire.confidence > 0.5);
+        assert!((goal.desire.frequency - 0.8).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_display_distinguishes_desire() {
+        assert_eq!(DesireValue::new(0.9, 0.8).to_string(), "!⟨90%, 80%⟩");
+    }
+}
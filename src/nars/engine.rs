@@ -0,0 +1,328 @@
+//! InferenceEngine — forward chaining over a belief table
+
+use std::collections::{BTreeMap, HashMap};
+use crate::core::Fingerprint;
+use crate::nars::logic::{NalTruth, TruthFunctions};
+use crate::nars::precise::TruthValueF64;
+use crate::nars::statement::{Statement, Term};
+use crate::nars::truth::TruthValue;
+
+pub type BeliefId = u64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InferenceRule {
+    Deduction,
+    Induction,
+    Abduction,
+}
+
+/// Where a derived belief came from
+#[derive(Clone, Debug, PartialEq)]
+pub struct Provenance {
+    pub rule: InferenceRule,
+    pub premises: (BeliefId, BeliefId),
+}
+
+#[derive(Clone, Debug)]
+pub struct Belief {
+    pub id: BeliefId,
+    pub statement: Statement,
+    pub truth: TruthValue,
+    /// Full-precision truth that revisions accumulate into; `truth` is its f32 view
+    pub precise: TruthValueF64,
+    /// 0 for asserted beliefs, premise depth + 1 for derived ones
+    pub depth: u32,
+    pub provenance: Option<Provenance>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EngineConfig {
+    /// Deepest derivation `step()` will produce
+    pub max_depth: u32,
+    /// Belief table size; the lowest-expectation beliefs are evicted beyond it
+    pub capacity: usize,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self { max_depth: 3, capacity: 1_000 }
+    }
+}
+
+/// Belief table plus forward chaining, over a pluggable truth logic
+pub struct InferenceEngine<L: TruthFunctions = NalTruth> {
+    pub config: EngineConfig,
+    logic: L,
+    beliefs: BTreeMap<BeliefId, Belief>,
+    index: HashMap<Fingerprint, BeliefId>,
+    next_id: BeliefId,
+    pub total_derivations: u64,
+}
+
+impl InferenceEngine {
+    pub fn new() -> Self {
+        Self::with_config(EngineConfig::default())
+    }
+    
+    pub fn with_config(config: EngineConfig) -> Self {
+        Self::with_logic(config, NalTruth)
+    }
+}
+
+impl<L: TruthFunctions> InferenceEngine<L> {
+    pub fn with_logic(config: EngineConfig, logic: L) -> Self {
+        Self {
+            config,
+            logic,
+            beliefs: BTreeMap::new(),
+            index: HashMap::new(),
+            next_id: 1,
+            total_derivations: 0,
+        }
+    }
+    
+    /// Add a belief, revising it into an existing belief about the same statement
+    pub fn assert(&mut self, statement: Statement, truth: TruthValue) -> BeliefId {
+        if let Some(&id) = self.index.get(statement.fingerprint()) {
+            if let Some(belief) = self.beliefs.get_mut(&id) {
+                belief.precise = self.logic.revision_precise(&belief.precise, &(&truth).into());
+                belief.truth = belief.precise.to_f32();
+                return id;
+            }
+        }
+        self.insert(statement, truth, 0, None)
+    }
+    
+    pub fn get(&self, statement: &Statement) -> Option<&Belief> {
+        self.index.get(statement.fingerprint()).and_then(|id| self.beliefs.get(id))
+    }
+    
+    pub fn belief(&self, id: BeliefId) -> Option<&Belief> {
+        self.beliefs.get(&id)
+    }
+    
+    pub fn beliefs(&self) -> impl Iterator<Item = &Belief> {
+        self.beliefs.values()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.beliefs.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.beliefs.is_empty()
+    }
+    
+    /// One round of forward chaining over all premise pairs sharing a term.
+    ///
+    /// Only statements not yet in the table are derived, and once the table is
+    /// full a conclusion must beat the weakest belief to get in, so repeated
+    /// steps reach a fixpoint instead of re-deriving what was just evicted.
+    /// Returns the number of new beliefs.
+    pub fn step(&mut self) -> usize {
+        let snapshot: Vec<Belief> = self.beliefs.values().cloned().collect();
+        let mut derived: Vec<(Statement, TruthValue, u32, Provenance)> = Vec::new();
+        
+        for a in &snapshot {
+            for b in &snapshot {
+                if a.id == b.id || a.statement.copula() != b.statement.copula() || a.statement.copula().is_symmetric() {
+                    continue;
+                }
+                let depth = a.depth.max(b.depth) + 1;
+                if depth > self.config.max_depth {
+                    continue;
+                }
+                for (rule, subject, predicate, truth) in self.syllogisms(a, b) {
+                    if subject == predicate {
+                        continue;
+                    }
+                    let conclusion = Statement::new(subject, a.statement.copula(), predicate);
+                    if self.index.contains_key(conclusion.fingerprint())
+                        || derived.iter().any(|(s, ..)| *s == conclusion)
+                    {
+                        continue;
+                    }
+                    derived.push((conclusion, truth, depth, Provenance { rule, premises: (a.id, b.id) }));
+                }
+            }
+        }
+        
+        let mut count = 0;
+        for (statement, truth, depth, provenance) in derived {
+            if self.beliefs.len() >= self.config.capacity && !self.outranks_weakest(&truth) {
+                continue;
+            }
+            self.insert(statement, truth, depth, Some(provenance));
+            count += 1;
+        }
+        self.total_derivations += count as u64;
+        count
+    }
+    
+    fn syllogisms(&self, a: &Belief, b: &Belief) -> Vec<(InferenceRule, Term, Term, TruthValue)> {
+        let (sa, pa) = (a.statement.subject(), a.statement.predicate());
+        let (sb, pb) = (b.statement.subject(), b.statement.predicate());
+        let mut out = Vec::new();
+        // A→B, B→C ⊢ A→C
+        if pa == sb {
+            out.push((InferenceRule::Deduction, sa.clone(), pb.clone(), self.logic.deduction(&a.truth, &b.truth)));
+        }
+        // A→B, A→C ⊢ B→C
+        if sa == sb && pa != pb {
+            out.push((InferenceRule::Induction, pa.clone(), pb.clone(), self.logic.induction(&a.truth, &b.truth)));
+        }
+        // A→B, C→B ⊢ A→C
+        if pa == pb && sa != sb {
+            out.push((InferenceRule::Abduction, sa.clone(), sb.clone(), self.logic.abduction(&a.truth, &b.truth)));
+        }
+        out
+    }
+    
+    fn insert(&mut self, statement: Statement, truth: TruthValue, depth: u32, provenance: Option<Provenance>) -> BeliefId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.index.insert(statement.fingerprint().clone(), id);
+        self.beliefs.insert(id, Belief { id, statement, precise: (&truth).into(), truth, depth, provenance });
+        self.evict(id);
+        id
+    }
+    
+    fn outranks_weakest(&self, truth: &TruthValue) -> bool {
+        self.beliefs.values()
+            .min_by(|a, b| a.truth.cmp_expectation(&b.truth))
+            .is_none_or(|weakest| truth.cmp_expectation(&weakest.truth).is_gt())
+    }
+    
+    /// Evict down to capacity, never touching `keep` so the caller's id stays valid
+    fn evict(&mut self, keep: BeliefId) {
+        while self.beliefs.len() > self.config.capacity {
+            let weakest = self.beliefs.values()
+                .filter(|b| b.id != keep)
+                .min_by(|a, b| a.truth.cmp_expectation(&b.truth))
+                .map(|b| b.id);
+            let Some(id) = weakest else { break };
+            if let Some(belief) = self.beliefs.remove(&id) {
+                self.index.remove(belief.statement.fingerprint());
+            }
+        }
+    }
+}
+
+impl Default for InferenceEngine {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_tweety_flies() {
+        let mut engine = InferenceEngine::new();
+        let bird_flyer = engine.assert(Statement::inheritance("bird", "flyer"), TruthValue::new(0.9, 0.9));
+        let tweety_bird = engine.assert(Statement::inheritance("tweety", "bird"), TruthValue::new(1.0, 0.9));
+        
+        assert!(engine.step() > 0);
+        
+        let tweety_flyer = engine.get(&Statement::inheritance("tweety", "flyer")).unwrap();
+        assert!(tweety_flyer.truth.frequency > 0.8);
+        assert_eq!(tweety_flyer.depth, 1);
+        let provenance = tweety_flyer.provenance.as_ref().unwrap();
+        assert_eq!(provenance.rule, InferenceRule::Deduction);
+        assert_eq!(provenance.premises, (tweety_bird, bird_flyer));
+    }
+    
+    #[test]
+    fn test_product_logic() {
+        let mut engine = InferenceEngine::with_logic(EngineConfig::default(), crate::nars::ProductTruth);
+        engine.assert(Statement::inheritance("bird", "flyer"), TruthValue::new(0.9, 0.9));
+        engine.assert(Statement::inheritance("tweety", "bird"), TruthValue::new(1.0, 0.9));
+        engine.step();
+        let derived = engine.get(&Statement::inheritance("tweety", "flyer")).unwrap();
+        assert!((derived.truth.confidence - 0.81).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_assert_revises_duplicates() {
+        let mut engine = InferenceEngine::new();
+        let first = engine.assert(Statement::inheritance("a", "b"), TruthValue::new(0.8, 0.5));
+        let second = engine.assert(Statement::inheritance("a", "b"), TruthValue::new(0.8, 0.5));
+        assert_eq!(first, second);
+        assert_eq!(engine.len(), 1);
+        assert!(engine.belief(first).unwrap().truth.confidence > 0.5);
+    }
+    
+    #[test]
+    fn test_revision_accumulates_in_f64() {
+        let mut engine = InferenceEngine::new();
+        let statement = Statement::inheritance("a", "b");
+        for i in 0..1_000 {
+            engine.assert(statement.clone(), TruthValue::new(if i % 2 == 0 { 0.9 } else { 0.2 }, 0.9));
+        }
+        let belief = engine.get(&statement).unwrap();
+        assert!((belief.precise.evidence_weight() - 9_000.0).abs() < 1.0);
+        assert!((belief.truth.frequency - 0.55).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn test_capacity_evicts_lowest_expectation() {
+        let mut engine = InferenceEngine::with_config(EngineConfig { max_depth: 3, capacity: 2 });
+        engine.assert(Statement::inheritance("a", "b"), TruthValue::new(0.9, 0.9));
+        engine.assert(Statement::inheritance("c", "d"), TruthValue::new(0.1, 0.9));
+        engine.assert(Statement::inheritance("e", "f"), TruthValue::new(0.7, 0.9));
+        
+        assert_eq!(engine.len(), 2);
+        assert!(engine.get(&Statement::inheritance("c", "d")).is_none());
+        assert!(engine.get(&Statement::inheritance("a", "b")).is_some());
+    }
+    
+    #[test]
+    fn test_reaches_fixpoint() {
+        let mut engine = InferenceEngine::with_config(EngineConfig { max_depth: 10, capacity: 1_000 });
+        engine.assert(Statement::inheritance("a", "b"), TruthValue::new(0.9, 0.9));
+        engine.assert(Statement::inheritance("b", "a"), TruthValue::new(0.9, 0.9));
+        engine.assert(Statement::inheritance("b", "c"), TruthValue::new(0.9, 0.9));
+        
+        let mut rounds = 0;
+        while engine.step() > 0 {
+            rounds += 1;
+            assert!(rounds < 20, "derivation did not terminate");
+        }
+        assert!(engine.beliefs().all(|b| b.statement.subject() != b.statement.predicate()));
+    }
+    
+    #[test]
+    fn test_full_table_reaches_fixpoint() {
+        let mut engine = InferenceEngine::with_config(EngineConfig { max_depth: 3, capacity: 2 });
+        engine.assert(Statement::inheritance("a", "b"), TruthValue::new(0.9, 0.9));
+        engine.assert(Statement::inheritance("b", "c"), TruthValue::new(0.9, 0.9));
+        
+        let mut rounds = 0;
+        while engine.step() > 0 {
+            rounds += 1;
+            assert!(rounds < 20, "derivation did not terminate");
+        }
+        assert_eq!(engine.len(), 2);
+        assert_eq!(engine.total_derivations, rounds as u64);
+    }
+    
+    #[test]
+    fn test_assert_id_survives_eviction() {
+        let mut engine = InferenceEngine::with_config(EngineConfig { max_depth: 3, capacity: 1 });
+        engine.assert(Statement::inheritance("a", "b"), TruthValue::new(0.9, 0.9));
+        let weak = engine.assert(Statement::inheritance("c", "d"), TruthValue::new(0.1, 0.9));
+        assert!(engine.belief(weak).is_some());
+        assert_eq!(engine.len(), 1);
+    }
+    
+    #[test]
+    fn test_max_depth() {
+        let mut engine = InferenceEngine::with_config(EngineConfig { max_depth: 1, capacity: 1_000 });
+        engine.assert(Statement::inheritance("a", "b"), TruthValue::new(0.9, 0.9));
+        engine.assert(Statement::inheritance("b", "c"), TruthValue::new(0.9, 0.9));
+        engine.assert(Statement::inheritance("c", "d"), TruthValue::new(0.9, 0.9));
+        while engine.step() > 0 {}
+        assert!(engine.get(&Statement::inheritance("a", "d")).is_none());
+        assert!(engine.beliefs().all(|b| b.depth <= 1));
+    }
+}
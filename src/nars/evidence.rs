@@ -0,0 +1,102 @@
+//! EvidenceCounter — incremental positive/negative evidence for a belief
+
+use serde::{Serialize, Deserialize};
+use crate::nars::precise::TruthValueF64;
+use crate::nars::truth::{TruthValue, TruthParams};
+
+/// Counts are kept in f64 so long-running tallies don't lose small weights;
+/// `truth()` converts to f32 at the edge.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvidenceCounter {
+    pub positive: f64,
+    pub negative: f64,
+}
+
+impl EvidenceCounter {
+    pub fn new() -> Self { Self::default() }
+    
+    /// Add positive evidence; negative or non-finite weights are ignored
+    pub fn record_success(&mut self, weight: f32) {
+        if weight.is_finite() && weight > 0.0 {
+            self.positive = (self.positive + weight as f64).min(f64::MAX);
+        }
+    }
+    
+    /// Add negative evidence; negative or non-finite weights are ignored
+    pub fn record_failure(&mut self, weight: f32) {
+        if weight.is_finite() && weight > 0.0 {
+            self.negative = (self.negative + weight as f64).min(f64::MAX);
+        }
+    }
+    
+    pub fn total(&self) -> f64 {
+        self.positive + self.negative
+    }
+    
+    pub fn truth(&self, params: &TruthParams) -> TruthValue {
+        self.truth_precise(params).to_f32()
+    }
+    
+    pub fn truth_precise(&self, params: &TruthParams) -> TruthValueF64 {
+        TruthValueF64::from_evidence_with(self.positive, self.negative, params)
+    }
+    
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_interleaved_matches_batch() {
+        let mut counter = EvidenceCounter::new();
+        for outcome in [true, true, false, true, false, true, true] {
+            if outcome { counter.record_success(1.0) } else { counter.record_failure(1.0) }
+        }
+        let params = TruthParams::default();
+        let batch = TruthValue::from_evidence(5.0, 2.0);
+        let incremental = counter.truth(&params);
+        assert_eq!(incremental.frequency, batch.frequency);
+        assert_eq!(incremental.confidence, batch.confidence);
+    }
+    
+    #[test]
+    fn test_fractional_weights() {
+        let mut counter = EvidenceCounter::new();
+        counter.record_success(0.5);
+        counter.record_success(0.25);
+        counter.record_failure(0.25);
+        counter.record_failure(-3.0);
+        counter.record_success(f32::NAN);
+        assert_eq!(counter.total(), 1.0);
+        let truth = counter.truth(&TruthParams::default());
+        assert!((truth.frequency - 0.75).abs() < 1e-6);
+        assert!((truth.confidence - 0.5).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_truth_stays_clamped() {
+        let mut counter = EvidenceCounter::new();
+        counter.record_success(f32::MAX);
+        counter.record_success(f32::MAX);
+        let truth = counter.truth(&TruthParams::default());
+        assert!((0.0..=1.0).contains(&truth.frequency));
+        assert!((0.0..=1.0).contains(&truth.confidence));
+        
+        counter.reset();
+        assert_eq!(counter.truth(&TruthParams::default()).confidence, 0.0);
+    }
+    
+    #[test]
+    fn test_serde_round_trip() {
+        let mut counter = EvidenceCounter::new();
+        counter.record_success(2.5);
+        counter.record_failure(1.0);
+        let json = serde_json::to_string(&counter).unwrap();
+        let back: EvidenceCounter = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, counter);
+    }
+}
@@ -0,0 +1,140 @@
+//! TruthInterval — frequency bounds whose width is the ignorance
+
+use std::fmt;
+use crate::nars::truth::TruthValue;
+
+/// Interval form of a truth value: the frequency will end up somewhere in
+/// [lower, upper] whatever the future evidence. lower = f·c, upper = 1 − c·(1−f).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TruthInterval {
+    pub lower: f32,
+    pub upper: f32,
+}
+
+impl TruthInterval {
+    /// Bounds are clamped into [0, 1]; an upper bound below the lower one
+    /// collapses to the lower bound.
+    pub fn new(lower: f32, upper: f32) -> Self {
+        let lower = lower.clamp(0.0, 1.0);
+        let upper = upper.clamp(0.0, 1.0).max(lower);
+        Self { lower, upper }
+    }
+    
+    /// Interval width: 1 − c
+    pub fn ignorance(&self) -> f32 {
+        self.upper - self.lower
+    }
+    
+    pub fn contains(&self, frequency: f32) -> bool {
+        frequency >= self.lower && frequency <= self.upper
+    }
+    
+    pub fn contains_interval(&self, other: &TruthInterval) -> bool {
+        self.lower <= other.lower && other.upper <= self.upper
+    }
+    
+    /// Overlap of both intervals, `None` when they are disjoint
+    pub fn intersection(&self, other: &TruthInterval) -> Option<TruthInterval> {
+        let lower = self.lower.max(other.lower);
+        let upper = self.upper.min(other.upper);
+        (lower <= upper).then_some(TruthInterval { lower, upper })
+    }
+    
+    pub fn is_disjoint(&self, other: &TruthInterval) -> bool {
+        self.intersection(other).is_none()
+    }
+    
+    pub fn to_truth(&self) -> TruthValue {
+        TruthValue::from(*self)
+    }
+}
+
+impl From<&TruthValue> for TruthInterval {
+    fn from(tv: &TruthValue) -> Self {
+        TruthInterval {
+            lower: tv.frequency * tv.confidence,
+            upper: 1.0 - tv.confidence * (1.0 - tv.frequency),
+        }
+    }
+}
+
+impl From<TruthValue> for TruthInterval {
+    fn from(tv: TruthValue) -> Self {
+        TruthInterval::from(&tv)
+    }
+}
+
+impl From<TruthInterval> for TruthValue {
+    /// c = 1 − width, f = lower / c. A zero-confidence interval has no
+    /// frequency information and maps to `unknown()`.
+    fn from(interval: TruthInterval) -> Self {
+        let confidence = 1.0 - interval.ignorance();
+        if confidence <= 0.0 {
+            return TruthValue::unknown();
+        }
+        TruthValue::new(interval.lower / confidence, confidence)
+    }
+}
+
+impl TruthValue {
+    pub fn to_interval(&self) -> TruthInterval {
+        TruthInterval::from(self)
+    }
+}
+
+impl fmt::Display for TruthInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:.2}, {:.2}]", self.lower, self.upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_round_trip() {
+        for (f, c) in [(0.9, 0.8), (0.1, 0.5), (0.5, 0.99), (1.0, 0.9), (0.0, 0.3), (0.73, 0.01)] {
+            let tv = TruthValue::new(f, c);
+            let back = tv.to_interval().to_truth();
+            assert!((back.frequency - f).abs() < 1e-4, "f {} -> {}", f, back.frequency);
+            assert!((back.confidence - c).abs() < 1e-5, "c {} -> {}", c, back.confidence);
+        }
+    }
+    
+    #[test]
+    fn test_landmarks() {
+        let unknown = TruthValue::unknown().to_interval();
+        assert_eq!(unknown, TruthInterval { lower: 0.0, upper: 1.0 });
+        assert_eq!(unknown.ignorance(), 1.0);
+        
+        let certain = TruthValue::certain_true().to_interval();
+        assert!((certain.lower - 0.9).abs() < 1e-6);
+        assert!((certain.upper - 1.0).abs() < 1e-6);
+        assert!(certain.ignorance() < 0.11);
+        
+        let back = TruthValue::from(unknown);
+        assert_eq!(back.confidence, 0.0);
+    }
+    
+    #[test]
+    fn test_intersection_and_containment() {
+        let low = TruthInterval::new(0.1, 0.3);
+        let high = TruthInterval::new(0.6, 0.9);
+        assert!(low.is_disjoint(&high));
+        
+        let wide = TruthInterval::new(0.2, 0.7);
+        let overlap = wide.intersection(&high).unwrap();
+        assert_eq!(overlap, TruthInterval { lower: 0.6, upper: 0.7 });
+        
+        assert!(TruthInterval::new(0.0, 1.0).contains_interval(&wide));
+        assert!(!wide.contains_interval(&high));
+        assert!(wide.contains(0.5));
+        assert!(!wide.contains(0.8));
+    }
+    
+    #[test]
+    fn test_display() {
+        assert_eq!(TruthInterval::new(0.45, 0.8).to_string(), "[0.45, 0.80]");
+    }
+}
@@ -0,0 +1,130 @@
+//! TruthFunctions — pluggable uncertainty logics
+
+use crate::nars::precise::TruthValueF64;
+use crate::nars::truth::TruthValue;
+
+/// The truth functions inference runs on. `NalTruth` is the default
+/// everywhere; implement this to try a different uncertainty calculus.
+pub trait TruthFunctions {
+    /// A→B, B→C ⊢ A→C
+    fn deduction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue;
+    /// A→B, A→C ⊢ B→C
+    fn induction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue;
+    /// A→B, C→B ⊢ A→C
+    fn abduction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue;
+    /// Two judgments about the same statement
+    fn revision(&self, a: &TruthValue, b: &TruthValue) -> TruthValue;
+    fn negation(&self, a: &TruthValue) -> TruthValue;
+    
+    /// Revision for accumulated beliefs. Defaults to f32 `revision`;
+    /// override to keep long revision chains in f64.
+    fn revision_precise(&self, a: &TruthValueF64, b: &TruthValueF64) -> TruthValueF64 {
+        self.revision(&a.to_f32(), &b.to_f32()).into()
+    }
+}
+
+/// Non-Axiomatic Logic: the `TruthValue` methods
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NalTruth;
+
+impl TruthFunctions for NalTruth {
+    fn deduction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue { a.deduction(b) }
+    fn induction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue { a.induction(b) }
+    fn abduction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue { a.abduction(b) }
+    fn revision(&self, a: &TruthValue, b: &TruthValue) -> TruthValue { a.revision(b) }
+    fn negation(&self, a: &TruthValue) -> TruthValue { a.negation() }
+    fn revision_precise(&self, a: &TruthValueF64, b: &TruthValueF64) -> TruthValueF64 { a.revision(b) }
+}
+
+/// Probabilistic reference logic: frequencies multiply like independent
+/// probabilities and confidence is a reliability that multiplies along a
+/// chain. Differences from NAL:
+///
+/// - deduction confidence is c1·c2, not damped by the frequencies
+/// - induction/abduction confidence is damped by the other premise's
+///   frequency but has no evidential horizon
+/// - revision averages frequencies by confidence and combines confidences
+///   as noisy-or, 1 − (1−c1)(1−c2), so it grows faster than NAL revision
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProductTruth;
+
+impl TruthFunctions for ProductTruth {
+    fn deduction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue {
+        TruthValue::new(a.frequency * b.frequency, a.confidence * b.confidence)
+    }
+    
+    fn induction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue {
+        TruthValue::new(b.frequency, a.confidence * b.confidence * a.frequency)
+    }
+    
+    fn abduction(&self, a: &TruthValue, b: &TruthValue) -> TruthValue {
+        TruthValue::new(a.frequency, a.confidence * b.confidence * b.frequency)
+    }
+    
+    fn revision(&self, a: &TruthValue, b: &TruthValue) -> TruthValue {
+        let weight = a.confidence + b.confidence;
+        let f = if weight > 0.0 {
+            (a.frequency * a.confidence + b.frequency * b.confidence) / weight
+        } else {
+            (a.frequency + b.frequency) / 2.0
+        };
+        TruthValue::new(f, 1.0 - (1.0 - a.confidence) * (1.0 - b.confidence))
+    }
+    
+    fn negation(&self, a: &TruthValue) -> TruthValue {
+        TruthValue::new(1.0 - a.frequency, a.confidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn premises() -> Vec<(TruthValue, TruthValue)> {
+        vec![
+            (TruthValue::new(0.9, 0.9), TruthValue::new(0.8, 0.8)),
+            (TruthValue::new(0.5, 0.5), TruthValue::new(0.5, 0.5)),
+            (TruthValue::new(0.2, 0.7), TruthValue::new(1.0, 0.3)),
+        ]
+    }
+    
+    #[test]
+    fn test_nal_matches_methods() {
+        for (a, b) in premises() {
+            assert_eq!(NalTruth.deduction(&a, &b).confidence, a.deduction(&b).confidence);
+            assert_eq!(NalTruth.revision(&a, &b).frequency, a.revision(&b).frequency);
+        }
+    }
+    
+    #[test]
+    fn test_same_frequencies_different_confidence() {
+        for (a, b) in premises() {
+            let nal = NalTruth.deduction(&a, &b);
+            let product = ProductTruth.deduction(&a, &b);
+            assert_eq!(nal.frequency, product.frequency);
+            // NAL damps deduction confidence by the frequencies
+            assert!(nal.confidence <= product.confidence);
+        }
+    }
+    
+    #[test]
+    fn test_revision_differs() {
+        let a = TruthValue::new(0.5, 0.5);
+        // NAL: w = 1 + 1, c = 2/3. Noisy-or: 1 − 0.25
+        assert!((NalTruth.revision(&a, &a).confidence - 2.0 / 3.0).abs() < 1e-6);
+        assert!((ProductTruth.revision(&a, &a).confidence - 0.75).abs() < 1e-6);
+        for (a, b) in premises() {
+            assert!(ProductTruth.revision(&a, &b).confidence >= NalTruth.revision(&a, &b).confidence - 1e-6);
+        }
+    }
+    
+    #[test]
+    fn test_dyn_dispatch() {
+        let logics: Vec<Box<dyn TruthFunctions>> = vec![Box::new(NalTruth), Box::new(ProductTruth)];
+        let a = TruthValue::new(0.9, 0.9);
+        for logic in &logics {
+            let back = logic.negation(&logic.negation(&a));
+            assert!((back.frequency - a.frequency).abs() < 1e-6);
+        }
+    }
+}
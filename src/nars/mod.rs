@@ -0,0 +1,27 @@
+//! NARS primitives - embedded for standalone operation
+
+pub mod truth;
+pub mod logic;
+pub mod precise;
+pub mod interval;
+pub mod evidence;
+pub mod chain;
+pub mod desire;
+pub mod question;
+pub mod statement;
+pub mod engine;
+
+pub use truth::{
+    TruthValue, TruthParams, InvalidHorizon, ParseTruthError, LowConfidenceError, DecayPolicy,
+    ChoiceBasis, ChoiceError, choose, choose_many,
+    max_by_expectation, min_by_expectation, analogy_from_fingerprints, RANDOM_SIMILARITY,
+};
+pub use logic::{TruthFunctions, NalTruth, ProductTruth};
+pub use precise::TruthValueF64;
+pub use interval::TruthInterval;
+pub use evidence::EvidenceCounter;
+pub use chain::{deduce_chain, deduce_chain_with, ChainResult, ChainError};
+pub use desire::{DesireValue, Goal};
+pub use question::{answer, answer_which, Answer, RankedAnswers};
+pub use statement::{Term, Copula, Statement};
+pub use engine::{InferenceEngine, EngineConfig, Belief, BeliefId, InferenceRule, Provenance};
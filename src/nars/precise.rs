@@ -0,0 +1,197 @@
+//! TruthValueF64 — double-precision truth arithmetic for long revision chains
+
+use serde::{Serialize, Deserialize};
+use crate::nars::truth::{TruthValue, TruthParams};
+
+/// f64 counterpart of `TruthValue`, used where hundreds of revisions
+/// accumulate (evidence counters, the inference engine's belief table).
+/// Convert to `TruthValue` at the edges.
+///
+/// Near c = 1 the f32 round trip w = c / (1 − c) loses most of its
+/// precision, so two equal evidence histories revised in f32 end up with
+/// visibly different evidence totals.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TruthValueF64 {
+    #[serde(rename = "f")]
+    pub frequency: f64,
+    #[serde(rename = "c")]
+    pub confidence: f64,
+}
+
+impl TruthValueF64 {
+    pub fn new(frequency: f64, confidence: f64) -> Self {
+        let frequency = if frequency.is_nan() { 0.5 } else { frequency };
+        let confidence = if confidence.is_nan() { 0.0 } else { confidence };
+        Self {
+            frequency: frequency.clamp(0.0, 1.0),
+            confidence: confidence.clamp(0.0, 1.0),
+        }
+    }
+    
+    pub fn unknown() -> Self {
+        Self { frequency: 0.5, confidence: 0.0 }
+    }
+    
+    pub fn from_evidence_with(positive: f64, negative: f64, params: &TruthParams) -> Self {
+        let (positive, negative) = (positive.max(0.0), negative.max(0.0));
+        let total = positive + negative;
+        if total.is_nan() || total == 0.0 {
+            return Self::unknown();
+        }
+        Self::new(positive / total, total / (total + params.k as f64))
+    }
+    
+    /// Evidence weight w = c / (1 − c), infinite at c = 1
+    pub fn evidence_weight(&self) -> f64 {
+        if self.confidence >= 1.0 {
+            f64::INFINITY
+        } else {
+            self.confidence / (1.0 - self.confidence)
+        }
+    }
+    
+    pub fn expectation(&self) -> f64 {
+        self.confidence * (self.frequency - 0.5) + 0.5
+    }
+    
+    /// Same semantics as `TruthValue::revision`
+    pub fn revision(&self, other: &TruthValueF64) -> TruthValueF64 {
+        self.revision_with(other, &TruthParams::default())
+    }
+    
+    /// Same semantics as `TruthValue::revision_with`: w = k·c / (1 − c)
+    pub fn revision_with(&self, other: &TruthValueF64, params: &TruthParams) -> TruthValueF64 {
+        let k = params.k as f64;
+        let (w1, w2) = (k * self.evidence_weight(), k * other.evidence_weight());
+        match (w1.is_infinite(), w2.is_infinite()) {
+            (true, true) => return Self::new((self.frequency + other.frequency) / 2.0, 1.0),
+            (true, false) => return Self::new(self.frequency, 1.0),
+            (false, true) => return Self::new(other.frequency, 1.0),
+            (false, false) => {}
+        }
+        let w = w1 + w2;
+        if w <= 0.0 {
+            return Self::new((self.frequency + other.frequency) / 2.0, 0.0);
+        }
+        Self::new((w1 * self.frequency + w2 * other.frequency) / w, w / (w + k))
+    }
+    
+    pub fn to_f32(&self) -> TruthValue {
+        TruthValue::new(self.frequency as f32, self.confidence as f32)
+    }
+}
+
+impl Default for TruthValueF64 {
+    fn default() -> Self { Self::unknown() }
+}
+
+impl From<&TruthValue> for TruthValueF64 {
+    fn from(tv: &TruthValue) -> Self {
+        Self::new(tv.frequency as f64, tv.confidence as f64)
+    }
+}
+
+impl From<TruthValue> for TruthValueF64 {
+    fn from(tv: TruthValue) -> Self {
+        Self::from(&tv)
+    }
+}
+
+impl From<TruthValueF64> for TruthValue {
+    fn from(tv: TruthValueF64) -> Self {
+        tv.to_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_f32_revision_chain_drifts() {
+        // 1,000 observations of w = 9 each, alternating frequencies
+        let mut single = TruthValue::unknown();
+        let mut double = TruthValueF64::unknown();
+        for i in 0..1_000 {
+            let observation = TruthValue::new(if i % 2 == 0 { 0.9 } else { 0.2 }, 0.9);
+            single = single.revision(&observation);
+            double = double.revision(&TruthValueF64::from(&observation));
+        }
+        
+        let exact = 9_000.0;
+        let w64 = double.evidence_weight();
+        let w32 = TruthValueF64::from(&single).evidence_weight();
+        assert!((w64 - exact).abs() / exact < 1e-3, "f64 weight {}", w64);
+        assert!((w32 - w64).abs() / w64 > 1e-3, "f32 weight {} vs f64 {}", w32, w64);
+    }
+    
+    /// Cost of f64 revision against f32 over the same chain; run with
+    /// `cargo test --release -- --ignored revision_chain_cost --nocapture`
+    #[test]
+    #[ignore]
+    fn test_revision_chain_cost() {
+        use std::hint::black_box;
+        use std::time::Instant;
+        const STEPS: usize = 1_000_000;
+        let observations: Vec<TruthValue> = (0..64).map(|i| TruthValue::new(i as f32 / 64.0, 0.5)).collect();
+        let precise: Vec<TruthValueF64> = observations.iter().map(TruthValueF64::from).collect();
+        
+        let start = Instant::now();
+        let mut single = TruthValue::unknown();
+        for i in 0..STEPS {
+            single = black_box(single.revision(&observations[i % 64]));
+        }
+        let f32_time = start.elapsed();
+        
+        let start = Instant::now();
+        let mut double = TruthValueF64::unknown();
+        for i in 0..STEPS {
+            double = black_box(double.revision(&precise[i % 64]));
+        }
+        let f64_time = start.elapsed();
+        
+        let ratio = f64_time.as_secs_f64() / f32_time.as_secs_f64();
+        println!("{} revisions: f32 {:?}, f64 {:?} ({:.2}x)", STEPS, f32_time, f64_time, ratio);
+        assert!(ratio < 2.0, "f64 revision costs {:.2}x f32", ratio);
+    }
+    
+    #[test]
+    fn test_conversions() {
+        let tv = TruthValue::new(0.9, 0.8);
+        let precise = TruthValueF64::from(&tv);
+        let back: TruthValue = precise.into();
+        assert_eq!(back.frequency, tv.frequency);
+        assert_eq!(back.confidence, tv.confidence);
+    }
+    
+    #[test]
+    fn test_matches_f32_revision() {
+        let a = TruthValue::new(0.8, 0.6);
+        let b = TruthValue::new(0.3, 0.4);
+        let single = a.revision(&b);
+        let double = TruthValueF64::from(&a).revision(&TruthValueF64::from(&b)).to_f32();
+        assert!((single.frequency - double.frequency).abs() < 1e-6);
+        assert!((single.confidence - double.confidence).abs() < 1e-6);
+        
+        let certain = TruthValueF64::new(1.0, 1.0).revision(&TruthValueF64::new(0.0, 0.5));
+        assert_eq!(certain, TruthValueF64::new(1.0, 1.0));
+    }
+    
+    #[test]
+    fn test_matches_f32_revision_at_k10() {
+        let k10 = TruthParams::new(10.0).unwrap();
+        let a = TruthValue::new(0.8, 0.6);
+        let b = TruthValue::new(0.3, 0.4);
+        let single = a.revision_with(&b, &k10);
+        let double = TruthValueF64::from(&a).revision_with(&TruthValueF64::from(&b), &k10).to_f32();
+        assert!((single.frequency - double.frequency).abs() < 1e-6);
+        assert!((single.confidence - double.confidence).abs() < 1e-6);
+        
+        // revising two 9:1 histories at k=10 is one 18:2 history
+        let history = TruthValueF64::from_evidence_with(9.0, 1.0, &k10);
+        let revised = history.revision_with(&history, &k10);
+        let pooled = TruthValueF64::from_evidence_with(18.0, 2.0, &k10);
+        assert!((revised.frequency - pooled.frequency).abs() < 1e-12);
+        assert!((revised.confidence - pooled.confidence).abs() < 1e-12);
+    }
+}
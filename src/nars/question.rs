@@ -0,0 +1,108 @@
+//! Question answering over a belief table
+
+use crate::cognitive::{evaluate_gate, GateState, SD_FLOW_THRESHOLD};
+use crate::nars::truth::TruthValue;
+
+#[derive(Clone, Debug)]
+pub struct Answer {
+    /// `Statement::key()` of the answering belief
+    pub statement_fingerprint: u64,
+    pub truth: TruthValue,
+    pub expectation: f32,
+}
+
+/// Answers to a "which X satisfies ?" question, best first
+#[derive(Clone, Debug)]
+pub struct RankedAnswers {
+    pub ranked: Vec<Answer>,
+    /// Collapse gate over the candidates' expectations
+    pub gate: GateState,
+    /// Confidence that `ranked[0]` really is the best answer, from the
+    /// expectation gap g to the runner-up: g / (g + SD_FLOW_THRESHOLD).
+    /// A single candidate is measured against an unknown (E = 0.5).
+    pub top_confidence: f32,
+}
+
+impl RankedAnswers {
+    pub fn best(&self) -> &Answer {
+        &self.ranked[0]
+    }
+}
+
+/// Best belief about one statement, by expectation. Ties go to the earliest.
+pub fn answer(question_fp: u64, beliefs: &[(u64, TruthValue)]) -> Option<Answer> {
+    let truth = crate::nars::max_by_expectation(
+        beliefs.iter().filter(|(fp, _)| *fp == question_fp).map(|(_, tv)| tv),
+    )?;
+    Some(Answer {
+        statement_fingerprint: question_fp,
+        truth: truth.clone(),
+        expectation: truth.expectation(),
+    })
+}
+
+/// Rank candidate statements by their best belief. Candidates without any
+/// belief are skipped; `None` when no candidate has one.
+pub fn answer_which(candidates: &[u64], beliefs: &[(u64, TruthValue)]) -> Option<RankedAnswers> {
+    let mut ranked: Vec<Answer> = candidates.iter()
+        .filter_map(|&fp| answer(fp, beliefs))
+        .collect();
+    if ranked.is_empty() {
+        return None;
+    }
+    ranked.sort_by(|a, b| b.truth.cmp_expectation(&a.truth));
+    
+    let expectations: Vec<f32> = ranked.iter().map(|a| a.expectation).collect();
+    let gate = evaluate_gate(&expectations, false).state;
+    let runner_up = expectations.get(1).copied().unwrap_or(0.5);
+    let gap = (expectations[0] - runner_up).max(0.0);
+    let top_confidence = gap / (gap + SD_FLOW_THRESHOLD);
+    
+    Some(RankedAnswers { ranked, gate, top_confidence })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nars::Statement;
+    
+    #[test]
+    fn test_exact_match() {
+        let scoped = Statement::inheritance("versions", "project-scoped").key();
+        let global = Statement::inheritance("versions", "global").key();
+        let beliefs = vec![
+            (global, TruthValue::new(0.2, 0.8)),
+            (scoped, TruthValue::new(0.7, 0.5)),
+            (scoped, TruthValue::new(0.9, 0.9)),
+        ];
+        let a = answer(scoped, &beliefs).unwrap();
+        assert_eq!(a.statement_fingerprint, scoped);
+        assert_eq!(a.truth.frequency, 0.9);
+        assert!(answer(42, &beliefs).is_none());
+    }
+    
+    #[test]
+    fn test_ranked_selection() {
+        let beliefs = vec![
+            (1, TruthValue::new(0.6, 0.5)),
+            (2, TruthValue::new(0.95, 0.9)),
+            (3, TruthValue::new(0.1, 0.9)),
+        ];
+        let answers = answer_which(&[1, 2, 3, 4], &beliefs).unwrap();
+        let order: Vec<u64> = answers.ranked.iter().map(|a| a.statement_fingerprint).collect();
+        assert_eq!(order, vec![2, 1, 3]);
+        assert_eq!(answers.best().statement_fingerprint, 2);
+        assert!(answers.top_confidence > 0.5);
+        
+        let close = vec![(1, TruthValue::new(0.8, 0.9)), (2, TruthValue::new(0.81, 0.9))];
+        let answers = answer_which(&[1, 2], &close).unwrap();
+        assert_eq!(answers.gate, GateState::Flow);
+        assert!(answers.top_confidence < 0.1);
+    }
+    
+    #[test]
+    fn test_empty_beliefs() {
+        assert!(answer(1, &[]).is_none());
+        assert!(answer_which(&[1, 2], &[]).is_none());
+    }
+}
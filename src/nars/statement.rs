@@ -0,0 +1,176 @@
+//! Term and Statement — what truth values are about
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use crate::core::Fingerprint;
+
+/// Atomic term, grounded in a fingerprint
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Term {
+    pub name: String,
+    pub fingerprint: Fingerprint,
+}
+
+impl Term {
+    /// Term grounded in its own name
+    pub fn atom(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            fingerprint: Fingerprint::from_content(&format!("term:{}", name)),
+        }
+    }
+    
+    /// Term grounded in an existing fingerprint (e.g. a concept's)
+    pub fn grounded(name: &str, fingerprint: Fingerprint) -> Self {
+        Self { name: name.to_string(), fingerprint }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Copula {
+    /// S --> P
+    Inheritance,
+    /// S <-> P
+    Similarity,
+    /// S ==> P
+    Implication,
+}
+
+impl Copula {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Inheritance => "-->",
+            Self::Similarity => "<->",
+            Self::Implication => "==>",
+        }
+    }
+    
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, Self::Similarity)
+    }
+}
+
+/// Statement: subject, copula, predicate.
+///
+/// The content fingerprint role-binds subject and predicate (the predicate
+/// is permuted for asymmetric copulas) and binds the copula. Equality and
+/// hashing use that fingerprint, so `<a <-> b>` equals `<b <-> a>`.
+#[derive(Clone, Debug)]
+pub struct Statement {
+    subject: Term,
+    copula: Copula,
+    predicate: Term,
+    fingerprint: Fingerprint,
+}
+
+impl Statement {
+    pub fn new(subject: Term, copula: Copula, predicate: Term) -> Self {
+        let copula_fp = Fingerprint::from_content(&format!("copula:{}", copula.symbol()));
+        let predicate_fp = if copula.is_symmetric() {
+            predicate.fingerprint.clone()
+        } else {
+            predicate.fingerprint.permute(1)
+        };
+        let fingerprint = subject.fingerprint.bind(&predicate_fp).bind(&copula_fp);
+        Self { subject, copula, predicate, fingerprint }
+    }
+    
+    pub fn inheritance(subject: &str, predicate: &str) -> Self {
+        Self::new(Term::atom(subject), Copula::Inheritance, Term::atom(predicate))
+    }
+    
+    pub fn similarity(subject: &str, predicate: &str) -> Self {
+        Self::new(Term::atom(subject), Copula::Similarity, Term::atom(predicate))
+    }
+    
+    pub fn implication(subject: &str, predicate: &str) -> Self {
+        Self::new(Term::atom(subject), Copula::Implication, Term::atom(predicate))
+    }
+    
+    pub fn subject(&self) -> &Term { &self.subject }
+    
+    pub fn copula(&self) -> Copula { self.copula }
+    
+    pub fn predicate(&self) -> &Term { &self.predicate }
+    
+    pub fn fingerprint(&self) -> &Fingerprint { &self.fingerprint }
+    
+    /// Compact 64-bit key of the content fingerprint, for tables keyed by statement
+    pub fn key(&self) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        let mut hasher = DefaultHasher::new();
+        self.fingerprint.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl PartialEq for Statement {
+    fn eq(&self, other: &Self) -> bool {
+        self.fingerprint == other.fingerprint
+    }
+}
+
+impl Eq for Statement {}
+
+impl Hash for Statement {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fingerprint.hash(state);
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} {} {}>", self.subject, self.copula.symbol(), self.predicate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_fingerprint_deterministic() {
+        let a = Statement::inheritance("versions", "project-scoped");
+        let b = Statement::inheritance("versions", "project-scoped");
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_eq!(a, b);
+    }
+    
+    #[test]
+    fn test_swap_subject_predicate() {
+        let forward = Statement::inheritance("versions", "project-scoped");
+        let backward = Statement::inheritance("project-scoped", "versions");
+        assert_ne!(forward, backward);
+        
+        let forward = Statement::similarity("versions", "sprints");
+        let backward = Statement::similarity("sprints", "versions");
+        assert_eq!(forward, backward);
+    }
+    
+    #[test]
+    fn test_key() {
+        let a = Statement::similarity("versions", "sprints");
+        assert_eq!(a.key(), Statement::similarity("sprints", "versions").key());
+        assert_ne!(a.key(), Statement::inheritance("versions", "sprints").key());
+    }
+    
+    #[test]
+    fn test_copula_distinguishes() {
+        let inh = Statement::inheritance("versions", "sprints");
+        let imp = Statement::implication("versions", "sprints");
+        assert_ne!(inh, imp);
+    }
+    
+    #[test]
+    fn test_display() {
+        assert_eq!(Statement::inheritance("versions", "project-scoped").to_string(), "<versions --> project-scoped>");
+        assert_eq!(Statement::similarity("versions", "sprints").to_string(), "<versions <-> sprints>");
+        assert_eq!(Statement::implication("a", "b").to_string(), "<a ==> b>");
+    }
+}
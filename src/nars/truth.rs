@@ -0,0 +1,1185 @@
+//! TruthValue — frequency/confidence pairs and the NAL truth functions
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+use crate::core::Fingerprint;
+
+/// NARS Truth Value (frequency, confidence)
+///
+/// Serializes as a `{f, c}` map; deserialized values are clamped.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "TruthRepr")]
+pub struct TruthValue {
+    #[serde(rename = "f")]
+    pub frequency: f32,
+    #[serde(rename = "c")]
+    pub confidence: f32,
+}
+
+#[derive(Deserialize)]
+struct TruthRepr {
+    f: f32,
+    c: f32,
+}
+
+impl From<TruthRepr> for TruthValue {
+    fn from(r: TruthRepr) -> Self {
+        TruthValue::new(r.f, r.c)
+    }
+}
+
+/// Evidential horizon k: how much new evidence counts against what is
+/// already known. Confidence is w / (w + k) for evidence weight w.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TruthParams {
+    pub k: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Error)]
+#[error("evidential horizon must be finite and > 0, got {0}")]
+pub struct InvalidHorizon(pub f32);
+
+impl TruthParams {
+    pub fn new(k: f32) -> Result<Self, InvalidHorizon> {
+        if k.is_finite() && k > 0.0 {
+            Ok(Self { k })
+        } else {
+            Err(InvalidHorizon(k))
+        }
+    }
+}
+
+impl Default for TruthParams {
+    fn default() -> Self {
+        Self { k: 1.0 }
+    }
+}
+
+impl TruthValue {
+    /// Values are clamped into [0, 1]; a NaN frequency becomes 0.5 and a
+    /// NaN confidence 0.0, i.e. no information.
+    pub fn new(frequency: f32, confidence: f32) -> Self {
+        let frequency = if frequency.is_nan() { 0.5 } else { frequency };
+        let confidence = if confidence.is_nan() { 0.0 } else { confidence };
+        Self {
+            frequency: frequency.clamp(0.0, 1.0),
+            confidence: confidence.clamp(0.0, 1.0),
+        }
+    }
+    
+    /// Unknown truth value
+    pub fn unknown() -> Self {
+        Self { frequency: 0.5, confidence: 0.0 }
+    }
+    
+    /// Certain true
+    pub fn certain_true() -> Self {
+        Self { frequency: 1.0, confidence: 0.9 }
+    }
+    
+    /// Certain false
+    pub fn certain_false() -> Self {
+        Self { frequency: 0.0, confidence: 0.9 }
+    }
+    
+    /// From positive/negative evidence counts
+    pub fn from_evidence(positive: f32, negative: f32) -> Self {
+        Self::from_evidence_with(positive, negative, &TruthParams::default())
+    }
+    
+    /// From positive/negative evidence counts with an explicit horizon
+    pub fn from_evidence_with(positive: f32, negative: f32, params: &TruthParams) -> Self {
+        let (positive, negative) = (positive.max(0.0), negative.max(0.0));
+        let total = positive + negative;
+        if total.is_nan() || total == 0.0 {
+            return Self::unknown();
+        }
+        if total.is_infinite() {
+            // Overflowed evidence: rescale for frequency, confidence saturates
+            let scale = positive.max(negative);
+            let (p, n) = (positive / scale, negative / scale);
+            return Self::new(p / (p + n), 1.0);
+        }
+        let frequency = positive / total;
+        let confidence = total / (total + params.k);
+        Self { frequency, confidence }
+    }
+    
+    /// Belief that two things are alike, from a VSA similarity score.
+    ///
+    /// `baseline` is the similarity expected by chance (0.5 for random
+    /// fingerprints, see `RANDOM_SIMILARITY`) and counts as no evidence:
+    ///
+    /// - sim = baseline → `unknown()`
+    /// - sim > baseline → f = 1, c = 0.9 · (sim − baseline) / (1 − baseline)
+    /// - sim < baseline → f = 0, c = 0.9 · (baseline − sim) / baseline
+    ///
+    /// Confidence grows linearly with the distance from chance and tops out
+    /// at 0.9, the confidence of `certain_true()`.
+    pub fn from_similarity(sim: f32, baseline: f32) -> Self {
+        let sim = sim.clamp(0.0, 1.0);
+        let baseline = baseline.clamp(0.0, 1.0);
+        if sim > baseline {
+            let excess = (sim - baseline) / (1.0 - baseline);
+            TruthValue::new(1.0, excess * 0.9)
+        } else if sim < baseline {
+            let deficit = (baseline - sim) / baseline;
+            TruthValue::new(0.0, deficit * 0.9)
+        } else {
+            TruthValue::unknown()
+        }
+    }
+    
+    /// Expectation: E = c * (f - 0.5) + 0.5
+    pub fn expectation(&self) -> f32 {
+        self.confidence * (self.frequency - 0.5) + 0.5
+    }
+    
+    /// Ignorance: 1 − c, which is also the width of the truth interval
+    pub fn ignorance(&self) -> f32 {
+        1.0 - self.confidence
+    }
+    
+    /// At least `min_confidence`, and never a zero-confidence belief
+    pub fn is_informative(&self, min_confidence: f32) -> bool {
+        self.confidence > 0.0 && self.confidence >= min_confidence
+    }
+    
+    /// Guard for acting on a belief: errors unless `is_informative`
+    pub fn require_informative(&self, min_confidence: f32) -> Result<&Self, LowConfidenceError> {
+        if self.is_informative(min_confidence) {
+            Ok(self)
+        } else {
+            Err(LowConfidenceError { confidence: self.confidence, min_confidence })
+        }
+    }
+    
+    /// Total order by expectation.
+    ///
+    /// TruthValue deliberately does not implement `Ord`: two values with equal
+    /// expectation (e.g. ⟨100%, 10%⟩ and ⟨55%, 100%⟩) are not the same belief,
+    /// so an `Ord` consistent with `Eq` would be misleading.
+    pub fn cmp_expectation(&self, other: &TruthValue) -> Ordering {
+        self.expectation().total_cmp(&other.expectation())
+    }
+    
+    /// Stronger: expectation higher by more than `epsilon`, and confidence
+    /// not lower by more than `epsilon`.
+    pub fn is_stronger_than(&self, other: &TruthValue, epsilon: f32) -> bool {
+        self.expectation() > other.expectation() + epsilon
+            && self.confidence + epsilon >= other.confidence
+    }
+    
+    /// Deduction: A→B, B→C ⊢ A→C
+    ///
+    /// `self` is A→B, `other` is B→C.
+    pub fn deduction(&self, other: &TruthValue) -> TruthValue {
+        let f = self.frequency * other.frequency;
+        let c = self.confidence * other.confidence * self.frequency * other.frequency;
+        TruthValue::new(f, c)
+    }
+    
+    /// Induction: A→B, A→C ⊢ B→C
+    ///
+    /// `self` is A→B, `other` is A→C. The conclusion takes its frequency from
+    /// `other` (the premise whose predicate it inherits) and weighs confidence
+    /// by `self`'s frequency, so swapping the arguments is not symmetric.
+    pub fn induction(&self, other: &TruthValue) -> TruthValue {
+        self.induction_with(other, &TruthParams::default())
+    }
+    
+    /// Induction with an explicit horizon: c = f1·c1·c2 / (f1 + k)
+    pub fn induction_with(&self, other: &TruthValue, params: &TruthParams) -> TruthValue {
+        let f = other.frequency;
+        let c = self.frequency * self.confidence * other.confidence / (self.frequency + params.k);
+        TruthValue::new(f, c)
+    }
+    
+    /// Abduction: A→B, C→B ⊢ A→C
+    ///
+    /// `self` is A→B, `other` is C→B. The conclusion takes its frequency from
+    /// `self` (the premise whose subject it inherits) and weighs confidence by
+    /// `other`'s frequency, so swapping the arguments is not symmetric.
+    pub fn abduction(&self, other: &TruthValue) -> TruthValue {
+        self.abduction_with(other, &TruthParams::default())
+    }
+    
+    /// Abduction with an explicit horizon: c = f2·c1·c2 / (f2 + k)
+    pub fn abduction_with(&self, other: &TruthValue, params: &TruthParams) -> TruthValue {
+        let f = self.frequency;
+        let c = other.frequency * self.confidence * other.confidence / (other.frequency + params.k);
+        TruthValue::new(f, c)
+    }
+    
+    /// Exemplification: A→B, B→C ⊢ C→A
+    ///
+    /// `self` is A→B, `other` is B→C, the same order as `deduction`; the
+    /// conclusion runs the other way. f = 1, c = w / (w + 1) with
+    /// w = f1·c1·f2·c2, so the result is always weak.
+    pub fn exemplification(&self, other: &TruthValue) -> TruthValue {
+        self.exemplification_with(other, &TruthParams::default())
+    }
+    
+    /// Exemplification with an explicit horizon: c = w / (w + k)
+    pub fn exemplification_with(&self, other: &TruthValue, params: &TruthParams) -> TruthValue {
+        let w = self.frequency * self.confidence * other.frequency * other.confidence;
+        TruthValue::new(1.0, w / (w + params.k))
+    }
+    
+    /// Comparison: M→P, M→S ⊢ S↔P
+    ///
+    /// Two inheritances sharing a subject yield a similarity between their
+    /// predicates. When both frequencies are 0 there is no positive evidence
+    /// to compare, so the result carries zero confidence.
+    pub fn comparison(&self, other: &TruthValue) -> TruthValue {
+        self.comparison_with(other, &TruthParams::default())
+    }
+    
+    /// Comparison with an explicit horizon: c = w / (w + k)
+    pub fn comparison_with(&self, other: &TruthValue, params: &TruthParams) -> TruthValue {
+        let f0 = self.frequency + other.frequency - self.frequency * other.frequency;
+        let f = if f0 > 0.0 { self.frequency * other.frequency / f0 } else { 0.0 };
+        let w = f0 * self.confidence * other.confidence;
+        let c = w / (w + params.k);
+        TruthValue::new(f, c)
+    }
+    
+    /// Analogy: M→P, S↔M ⊢ S→P
+    ///
+    /// `self` is the inheritance belief, `similarity_tv` the similarity
+    /// between its subject and the new subject.
+    pub fn analogy(&self, similarity_tv: &TruthValue) -> TruthValue {
+        let f = self.frequency * similarity_tv.frequency;
+        let c = self.confidence * similarity_tv.confidence * similarity_tv.frequency;
+        TruthValue::new(f, c)
+    }
+    
+    /// Intersection: M→T1, M→T2 ⊢ M→(T1 ∩ T2)
+    ///
+    /// f = f1·f2, c = c1·c2
+    ///
+    /// ```
+    /// use ladybug_learning_standalone::nars::TruthValue;
+    ///
+    /// let scoped_to_project = TruthValue::new(0.9, 0.9);
+    /// let has_timeline = TruthValue::new(0.8, 0.9);
+    /// // versions → (scoped-to-project ∩ has-timeline)
+    /// let both = scoped_to_project.intersection(&has_timeline);
+    /// assert!((both.frequency - 0.72).abs() < 1e-6);
+    /// assert!((both.confidence - 0.81).abs() < 1e-6);
+    /// ```
+    pub fn intersection(&self, other: &TruthValue) -> TruthValue {
+        let f = self.frequency * other.frequency;
+        let c = self.confidence * other.confidence;
+        TruthValue::new(f, c)
+    }
+    
+    /// Union: M→T1, M→T2 ⊢ M→(T1 ∪ T2)
+    ///
+    /// f = 1 − (1−f1)(1−f2), c = c1·c2
+    pub fn union(&self, other: &TruthValue) -> TruthValue {
+        let f = 1.0 - (1.0 - self.frequency) * (1.0 - other.frequency);
+        let c = self.confidence * other.confidence;
+        TruthValue::new(f, c)
+    }
+    
+    /// Difference: M→T1, M→T2 ⊢ M→(T1 − T2)
+    ///
+    /// f = f1·(1−f2), c = c1·c2
+    pub fn difference(&self, other: &TruthValue) -> TruthValue {
+        let f = self.frequency * (1.0 - other.frequency);
+        let c = self.confidence * other.confidence;
+        TruthValue::new(f, c)
+    }
+    
+    /// Revision: combine independent evidence
+    pub fn revision(&self, other: &TruthValue) -> TruthValue {
+        self.revision_with(other, &TruthParams::default())
+    }
+    
+    /// Revision with an explicit horizon: w = k·c / (1 − c)
+    pub fn revision_with(&self, other: &TruthValue, params: &TruthParams) -> TruthValue {
+        let w1 = evidence_weight(self.confidence, params.k, 1.0);
+        let w2 = evidence_weight(other.confidence, params.k, 1.0);
+        revise(self, w1, other, w2, params.k)
+    }
+    
+    /// Revision with per-premise source reliability in [0, 1].
+    ///
+    /// Each premise's evidence weight w = c / (1 − c) is scaled by its
+    /// reliability before combining; reliability 1.0 on both sides is plain
+    /// revision, reliability 0.0 ignores that premise.
+    pub fn revision_weighted(&self, other: &TruthValue, self_reliability: f32, other_reliability: f32) -> TruthValue {
+        self.revision_weighted_with(other, self_reliability, other_reliability, &TruthParams::default())
+    }
+    
+    /// Weighted revision with an explicit horizon: w = reliability·k·c / (1 − c)
+    pub fn revision_weighted_with(&self, other: &TruthValue, self_reliability: f32, other_reliability: f32, params: &TruthParams) -> TruthValue {
+        let w1 = evidence_weight(self.confidence, params.k, self_reliability.clamp(0.0, 1.0));
+        let w2 = evidence_weight(other.confidence, params.k, other_reliability.clamp(0.0, 1.0));
+        revise(self, w1, other, w2, params.k)
+    }
+    
+    /// Revision when the premises may share evidence.
+    ///
+    /// `overlap` ∈ [0, 1] is the estimated fraction of shared evidence; the
+    /// less confident premise's evidence weight is discounted by (1 − overlap).
+    /// overlap = 0 is plain revision, overlap = 1 is choice by confidence.
+    pub fn revision_with_overlap(&self, other: &TruthValue, overlap: f32) -> TruthValue {
+        self.revision_with_overlap_with(other, overlap, &TruthParams::default())
+    }
+    
+    /// Overlap-discounted revision with an explicit horizon
+    pub fn revision_with_overlap_with(&self, other: &TruthValue, overlap: f32, params: &TruthParams) -> TruthValue {
+        let overlap = overlap.clamp(0.0, 1.0);
+        let (primary, secondary) = if other.confidence > self.confidence { (other, self) } else { (self, other) };
+        let w1 = evidence_weight(primary.confidence, params.k, 1.0);
+        let w2 = evidence_weight(secondary.confidence, params.k, 1.0 - overlap);
+        revise(primary, w1, secondary, w2, params.k)
+    }
+    
+    /// Conversion: S→P ⊢ P→S
+    ///
+    /// Only the positive evidence carries over, so the result is weak by
+    /// design: c = f·c / (f·c + 1), never above 0.5.
+    pub fn conversion(&self) -> TruthValue {
+        self.conversion_with(&TruthParams::default())
+    }
+    
+    /// Conversion with an explicit horizon: c = f·c / (f·c + k)
+    pub fn conversion_with(&self, params: &TruthParams) -> TruthValue {
+        let w = self.frequency * self.confidence;
+        TruthValue::new(1.0, w / (w + params.k))
+    }
+    
+    /// Contraposition: (S⇒P) ⊢ (¬P⇒¬S)
+    ///
+    /// Only the negative evidence carries over, so the result is weak by
+    /// design: c = (1−f)·c / ((1−f)·c + 1), never above 0.5.
+    pub fn contraposition(&self) -> TruthValue {
+        self.contraposition_with(&TruthParams::default())
+    }
+    
+    /// Contraposition with an explicit horizon: c = (1−f)·c / ((1−f)·c + k)
+    pub fn contraposition_with(&self, params: &TruthParams) -> TruthValue {
+        let w = (1.0 - self.frequency) * self.confidence;
+        TruthValue::new(0.0, w / (w + params.k))
+    }
+    
+    /// Project a belief observed at `from_cycle` onto `to_cycle`.
+    ///
+    /// Confidence decays hyperbolically with temporal distance:
+    /// c' = c · h / (h + Δt), where `horizon` is the distance at which
+    /// confidence is halved.
+    pub fn project(&self, from_cycle: u64, to_cycle: u64, horizon: f32) -> TruthValue {
+        let distance = from_cycle.abs_diff(to_cycle) as f32;
+        if distance == 0.0 {
+            return self.clone();
+        }
+        if horizon.is_infinite() {
+            return self.clone();
+        }
+        let factor = horizon.max(0.0) / (horizon.max(0.0) + distance);
+        TruthValue::new(self.frequency, self.confidence * factor)
+    }
+    
+    /// Eternalize: the time-independent version of a temporal belief,
+    /// c_eternal = c / (c + k)
+    pub fn eternalize(&self) -> TruthValue {
+        self.eternalize_with(&TruthParams::default())
+    }
+    
+    pub fn eternalize_with(&self, params: &TruthParams) -> TruthValue {
+        TruthValue::new(self.frequency, self.confidence / (self.confidence + params.k))
+    }
+    
+    /// Exponential confidence decay: c' = c · 0.5^(elapsed / half_life).
+    /// Frequency is unchanged. A non-positive half-life disables decay.
+    pub fn decayed(&self, cycles_elapsed: u64, half_life: f32) -> TruthValue {
+        if cycles_elapsed == 0 || half_life <= 0.0 || !half_life.is_finite() {
+            return self.clone();
+        }
+        let factor = 0.5f32.powf(cycles_elapsed as f32 / half_life);
+        TruthValue::new(self.frequency, self.confidence * factor)
+    }
+    
+    /// Negation
+    pub fn negation(&self) -> TruthValue {
+        TruthValue::new(1.0 - self.frequency, self.confidence)
+    }
+}
+
+/// Evidence weight w = scale · k · c / (1 − c). Confidence 1 is infinite
+/// evidence; a zero scale contributes nothing even then.
+fn evidence_weight(confidence: f32, k: f32, scale: f32) -> f32 {
+    if scale <= 0.0 || confidence <= 0.0 {
+        0.0
+    } else if confidence >= 1.0 {
+        f32::INFINITY
+    } else {
+        scale * k * confidence / (1.0 - confidence)
+    }
+}
+
+/// Combine two evidence-weighted premises. Infinite weights dominate finite
+/// ones (and average among themselves); with no evidence at all the
+/// frequencies are averaged at zero confidence.
+fn revise(a: &TruthValue, w1: f32, b: &TruthValue, w2: f32, k: f32) -> TruthValue {
+    match (w1.is_infinite(), w2.is_infinite()) {
+        (true, true) => return TruthValue::new((a.frequency + b.frequency) / 2.0, 1.0),
+        (true, false) => return TruthValue::new(a.frequency, 1.0),
+        (false, true) => return TruthValue::new(b.frequency, 1.0),
+        (false, false) => {}
+    }
+    let w = w1 + w2;
+    if w <= 0.0 {
+        return TruthValue::new((a.frequency + b.frequency) / 2.0, 0.0);
+    }
+    let f = (w1 * a.frequency + w2 * b.frequency) / w;
+    let c = w / (w + k);
+    TruthValue::new(f, c)
+}
+
+/// Lazy confidence decay for stored beliefs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecayPolicy {
+    /// Cycles after which confidence has halved
+    pub half_life: f32,
+}
+
+impl DecayPolicy {
+    pub fn new(half_life: f32) -> Self {
+        Self { half_life }
+    }
+    
+    /// Truth of a belief last confirmed at `last_confirmed_cycle`, as seen at `current_cycle`
+    pub fn apply(&self, truth: &TruthValue, last_confirmed_cycle: u64, current_cycle: u64) -> TruthValue {
+        truth.decayed(current_cycle.saturating_sub(last_confirmed_cycle), self.half_life)
+    }
+}
+
+/// Similarity of two random fingerprints, treated as zero evidence
+pub const RANDOM_SIMILARITY: f32 = 0.5;
+
+/// Analogy grounded in fingerprints: "b feels like a, so what holds for a
+/// probably holds for b".
+pub fn analogy_from_fingerprints(premise: &TruthValue, a: &Fingerprint, b: &Fingerprint) -> TruthValue {
+    premise.analogy(&TruthValue::from_similarity(a.similarity(b), RANDOM_SIMILARITY))
+}
+
+/// Highest expectation; ties go to the earliest
+pub fn max_by_expectation<'a, I>(values: I) -> Option<&'a TruthValue>
+where
+    I: IntoIterator<Item = &'a TruthValue>,
+{
+    values.into_iter().reduce(|best, tv| if tv.cmp_expectation(best) == Ordering::Greater { tv } else { best })
+}
+
+/// Lowest expectation; ties go to the earliest
+pub fn min_by_expectation<'a, I>(values: I) -> Option<&'a TruthValue>
+where
+    I: IntoIterator<Item = &'a TruthValue>,
+{
+    values.into_iter().reduce(|best, tv| if tv.cmp_expectation(best) == Ordering::Less { tv } else { best })
+}
+
+/// What the competing judgments are about
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChoiceBasis {
+    /// Same statement: the better-supported judgment wins (by confidence)
+    SameStatement,
+    /// Different statements answering one question: the likelier wins (by expectation)
+    DifferentStatements,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ChoiceError {
+    #[error("no judgments to choose from")]
+    Empty,
+}
+
+/// Choice rule: pick between two competing judgments.
+///
+/// Ties go to `a`, so the result is deterministic in argument order.
+pub fn choose<'a>(a: &'a TruthValue, b: &'a TruthValue, basis: ChoiceBasis) -> &'a TruthValue {
+    let (ka, kb) = match basis {
+        ChoiceBasis::SameStatement => (a.confidence, b.confidence),
+        ChoiceBasis::DifferentStatements => (a.expectation(), b.expectation()),
+    };
+    if kb > ka { b } else { a }
+}
+
+/// Choice rule over a slice. Returns the index and value of the winner;
+/// ties go to the earliest candidate.
+pub fn choose_many(candidates: &[TruthValue], basis: ChoiceBasis) -> Result<(usize, &TruthValue), ChoiceError> {
+    let mut iter = candidates.iter().enumerate();
+    let mut best = iter.next().ok_or(ChoiceError::Empty)?;
+    for (i, tv) in iter {
+        if !std::ptr::eq(choose(best.1, tv, basis), best.1) {
+            best = (i, tv);
+        }
+    }
+    Ok(best)
+}
+
+impl Default for TruthValue {
+    fn default() -> Self {
+        Self::unknown()
+    }
+}
+
+impl fmt::Display for TruthValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "⟨{:.0}%, {:.0}%⟩", self.frequency * 100.0, self.confidence * 100.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Error)]
+#[error("belief confidence {confidence} is below the required {min_confidence}")]
+pub struct LowConfidenceError {
+    pub confidence: f32,
+    pub min_confidence: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ParseTruthError {
+    #[error("empty truth value")]
+    Empty,
+    #[error("expected \"⟨f%, c%⟩\" or \"f;c\", got {0:?}")]
+    Format(String),
+    #[error("invalid number {0:?}")]
+    Number(String),
+}
+
+impl FromStr for TruthValue {
+    type Err = ParseTruthError;
+    
+    /// Parses the display form "⟨90%, 80%⟩" or the plain form "0.9;0.8".
+    /// Values are clamped into [0, 1].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseTruthError::Empty);
+        }
+        
+        let number = |part: &str, scale: f32| -> Result<f32, ParseTruthError> {
+            let part = part.trim();
+            part.parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(|v| v / scale)
+                .ok_or_else(|| ParseTruthError::Number(part.to_string()))
+        };
+        
+        if let Some(inner) = s.strip_prefix('⟨').and_then(|r| r.strip_suffix('⟩')) {
+            let (f, c) = inner.split_once(',').ok_or_else(|| ParseTruthError::Format(s.to_string()))?;
+            let f = f.trim().strip_suffix('%').ok_or_else(|| ParseTruthError::Format(s.to_string()))?;
+            let c = c.trim().strip_suffix('%').ok_or_else(|| ParseTruthError::Format(s.to_string()))?;
+            return Ok(TruthValue::new(number(f, 100.0)?, number(c, 100.0)?));
+        }
+        
+        let (f, c) = s.split_once(';').ok_or_else(|| ParseTruthError::Format(s.to_string()))?;
+        Ok(TruthValue::new(number(f, 1.0)?, number(c, 1.0)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_from_evidence() {
+        let tv = TruthValue::from_evidence(9.0, 1.0);
+        assert!((tv.frequency - 0.9).abs() < 0.01);
+    }
+    
+    #[test]
+    fn test_deduction() {
+        let birds_fly = TruthValue::new(0.9, 0.9);
+        let tweety_bird = TruthValue::certain_true();
+        let result = birds_fly.deduction(&tweety_bird);
+        assert!(result.frequency > 0.8);
+    }
+    
+    #[test]
+    fn test_revision() {
+        let ev1 = TruthValue::new(0.8, 0.8);
+        let ev2 = TruthValue::new(0.9, 0.7);
+        let combined = ev1.revision(&ev2);
+        // Combined should be between the two and higher confidence
+        assert!(combined.frequency > 0.75 && combined.frequency < 0.95);
+    }
+    
+    #[test]
+    fn test_comparison() {
+        let versions_scoped = TruthValue::new(0.9, 0.9);
+        let sprints_scoped = TruthValue::new(0.8, 0.8);
+        let similar = versions_scoped.comparison(&sprints_scoped);
+        // f0 = 0.98, f = 0.72 / 0.98, w = 0.98 * 0.72, c = w / (w + 1)
+        assert!((similar.frequency - 0.734_694).abs() < 1e-4);
+        assert!((similar.confidence - 0.413_696).abs() < 1e-4);
+    }
+    
+    #[test]
+    fn test_analogy() {
+        let versions_scoped = TruthValue::new(0.9, 0.9);
+        let sprints_like_versions = TruthValue::new(0.8, 0.5);
+        let sprints_scoped = versions_scoped.analogy(&sprints_like_versions);
+        assert!((sprints_scoped.frequency - 0.72).abs() < 1e-6);
+        assert!((sprints_scoped.confidence - 0.36).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_analogy_from_fingerprints() {
+        let premise = TruthValue::new(0.9, 0.9);
+        let versions = Fingerprint::from_content("versions");
+        
+        let same = analogy_from_fingerprints(&premise, &versions, &versions.clone());
+        assert!((same.frequency - 0.9).abs() < 1e-6);
+        assert!(same.confidence > 0.7);
+        
+        let unrelated = analogy_from_fingerprints(&premise, &versions, &Fingerprint::from_content("bananas"));
+        assert!(unrelated.confidence < 0.1);
+    }
+    
+    #[test]
+    fn test_intersection_union_difference() {
+        let a = TruthValue::new(0.9, 0.8);
+        let b = TruthValue::new(0.6, 0.5);
+        
+        let and = a.intersection(&b);
+        assert!((and.frequency - 0.54).abs() < 1e-6);
+        assert!((and.confidence - 0.4).abs() < 1e-6);
+        
+        let or = a.union(&b);
+        assert!((or.frequency - 0.96).abs() < 1e-6);
+        assert!((or.confidence - 0.4).abs() < 1e-6);
+        
+        let minus = a.difference(&b);
+        assert!((minus.frequency - 0.36).abs() < 1e-6);
+        assert!((minus.confidence - 0.4).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_conversion() {
+        let versions_scoped = TruthValue::new(0.9, 0.8);
+        let converse = versions_scoped.conversion();
+        assert_eq!(converse.frequency, 1.0);
+        // w = 0.72, c = 0.72 / 1.72
+        assert!((converse.confidence - 0.418_605).abs() < 1e-4);
+        assert!(converse.confidence < versions_scoped.confidence);
+    }
+    
+    #[test]
+    fn test_contraposition() {
+        let unknown = TruthValue::unknown().contraposition();
+        assert_eq!(unknown.confidence, 0.0);
+        
+        let mostly_false = TruthValue::new(0.2, 0.9).contraposition();
+        assert_eq!(mostly_false.frequency, 0.0);
+        // w = 0.72, c = 0.72 / 1.72
+        assert!((mostly_false.confidence - 0.418_605).abs() < 1e-4);
+    }
+    
+    #[test]
+    fn test_choose() {
+        let confident = TruthValue::new(0.6, 0.9);
+        let likely = TruthValue::new(0.95, 0.5);
+        
+        let same = choose(&confident, &likely, ChoiceBasis::SameStatement);
+        assert_eq!(same.confidence, 0.9);
+        
+        // E(confident) = 0.59, E(likely) = 0.725
+        let different = choose(&confident, &likely, ChoiceBasis::DifferentStatements);
+        assert_eq!(different.frequency, 0.95);
+    }
+    
+    #[test]
+    fn test_choose_ties_go_first() {
+        let a = TruthValue::new(0.7, 0.6);
+        let b = TruthValue::new(0.7, 0.6);
+        assert!(std::ptr::eq(choose(&a, &b, ChoiceBasis::SameStatement), &a));
+        assert!(std::ptr::eq(choose(&a, &b, ChoiceBasis::DifferentStatements), &a));
+        
+        let candidates = vec![TruthValue::new(0.2, 0.5), TruthValue::new(0.8, 0.7), TruthValue::new(0.8, 0.7)];
+        let (idx, _) = choose_many(&candidates, ChoiceBasis::DifferentStatements).unwrap();
+        assert_eq!(idx, 1);
+    }
+    
+    #[test]
+    fn test_choose_many_empty() {
+        assert!(matches!(choose_many(&[], ChoiceBasis::SameStatement), Err(ChoiceError::Empty)));
+    }
+    
+    #[test]
+    fn test_project() {
+        let tv = TruthValue::new(0.8, 0.9);
+        let same = tv.project(42, 42, 100.0);
+        assert_eq!(same.frequency, tv.frequency);
+        assert_eq!(same.confidence, tv.confidence);
+        
+        let mut last = tv.confidence;
+        for distance in [1, 10, 100, 1_000, 10_000] {
+            let projected = tv.project(0, distance, 100.0);
+            assert_eq!(projected.frequency, tv.frequency);
+            assert!(projected.confidence < last);
+            last = projected.confidence;
+        }
+        assert!((tv.project(100, 0, 100.0).confidence - 0.45).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_eternalize() {
+        for c in [0.0, 0.1, 0.5, 0.9, 1.0] {
+            let tv = TruthValue::new(0.7, c);
+            let eternal = tv.eternalize();
+            assert!(eternal.confidence <= tv.confidence);
+            assert_eq!(eternal.frequency, tv.frequency);
+        }
+    }
+    
+    #[test]
+    fn test_serde_round_trip() {
+        for tv in [TruthValue::new(0.0, 0.0), TruthValue::new(1.0, 1.0), TruthValue::new(0.9, 0.8)] {
+            let json = serde_json::to_string(&tv).unwrap();
+            let back: TruthValue = serde_json::from_str(&json).unwrap();
+            assert_eq!(back.frequency, tv.frequency);
+            assert_eq!(back.confidence, tv.confidence);
+        }
+        assert_eq!(serde_json::to_string(&TruthValue::new(1.0, 0.5)).unwrap(), r#"{"f":1.0,"c":0.5}"#);
+        
+        let clamped: TruthValue = serde_json::from_str(r#"{"f":1.5,"c":-0.2}"#).unwrap();
+        assert_eq!(clamped.frequency, 1.0);
+        assert_eq!(clamped.confidence, 0.0);
+    }
+    
+    #[test]
+    fn test_from_str() {
+        for tv in [TruthValue::new(0.0, 0.0), TruthValue::new(1.0, 1.0), TruthValue::new(0.9, 0.8)] {
+            let parsed: TruthValue = tv.to_string().parse().unwrap();
+            assert!((parsed.frequency - tv.frequency).abs() < 1e-6);
+            assert!((parsed.confidence - tv.confidence).abs() < 1e-6);
+        }
+        
+        let plain: TruthValue = " 0.9 ; 0.8 ".parse().unwrap();
+        assert!((plain.frequency - 0.9).abs() < 1e-6);
+        assert!((plain.confidence - 0.8).abs() < 1e-6);
+        
+        let clamped: TruthValue = "1.2;-3".parse().unwrap();
+        assert_eq!((clamped.frequency, clamped.confidence), (1.0, 0.0));
+    }
+    
+    #[test]
+    fn test_from_str_errors() {
+        assert_eq!("".parse::<TruthValue>().unwrap_err(), ParseTruthError::Empty);
+        assert!(matches!("0.9".parse::<TruthValue>(), Err(ParseTruthError::Format(_))));
+        assert!(matches!("⟨90, 80%⟩".parse::<TruthValue>(), Err(ParseTruthError::Format(_))));
+        assert!(matches!("high;0.8".parse::<TruthValue>(), Err(ParseTruthError::Number(_))));
+        assert!(matches!("NaN;0.8".parse::<TruthValue>(), Err(ParseTruthError::Number(_))));
+    }
+    
+    #[test]
+    fn test_horizon_validation() {
+        assert!(TruthParams::new(10.0).is_ok());
+        assert_eq!(TruthParams::new(0.0), Err(InvalidHorizon(0.0)));
+        assert!(TruthParams::new(-1.0).is_err());
+        assert!(TruthParams::new(f32::NAN).is_err());
+    }
+    
+    #[test]
+    fn test_horizon_growth() {
+        let k1 = TruthParams::default();
+        let k10 = TruthParams::new(10.0).unwrap();
+        for n in [1.0, 5.0, 10.0, 50.0] {
+            let fast = TruthValue::from_evidence_with(n, 0.0, &k1);
+            let slow = TruthValue::from_evidence_with(n, 0.0, &k10);
+            assert!(slow.confidence < fast.confidence);
+        }
+        // 10 observations: 10/11 at k=1, 10/20 at k=10
+        assert!((TruthValue::from_evidence_with(10.0, 0.0, &k10).confidence - 0.5).abs() < 1e-6);
+        assert!((TruthValue::from_evidence_with(10.0, 0.0, &k1).confidence - 10.0 / 11.0).abs() < 1e-6);
+        // w = 0.25 from premises ⟨1, 0.5⟩: 0.25/1.25 at k=1, 0.25/10.25 at k=10
+        let half = TruthValue::new(1.0, 0.5);
+        assert!((half.exemplification_with(&half, &k1).confidence - 0.2).abs() < 1e-6);
+        assert!((half.exemplification_with(&half, &k10).confidence - 0.25 / 10.25).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_default_horizon_is_identical() {
+        let params = TruthParams::default();
+        let a = TruthValue::new(0.8, 0.7);
+        let b = TruthValue::new(0.3, 0.4);
+        for (pos, neg) in [(9.0, 1.0), (0.5, 0.25), (3.0, 7.0)] {
+            let expected = TruthValue::from_evidence(pos, neg);
+            let got = TruthValue::from_evidence_with(pos, neg, &params);
+            assert_eq!(got.frequency.to_bits(), expected.frequency.to_bits());
+            assert_eq!(got.confidence.to_bits(), expected.confidence.to_bits());
+        }
+        let (x, y) = (a.revision(&b), a.revision_with(&b, &params));
+        assert_eq!(x.confidence.to_bits(), y.confidence.to_bits());
+        assert_eq!(x.frequency.to_bits(), y.frequency.to_bits());
+        assert_eq!(a.eternalize().confidence.to_bits(), a.eternalize_with(&params).confidence.to_bits());
+        assert_eq!(a.exemplification(&b), a.exemplification_with(&b, &params));
+        assert_eq!(a.comparison(&b), a.comparison_with(&b, &params));
+        assert_eq!(a.conversion(), a.conversion_with(&params));
+        assert_eq!(b.contraposition(), b.contraposition_with(&params));
+        assert_eq!(a.induction(&b), a.induction_with(&b, &params));
+        assert_eq!(a.abduction(&b), a.abduction_with(&b, &params));
+        assert_eq!(a.revision_weighted(&b, 1.0, 0.5), a.revision_weighted_with(&b, 1.0, 0.5, &params));
+        assert_eq!(a.revision_with_overlap(&b, 0.5), a.revision_with_overlap_with(&b, 0.5, &params));
+    }
+    
+    #[test]
+    fn test_weak_rules_at_k10() {
+        let k10 = TruthParams::new(10.0).unwrap();
+        let a = TruthValue::new(0.8, 0.5);
+        let b = TruthValue::new(0.5, 0.8);
+        // comparison: f0 = 0.9, w = 0.9·0.5·0.8 = 0.36
+        let compared = a.comparison_with(&b, &k10);
+        assert!((compared.frequency - 0.4 / 0.9).abs() < 1e-6);
+        assert!((compared.confidence - 0.36 / 10.36).abs() < 1e-6);
+        // conversion: w = 0.8·0.5 = 0.4; contraposition: w = 0.2·0.5 = 0.1
+        assert!((a.conversion_with(&k10).confidence - 0.4 / 10.4).abs() < 1e-6);
+        assert!((a.contraposition_with(&k10).confidence - 0.1 / 10.1).abs() < 1e-6);
+        assert!(a.conversion_with(&k10).confidence < a.conversion().confidence);
+        // induction: 0.8·0.5·0.8 / (0.8 + 10); abduction: 0.5·0.5·0.8 / (0.5 + 10)
+        assert!((a.induction_with(&b, &k10).confidence - 0.32 / 10.8).abs() < 1e-6);
+        assert!((a.abduction_with(&b, &k10).confidence - 0.2 / 10.5).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_weighted_revision_at_k10() {
+        let k10 = TruthParams::new(10.0).unwrap();
+        let a = TruthValue::new(0.9, 0.6);
+        let b = TruthValue::new(0.2, 0.5);
+        // Evidence weights scale with k and the horizon cancels, so k = 10
+        // pools exactly like the evidence counts it came from
+        let pooled = TruthValue::from_evidence_with(15.0 * 0.9 + 10.0 * 0.5 * 0.2, 15.0 * 0.1 + 10.0 * 0.5 * 0.8, &k10);
+        let weighted = a.revision_weighted_with(&b, 1.0, 0.5, &k10);
+        assert!((weighted.frequency - pooled.frequency).abs() < 1e-5);
+        assert!((weighted.confidence - pooled.confidence).abs() < 1e-5);
+        assert_eq!(a.revision_with_overlap_with(&b, 0.0, &k10), a.revision_with(&b, &k10));
+        let chosen = a.revision_with_overlap_with(&b, 1.0, &k10);
+        assert!((chosen.frequency - a.frequency).abs() < 1e-6 && (chosen.confidence - a.confidence).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_rank_by_expectation() {
+        let mut beliefs = vec![
+            TruthValue::new(0.6, 0.5),
+            TruthValue::new(0.9, 0.9),
+            TruthValue::unknown(),
+            TruthValue::new(0.1, 0.9),
+        ];
+        assert_eq!(max_by_expectation(&beliefs).unwrap().frequency, 0.9);
+        assert_eq!(min_by_expectation(&beliefs).unwrap().frequency, 0.1);
+        
+        beliefs.sort_by(|a, b| b.cmp_expectation(a));
+        let ranked: Vec<f32> = beliefs.iter().map(|tv| tv.frequency).collect();
+        assert_eq!(ranked, vec![0.9, 0.6, 0.5, 0.1]);
+        
+        assert!(max_by_expectation(&[]).is_none());
+    }
+    
+    #[test]
+    fn test_is_stronger_than() {
+        let strong = TruthValue::new(0.9, 0.9);
+        let weak = TruthValue::new(0.7, 0.9);
+        assert!(strong.is_stronger_than(&weak, 0.01));
+        assert!(!weak.is_stronger_than(&strong, 0.01));
+        assert!(!strong.is_stronger_than(&strong, 0.0));
+        
+        // Higher expectation but built on much less evidence
+        let lucky = TruthValue::new(1.0, 0.5);
+        let solid = TruthValue::new(0.7, 0.95);
+        assert!(lucky.expectation() > solid.expectation());
+        assert!(!lucky.is_stronger_than(&solid, 0.05));
+        
+        // Within epsilon is not stronger
+        let nearly = TruthValue::new(0.71, 0.9);
+        assert!(!nearly.is_stronger_than(&weak, 0.05));
+    }
+    
+    #[test]
+    fn test_decay_half_life() {
+        let tv = TruthValue::new(0.8, 0.8);
+        let half = tv.decayed(100, 100.0);
+        assert!((half.confidence - 0.4).abs() < 1e-6);
+        assert_eq!(half.frequency, tv.frequency);
+        
+        let same = tv.decayed(0, 100.0);
+        assert_eq!(same.confidence, tv.confidence);
+    }
+    
+    #[test]
+    fn test_decay_monotonic() {
+        let tv = TruthValue::new(0.3, 0.9);
+        let mut last = tv.confidence;
+        for elapsed in [1, 10, 50, 200, 10_000] {
+            let decayed = tv.decayed(elapsed, 50.0);
+            assert_eq!(decayed.frequency, tv.frequency);
+            assert!(decayed.confidence < last);
+            assert!(decayed.confidence >= 0.0);
+            last = decayed.confidence;
+        }
+        
+        let policy = DecayPolicy::new(50.0);
+        assert_eq!(policy.apply(&tv, 100, 100).confidence, tv.confidence);
+        assert!((policy.apply(&tv, 100, 150).confidence - 0.45).abs() < 1e-6);
+        // A confirmation "from the future" never increases confidence
+        assert_eq!(policy.apply(&tv, 200, 100).confidence, tv.confidence);
+    }
+    
+    #[test]
+    fn test_from_similarity() {
+        let chance = TruthValue::from_similarity(0.5, RANDOM_SIMILARITY);
+        assert_eq!((chance.frequency, chance.confidence), (0.5, 0.0));
+        
+        let identical = TruthValue::from_similarity(1.0, RANDOM_SIMILARITY);
+        assert_eq!(identical.frequency, 1.0);
+        assert!((identical.confidence - 0.9).abs() < 1e-6);
+        
+        let opposite = TruthValue::from_similarity(0.0, RANDOM_SIMILARITY);
+        assert_eq!(opposite.frequency, 0.0);
+        assert!((opposite.confidence - 0.9).abs() < 1e-6);
+        
+        let close = TruthValue::from_similarity(0.75, RANDOM_SIMILARITY);
+        assert!((close.confidence - 0.45).abs() < 1e-6);
+        
+        let custom = TruthValue::from_similarity(0.8, 0.8);
+        assert_eq!(custom.confidence, 0.0);
+    }
+    
+    #[test]
+    fn test_revision_with_overlap_extremes() {
+        let a = TruthValue::new(0.8, 0.6);
+        let b = TruthValue::new(0.4, 0.7);
+        
+        let independent = a.revision_with_overlap(&b, 0.0);
+        let plain = a.revision(&b);
+        assert!((independent.frequency - plain.frequency).abs() < 1e-6);
+        assert!((independent.confidence - plain.confidence).abs() < 1e-6);
+        
+        let same_evidence = a.revision_with_overlap(&b, 1.0);
+        let chosen = choose(&a, &b, ChoiceBasis::SameStatement);
+        assert!((same_evidence.frequency - chosen.frequency).abs() < 1e-5);
+        assert!((same_evidence.confidence - chosen.confidence).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn test_revision_with_overlap_monotonic() {
+        let a = TruthValue::new(0.8, 0.6);
+        let b = TruthValue::new(0.4, 0.7);
+        let mut last = f32::INFINITY;
+        for step in 0..=10 {
+            let c = a.revision_with_overlap(&b, step as f32 / 10.0).confidence;
+            assert!(c <= last);
+            last = c;
+        }
+    }
+    
+    #[test]
+    fn test_revision_with_overlap_bounded_by_revision() {
+        let grid = [0.0, 0.1, 0.35, 0.5, 0.8, 0.95];
+        for &f1 in &grid { for &c1 in &grid { for &f2 in &grid { for &c2 in &grid {
+            let a = TruthValue::new(f1, c1);
+            let b = TruthValue::new(f2, c2);
+            let independent = a.revision(&b).confidence;
+            for overlap in [0.0, 0.25, 0.5, 1.0] {
+                assert!(a.revision_with_overlap(&b, overlap).confidence <= independent + 1e-6);
+            }
+        }}}}
+    }
+    
+    #[test]
+    fn test_exemplification() {
+        let a = TruthValue::new(0.9, 0.9);
+        let b = TruthValue::new(1.0, 0.9);
+        let result = a.exemplification(&b);
+        // w = 0.729, c = 0.729 / 1.729
+        assert_eq!(result.frequency, 1.0);
+        assert!((result.confidence - 0.421_631).abs() < 1e-4);
+    }
+    
+    type Rule = fn(&TruthValue, &TruthValue) -> TruthValue;
+    
+    #[test]
+    fn test_syllogistic_table() {
+        let a = TruthValue::new(0.9, 0.8);
+        let b = TruthValue::new(0.7, 0.6);
+        let rules: [(&str, Rule, f32, f32); 5] = [
+            ("deduction", TruthValue::deduction, 0.63, 0.3024),
+            ("induction", TruthValue::induction, 0.7, 0.432 / 1.9),
+            ("abduction", TruthValue::abduction, 0.9, 0.336 / 1.7),
+            ("exemplification", TruthValue::exemplification, 1.0, 0.3024 / 1.3024),
+            ("comparison", TruthValue::comparison, 0.63 / 0.97, 0.4656 / 1.4656),
+        ];
+        for (name, rule, f, c) in rules {
+            let result = rule(&a, &b);
+            assert!((result.frequency - f).abs() < 1e-5, "{} frequency {}", name, result.frequency);
+            assert!((result.confidence - c).abs() < 1e-5, "{} confidence {}", name, result.confidence);
+        }
+    }
+    
+    #[test]
+    fn test_revision_weighted_identity() {
+        let a = TruthValue::new(0.8, 0.7);
+        let b = TruthValue::new(0.3, 0.4);
+        let plain = a.revision(&b);
+        let weighted = a.revision_weighted(&b, 1.0, 1.0);
+        assert_eq!(plain.frequency.to_bits(), weighted.frequency.to_bits());
+        assert_eq!(plain.confidence.to_bits(), weighted.confidence.to_bits());
+    }
+    
+    #[test]
+    fn test_revision_weighted_ignores_unreliable() {
+        let a = TruthValue::new(0.8, 0.7);
+        let b = TruthValue::new(0.1, 0.9);
+        let result = a.revision_weighted(&b, 1.0, 0.0);
+        assert!((result.frequency - a.frequency).abs() < 1e-5);
+        assert!((result.confidence - a.confidence).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn test_revision_weighted_monotonic() {
+        let a = TruthValue::new(0.8, 0.7);
+        let b = TruthValue::new(0.1, 0.6);
+        let mut last_c = 0.0;
+        let mut last_f = 1.0;
+        for step in 0..=10 {
+            let result = a.revision_weighted(&b, 1.0, step as f32 / 10.0);
+            assert!(result.confidence >= last_c);
+            // More weight on the low-frequency premise pulls frequency down
+            assert!(result.frequency <= last_f);
+            last_c = result.confidence;
+            last_f = result.frequency;
+        }
+    }
+    
+    #[test]
+    fn test_ignorance() {
+        assert_eq!(TruthValue::unknown().ignorance(), 1.0);
+        let tv = TruthValue::new(0.7, 0.8);
+        assert!((tv.ignorance() - tv.to_interval().ignorance()).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_informative_floor() {
+        let tv = TruthValue::new(0.9, 0.4);
+        assert!(tv.is_informative(0.4));
+        assert!(!tv.is_informative(0.41));
+        assert!(tv.require_informative(0.3).is_ok());
+        let err = tv.require_informative(0.5).unwrap_err();
+        assert_eq!(err.min_confidence, 0.5);
+        
+        for floor in [0.0, 0.1, 0.9] {
+            assert!(TruthValue::unknown().require_informative(floor).is_err());
+        }
+    }
+    
+    #[test]
+    fn test_comparison_zero_frequency() {
+        let a = TruthValue::new(0.0, 0.9);
+        let b = TruthValue::new(0.0, 0.8);
+        let similar = a.comparison(&b);
+        assert_eq!(similar.frequency, 0.0);
+        assert_eq!(similar.confidence, 0.0);
+    }
+}
+
+#[cfg(test)]
+mod invariants {
+    use super::*;
+    use proptest::prelude::*;
+    
+    fn unit() -> impl Strategy<Value = f32> {
+        prop_oneof![Just(0.0f32), Just(1.0f32), 0.0f32..=1.0f32]
+    }
+    
+    fn truth() -> impl Strategy<Value = TruthValue> {
+        (unit(), unit()).prop_map(|(f, c)| TruthValue::new(f, c))
+    }
+    
+    fn assert_valid(name: &str, tv: &TruthValue) {
+        assert!(tv.frequency.is_finite() && (0.0..=1.0).contains(&tv.frequency), "{}: frequency {}", name, tv.frequency);
+        assert!(tv.confidence.is_finite() && (0.0..=1.0).contains(&tv.confidence), "{}: confidence {}", name, tv.confidence);
+    }
+    
+    proptest! {
+        #[test]
+        fn binary_rules_stay_in_range(a in truth(), b in truth(), x in unit(), y in unit()) {
+            assert_valid("deduction", &a.deduction(&b));
+            assert_valid("induction", &a.induction(&b));
+            assert_valid("abduction", &a.abduction(&b));
+            assert_valid("exemplification", &a.exemplification(&b));
+            assert_valid("comparison", &a.comparison(&b));
+            assert_valid("analogy", &a.analogy(&b));
+            assert_valid("intersection", &a.intersection(&b));
+            assert_valid("union", &a.union(&b));
+            assert_valid("difference", &a.difference(&b));
+            assert_valid("revision", &a.revision(&b));
+            assert_valid("revision_with_overlap", &a.revision_with_overlap(&b, x));
+            assert_valid("revision_weighted", &a.revision_weighted(&b, x, y));
+        }
+        
+        #[test]
+        fn unary_rules_stay_in_range(a in truth(), x in unit(), dt in 0u64..1_000_000, horizon in prop_oneof![Just(0.0f32), Just(f32::INFINITY), 0.0f32..1e6]) {
+            assert_valid("negation", &a.negation());
+            assert_valid("conversion", &a.conversion());
+            assert_valid("contraposition", &a.contraposition());
+            assert_valid("eternalize", &a.eternalize());
+            assert_valid("project", &a.project(0, dt, horizon));
+            assert_valid("decayed", &a.decayed(dt, horizon));
+            assert_valid("from_similarity", &TruthValue::from_similarity(x, RANDOM_SIMILARITY));
+            assert_valid("interval", &a.to_interval().to_truth());
+        }
+        
+        #[test]
+        fn independent_revision_never_loses_confidence(a in truth(), b in truth()) {
+            let revised = a.revision(&b);
+            prop_assert!(revised.confidence + 1e-6 >= a.confidence.max(b.confidence));
+        }
+        
+        #[test]
+        fn overlapping_revision_never_exceeds_independent(a in truth(), b in truth(), overlap in unit()) {
+            let independent = a.revision(&b);
+            prop_assert!(a.revision_with_overlap(&b, overlap).confidence <= independent.confidence + 1e-6);
+        }
+        
+        #[test]
+        fn negation_is_an_involution(a in truth()) {
+            let back = a.negation().negation();
+            prop_assert!((back.frequency - a.frequency).abs() < 1e-6);
+            prop_assert_eq!(back.confidence, a.confidence);
+        }
+        
+        #[test]
+        fn evidence_stays_in_range(p in prop_oneof![Just(f32::MAX), 0.0f32..1e6], n in prop_oneof![Just(f32::MAX), 0.0f32..1e6]) {
+            assert_valid("from_evidence", &TruthValue::from_evidence(p, n));
+        }
+    }
+    
+    #[test]
+    fn revision_of_certain_beliefs() {
+        let certain = TruthValue::new(1.0, 1.0);
+        let weak = TruthValue::new(0.0, 0.5);
+        let revised = certain.revision(&weak);
+        assert_eq!((revised.frequency, revised.confidence), (1.0, 1.0));
+        
+        let both = certain.revision(&TruthValue::new(0.0, 1.0));
+        assert_eq!((both.frequency, both.confidence), (0.5, 1.0));
+        
+        let ignored = certain.revision_weighted(&weak, 0.0, 1.0);
+        assert_eq!((ignored.frequency, ignored.confidence), (0.0, 0.5));
+    }
+    
+    #[test]
+    fn revision_of_unknowns_is_unknown() {
+        let revised = TruthValue::unknown().revision(&TruthValue::unknown());
+        assert_eq!((revised.frequency, revised.confidence), (0.5, 0.0));
+    }
+}